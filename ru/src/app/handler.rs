@@ -38,6 +38,9 @@ impl winit::application::ApplicationHandler<RupyAppEvent> for Rupy {
         } else {
             return;
         };
+        let Some(window) = state.window.clone() else {
+            return;
+        };
         if state.bit_flags.is_shutting_down() || event == WindowEvent::CloseRequested {
             shutdown(event_loop);
         }
@@ -54,18 +57,19 @@ impl winit::application::ApplicationHandler<RupyAppEvent> for Rupy {
                 }
                 match event.physical_key {
                     PhysicalKey::Code(KeyCode::KeyQ) => state.renderer.ctx.set_next_debug_mode(),
+                    PhysicalKey::Code(KeyCode::KeyM) => {
+                        state.renderer.shadows.toggle_atlas_view();
+                    }
                     PhysicalKey::Code(KeyCode::ControlLeft) => {
                         state.renderer.ctx.set_next_topology()
                     }
 
                     PhysicalKey::Code(KeyCode::Escape) => state.bit_flags.set_shutting_down(),
-                    PhysicalKey::Code(KeyCode::Tab) => {
-                        if state.window.is_resizable() {
-                            if state.window.fullscreen() != None {
-                                state.window.set_fullscreen(None);
-                            } else {
-                                state.window.set_fullscreen(CUR_MONITOR_FULLSCREEN);
-                            }
+                    PhysicalKey::Code(KeyCode::Tab) if window.is_resizable() => {
+                        if window.fullscreen().is_none() {
+                            window.set_fullscreen(CUR_MONITOR_FULLSCREEN);
+                        } else {
+                            window.set_fullscreen(None);
                         }
                     }
 
@@ -80,17 +84,20 @@ impl winit::application::ApplicationHandler<RupyAppEvent> for Rupy {
                 }
             }
             WindowEvent::CursorEntered { .. } => {
-                state.window.set_cursor_visible(false);
+                window.set_cursor_visible(false);
             }
             WindowEvent::CursorLeft { .. } => {
-                state.window.set_cursor_visible(true);
+                window.set_cursor_visible(true);
             }
             WindowEvent::RedrawRequested => {
                 if state.bit_flags.is_running() {
                     state.update();
                     state.render();
                 }
-                state.window.request_redraw()
+                window.request_redraw();
+                for event in state.drain_events() {
+                    self.send_event(event);
+                }
             }
 
             _ => {}
@@ -109,6 +116,19 @@ impl winit::application::ApplicationHandler<RupyAppEvent> for Rupy {
             RupyAppEvent::RenderStart(window) => {
                 pollster::block_on(handle_render_start(self, window));
             }
+            RupyAppEvent::InputCommand { command } => {
+                if let Some(state) = &mut self.state {
+                    match state.execute_command(&command) {
+                        Ok(message) if !message.is_empty() => {
+                            log_info!("{}", message);
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            log_error!("{}: {}", command, e);
+                        }
+                    }
+                }
+            }
             RupyAppEvent::TaskCompleted => {}
             _ => {}
         }
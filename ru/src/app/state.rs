@@ -3,81 +3,181 @@ use super::DebugMode;
 use crate::camera::frustum::Frustum;
 use crate::camera::handler::{create_camera_handler, CameraHandler};
 use crate::core::files::FileSystem;
+use crate::core::watcher::{AssetChange, AssetWatcher};
+use crate::ecs::components::animation::manager::AnimationManager;
 use crate::ecs::components::instance::manager::InstanceManager;
 use crate::ecs::components::instance::model::Instance;
+use crate::ecs::components::light::Light;
 use crate::ecs::components::material::manager::MaterialManager;
+use crate::ecs::components::material::model::{
+    create_default_material, rebuild_material_bind_groups,
+};
 use crate::ecs::components::mesh::manager::MeshManager;
 use crate::ecs::components::model::manager::ModelManager;
+use crate::ecs::components::model::model::Model;
 use crate::ecs::components::transform::Transform;
 use crate::ecs::components::ResourceContext;
+use crate::ecs::scene::load_scene_file;
+use crate::ecs::systems::physics::{self, PickHit};
 use crate::ecs::systems::render::{BufferFactory, BufferManager, RenderInfo, Renderer3D};
+use crate::ecs::systems::{animation, lighting};
+use crate::ecs::traits::Cache;
 use crate::graphics::binding::{
     initialize_common_bind_groups, setup_bind_group_layouts, BindGroupManager,
 };
+use crate::graphics::cluster::LightClusters;
 use crate::graphics::context::GpuResourceCache;
+use crate::graphics::global::initialize_headless_instance;
 use crate::graphics::glyphon::GlyphonRender;
-use crate::graphics::pipelines::common::PipelineBase;
-use crate::graphics::pipelines::hdr::{self, HdrLoader};
+use crate::graphics::graph::pool::TransientPool;
+use crate::graphics::msaa::MsaaSettings;
+use crate::graphics::pipelines::hdr::HdrLoader;
+use crate::graphics::pipelines::ibl::IblBaker;
 use crate::graphics::pipelines::manager::PipelineManager;
+use crate::graphics::post::PostProcessStack;
+use crate::graphics::settings::RenderSettings;
 use crate::graphics::shaders::manager::ShaderManager;
-use crate::graphics::textures::depth_texture::DepthTexture;
+use crate::graphics::shadow::ShadowMaps;
 use crate::graphics::textures::manager::TextureManager;
 use crate::graphics::textures::Texture;
 use crate::graphics::uniform::camera::CameraUniform;
-use crate::graphics::uniform::lighting::LightUniform;
+use crate::graphics::uniform::lighting::{
+    LightUniform, DEFAULT_SUN_DIRECTION, DEFAULT_SUN_INTENSITY,
+};
 use crate::graphics::uniform::Uniforms;
 use crate::graphics::PrimitiveTopology;
 use crate::prelude::cache::CacheKey;
 use crate::{
-    core::{error::AppError, surface::RenderSurface},
-    ecs::world::World,
+    core::{
+        error::AppError,
+        events::RupyAppEvent,
+        surface::{OffscreenSurface, RenderSurface, RenderTarget},
+    },
+    ecs::{entity::Entity, world::World},
     prelude::metrics::FrameMetrics,
 };
-use crate::{log_error, log_warning};
+use crate::{log_error, log_info, log_warning};
 use bytemuck::cast_slice;
 use cgmath::{Quaternion, Vector3};
+use image::RgbaImage;
 
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 
-use cgmath::Rotation3;
+use cgmath::{InnerSpace, Rotation3};
 use winit::dpi::PhysicalSize;
-use winit::event::WindowEvent;
+use winit::event::{ElementState, MouseButton, WindowEvent};
 
 const NUM_INSTANCES_PER_ROW: u32 = 10;
 const SPACE_BETWEEN: f32 = 2.0;
+const SKY_TEXTURE: &str = "pure-sky.hdr";
+const DEMO_LIGHT_POSITION: [f32; 3] = [1.0, 5.0, 1.0];
+const DEMO_LIGHT_INTENSITY: f32 = 25.0;
+const DEMO_LIGHT_RANGE: f32 = 20.0;
+const FALLBACK_SKY: [[f32; 4]; 2] = [[0.6, 0.75, 0.95, 1.0], [0.25, 0.22, 0.2, 1.0]];
 
 impl State {
     pub async fn new(
         gpu: GpuResourceCache,
         bit_flags: BitFlags,
         window: std::sync::Arc<winit::window::Window>,
+    ) -> Result<Self, AppError> {
+        let target_surface = gpu.instance().create_surface(window.clone())?;
+        let surface = RenderSurface::new(
+            target_surface,
+            window.inner_size(),
+            &gpu.adapter(),
+            &gpu.device(),
+        );
+        let mut state = Self::with_target(
+            gpu,
+            bit_flags,
+            RenderTarget::Surface(surface),
+            Some(window),
+            RenderSettings::load(),
+        )
+        .await?;
+        state.spawn_demo_scene().await?;
+        state.asset_watcher = match AssetWatcher::from_assets_dir() {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                log_warning!("Asset hot reload disabled: {:?}", e);
+                None
+            }
+        };
+        Ok(state)
+    }
+
+    pub async fn headless(
+        width: u32,
+        height: u32,
+        settings: RenderSettings,
+    ) -> Result<Self, AppError> {
+        initialize_headless_instance().await?;
+        let gpu = GpuResourceCache::new().await;
+        let target = RenderTarget::Offscreen(OffscreenSurface::new(&gpu.device(), width, height));
+        let mut bit_flags = BitFlags::empty();
+        bit_flags.set_running();
+        Self::with_target(gpu, bit_flags, target, None, settings).await
+    }
+
+    pub async fn with_target(
+        gpu: GpuResourceCache,
+        bit_flags: BitFlags,
+        target: RenderTarget<'static>,
+        window: Option<std::sync::Arc<winit::window::Window>>,
+        settings: RenderSettings,
     ) -> Result<Self, AppError> {
         let device = gpu.device();
         let queue = gpu.queue();
 
-        let inner_size = window.inner_size();
+        let inner_size = target.size();
+        let surface_format = target.config().format;
 
-        let target_surface = gpu.instance().create_surface(window.clone())?;
-        let surface = RenderSurface::new(target_surface, inner_size, &gpu.adapter(), &device);
-
-        let bind_group_layouts = setup_bind_group_layouts(&device, surface.config.format);
+        let bind_group_layouts = setup_bind_group_layouts(&device, surface_format);
 
         let preload_paths = vec![
-            "effects/tone_mapping.wgsl",
+            PostProcessStack::SHADER_PATH,
             "compute/equirectangular.wgsl",
             "core/lighting.wgsl",
             "objects/skybox.wgsl",
             "core/normal.wgsl",
+            "core/pbr.wgsl",
+            ShadowMaps::SHADER_PATH,
+            ShadowMaps::ATLAS_SHADER_PATH,
+            LightClusters::SHADER_PATH,
         ];
         let mut shader_manager = ShaderManager::new(&device, preload_paths)?;
-        let hdr = hdr::HdrPipeline::new(
+        let RenderSettings {
+            shadows: shadow_settings,
+            post: post_settings,
+            msaa: msaa_settings,
+            textures: texture_settings,
+        } = settings;
+        let post = PostProcessStack::new(
             &device,
-            surface.config.format,
-            inner_size.width,
-            inner_size.height,
+            post_settings,
+            surface_format,
             &mut shader_manager,
             &bind_group_layouts,
+        )?;
+        let supported_sample_counts = MsaaSettings::supported_sample_counts(
+            &gpu.adapter(),
+            &device,
+            &[post.format(), Texture::DEPTH_FORMAT],
+        );
+        let sample_count = MsaaSettings::resolve_sample_count(
+            msaa_settings.sample_count,
+            &supported_sample_counts,
         );
+        if sample_count != msaa_settings.sample_count {
+            log_warning!(
+                "{}x MSAA is unsupported, using {}x (supported: {:?})",
+                msaa_settings.sample_count,
+                sample_count,
+                supported_sample_counts
+            );
+        }
         let pipeline_manager = match PipelineManager::setup(
             &device,
             vec![
@@ -89,7 +189,8 @@ impl State {
             ],
             Some(Texture::DEPTH_FORMAT),
             &bind_group_layouts,
-            hdr.format(),
+            post.format(),
+            sample_count,
             &mut shader_manager,
         ) {
             Ok(pipelines) => pipelines,
@@ -99,46 +200,44 @@ impl State {
         };
         let mut buffer_manager = BufferManager::new();
         let mut bind_group_manager = BindGroupManager::new(bind_group_layouts);
-        let mut instance_manager = InstanceManager::new();
-
-        let instances = (0..NUM_INSTANCES_PER_ROW)
-            .flat_map(|z| {
-                (0..NUM_INSTANCES_PER_ROW).map(move |x| {
-                    let x = SPACE_BETWEEN * (x as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
-                    let z = SPACE_BETWEEN * (z as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
-
-                    let scale = Vector3::new(1.0, 1.0, 1.0);
-
-                    let transform = Transform {
-                        position: Vector3 { x, y: 1.0, z },
-                        rotation: Quaternion::from_axis_angle(Vector3::unit_z(), cgmath::Deg(0.0)),
-                        scale,
-                    };
-
-                    Instance { transform }
-                })
-            })
-            .collect::<Vec<_>>();
+        let instance_manager = InstanceManager::new();
 
         let mesh_manager = MeshManager::new();
         let material_manager = MaterialManager::new();
-        let texture_manager = TextureManager::new();
+        let mut texture_manager = TextureManager::new();
+        texture_settings.apply(&mut texture_manager);
 
         let model_manager = ModelManager::new();
         let light_bg_cache_key = CacheKey::from("bind:group:light");
         let camera_bg_cache_key = CacheKey::from("bind:group:camera");
         let environment_bg_cache_key = CacheKey::from("bind:group:environment");
         let skybox_cache_key = CacheKey::from("skybox");
-        let sky_texture = HdrLoader::new(&device).from_equirectangular_bytes(
-            &device,
-            &queue,
-            &FileSystem::load_binary("pure-sky.hdr")?,
-            1080,
-            Some("Sky Texture"),
-        )?;
+        let hdr_loader = HdrLoader::new(&device);
+        let sky_texture = match FileSystem::load_binary(SKY_TEXTURE) {
+            Ok(bytes) => hdr_loader.from_equirectangular_bytes(
+                &device,
+                &queue,
+                &bytes,
+                1080,
+                Some("Sky Texture"),
+            )?,
+            Err(e) => {
+                log_warning!("Using a flat sky, {} is unavailable: {}", SKY_TEXTURE, e);
+                hdr_loader.from_equirectangular_pixels(
+                    &device,
+                    &queue,
+                    1,
+                    FALLBACK_SKY.len() as u32,
+                    &FALLBACK_SKY,
+                    64,
+                    Some("Sky Texture"),
+                )?
+            }
+        };
+        let environment = IblBaker::new(&device).bake(&device, &queue, sky_texture)?;
         let uniforms = Uniforms {
             camera: CameraUniform::new(),
-            lighting: LightUniform::new([1.0, 5.0, 1.0], [1.0, 1.0, 1.0]),
+            lighting: LightUniform::default(),
         };
         let camera_position = Vector3::new(0.0, 0.0, -10.0);
 
@@ -150,7 +249,7 @@ impl State {
         let _ = initialize_common_bind_groups(
             &device,
             &uniforms,
-            sky_texture,
+            &environment,
             environment_bg_cache_key,
             light_bg_cache_key,
             camera_bg_cache_key,
@@ -165,21 +264,18 @@ impl State {
             stencil: wgpu::StencilState::default(),
             bias: wgpu::DepthBiasState::default(),
         };
-        let glyphon = GlyphonRender::new(&device, &queue, surface.config.format, &depth_stencil);
+        let glyphon =
+            GlyphonRender::new(&device, &queue, post.format(), &depth_stencil, sample_count);
         let ctx = RenderInfo::new(
             FrameMetrics::new(),
             DebugMode::None,
             PrimitiveTopology::TriangleList,
         );
-        let depth_texture = Texture::create_depth_texture(&device, inner_size, "texture:depth");
-        let depth_buffer = DepthTexture::new(depth_texture, depth_stencil);
+        let transients = TransientPool::new(inner_size.width, inner_size.height);
 
         let mut world = World::new();
+        let demo_light = spawn_default_lights(&mut world);
 
-        let entity = world.create_entity();
-        for instance in instances {
-            let _ = instance_manager.add_instance(&entity, instance);
-        }
         let mut resources = ResourceContext {
             bind_group_manager,
             buffer_manager,
@@ -190,18 +286,29 @@ impl State {
             mesh_manager,
             shader_manager,
             instance_manager,
+            animation_manager: AnimationManager::new(),
         };
-        let model =
-            ModelManager::load_model_from_file("cube.obj", &device, &queue, &mut resources).await?;
-
-        if let Err(e) = world.add_component(entity, model) {
-            log_error!("Error from world: {:?}", e);
-        };
+        create_default_material(&device, &queue, &mut resources)?;
+        let shadows = ShadowMaps::new(
+            &device,
+            shadow_settings,
+            surface_format,
+            &mut resources.shader_manager,
+            &resources.bind_group_manager.bind_group_layouts,
+        )?;
+        let clusters = LightClusters::new(
+            &device,
+            &mut resources.shader_manager,
+            &resources.bind_group_manager.bind_group_layouts,
+        )?;
 
         let renderer = Renderer3D::new(
             ctx,
-            hdr,
-            depth_buffer,
+            post,
+            depth_stencil,
+            transients,
+            shadows,
+            clusters,
             glyphon,
             camera_bg_cache_key,
             environment_bg_cache_key,
@@ -216,10 +323,16 @@ impl State {
             camera_handler,
             uniforms,
             world,
-            target: surface,
+            target,
             window,
             renderer,
             frustum,
+            demo_light,
+            asset_watcher: None,
+            reload_errors: BTreeMap::new(),
+            pending_events: Vec::new(),
+            overlaps: HashSet::new(),
+            fixed_delta_time: None,
         })
     }
 }
@@ -233,23 +346,109 @@ pub struct State {
     pub frustum: Frustum,
 
     pub world: World,
-    pub target: RenderSurface<'static>,
-    pub window: Arc<winit::window::Window>,
+    pub demo_light: Option<Entity>,
+    pub target: RenderTarget<'static>,
+    pub window: Option<Arc<winit::window::Window>>,
+    pub asset_watcher: Option<AssetWatcher>,
+    pub reload_errors: BTreeMap<String, String>,
+    pub pending_events: Vec<RupyAppEvent>,
+    pub overlaps: HashSet<(Entity, Entity)>,
+    pub fixed_delta_time: Option<f32>,
 }
 
 impl State {
+    pub async fn spawn_demo_scene(&mut self) -> Result<(), AppError> {
+        let device = self.gpu.device();
+        let queue = self.gpu.queue();
+
+        let instances = (0..NUM_INSTANCES_PER_ROW)
+            .flat_map(|z| {
+                (0..NUM_INSTANCES_PER_ROW).map(move |x| {
+                    let x = SPACE_BETWEEN * (x as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+                    let z = SPACE_BETWEEN * (z as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+
+                    let scale = Vector3::new(1.0, 1.0, 1.0);
+
+                    let transform = Transform {
+                        position: Vector3 { x, y: 1.0, z },
+                        rotation: Quaternion::from_axis_angle(Vector3::unit_z(), cgmath::Deg(0.0)),
+                        scale,
+                    };
+
+                    Instance { transform }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let entity = self.world.create_entity();
+        for instance in instances {
+            let _ = self
+                .resources
+                .instance_manager
+                .add_instance(&entity, instance);
+        }
+        let model =
+            ModelManager::load_model_from_file("cube.obj", &device, &queue, &mut self.resources)
+                .await?;
+        if let Err(e) = self.world.add_component(entity, model) {
+            log_error!("Error from world: {:?}", e);
+        };
+        Ok(())
+    }
+
     pub fn gpu(&mut self) -> &GpuResourceCache {
         &self.gpu
     }
     pub fn update(&mut self) {
+        self.reload_assets();
         self.transform_entity_instances();
+        self.update_animations();
+        self.update_physics();
         self.update_lighting();
         self.update_camera();
         self.update_metrics();
     }
+    pub fn delta_time(&self) -> f32 {
+        self.fixed_delta_time
+            .unwrap_or(self.renderer.ctx.frame_metrics().delta_time)
+    }
+    pub fn update_animations(&mut self) {
+        let device = self.gpu.device();
+        let queue = self.gpu.queue();
+        let dt = self.delta_time();
+        let events = animation::update_animations(&self.world, &self.resources, dt);
+        self.pending_events.extend(events);
+        let events = animation::update_animators(&self.world, &mut self.resources, dt);
+        self.pending_events.extend(events);
+        animation::update_skinning(&device, &queue, &self.world, &mut self.resources);
+    }
+    pub fn update_physics(&mut self) {
+        let bounds = physics::world_bounds(&self.world, &self.resources);
+        let overlaps = physics::entity_pairs(&physics::broadphase(&bounds));
+        for (first, second) in overlaps.difference(&self.overlaps) {
+            self.pending_events.push(RupyAppEvent::OverlapStarted {
+                first: first.id as u64,
+                second: second.id as u64,
+            });
+        }
+        for (first, second) in self.overlaps.difference(&overlaps) {
+            self.pending_events.push(RupyAppEvent::OverlapEnded {
+                first: first.id as u64,
+                second: second.id as u64,
+            });
+        }
+        self.overlaps = overlaps;
+    }
+    pub fn pick(&self, origin: Vector3<f32>, direction: Vector3<f32>) -> Option<PickHit> {
+        let bounds = physics::world_bounds(&self.world, &self.resources);
+        physics::pick(&bounds, origin, direction)
+    }
     pub fn update_camera(&mut self) {
         let device = &self.gpu().device();
         let queue = &self.gpu().queue();
+        if let Some(target) = animation::camera_target(&self.world) {
+            self.camera_handler.view.follow(&target);
+        }
         let view_projection = self.camera_handler.view_projection_matrix();
         self.frustum.update_planes(view_projection);
         self.camera_handler
@@ -261,6 +460,192 @@ impl State {
             &self.renderer.ctx.debug_mode(),
             &self.renderer.ctx.frame_metrics(),
         );
+        if !self.reload_errors.is_empty() {
+            let errors: Vec<String> = self
+                .reload_errors
+                .iter()
+                .map(|(name, error)| format!("{}: {}", name, error))
+                .collect();
+            self.renderer.glyphon.errors([14.0, 14.0], &errors);
+        }
+    }
+
+    pub fn reload_assets(&mut self) {
+        let changes = match &self.asset_watcher {
+            Some(watcher) => watcher.poll(),
+            None => return,
+        };
+        for change in changes {
+            match self.reload_asset(&change) {
+                Ok(()) => {
+                    log_info!("Reloaded {:?}", change);
+                    self.reload_errors.remove(change.name());
+                }
+                Err(e) => {
+                    log_error!("Failed to reload {:?}: {}", change, e);
+                    self.reload_errors
+                        .insert(change.name().to_string(), e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Rebuilds the pipelines that use `path` from the freshly reloaded shader.
+    fn rebuild_for_shader(&mut self, path: &str) -> Result<(), AppError> {
+        let device = self.gpu.device();
+        let resources = &mut self.resources;
+        if path == PostProcessStack::SHADER_PATH {
+            self.renderer.post.reload_pipelines(
+                &device,
+                &mut resources.shader_manager,
+                &resources.bind_group_manager.bind_group_layouts,
+            )?;
+        } else if path == ShadowMaps::SHADER_PATH || path == ShadowMaps::ATLAS_SHADER_PATH {
+            self.renderer.shadows.reload_pipelines(
+                &device,
+                path,
+                &mut resources.shader_manager,
+                &resources.bind_group_manager.bind_group_layouts,
+            )?;
+        } else if path == LightClusters::SHADER_PATH {
+            self.renderer.clusters.reload_pipeline(
+                &device,
+                &mut resources.shader_manager,
+                &resources.bind_group_manager.bind_group_layouts,
+            )?;
+        } else {
+            resources.pipeline_manager.rebuild_for_shader(
+                &device,
+                path,
+                &resources.bind_group_manager.bind_group_layouts,
+                &mut resources.shader_manager,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn reload_asset(&mut self, change: &AssetChange) -> Result<(), AppError> {
+        let device = self.gpu.device();
+        let queue = self.gpu.queue();
+        let resources = &mut self.resources;
+        match change {
+            AssetChange::Shader(path) => {
+                let reload = resources.shader_manager.reload_shader(&device, path)?;
+                if let Err(e) = self.rebuild_for_shader(path) {
+                    self.resources.shader_manager.revert(reload);
+                    return Err(e);
+                }
+            }
+            AssetChange::Texture(file_name) => {
+                if !resources
+                    .texture_manager
+                    .textures
+                    .contains(&CacheKey::from(file_name.as_str()))
+                {
+                    return Ok(());
+                }
+                if resources
+                    .texture_manager
+                    .reload_texture(&device, &queue, file_name)?
+                {
+                    rebuild_material_bind_groups(
+                        &device,
+                        &queue,
+                        CacheKey::from(file_name.as_str()),
+                        &resources.material_manager,
+                        &mut resources.texture_manager,
+                        &mut resources.buffer_manager,
+                        &mut resources.bind_group_manager,
+                    )?;
+                }
+            }
+            AssetChange::Model(file_name) => {
+                if resources.model_manager.files.contains(file_name) {
+                    self.reload_model(file_name)?;
+                }
+            }
+            AssetChange::Material(_) => {
+                for file_name in resources.model_manager.files.clone() {
+                    self.reload_model(&file_name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn reload_model(&mut self, file_name: &str) -> Result<(), AppError> {
+        let device = self.gpu.device();
+        let queue = self.gpu.queue();
+        let (previous, model) = pollster::block_on(ModelManager::reload_model_from_file(
+            file_name,
+            &device,
+            &queue,
+            &mut self.resources,
+        ))?;
+        if let Some(previous) = previous {
+            self.world.query_mut::<Model>(|_, component| {
+                if component.mesh_ids == previous.mesh_ids {
+                    *component = model.clone();
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn drain_events(&mut self) -> Vec<RupyAppEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    pub fn execute_command(&mut self, command: &str) -> Result<String, AppError> {
+        let args: Vec<&str> = command.split_whitespace().collect();
+        match args.split_first() {
+            Some((&"post", args)) => self.renderer.post.settings.apply_command(args),
+            Some((&"msaa", [])) => Ok(format!(
+                "MSAA: {}x (supported: {:?})",
+                self.resources.pipeline_manager.sample_count(),
+                self.supported_sample_counts()
+            )),
+            Some((&"msaa", [count])) => {
+                let count = count.parse::<u32>().map_err(|_| {
+                    AppError::ConfigError(format!("Expected a sample count, got {}", count))
+                })?;
+                self.set_sample_count(count)?;
+                Ok(format!(
+                    "MSAA: {}x",
+                    self.resources.pipeline_manager.sample_count()
+                ))
+            }
+            Some((name, _)) => Err(AppError::ConfigError(format!("Unknown command: {}", name))),
+            None => Ok(String::new()),
+        }
+    }
+
+    pub fn supported_sample_counts(&self) -> Vec<u32> {
+        MsaaSettings::supported_sample_counts(
+            &self.gpu.adapter(),
+            &self.gpu.device(),
+            &[self.renderer.post.format(), Texture::DEPTH_FORMAT],
+        )
+    }
+
+    pub fn set_sample_count(&mut self, sample_count: u32) -> Result<(), AppError> {
+        let supported = self.supported_sample_counts();
+        if !supported.contains(&sample_count) {
+            return Err(AppError::ConfigError(format!(
+                "{}x MSAA is unsupported (supported: {:?})",
+                sample_count, supported
+            )));
+        }
+        let device = self.gpu.device();
+        let resources = &mut self.resources;
+        resources.pipeline_manager.set_sample_count(
+            &device,
+            sample_count,
+            &resources.bind_group_manager.bind_group_layouts,
+            &mut resources.shader_manager,
+        )?;
+        self.renderer.set_sample_count(&device, sample_count);
+        Ok(())
     }
 
     pub fn compute_metrics(&mut self) {
@@ -269,14 +654,23 @@ impl State {
     pub fn update_lighting(&mut self) {
         let device = &self.gpu().device();
         let queue = &self.gpu().queue();
+        if let Some(demo_light) = self.demo_light {
+            let _ = self.world.query_mut::<Transform>(|entity, transform| {
+                if entity == demo_light {
+                    transform.position =
+                        Quaternion::from_axis_angle(Vector3::unit_y(), cgmath::Deg(1.0))
+                            * transform.position;
+                }
+            });
+        }
+        let scene_lights = lighting::collect_lights(&self.world);
+        self.uniforms.lighting = scene_lights.primary;
+        self.renderer
+            .clusters
+            .upload_lights(queue, &scene_lights.lights);
+
         let resources = &mut self.resources;
         let cache_id = CacheKey::from("bind:group:light");
-        let old_position: cgmath::Vector3<_> = self.uniforms.lighting.position.into();
-        self.uniforms.lighting.position =
-            (cgmath::Quaternion::from_axis_angle((0.0, 1.0, 0.0).into(), cgmath::Deg(1.0))
-                * old_position)
-                .into();
-
         if let Ok(buffer) = resources
             .buffer_manager
             .get_or_create_buffer(cache_id, || Ok(BufferFactory::create_light_buffer(device)))
@@ -294,9 +688,8 @@ impl State {
     pub fn resize<P: winit::dpi::Pixel>(&mut self, size: PhysicalSize<P>) {
         if self.target.update_config_size(size) {
             let device = self.gpu.device();
-            self.target.surface.configure(&device, &self.target.config);
-            self.renderer
-                .resize_textures(&device, size, &self.resources);
+            self.target.configure(&device);
+            self.renderer.resize_textures(size);
             self.camera_handler.set_aspect_ratio_from_size(size);
         };
     }
@@ -317,24 +710,145 @@ impl State {
                 Ok(_) => {
                     self.compute_metrics();
                 }
-                Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                    self.resize(self.window.inner_size());
+                Err(AppError::SurfaceError(
+                    wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated,
+                )) => {
+                    self.resize(self.target.size());
                 }
-                Err(wgpu::SurfaceError::OutOfMemory) => {
+                Err(AppError::SurfaceError(wgpu::SurfaceError::OutOfMemory)) => {
                     log_warning!("Out of memory");
                 }
-                Err(wgpu::SurfaceError::Timeout) => {
+                Err(AppError::SurfaceError(wgpu::SurfaceError::Timeout)) => {
                     log_warning!("Surface timeout");
                 }
+                Err(e) => {
+                    log_error!("Render failed: {}", e);
+                }
             }
         }
     }
 }
 impl State {
+    pub async fn load_scene(&mut self, file_name: &str) -> Result<u64, AppError> {
+        let device = self.gpu.device();
+        let queue = self.gpu.queue();
+        load_scene_file(
+            file_name,
+            &device,
+            &queue,
+            &mut self.world,
+            &mut self.resources,
+        )
+        .await
+    }
+
+    pub fn render_frame(&mut self) -> Result<(), AppError> {
+        let device = self.gpu.device();
+        let queue = self.gpu.queue();
+        self.renderer.render(
+            &device,
+            &queue,
+            &self.target,
+            &mut self.world,
+            &mut self.resources,
+            &self.camera_handler,
+            &self.frustum,
+            &self.uniforms,
+        )?;
+        self.compute_metrics();
+        Ok(())
+    }
+
+    pub fn render_to_image(&mut self) -> Result<RgbaImage, AppError> {
+        self.render_frame()?;
+        match &self.target {
+            RenderTarget::Offscreen(offscreen) => {
+                offscreen.read_pixels(&self.gpu.device(), &self.gpu.queue())
+            }
+            RenderTarget::Surface(_) => Err(AppError::GPUResourceError(String::from(
+                "render_to_image requires an offscreen target",
+            ))),
+        }
+    }
+
+    pub fn save_png<P: AsRef<std::path::Path>>(&mut self, path: P) -> Result<(), AppError> {
+        let image = self.render_to_image()?;
+        image.save(path.as_ref())?;
+        log_info!("Wrote {:?}", path.as_ref());
+        Ok(())
+    }
+
     pub fn input(&mut self, event: &WindowEvent, delta_time: f32) {
         let camera_handler = &mut self.camera_handler;
         camera_handler
             .controller
             .process_movement(event, &mut camera_handler.view, delta_time);
+
+        if let WindowEvent::MouseInput {
+            state: ElementState::Pressed,
+            button: MouseButton::Left,
+            ..
+        } = event
+        {
+            let origin = self.camera_handler.position();
+            let forward = self.camera_handler.forward();
+            if let Some(hit) = self.pick(origin, forward) {
+                log_info!(
+                    "Picked entity {} instance {} at {:.2}",
+                    hit.target.entity.id,
+                    hit.target.instance,
+                    hit.distance
+                );
+                self.pending_events.push(RupyAppEvent::ObjectPicked {
+                    object_id: hit.target.entity.id as u64,
+                    instance: hit.target.instance,
+                    distance: hit.distance,
+                });
+            }
+        }
+    }
+}
+
+fn spawn_default_lights(world: &mut World) -> Option<Entity> {
+    let sun = world.create_entity();
+    let sun_transform = Transform {
+        rotation: Quaternion::from_arc(
+            -Vector3::unit_z(),
+            Vector3::from(DEFAULT_SUN_DIRECTION).normalize(),
+            None,
+        ),
+        ..Transform::identity()
+    };
+    if let Err(e) = world.add_component(sun, sun_transform).and_then(|_| {
+        world.add_component(
+            sun,
+            Light::directional([1.0, 1.0, 1.0], DEFAULT_SUN_INTENSITY),
+        )
+    }) {
+        log_error!("Failed to spawn the sun light: {:?}", e);
+    }
+
+    let demo_light = world.create_entity();
+    let demo_transform = Transform {
+        position: Vector3::new(
+            DEMO_LIGHT_POSITION[0],
+            DEMO_LIGHT_POSITION[1],
+            DEMO_LIGHT_POSITION[2],
+        ),
+        ..Transform::identity()
+    };
+    match world
+        .add_component(demo_light, demo_transform)
+        .and_then(|_| {
+            world.add_component(
+                demo_light,
+                Light::point([1.0, 1.0, 1.0], DEMO_LIGHT_INTENSITY, DEMO_LIGHT_RANGE),
+            )
+        }) {
+        Ok(()) => Some(demo_light),
+        Err(e) => {
+            log_error!("Failed to spawn the demo light: {:?}", e);
+            None
+        }
     }
 }
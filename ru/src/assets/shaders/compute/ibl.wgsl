@@ -0,0 +1,171 @@
+const PI: f32 = 3.14159265359;
+const IRRADIANCE_STEP: f32 = 0.05;
+const DOWNSAMPLE_TAPS: u32 = 8u;
+
+struct IblParams {
+    roughness: f32,
+    sample_count: u32,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var src_map: texture_cube<f32>;
+@group(0) @binding(1)
+var src_sampler: sampler;
+@group(0) @binding(2)
+var dst: texture_storage_2d_array<rgba16float, write>;
+@group(0) @binding(3)
+var<uniform> params: IblParams;
+
+@group(0) @binding(4)
+var brdf_lut: texture_storage_2d<rgba16float, write>;
+
+// Face order and orientation follow the cube sampling convention, so texels
+// written here are read back at the same direction by `textureSample`.
+fn cube_direction(face: u32, uv: vec2<f32>) -> vec3<f32> {
+    let st = uv * 2.0 - 1.0;
+    switch face {
+        case 0u: { return normalize(vec3<f32>(1.0, -st.y, -st.x)); }
+        case 1u: { return normalize(vec3<f32>(-1.0, -st.y, st.x)); }
+        case 2u: { return normalize(vec3<f32>(st.x, 1.0, st.y)); }
+        case 3u: { return normalize(vec3<f32>(st.x, -1.0, -st.y)); }
+        case 4u: { return normalize(vec3<f32>(st.x, -st.y, 1.0)); }
+        default: { return normalize(vec3<f32>(-st.x, -st.y, -1.0)); }
+    }
+}
+
+fn tangent_basis(n: vec3<f32>) -> mat3x3<f32> {
+    let up = select(vec3<f32>(1.0, 0.0, 0.0), vec3<f32>(0.0, 1.0, 0.0), abs(n.y) < 0.999);
+    let tangent = normalize(cross(up, n));
+    let bitangent = cross(n, tangent);
+    return mat3x3<f32>(tangent, bitangent, n);
+}
+
+fn radical_inverse(bits_in: u32) -> f32 {
+    var bits = bits_in;
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return f32(bits) * 2.3283064365386963e-10;
+}
+
+fn hammersley(i: u32, count: u32) -> vec2<f32> {
+    return vec2<f32>(f32(i) / f32(count), radical_inverse(i));
+}
+
+fn importance_sample_ggx(xi: vec2<f32>, n: vec3<f32>, roughness: f32) -> vec3<f32> {
+    let alpha = roughness * roughness;
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (alpha * alpha - 1.0) * xi.y));
+    let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+    let h = vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+    return normalize(tangent_basis(n) * h);
+}
+
+fn texel_uv(id: vec2<u32>, offset: vec2<f32>) -> vec2<f32> {
+    return (vec2<f32>(id) + offset) / vec2<f32>(textureDimensions(dst));
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn downsample(@builtin(global_invocation_id) id: vec3<u32>) {
+    if any(id.xy >= textureDimensions(dst)) {
+        return;
+    }
+    var color = vec3<f32>(0.0);
+    for (var y = 0u; y < DOWNSAMPLE_TAPS; y++) {
+        for (var x = 0u; x < DOWNSAMPLE_TAPS; x++) {
+            let offset = (vec2<f32>(f32(x), f32(y)) + 0.5) / f32(DOWNSAMPLE_TAPS);
+            let direction = cube_direction(id.z, texel_uv(id.xy, offset));
+            color += textureSampleLevel(src_map, src_sampler, direction, 0.0).rgb;
+        }
+    }
+    color /= f32(DOWNSAMPLE_TAPS * DOWNSAMPLE_TAPS);
+    textureStore(dst, id.xy, id.z, vec4<f32>(color, 1.0));
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn irradiance(@builtin(global_invocation_id) id: vec3<u32>) {
+    if any(id.xy >= textureDimensions(dst)) {
+        return;
+    }
+    let n = cube_direction(id.z, texel_uv(id.xy, vec2<f32>(0.5)));
+    let basis = tangent_basis(n);
+    var sum = vec3<f32>(0.0);
+    var count = 0.0;
+    for (var phi = 0.0; phi < 2.0 * PI; phi += IRRADIANCE_STEP) {
+        for (var theta = 0.0; theta < 0.5 * PI; theta += IRRADIANCE_STEP) {
+            let local = vec3<f32>(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            let radiance = textureSampleLevel(src_map, src_sampler, basis * local, 0.0).rgb;
+            sum += radiance * cos(theta) * sin(theta);
+            count += 1.0;
+        }
+    }
+    textureStore(dst, id.xy, id.z, vec4<f32>(PI * sum / count, 1.0));
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn prefilter(@builtin(global_invocation_id) id: vec3<u32>) {
+    if any(id.xy >= textureDimensions(dst)) {
+        return;
+    }
+    let n = cube_direction(id.z, texel_uv(id.xy, vec2<f32>(0.5)));
+    if params.roughness <= 0.0 {
+        textureStore(dst, id.xy, id.z, textureSampleLevel(src_map, src_sampler, n, 0.0));
+        return;
+    }
+    var sum = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i = 0u; i < params.sample_count; i++) {
+        let h = importance_sample_ggx(hammersley(i, params.sample_count), n, params.roughness);
+        let l = normalize(2.0 * dot(n, h) * h - n);
+        let n_dot_l = dot(n, l);
+        if n_dot_l > 0.0 {
+            sum += textureSampleLevel(src_map, src_sampler, l, 0.0).rgb * n_dot_l;
+            weight += n_dot_l;
+        }
+    }
+    textureStore(dst, id.xy, id.z, vec4<f32>(sum / max(weight, 1e-4), 1.0));
+}
+
+fn geometry_schlick_ggx(n_dot_x: f32, roughness: f32) -> f32 {
+    let k = roughness * roughness * 0.5;
+    return n_dot_x / (n_dot_x * (1.0 - k) + k);
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn integrate_brdf(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(brdf_lut);
+    if any(id.xy >= size) {
+        return;
+    }
+    let uv = (vec2<f32>(id.xy) + 0.5) / vec2<f32>(size);
+    let n_dot_v = max(uv.x, 1e-3);
+    let roughness = uv.y;
+    let v = vec3<f32>(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    let n = vec3<f32>(0.0, 0.0, 1.0);
+
+    var scale = 0.0;
+    var bias = 0.0;
+    for (var i = 0u; i < params.sample_count; i++) {
+        let h = importance_sample_ggx(hammersley(i, params.sample_count), n, roughness);
+        let l = normalize(2.0 * dot(v, h) * h - v);
+        let n_dot_l = max(l.z, 0.0);
+        let n_dot_h = max(h.z, 0.0);
+        let v_dot_h = max(dot(v, h), 0.0);
+        if n_dot_l > 0.0 {
+            let g = geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+            let g_vis = g * v_dot_h / max(n_dot_h * n_dot_v, 1e-4);
+            let fc = pow(1.0 - v_dot_h, 5.0);
+            scale += (1.0 - fc) * g_vis;
+            bias += fc * g_vis;
+        }
+    }
+    let count = f32(params.sample_count);
+    textureStore(brdf_lut, id.xy, vec4<f32>(scale / count, bias / count, 0.0, 1.0));
+}
@@ -0,0 +1,91 @@
+struct Light {
+    position: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    intensity: f32,
+    direction: vec3<f32>,
+    kind: u32,
+    spot_scale: f32,
+    spot_offset: f32,
+    shadow: u32,
+    _padding: u32,
+};
+
+struct ClusterInfo {
+    view: mat4x4<f32>,
+    screen_size: vec2<f32>,
+    z_near: f32,
+    z_far: f32,
+    tan_half_fov: vec2<f32>,
+    _padding: vec2<f32>,
+    grid: vec3<u32>,
+    light_count: u32,
+};
+
+const LIGHT_DIRECTIONAL: u32 = 2u;
+const MAX_LIGHTS_PER_CLUSTER: u32 = 63u;
+const CLUSTER_STRIDE: u32 = 64u;
+
+@group(0) @binding(0)
+var<uniform> info: ClusterInfo;
+@group(0) @binding(1)
+var<storage, read> lights: array<Light>;
+@group(0) @binding(2)
+var<storage, read_write> clusters: array<u32>;
+
+fn slice_depth(slice: u32) -> f32 {
+    return info.z_near * pow(info.z_far / info.z_near, f32(slice) / f32(info.grid.z));
+}
+
+fn view_point(ndc: vec2<f32>, depth: f32) -> vec3<f32> {
+    return vec3<f32>(ndc * info.tan_half_fov * depth, -depth);
+}
+
+@compute
+@workgroup_size(4, 3, 4)
+fn assign_lights(@builtin(global_invocation_id) id: vec3<u32>) {
+    if any(id >= info.grid) {
+        return;
+    }
+    let cluster = id.x + id.y * info.grid.x + id.z * info.grid.x * info.grid.y;
+
+    // Tiles are indexed from the top-left corner of the screen, like fragment coordinates.
+    let tile = vec2<f32>(info.grid.xy);
+    let ndc_min = vec2<f32>(f32(id.x) / tile.x * 2.0 - 1.0, 1.0 - f32(id.y + 1u) / tile.y * 2.0);
+    let ndc_max = vec2<f32>(f32(id.x + 1u) / tile.x * 2.0 - 1.0, 1.0 - f32(id.y) / tile.y * 2.0);
+    let near = slice_depth(id.z);
+    let far = slice_depth(id.z + 1u);
+
+    var aabb_min = vec3<f32>(3.4e38);
+    var aabb_max = vec3<f32>(-3.4e38);
+    for (var corner = 0u; corner < 8u; corner++) {
+        let ndc = vec2<f32>(
+            select(ndc_min.x, ndc_max.x, (corner & 1u) != 0u),
+            select(ndc_min.y, ndc_max.y, (corner & 2u) != 0u),
+        );
+        let point = view_point(ndc, select(near, far, (corner & 4u) != 0u));
+        aabb_min = min(aabb_min, point);
+        aabb_max = max(aabb_max, point);
+    }
+
+    var count = 0u;
+    let base = cluster * CLUSTER_STRIDE;
+    for (var i = 0u; i < info.light_count; i++) {
+        if count >= MAX_LIGHTS_PER_CLUSTER {
+            break;
+        }
+        let light = lights[i];
+        var visible = light.kind == LIGHT_DIRECTIONAL;
+        if !visible {
+            let center = (info.view * vec4<f32>(light.position, 1.0)).xyz;
+            let closest = clamp(center, aabb_min, aabb_max);
+            let offset = closest - center;
+            visible = dot(offset, offset) <= light.range * light.range;
+        }
+        if visible {
+            clusters[base + 1u + count] = i;
+            count++;
+        }
+    }
+    clusters[base] = count;
+}
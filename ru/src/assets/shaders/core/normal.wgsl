@@ -15,25 +15,246 @@ var t_normal: texture_2d<f32>;
 @group(0) @binding(3)
 var s_normal: sampler;
 
+struct Material {
+    base_color_factor: vec4<f32>,
+    ambient: vec3<f32>,
+    shininess: f32,
+    diffuse: vec3<f32>,
+    dissolve: f32,
+    specular: vec3<f32>,
+    illumination_model: u32,
+    emissive_factor: vec3<f32>,
+    metallic_factor: f32,
+    roughness_factor: f32,
+    occlusion_strength: f32,
+    normal_scale: f32,
+    optical_density: f32,
+};
+
+@group(0) @binding(4)
+var<uniform> material: Material;
+
 @group(1) @binding(0)
 var<uniform> camera: Camera;
 
+#ifdef SKINNED
+@group(1) @binding(1)
+var<storage, read> joint_matrices: array<mat4x4<f32>>;
+
+fn skin_matrix(joints: vec4<u32>, weights: vec4<f32>) -> mat4x4<f32> {
+    return joint_matrices[joints.x] * weights.x
+        + joint_matrices[joints.y] * weights.y
+        + joint_matrices[joints.z] * weights.z
+        + joint_matrices[joints.w] * weights.w;
+}
+#endif
+
 struct Light {
     position: vec3<f32>,
+    range: f32,
     color: vec3<f32>,
+    intensity: f32,
+    direction: vec3<f32>,
+    kind: u32,
+    spot_scale: f32,
+    spot_offset: f32,
+    shadow: u32,
+    _padding: u32,
+}
+
+struct ClusterInfo {
+    view: mat4x4<f32>,
+    screen_size: vec2<f32>,
+    z_near: f32,
+    z_far: f32,
+    tan_half_fov: vec2<f32>,
+    _padding: vec2<f32>,
+    grid: vec3<u32>,
+    light_count: u32,
+}
+
+struct ShadowView {
+    view_proj: mat4x4<f32>,
+    rect: vec4<f32>,
+}
+
+struct Shadows {
+    views: array<ShadowView, 10>,
+    cascade_splits: vec4<f32>,
+    camera_forward: vec3<f32>,
+    cascade_count: u32,
+    depth_bias: f32,
+    slope_bias: f32,
+    normal_bias: f32,
+    pcf_radius: u32,
+    texel_size: vec2<f32>,
+    sun_enabled: u32,
+    point_enabled: u32,
+    point_position: vec3<f32>,
+    point_far: f32,
+}
+
+@group(2) @binding(1)
+var<uniform> shadows: Shadows;
+@group(2) @binding(2)
+var shadow_atlas: texture_depth_2d;
+@group(2) @binding(3)
+var shadow_sampler: sampler_comparison;
+@group(2) @binding(4)
+var<uniform> cluster_info: ClusterInfo;
+@group(2) @binding(5)
+var<storage, read> lights: array<Light>;
+@group(2) @binding(6)
+var<storage, read> light_clusters: array<u32>;
+
+const CUBE_FACE_OFFSET: u32 = 4u;
+
+fn shadow_bias(n_dot_l: f32) -> f32 {
+    return shadows.depth_bias + shadows.slope_bias * (1.0 - clamp(n_dot_l, 0.0, 1.0));
+}
+
+fn shadow_offset_position(world_position: vec3<f32>, normal: vec3<f32>, n_dot_l: f32) -> vec3<f32> {
+    return world_position + normal * shadows.normal_bias * (1.0 - clamp(n_dot_l, 0.0, 1.0));
+}
+
+fn shadow_tile_uv(clip: vec4<f32>) -> vec3<f32> {
+    let ndc = clip.xyz / clip.w;
+    return vec3<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5, ndc.z);
+}
+
+fn sample_shadow_tile(view: ShadowView, uv: vec2<f32>, reference: f32) -> f32 {
+    let low = view.rect.xy + shadows.texel_size * 0.5;
+    let high = view.rect.xy + view.rect.zw - shadows.texel_size * 0.5;
+    let center = view.rect.xy + uv * view.rect.zw;
+    let radius = i32(shadows.pcf_radius);
+    var lit = 0.0;
+    for (var y = -radius; y <= radius; y++) {
+        for (var x = -radius; x <= radius; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * shadows.texel_size;
+            lit += textureSampleCompareLevel(shadow_atlas, shadow_sampler, clamp(center + offset, low, high), reference);
+        }
+    }
+    let taps = f32((2 * radius + 1) * (2 * radius + 1));
+    return lit / taps;
 }
 
-@group(2) @binding(0)
-var<uniform> light: Light;
+fn sun_shadow(world_position: vec3<f32>, normal: vec3<f32>, view_position: vec3<f32>, n_dot_l: f32) -> f32 {
+    if shadows.sun_enabled == 0u {
+        return 1.0;
+    }
+    let depth = dot(world_position - view_position, shadows.camera_forward);
+    var cascade = shadows.cascade_count;
+    for (var i = 0u; i < shadows.cascade_count; i++) {
+        if depth <= shadows.cascade_splits[i] {
+            cascade = i;
+            break;
+        }
+    }
+    if cascade >= shadows.cascade_count {
+        return 1.0;
+    }
+    let view = shadows.views[cascade];
+    let position = shadow_offset_position(world_position, normal, n_dot_l);
+    let coords = shadow_tile_uv(view.view_proj * vec4<f32>(position, 1.0));
+    if any(coords.xy < vec2<f32>(0.0)) || any(coords.xy > vec2<f32>(1.0)) || coords.z > 1.0 {
+        return 1.0;
+    }
+    return sample_shadow_tile(view, coords.xy, coords.z - shadow_bias(n_dot_l));
+}
+
+fn cube_face(direction: vec3<f32>) -> u32 {
+    let magnitude = abs(direction);
+    if magnitude.x >= magnitude.y && magnitude.x >= magnitude.z {
+        return select(1u, 0u, direction.x > 0.0);
+    }
+    if magnitude.y >= magnitude.z {
+        return select(3u, 2u, direction.y > 0.0);
+    }
+    return select(5u, 4u, direction.z > 0.0);
+}
+
+fn point_shadow(world_position: vec3<f32>, normal: vec3<f32>, n_dot_l: f32) -> f32 {
+    if shadows.point_enabled == 0u {
+        return 1.0;
+    }
+    let position = shadow_offset_position(world_position, normal, n_dot_l);
+    let to_fragment = position - shadows.point_position;
+    let distance = length(to_fragment);
+    if distance >= shadows.point_far {
+        return 1.0;
+    }
+    let view = shadows.views[CUBE_FACE_OFFSET + cube_face(to_fragment)];
+    let coords = shadow_tile_uv(view.view_proj * vec4<f32>(position, 1.0));
+    let uv = clamp(coords.xy, vec2<f32>(0.0), vec2<f32>(1.0));
+    return sample_shadow_tile(view, uv, distance / shadows.point_far - shadow_bias(n_dot_l));
+}
+
+const LIGHT_SPOT: u32 = 1u;
+const LIGHT_DIRECTIONAL: u32 = 2u;
+const SHADOW_SUN: u32 = 1u;
+const SHADOW_POINT: u32 = 2u;
+const MAX_LIGHTS_PER_CLUSTER: u32 = 63u;
+const CLUSTER_STRIDE: u32 = 64u;
+
+struct IncomingLight {
+    direction: vec3<f32>,
+    radiance: vec3<f32>,
+}
+
+fn cluster_base(frag_coord: vec2<f32>, world_position: vec3<f32>) -> u32 {
+    let grid = cluster_info.grid;
+    let tile = clamp(
+        vec2<u32>(frag_coord / cluster_info.screen_size * vec2<f32>(grid.xy)),
+        vec2<u32>(0u),
+        grid.xy - vec2<u32>(1u),
+    );
+    let depth = max(-(cluster_info.view * vec4<f32>(world_position, 1.0)).z, cluster_info.z_near);
+    let slice_scale = f32(grid.z) / log(cluster_info.z_far / cluster_info.z_near);
+    let slice = min(u32(log(depth / cluster_info.z_near) * slice_scale), grid.z - 1u);
+    return (tile.x + tile.y * grid.x + slice * grid.x * grid.y) * CLUSTER_STRIDE;
+}
+
+fn incoming_light(light: Light, world_position: vec3<f32>) -> IncomingLight {
+    if light.kind == LIGHT_DIRECTIONAL {
+        return IncomingLight(normalize(-light.direction), light.color * light.intensity);
+    }
+    let to_light = light.position - world_position;
+    let distance_squared = max(dot(to_light, to_light), 1e-4);
+    let direction = to_light * inverseSqrt(distance_squared);
+    let falloff = distance_squared / (light.range * light.range);
+    let window = clamp(1.0 - falloff * falloff, 0.0, 1.0);
+    var attenuation = window * window / max(distance_squared, 0.01);
+    if light.kind == LIGHT_SPOT {
+        let cone = clamp(dot(-direction, light.direction) * light.spot_scale + light.spot_offset, 0.0, 1.0);
+        attenuation *= cone * cone;
+    }
+    return IncomingLight(direction, light.color * light.intensity * attenuation);
+}
+
+fn light_visibility(light: Light, world_position: vec3<f32>, normal: vec3<f32>, view_position: vec3<f32>, n_dot_l: f32) -> f32 {
+    if light.shadow == SHADOW_SUN {
+        return sun_shadow(world_position, normal, view_position, n_dot_l);
+    }
+    if light.shadow == SHADOW_POINT {
+        return point_shadow(world_position, normal, n_dot_l);
+    }
+    return 1.0;
+}
 
 
 
 @group(3)
-@binding(0)
-var env_map: texture_cube<f32>;
+@binding(2)
+var irradiance_map: texture_cube<f32>;
 @group(3)
-@binding(1)
-var env_sampler: sampler;
+@binding(3)
+var prefiltered_map: texture_cube<f32>;
+@group(3)
+@binding(4)
+var brdf_lut: texture_2d<f32>;
+@group(3)
+@binding(5)
+var ibl_sampler: sampler;
 
 
 struct VertexInput {
@@ -43,6 +264,10 @@ struct VertexInput {
     @location(2) normal: vec3<f32>,
     @location(3) tangent: vec3<f32>,
     @location(4) bitangent: vec3<f32>,
+#ifdef SKINNED
+    @location(13) joints: vec4<u32>,
+    @location(14) weights: vec4<f32>,
+#endif
 };
 
 struct InstanceInput {
@@ -84,23 +309,28 @@ fn vs_main(
         instance.normal_matrix_2,
     );
 
-    let world_normal = normalize(normal_matrix * model.normal);
-    let world_tangent = normalize(normal_matrix * model.tangent);
-    let world_bitangent = normalize(normal_matrix * model.bitangent);
-    let tangent_matrix = transpose(mat3x3<f32>(
-        world_tangent,
-        world_bitangent,
-        world_normal,
-    ));
+#ifdef SKINNED
+    let skin = skin_matrix(model.joints, model.weights);
+    let skin_normal = mat3x3<f32>(skin[0].xyz, skin[1].xyz, skin[2].xyz);
+    let local_position = (skin * vec4<f32>(model.position, 1.0)).xyz;
+    let local_normal = skin_normal * model.normal;
+    let local_tangent = skin_normal * model.tangent;
+    let local_bitangent = skin_normal * model.bitangent;
+#else
+    let local_position = model.position;
+    let local_normal = model.normal;
+    let local_tangent = model.tangent;
+    let local_bitangent = model.bitangent;
+#endif
 
-    let world_position = model_matrix * vec4<f32>(model.position, 1.0);
+    let world_position = model_matrix * vec4<f32>(local_position, 1.0);
 
     var out: VertexOutput;
     out.clip_position = camera.view_proj * world_position;
     out.tex_coords = model.tex_coords;
-    out.world_normal = normalize(normal_matrix * model.normal);
-    out.world_tangent = normalize(normal_matrix * model.tangent);
-    out.world_bitangent = normalize(normal_matrix * model.bitangent);
+    out.world_normal = normalize(normal_matrix * local_normal);
+    out.world_tangent = normalize(normal_matrix * local_tangent);
+    out.world_bitangent = normalize(normal_matrix * local_bitangent);
     out.world_position = world_position.xyz;
     out.world_view_position = camera.view_position.xyz;
     out.instance_color = instance.color; // Pass instance color
@@ -108,12 +338,18 @@ fn vs_main(
 }
 
 
+// MTL illum 3-9 enable reflection; 0-2 and 10 don't.
+fn has_reflection(illumination_model: u32) -> bool {
+    return illumination_model >= 3u && illumination_model <= 9u;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let object_color: vec4<f32> = textureSample(t_diffuse, s_diffuse, in.tex_coords);
     let object_normal: vec4<f32> = textureSample(t_normal, s_normal, in.tex_coords);
     let world_tangent = normalize(in.world_tangent - dot(in.world_tangent, in.world_normal) * in.world_normal);
-    let world_bitangent = cross(world_tangent, in.world_normal);
+    let handedness = select(-1.0, 1.0, dot(cross(in.world_normal, world_tangent), in.world_bitangent) >= 0.0);
+    let world_bitangent = cross(in.world_normal, world_tangent) * handedness;
 
     let TBN = mat3x3(
         world_tangent,
@@ -123,23 +359,48 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let tangent_normal = object_normal.xyz * 2.0 - 1.0;
     let world_normal = TBN * tangent_normal;
 
- 
-    let light_dir = normalize(light.position - in.world_position);
-    let view_dir = normalize(in.world_view_position - in.world_position);
-    let half_dir = normalize(view_dir + light_dir);
+    let geometric_normal = normalize(in.world_normal);
 
-    let diffuse_strength = max(dot(world_normal, light_dir), 0.0);
-    let diffuse_color = light.color * diffuse_strength;
+    let view_dir = normalize(in.world_view_position - in.world_position);
+    let shininess = max(material.shininess, 1.0);
+    let albedo = object_color.rgb * material.diffuse;
+    let irradiance = textureSampleLevel(irradiance_map, ibl_sampler, world_normal, 0.0).rgb;
+    let ambient_color = material.ambient * irradiance * albedo;
 
-    let specular_strength = pow(max(dot(world_normal, half_dir), 0.0), 32.0);
-    let specular_color = specular_strength * light.color;
+    var diffuse_light = vec3<f32>(0.0);
+    var specular_light = vec3<f32>(0.0);
+    let cluster = cluster_base(in.clip_position.xy, in.world_position);
+    let light_count = min(light_clusters[cluster], MAX_LIGHTS_PER_CLUSTER);
+    for (var i = 0u; i < light_count; i++) {
+        let light = lights[light_clusters[cluster + 1u + i]];
+        let incoming = incoming_light(light, in.world_position);
+        let visibility = light_visibility(light, in.world_position, geometric_normal, in.world_view_position, dot(geometric_normal, incoming.direction));
+        let radiance = incoming.radiance * visibility;
+        let half_dir = normalize(view_dir + incoming.direction);
+        diffuse_light += radiance * max(dot(world_normal, incoming.direction), 0.0);
+        specular_light += radiance * pow(max(dot(world_normal, half_dir), 0.0), shininess);
+    }
+    let diffuse_color = diffuse_light * albedo;
+    let specular_color = specular_light * material.specular;
 
-   
+    let roughness = sqrt(2.0 / (shininess + 2.0));
+    let max_lod = f32(textureNumLevels(prefiltered_map) - 1u);
     let world_reflect = reflect(-view_dir, world_normal);
-    let reflection = textureSample(env_map, env_sampler, world_reflect).rgb;
-    let shininess = 0.1;
+    let prefiltered = textureSampleLevel(prefiltered_map, ibl_sampler, world_reflect, roughness * max_lod).rgb;
+    let env_brdf = textureSampleLevel(brdf_lut, ibl_sampler, vec2<f32>(max(dot(normalize(world_normal), view_dir), 0.0), roughness), 0.0).rg;
+    let reflection = prefiltered * (material.specular * env_brdf.x + env_brdf.y);
 
-    let final_color = (diffuse_color + specular_color) * object_color.xyz + reflection * shininess;
+    var final_color = albedo;
+    if material.illumination_model >= 1u {
+        final_color = ambient_color + diffuse_color;
+    }
+    if material.illumination_model >= 2u {
+        final_color += specular_color;
+    }
+    if has_reflection(material.illumination_model) {
+        final_color += reflection;
+    }
+    final_color += material.emissive_factor;
 
-    return vec4<f32>(final_color * in.instance_color.rgb, object_color.a * in.instance_color.a);
-}
\ No newline at end of file
+    return vec4<f32>(final_color * in.instance_color.rgb, object_color.a * material.dissolve * in.instance_color.a);
+}
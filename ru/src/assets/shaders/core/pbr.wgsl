@@ -0,0 +1,435 @@
+struct Camera {
+    view_position: vec4<f32>,
+    view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+};
+
+struct Material {
+    base_color_factor: vec4<f32>,
+    ambient: vec3<f32>,
+    shininess: f32,
+    diffuse: vec3<f32>,
+    dissolve: f32,
+    specular: vec3<f32>,
+    illumination_model: u32,
+    emissive_factor: vec3<f32>,
+    metallic_factor: f32,
+    roughness_factor: f32,
+    occlusion_strength: f32,
+    normal_scale: f32,
+    optical_density: f32,
+};
+
+@group(0) @binding(0)
+var t_base_color: texture_2d<f32>;
+@group(0) @binding(1)
+var s_base_color: sampler;
+@group(0) @binding(2)
+var t_normal: texture_2d<f32>;
+@group(0) @binding(3)
+var s_normal: sampler;
+@group(0) @binding(4)
+var t_metallic_roughness: texture_2d<f32>;
+@group(0) @binding(5)
+var s_metallic_roughness: sampler;
+@group(0) @binding(6)
+var t_occlusion: texture_2d<f32>;
+@group(0) @binding(7)
+var s_occlusion: sampler;
+@group(0) @binding(8)
+var t_emissive: texture_2d<f32>;
+@group(0) @binding(9)
+var s_emissive: sampler;
+@group(0) @binding(10)
+var<uniform> material: Material;
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
+#ifdef SKINNED
+@group(1) @binding(1)
+var<storage, read> joint_matrices: array<mat4x4<f32>>;
+
+fn skin_matrix(joints: vec4<u32>, weights: vec4<f32>) -> mat4x4<f32> {
+    return joint_matrices[joints.x] * weights.x
+        + joint_matrices[joints.y] * weights.y
+        + joint_matrices[joints.z] * weights.z
+        + joint_matrices[joints.w] * weights.w;
+}
+#endif
+
+struct Light {
+    position: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    intensity: f32,
+    direction: vec3<f32>,
+    kind: u32,
+    spot_scale: f32,
+    spot_offset: f32,
+    shadow: u32,
+    _padding: u32,
+}
+
+struct ClusterInfo {
+    view: mat4x4<f32>,
+    screen_size: vec2<f32>,
+    z_near: f32,
+    z_far: f32,
+    tan_half_fov: vec2<f32>,
+    _padding: vec2<f32>,
+    grid: vec3<u32>,
+    light_count: u32,
+}
+
+struct ShadowView {
+    view_proj: mat4x4<f32>,
+    rect: vec4<f32>,
+}
+
+struct Shadows {
+    views: array<ShadowView, 10>,
+    cascade_splits: vec4<f32>,
+    camera_forward: vec3<f32>,
+    cascade_count: u32,
+    depth_bias: f32,
+    slope_bias: f32,
+    normal_bias: f32,
+    pcf_radius: u32,
+    texel_size: vec2<f32>,
+    sun_enabled: u32,
+    point_enabled: u32,
+    point_position: vec3<f32>,
+    point_far: f32,
+}
+
+@group(2) @binding(1)
+var<uniform> shadows: Shadows;
+@group(2) @binding(2)
+var shadow_atlas: texture_depth_2d;
+@group(2) @binding(3)
+var shadow_sampler: sampler_comparison;
+@group(2) @binding(4)
+var<uniform> cluster_info: ClusterInfo;
+@group(2) @binding(5)
+var<storage, read> lights: array<Light>;
+@group(2) @binding(6)
+var<storage, read> light_clusters: array<u32>;
+
+const CUBE_FACE_OFFSET: u32 = 4u;
+
+fn shadow_bias(n_dot_l: f32) -> f32 {
+    return shadows.depth_bias + shadows.slope_bias * (1.0 - clamp(n_dot_l, 0.0, 1.0));
+}
+
+fn shadow_offset_position(world_position: vec3<f32>, normal: vec3<f32>, n_dot_l: f32) -> vec3<f32> {
+    return world_position + normal * shadows.normal_bias * (1.0 - clamp(n_dot_l, 0.0, 1.0));
+}
+
+fn shadow_tile_uv(clip: vec4<f32>) -> vec3<f32> {
+    let ndc = clip.xyz / clip.w;
+    return vec3<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5, ndc.z);
+}
+
+fn sample_shadow_tile(view: ShadowView, uv: vec2<f32>, reference: f32) -> f32 {
+    let low = view.rect.xy + shadows.texel_size * 0.5;
+    let high = view.rect.xy + view.rect.zw - shadows.texel_size * 0.5;
+    let center = view.rect.xy + uv * view.rect.zw;
+    let radius = i32(shadows.pcf_radius);
+    var lit = 0.0;
+    for (var y = -radius; y <= radius; y++) {
+        for (var x = -radius; x <= radius; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * shadows.texel_size;
+            lit += textureSampleCompareLevel(shadow_atlas, shadow_sampler, clamp(center + offset, low, high), reference);
+        }
+    }
+    let taps = f32((2 * radius + 1) * (2 * radius + 1));
+    return lit / taps;
+}
+
+fn sun_shadow(world_position: vec3<f32>, normal: vec3<f32>, view_position: vec3<f32>, n_dot_l: f32) -> f32 {
+    if shadows.sun_enabled == 0u {
+        return 1.0;
+    }
+    let depth = dot(world_position - view_position, shadows.camera_forward);
+    var cascade = shadows.cascade_count;
+    for (var i = 0u; i < shadows.cascade_count; i++) {
+        if depth <= shadows.cascade_splits[i] {
+            cascade = i;
+            break;
+        }
+    }
+    if cascade >= shadows.cascade_count {
+        return 1.0;
+    }
+    let view = shadows.views[cascade];
+    let position = shadow_offset_position(world_position, normal, n_dot_l);
+    let coords = shadow_tile_uv(view.view_proj * vec4<f32>(position, 1.0));
+    if any(coords.xy < vec2<f32>(0.0)) || any(coords.xy > vec2<f32>(1.0)) || coords.z > 1.0 {
+        return 1.0;
+    }
+    return sample_shadow_tile(view, coords.xy, coords.z - shadow_bias(n_dot_l));
+}
+
+fn cube_face(direction: vec3<f32>) -> u32 {
+    let magnitude = abs(direction);
+    if magnitude.x >= magnitude.y && magnitude.x >= magnitude.z {
+        return select(1u, 0u, direction.x > 0.0);
+    }
+    if magnitude.y >= magnitude.z {
+        return select(3u, 2u, direction.y > 0.0);
+    }
+    return select(5u, 4u, direction.z > 0.0);
+}
+
+fn point_shadow(world_position: vec3<f32>, normal: vec3<f32>, n_dot_l: f32) -> f32 {
+    if shadows.point_enabled == 0u {
+        return 1.0;
+    }
+    let position = shadow_offset_position(world_position, normal, n_dot_l);
+    let to_fragment = position - shadows.point_position;
+    let distance = length(to_fragment);
+    if distance >= shadows.point_far {
+        return 1.0;
+    }
+    let view = shadows.views[CUBE_FACE_OFFSET + cube_face(to_fragment)];
+    let coords = shadow_tile_uv(view.view_proj * vec4<f32>(position, 1.0));
+    let uv = clamp(coords.xy, vec2<f32>(0.0), vec2<f32>(1.0));
+    return sample_shadow_tile(view, uv, distance / shadows.point_far - shadow_bias(n_dot_l));
+}
+
+const LIGHT_SPOT: u32 = 1u;
+const LIGHT_DIRECTIONAL: u32 = 2u;
+const SHADOW_SUN: u32 = 1u;
+const SHADOW_POINT: u32 = 2u;
+const MAX_LIGHTS_PER_CLUSTER: u32 = 63u;
+const CLUSTER_STRIDE: u32 = 64u;
+
+struct IncomingLight {
+    direction: vec3<f32>,
+    radiance: vec3<f32>,
+}
+
+fn cluster_base(frag_coord: vec2<f32>, world_position: vec3<f32>) -> u32 {
+    let grid = cluster_info.grid;
+    let tile = clamp(
+        vec2<u32>(frag_coord / cluster_info.screen_size * vec2<f32>(grid.xy)),
+        vec2<u32>(0u),
+        grid.xy - vec2<u32>(1u),
+    );
+    let depth = max(-(cluster_info.view * vec4<f32>(world_position, 1.0)).z, cluster_info.z_near);
+    let slice_scale = f32(grid.z) / log(cluster_info.z_far / cluster_info.z_near);
+    let slice = min(u32(log(depth / cluster_info.z_near) * slice_scale), grid.z - 1u);
+    return (tile.x + tile.y * grid.x + slice * grid.x * grid.y) * CLUSTER_STRIDE;
+}
+
+fn incoming_light(light: Light, world_position: vec3<f32>) -> IncomingLight {
+    if light.kind == LIGHT_DIRECTIONAL {
+        return IncomingLight(normalize(-light.direction), light.color * light.intensity);
+    }
+    let to_light = light.position - world_position;
+    let distance_squared = max(dot(to_light, to_light), 1e-4);
+    let direction = to_light * inverseSqrt(distance_squared);
+    let falloff = distance_squared / (light.range * light.range);
+    let window = clamp(1.0 - falloff * falloff, 0.0, 1.0);
+    var attenuation = window * window / max(distance_squared, 0.01);
+    if light.kind == LIGHT_SPOT {
+        let cone = clamp(dot(-direction, light.direction) * light.spot_scale + light.spot_offset, 0.0, 1.0);
+        attenuation *= cone * cone;
+    }
+    return IncomingLight(direction, light.color * light.intensity * attenuation);
+}
+
+fn light_visibility(light: Light, world_position: vec3<f32>, normal: vec3<f32>, view_position: vec3<f32>, n_dot_l: f32) -> f32 {
+    if light.shadow == SHADOW_SUN {
+        return sun_shadow(world_position, normal, view_position, n_dot_l);
+    }
+    if light.shadow == SHADOW_POINT {
+        return point_shadow(world_position, normal, n_dot_l);
+    }
+    return 1.0;
+}
+
+@group(3)
+@binding(2)
+var irradiance_map: texture_cube<f32>;
+@group(3)
+@binding(3)
+var prefiltered_map: texture_cube<f32>;
+@group(3)
+@binding(4)
+var brdf_lut: texture_2d<f32>;
+@group(3)
+@binding(5)
+var ibl_sampler: sampler;
+
+const PI: f32 = 3.14159265359;
+const MIN_ROUGHNESS: f32 = 0.045;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) tangent: vec3<f32>,
+    @location(4) bitangent: vec3<f32>,
+#ifdef SKINNED
+    @location(13) joints: vec4<u32>,
+    @location(14) weights: vec4<f32>,
+#endif
+};
+
+struct InstanceInput {
+    @location(5) model_matrix_0: vec4<f32>,
+    @location(6) model_matrix_1: vec4<f32>,
+    @location(7) model_matrix_2: vec4<f32>,
+    @location(8) model_matrix_3: vec4<f32>,
+    @location(9) normal_matrix_0: vec3<f32>,
+    @location(10) normal_matrix_1: vec3<f32>,
+    @location(11) normal_matrix_2: vec3<f32>,
+    @location(12) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) world_position: vec3<f32>,
+    @location(2) world_view_position: vec3<f32>,
+    @location(3) world_normal: vec3<f32>,
+    @location(4) world_tangent: vec3<f32>,
+    @location(5) instance_color: vec4<f32>,
+    @location(6) tangent_handedness: f32,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    let model_matrix = mat4x4<f32>(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+    let normal_matrix = mat3x3<f32>(
+        instance.normal_matrix_0,
+        instance.normal_matrix_1,
+        instance.normal_matrix_2,
+    );
+#ifdef SKINNED
+    let skin = skin_matrix(model.joints, model.weights);
+    let skin_normal = mat3x3<f32>(skin[0].xyz, skin[1].xyz, skin[2].xyz);
+    let local_position = (skin * vec4<f32>(model.position, 1.0)).xyz;
+    let local_normal = skin_normal * model.normal;
+    let local_tangent = skin_normal * model.tangent;
+    let local_bitangent = skin_normal * model.bitangent;
+#else
+    let local_position = model.position;
+    let local_normal = model.normal;
+    let local_tangent = model.tangent;
+    let local_bitangent = model.bitangent;
+#endif
+    let world_position = model_matrix * vec4<f32>(local_position, 1.0);
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * world_position;
+    out.tex_coords = model.tex_coords;
+    out.world_position = world_position.xyz;
+    out.world_view_position = camera.view_position.xyz;
+    out.world_normal = normalize(normal_matrix * local_normal);
+    out.world_tangent = normal_matrix * local_tangent;
+    out.instance_color = instance.color;
+    out.tangent_handedness = select(-1.0, 1.0, dot(cross(local_normal, local_tangent), local_bitangent) >= 0.0);
+    return out;
+}
+
+fn distribution_ggx(n_dot_h: f32, alpha: f32) -> f32 {
+    let alpha2 = alpha * alpha;
+    let denom = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    return alpha2 / (PI * denom * denom);
+}
+
+fn visibility_smith_ggx(n_dot_v: f32, n_dot_l: f32, alpha: f32) -> f32 {
+    let alpha2 = alpha * alpha;
+    let ggx_v = n_dot_l * sqrt(n_dot_v * n_dot_v * (1.0 - alpha2) + alpha2);
+    let ggx_l = n_dot_v * sqrt(n_dot_l * n_dot_l * (1.0 - alpha2) + alpha2);
+    return 0.5 / max(ggx_v + ggx_l, 1e-5);
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: vec3<f32>) -> vec3<f32> {
+    return f0 + (vec3<f32>(1.0) - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+fn fresnel_schlick_roughness(cos_theta: f32, f0: vec3<f32>, roughness: f32) -> vec3<f32> {
+    return f0 + (max(vec3<f32>(1.0 - roughness), f0) - f0) * pow(1.0 - cos_theta, 5.0);
+}
+
+fn brdf(n: vec3<f32>, v: vec3<f32>, l: vec3<f32>, f0: vec3<f32>, diffuse_color: vec3<f32>, alpha: f32, n_dot_v: f32) -> vec3<f32> {
+    let h = normalize(v + l);
+    let n_dot_l = max(dot(n, l), 0.0);
+    let n_dot_h = max(dot(n, h), 0.0);
+    let v_dot_h = max(dot(v, h), 0.0);
+
+    let f = fresnel_schlick(v_dot_h, f0);
+    let specular = distribution_ggx(n_dot_h, alpha) * visibility_smith_ggx(n_dot_v, n_dot_l, alpha) * f;
+    let k_d = (vec3<f32>(1.0) - f) * diffuse_color / PI;
+    return (k_d + specular) * n_dot_l;
+}
+
+fn perturb_normal(in: VertexOutput) -> vec3<f32> {
+    let n = normalize(in.world_normal);
+    let t_raw = in.world_tangent - dot(in.world_tangent, n) * n;
+    if dot(t_raw, t_raw) < 1e-8 {
+        return n;
+    }
+    let t = normalize(t_raw);
+    let b = cross(n, t) * in.tangent_handedness;
+    var tangent_normal = textureSample(t_normal, s_normal, in.tex_coords).xyz * 2.0 - 1.0;
+    tangent_normal = vec3<f32>(tangent_normal.xy * material.normal_scale, tangent_normal.z);
+    return normalize(mat3x3<f32>(t, b, n) * tangent_normal);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base_color = textureSample(t_base_color, s_base_color, in.tex_coords) * material.base_color_factor;
+    let metallic_roughness = textureSample(t_metallic_roughness, s_metallic_roughness, in.tex_coords);
+    let metallic = clamp(metallic_roughness.b * material.metallic_factor, 0.0, 1.0);
+    let roughness = clamp(metallic_roughness.g * material.roughness_factor, MIN_ROUGHNESS, 1.0);
+    let ao = 1.0 + material.occlusion_strength * (textureSample(t_occlusion, s_occlusion, in.tex_coords).r - 1.0);
+    let emissive = textureSample(t_emissive, s_emissive, in.tex_coords).rgb * material.emissive_factor;
+
+    let n = perturb_normal(in);
+    let v = normalize(in.world_view_position - in.world_position);
+    let n_dot_v = max(dot(n, v), 1e-4);
+
+    let albedo = base_color.rgb;
+    let f0 = mix(vec3<f32>(0.04), albedo, metallic);
+    let diffuse_color = albedo * (1.0 - metallic);
+    let alpha = roughness * roughness;
+
+    let geometric_normal = normalize(in.world_normal);
+    var direct = vec3<f32>(0.0);
+    let cluster = cluster_base(in.clip_position.xy, in.world_position);
+    let light_count = min(light_clusters[cluster], MAX_LIGHTS_PER_CLUSTER);
+    for (var i = 0u; i < light_count; i++) {
+        let light = lights[light_clusters[cluster + 1u + i]];
+        let incoming = incoming_light(light, in.world_position);
+        let visibility = light_visibility(light, in.world_position, geometric_normal, in.world_view_position, dot(geometric_normal, incoming.direction));
+        direct += brdf(n, v, incoming.direction, f0, diffuse_color, alpha, n_dot_v) * incoming.radiance * visibility;
+    }
+
+    let max_lod = f32(textureNumLevels(prefiltered_map) - 1u);
+    let r = reflect(-v, n);
+    let prefiltered = textureSampleLevel(prefiltered_map, ibl_sampler, r, roughness * max_lod).rgb;
+    let irradiance = textureSampleLevel(irradiance_map, ibl_sampler, n, 0.0).rgb;
+    let env_brdf = textureSampleLevel(brdf_lut, ibl_sampler, vec2<f32>(n_dot_v, roughness), 0.0).rg;
+    let f_ambient = fresnel_schlick_roughness(n_dot_v, f0, roughness);
+    let specular_ambient = prefiltered * (f_ambient * env_brdf.x + env_brdf.y);
+    let diffuse_ambient = (vec3<f32>(1.0) - f_ambient) * irradiance * diffuse_color;
+    let ambient = (diffuse_ambient + specular_ambient) * ao;
+
+    let color = direct + ambient + emissive;
+    return vec4<f32>(color * in.instance_color.rgb, base_color.a * in.instance_color.a);
+}
@@ -0,0 +1,70 @@
+struct Caster {
+    view_proj: mat4x4<f32>,
+    light_position: vec3<f32>,
+    far: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> caster: Caster;
+
+#ifdef SKINNED
+@group(1) @binding(0)
+var<storage, read> joint_matrices: array<mat4x4<f32>>;
+
+fn skin_matrix(joints: vec4<u32>, weights: vec4<f32>) -> mat4x4<f32> {
+    return joint_matrices[joints.x] * weights.x
+        + joint_matrices[joints.y] * weights.y
+        + joint_matrices[joints.z] * weights.z
+        + joint_matrices[joints.w] * weights.w;
+}
+#endif
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+#ifdef SKINNED
+    @location(13) joints: vec4<u32>,
+    @location(14) weights: vec4<f32>,
+#endif
+};
+
+struct InstanceInput {
+    @location(5) model_matrix_0: vec4<f32>,
+    @location(6) model_matrix_1: vec4<f32>,
+    @location(7) model_matrix_2: vec4<f32>,
+    @location(8) model_matrix_3: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    let model_matrix = mat4x4<f32>(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+#ifdef SKINNED
+    let local_position = (skin_matrix(model.joints, model.weights) * vec4<f32>(model.position, 1.0)).xyz;
+#else
+    let local_position = model.position;
+#endif
+    let world_position = model_matrix * vec4<f32>(local_position, 1.0);
+
+    var out: VertexOutput;
+    out.clip_position = caster.view_proj * world_position;
+    out.world_position = world_position.xyz;
+    return out;
+}
+
+// Point lights store linear distance so the forward pass can compare against a single range.
+@fragment
+fn fs_point(in: VertexOutput) -> @builtin(frag_depth) f32 {
+    return clamp(length(in.world_position - caster.light_position) / caster.far, 0.0, 1.0);
+}
@@ -0,0 +1,260 @@
+const TONE_MAP_ACES: u32 = 0u;
+const TONE_MAP_REINHARD: u32 = 1u;
+const TONE_MAP_AGX: u32 = 2u;
+const TONE_MAP_FILMIC: u32 = 3u;
+
+struct PostUniform {
+    exposure: f32,
+    tone_mapper: u32,
+    bloom_intensity: f32,
+    bloom_threshold: f32,
+    bloom_knee: f32,
+    bloom_radius: f32,
+    vignette_intensity: f32,
+    vignette_smoothness: f32,
+    grain_intensity: f32,
+    frame: u32,
+    _padding: vec2<f32>,
+};
+
+// Maps HDR values to linear values
+// Based on http://www.oscars.org/science-technology/sci-tech-projects/aces
+fn aces_tone_map(hdr: vec3<f32>) -> vec3<f32> {
+    let m1 = mat3x3(
+        0.59719, 0.07600, 0.02840,
+        0.35458, 0.90834, 0.13383,
+        0.04823, 0.01566, 0.83777,
+    );
+    let m2 = mat3x3(
+        1.60475, -0.10208, -0.00327,
+        -0.53108,  1.10813, -0.07276,
+        -0.07367, -0.00605,  1.07602,
+    );
+    let v = m1 * hdr;
+    let a = v * (v + 0.0245786) - 0.000090537;
+    let b = v * (0.983729 * v + 0.4329510) + 0.238081;
+    return clamp(m2 * (a / b), vec3(0.0), vec3(1.0));
+}
+
+fn reinhard_tone_map(hdr: vec3<f32>) -> vec3<f32> {
+    return hdr / (1.0 + hdr);
+}
+
+// Minimal AgX fit, see https://iolite-engine.com/blog_posts/minimal_agx_implementation
+fn agx_contrast(x: vec3<f32>) -> vec3<f32> {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    return 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2
+        + 0.1191 * x - 0.00232;
+}
+
+fn agx_tone_map(hdr: vec3<f32>) -> vec3<f32> {
+    let inset = mat3x3(
+        0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+        0.0784335999999992, 0.878468636469772, 0.0784336,
+        0.0792237451477643, 0.0791661274605434, 0.879142973793104,
+    );
+    let outset = mat3x3(
+        1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+        -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+        -0.0990297440797205, -0.0989611768448433, 1.15107367264116,
+    );
+    let min_ev = -12.47393;
+    let max_ev = 4.026069;
+    var v = clamp(log2(max(inset * hdr, vec3(1e-10))), vec3(min_ev), vec3(max_ev));
+    v = agx_contrast((v - min_ev) / (max_ev - min_ev));
+    v = outset * v;
+    return pow(clamp(v, vec3(0.0), vec3(1.0)), vec3(2.2));
+}
+
+// Hable's filmic curve from Uncharted 2
+fn hable(x: vec3<f32>) -> vec3<f32> {
+    let a = 0.15;
+    let b = 0.50;
+    let c = 0.10;
+    let d = 0.20;
+    let e = 0.02;
+    let f = 0.30;
+    return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+fn filmic_tone_map(hdr: vec3<f32>) -> vec3<f32> {
+    let white = 11.2;
+    return clamp(hable(hdr * 2.0) / hable(vec3(white)), vec3(0.0), vec3(1.0));
+}
+
+fn tone_map(hdr: vec3<f32>) -> vec3<f32> {
+    switch post.tone_mapper {
+        case TONE_MAP_ACES: { return aces_tone_map(hdr); }
+        case TONE_MAP_REINHARD: { return reinhard_tone_map(hdr); }
+        case TONE_MAP_AGX: { return agx_tone_map(hdr); }
+        case TONE_MAP_FILMIC: { return filmic_tone_map(hdr); }
+        default: { return clamp(hdr, vec3(0.0), vec3(1.0)); }
+    }
+}
+
+struct VertexOutput {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vi: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.uv = vec2<f32>(
+        f32((vi << 1u) & 2u),
+        f32(vi & 2u),
+    );
+    out.clip_position = vec4<f32>(out.uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv.y = 1.0 - out.uv.y;
+    return out;
+}
+
+@group(0)
+@binding(0)
+var hdr_image: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var hdr_sampler: sampler;
+
+@group(0)
+@binding(2)
+var<uniform> post: PostUniform;
+
+@group(0)
+@binding(3)
+var bloom_image: texture_2d<f32>;
+
+fn sample_input(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(hdr_image, hdr_sampler, uv, 0.0).rgb;
+}
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+// 13 tap downsample from Jimenez, "Next Generation Post Processing in Call of Duty: Advanced Warfare"
+fn downsample_13(uv: vec2<f32>) -> array<vec3<f32>, 5> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(hdr_image));
+    let a = sample_input(uv + texel * vec2(-2.0, -2.0));
+    let b = sample_input(uv + texel * vec2(0.0, -2.0));
+    let c = sample_input(uv + texel * vec2(2.0, -2.0));
+    let d = sample_input(uv + texel * vec2(-2.0, 0.0));
+    let e = sample_input(uv);
+    let f = sample_input(uv + texel * vec2(2.0, 0.0));
+    let g = sample_input(uv + texel * vec2(-2.0, 2.0));
+    let h = sample_input(uv + texel * vec2(0.0, 2.0));
+    let i = sample_input(uv + texel * vec2(2.0, 2.0));
+    let j = sample_input(uv + texel * vec2(-1.0, -1.0));
+    let k = sample_input(uv + texel * vec2(1.0, -1.0));
+    let l = sample_input(uv + texel * vec2(-1.0, 1.0));
+    let m = sample_input(uv + texel * vec2(1.0, 1.0));
+    return array<vec3<f32>, 5>(
+        (j + k + l + m) * 0.25,
+        (a + b + d + e) * 0.25,
+        (b + c + e + f) * 0.25,
+        (d + e + g + h) * 0.25,
+        (e + f + h + i) * 0.25,
+    );
+}
+
+fn karis_weight(color: vec3<f32>) -> f32 {
+    return 1.0 / (1.0 + luminance(color));
+}
+
+fn soft_threshold(color: vec3<f32>) -> vec3<f32> {
+    let brightness = max(color.r, max(color.g, color.b));
+    let knee = post.bloom_threshold * post.bloom_knee;
+    var soft = clamp(brightness - post.bloom_threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 1e-5);
+    let contribution = max(soft, brightness - post.bloom_threshold) / max(brightness, 1e-5);
+    return color * contribution;
+}
+
+@fragment
+fn fs_bloom_prefilter(vs: VertexOutput) -> @location(0) vec4<f32> {
+    var groups = downsample_13(vs.uv);
+    var weights = array<f32, 5>(0.5, 0.125, 0.125, 0.125, 0.125);
+    var color = vec3<f32>(0.0);
+    var total = 0.0;
+    for (var i = 0; i < 5; i++) {
+        let group = groups[i];
+        let weight = weights[i] * karis_weight(group);
+        color += group * weight;
+        total += weight;
+    }
+    return vec4<f32>(soft_threshold(color / max(total, 1e-5)), 1.0);
+}
+
+@fragment
+fn fs_bloom_downsample(vs: VertexOutput) -> @location(0) vec4<f32> {
+    let groups = downsample_13(vs.uv);
+    let color = groups[0] * 0.5 + (groups[1] + groups[2] + groups[3] + groups[4]) * 0.125;
+    return vec4<f32>(color, 1.0);
+}
+
+@fragment
+fn fs_bloom_upsample(vs: VertexOutput) -> @location(0) vec4<f32> {
+    let offset = post.bloom_radius / vec2<f32>(textureDimensions(hdr_image));
+    var color = sample_input(vs.uv) * 4.0;
+    color += (sample_input(vs.uv + offset * vec2(0.0, -1.0))
+        + sample_input(vs.uv + offset * vec2(-1.0, 0.0))
+        + sample_input(vs.uv + offset * vec2(1.0, 0.0))
+        + sample_input(vs.uv + offset * vec2(0.0, 1.0))) * 2.0;
+    color += sample_input(vs.uv + offset * vec2(-1.0, -1.0))
+        + sample_input(vs.uv + offset * vec2(1.0, -1.0))
+        + sample_input(vs.uv + offset * vec2(-1.0, 1.0))
+        + sample_input(vs.uv + offset * vec2(1.0, 1.0));
+    let detail = textureSampleLevel(bloom_image, hdr_sampler, vs.uv, 0.0).rgb;
+    return vec4<f32>(detail + color / 16.0, 1.0);
+}
+
+@fragment
+fn fs_bloom(vs: VertexOutput) -> @location(0) vec4<f32> {
+    let bloom = textureSampleLevel(bloom_image, hdr_sampler, vs.uv, 0.0).rgb;
+    return vec4<f32>(mix(sample_input(vs.uv), bloom, post.bloom_intensity), 1.0);
+}
+
+@fragment
+fn fs_copy(vs: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(sample_input(vs.uv), 1.0);
+}
+
+@fragment
+fn fs_exposure(vs: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(sample_input(vs.uv) * post.exposure, 1.0);
+}
+
+@fragment
+fn fs_tone_map(vs: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(tone_map(sample_input(vs.uv)), 1.0);
+}
+
+@fragment
+fn fs_vignette(vs: VertexOutput) -> @location(0) vec4<f32> {
+    let radius = length(vs.uv - 0.5) * 1.41421356;
+    let falloff = smoothstep(1.0, 1.0 - post.vignette_smoothness, radius);
+    let color = sample_input(vs.uv) * mix(1.0, falloff, post.vignette_intensity);
+    return vec4<f32>(color, 1.0);
+}
+
+fn hash(p: vec3<u32>) -> f32 {
+    var v = p * vec3<u32>(1664525u, 1013904223u, 2654435761u);
+    v.x += v.y * v.z;
+    v.y += v.z * v.x;
+    v.z += v.x * v.y;
+    v ^= v >> vec3<u32>(16u);
+    v.x += v.y * v.z;
+    return f32(v.x) * 2.3283064365386963e-10;
+}
+
+@fragment
+fn fs_film_grain(vs: VertexOutput) -> @location(0) vec4<f32> {
+    let noise = hash(vec3<u32>(vec2<u32>(vs.clip_position.xy), post.frame)) - 0.5;
+    let color = sample_input(vs.uv);
+    let response = sqrt(clamp(luminance(color), 0.0, 1.0));
+    return vec4<f32>(max(color + noise * post.grain_intensity * response, vec3(0.0)), 1.0);
+}
@@ -0,0 +1,30 @@
+struct VertexOutput {
+    @location(0) uv: vec2<f32>,
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vi: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.uv = vec2<f32>(
+        f32((vi << 1u) & 2u),
+        f32(vi & 2u),
+    );
+    out.clip_position = vec4<f32>(out.uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv.y = 1.0 - out.uv.y;
+    return out;
+}
+
+@group(0)
+@binding(0)
+var shadow_atlas: texture_2d<f32>;
+
+@fragment
+fn fs_main(vs: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(shadow_atlas));
+    let texel = vec2<i32>(clamp(vs.uv * size, vec2<f32>(0.0), size - 1.0));
+    let depth = textureLoad(shadow_atlas, texel, 0).r;
+    return vec4<f32>(vec3<f32>(depth * depth), 1.0);
+}
@@ -0,0 +1,43 @@
+use std::env;
+
+use rupy::core::archive::ArchiveBuilder;
+
+fn pack(
+    input_dir: &str,
+    output_path: &str,
+    compress: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = ArchiveBuilder::new(compress);
+    let added = builder.add_dir(input_dir)?;
+    let entries = builder.write(output_path)?;
+    let size: u64 = entries.iter().map(|e| e.size).sum();
+    let stored: u64 = entries.iter().map(|e| e.stored_size).sum();
+    println!(
+        "Packed {} files ({} bytes -> {} bytes).",
+        added, size, stored
+    );
+    println!("Archive saved to {}", output_path);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let compress = !args.iter().any(|a| a == "--no-compress");
+    let paths: Vec<&String> = args
+        .iter()
+        .skip(1)
+        .filter(|a| !a.starts_with("--"))
+        .collect();
+    if paths.len() != 2 {
+        eprintln!(
+            "Usage: {} <assets_dir> <output.rpak> [--no-compress]",
+            args[0]
+        );
+        std::process::exit(1);
+    }
+
+    if let Err(e) = pack(paths[0], paths[1], compress) {
+        eprintln!("Error packing assets: {}", e);
+        std::process::exit(1);
+    }
+}
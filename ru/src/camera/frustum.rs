@@ -89,10 +89,6 @@ impl Frustum {
         }
         true
     }
-    pub fn calculate_instance_radius(scale: Vector3<f32>) -> f32 {
-        scale.magnitude() / 2.0
-    }
-
     pub fn contains(&self, volume: &BoundingVolume) -> bool {
         match volume {
             BoundingVolume::Sphere { center, radius } => self.contains_sphere(*center, *radius),
@@ -2,7 +2,9 @@ pub mod controller;
 pub mod frustum;
 pub mod handler;
 pub mod projection;
-use cgmath::{Angle, InnerSpace, Matrix4, Point3, Rad, Vector3};
+use cgmath::{Angle, EuclideanSpace, InnerSpace, Matrix4, Point3, Rad, Vector3};
+
+use crate::ecs::components::transform::Transform;
 
 #[derive(Debug)]
 pub struct Camera {
@@ -35,6 +37,12 @@ impl Camera {
 
         (forward, right, up)
     }
+    pub fn follow(&mut self, transform: &Transform) {
+        let forward = (transform.rotation * -Vector3::unit_z()).normalize();
+        self.position = Point3::from_vec(transform.position);
+        self.yaw = Rad(forward.z.atan2(forward.x));
+        self.pitch = Rad(forward.y.clamp(-1.0, 1.0).asin());
+    }
     pub fn calc_view_matrix(&self) -> Matrix4<f32> {
         let forward = Vector3::new(
             self.yaw.cos() * self.pitch.cos(),
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use walkdir::WalkDir;
+
+use super::error::AppError;
+use crate::{log_info, utilities::helpers::content_hash};
+
+pub const RPAK_MAGIC: &[u8; 4] = b"RPAK";
+pub const RPAK_VERSION: u32 = 1;
+pub const RPAK_EXTENSION: &str = "rpak";
+
+const FLAG_COMPRESSED: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub offset: u64,
+    pub stored_size: u64,
+    pub size: u64,
+    pub hash: u64,
+    pub compressed: bool,
+}
+
+#[derive(Debug)]
+pub struct Archive {
+    pub path: PathBuf,
+    entries: HashMap<String, ArchiveEntry>,
+}
+
+impl Archive {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Archive, AppError> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = File::open(&path)?;
+
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        if &magic != RPAK_MAGIC {
+            return Err(AppError::ArchiveError(format!(
+                "{:?} is not an rpak archive",
+                path
+            )));
+        }
+        let version = read_u32(&mut file)?;
+        if version != RPAK_VERSION {
+            return Err(AppError::ArchiveError(format!(
+                "Unsupported rpak version {} in {:?}",
+                version, path
+            )));
+        }
+
+        let count = read_u32(&mut file)?;
+        let mut entries = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            let path_len = read_u32(&mut file)? as usize;
+            let mut path_bytes = vec![0u8; path_len];
+            file.read_exact(&mut path_bytes)?;
+            let entry_path =
+                String::from_utf8(path_bytes).map_err(|e| AppError::ArchiveError(e.to_string()))?;
+            let offset = read_u64(&mut file)?;
+            let stored_size = read_u64(&mut file)?;
+            let size = read_u64(&mut file)?;
+            let hash = read_u64(&mut file)?;
+            let flags = read_u32(&mut file)?;
+            entries.insert(
+                entry_path.clone(),
+                ArchiveEntry {
+                    path: entry_path,
+                    offset,
+                    stored_size,
+                    size,
+                    hash,
+                    compressed: flags & FLAG_COMPRESSED != 0,
+                },
+            );
+        }
+
+        log_info!("Opened archive {:?} with {} entries", path, entries.len());
+        Ok(Archive { path, entries })
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    pub fn entry(&self, path: &str) -> Option<&ArchiveEntry> {
+        self.entries.get(path)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &ArchiveEntry> {
+        self.entries.values()
+    }
+
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, AppError> {
+        let entry = self
+            .entries
+            .get(path)
+            .ok_or_else(|| AppError::FileNotFoundError(path.to_string()))?;
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(entry.offset))?;
+        let mut stored = vec![0u8; entry.stored_size as usize];
+        file.read_exact(&mut stored)?;
+
+        let data = if entry.compressed {
+            let mut data = Vec::with_capacity(entry.size as usize);
+            ZlibDecoder::new(stored.as_slice()).read_to_end(&mut data)?;
+            data
+        } else {
+            stored
+        };
+
+        if data.len() as u64 != entry.size || content_hash(&data) != entry.hash {
+            return Err(AppError::ArchiveError(format!(
+                "Corrupt entry {} in {:?}",
+                path, self.path
+            )));
+        }
+        Ok(data)
+    }
+
+    pub fn read_to_string(&self, path: &str) -> Result<String, AppError> {
+        String::from_utf8(self.read(path)?).map_err(|e| AppError::ArchiveError(e.to_string()))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ArchiveBuilder {
+    files: Vec<(String, Vec<u8>)>,
+    compress: bool,
+}
+
+impl ArchiveBuilder {
+    pub fn new(compress: bool) -> Self {
+        Self {
+            files: Vec::new(),
+            compress,
+        }
+    }
+
+    pub fn add_file(&mut self, path: &str, data: Vec<u8>) {
+        let path = path.replace('\\', "/");
+        self.files.retain(|(p, _)| *p != path);
+        self.files.push((path, data));
+    }
+
+    pub fn add_dir<P: AsRef<Path>>(&mut self, root: P) -> Result<usize, AppError> {
+        let root = root.as_ref();
+        let mut added = 0;
+        for entry in WalkDir::new(root).sort_by_file_name() {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(root)
+                .map_err(|e| AppError::ArchiveError(e.to_string()))?;
+            let name: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            self.add_file(&name.join("/"), std::fs::read(entry.path())?);
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<Vec<ArchiveEntry>, AppError> {
+        let mut blobs = Vec::with_capacity(self.files.len());
+        let mut entries = Vec::with_capacity(self.files.len());
+        for (name, data) in &self.files {
+            let mut stored = None;
+            if self.compress {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+                encoder.write_all(data)?;
+                let compressed = encoder.finish()?;
+                if compressed.len() < data.len() {
+                    stored = Some(compressed);
+                }
+            }
+            let compressed = stored.is_some();
+            let blob = stored.unwrap_or_else(|| data.clone());
+            entries.push(ArchiveEntry {
+                path: name.clone(),
+                offset: 0,
+                stored_size: blob.len() as u64,
+                size: data.len() as u64,
+                hash: content_hash(data),
+                compressed,
+            });
+            blobs.push(blob);
+        }
+
+        let header_size = 12
+            + entries
+                .iter()
+                .map(|e| 4 + e.path.len() as u64 + 8 * 4 + 4)
+                .sum::<u64>();
+        let mut offset = header_size;
+        for entry in entries.iter_mut() {
+            entry.offset = offset;
+            offset += entry.stored_size;
+        }
+
+        let mut file = std::io::BufWriter::new(File::create(path)?);
+        file.write_all(RPAK_MAGIC)?;
+        file.write_all(&RPAK_VERSION.to_le_bytes())?;
+        file.write_all(&(entries.len() as u32).to_le_bytes())?;
+        for entry in &entries {
+            file.write_all(&(entry.path.len() as u32).to_le_bytes())?;
+            file.write_all(entry.path.as_bytes())?;
+            file.write_all(&entry.offset.to_le_bytes())?;
+            file.write_all(&entry.stored_size.to_le_bytes())?;
+            file.write_all(&entry.size.to_le_bytes())?;
+            file.write_all(&entry.hash.to_le_bytes())?;
+            let flags = if entry.compressed { FLAG_COMPRESSED } else { 0 };
+            file.write_all(&flags.to_le_bytes())?;
+        }
+        for blob in &blobs {
+            file.write_all(blob)?;
+        }
+        file.flush()?;
+
+        Ok(entries)
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, AppError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, AppError> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
@@ -0,0 +1,326 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::{BufReader, Cursor};
+
+use super::{error::AppError, files::FileSystem, vfs::AssetDir};
+use crate::ecs::components::model::gltf::gltf_dependencies;
+
+/// Assets the engine loads by name at startup; together with the scenes these are the
+/// graph roots, so anything only reachable from elsewhere is reported unused.
+pub const BUILTIN_ASSETS: &[&str] = &["pure-sky.hdr", "cube.obj"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AssetKind {
+    Scene,
+    Model,
+    Material,
+    Texture,
+    Shader,
+    Other,
+}
+
+impl AssetKind {
+    pub fn from_file_name(file_name: &str) -> AssetKind {
+        let extension = file_name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "obj" | "gltf" | "glb" => AssetKind::Model,
+            "mtl" => AssetKind::Material,
+            "png" | "jpg" | "jpeg" | "hdr" => AssetKind::Texture,
+            "wgsl" => AssetKind::Shader,
+            "yaml" | "yml" | "toml" | "json" => AssetKind::Scene,
+            _ => AssetKind::Other,
+        }
+    }
+
+    pub fn dir(&self) -> AssetDir {
+        match self {
+            AssetKind::Scene => AssetDir::Scenes,
+            AssetKind::Shader => AssetDir::Shaders,
+            _ => AssetDir::Res,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssetId {
+    pub dir: AssetDir,
+    pub name: String,
+}
+
+impl AssetId {
+    pub fn new(dir: AssetDir, name: &str) -> Self {
+        Self {
+            dir,
+            name: name.replace('\\', "/"),
+        }
+    }
+
+    pub fn from_reference(name: &str) -> Self {
+        Self::new(AssetKind::from_file_name(name).dir(), name)
+    }
+
+    pub fn kind(&self) -> AssetKind {
+        AssetKind::from_file_name(&self.name)
+    }
+
+    pub fn path(&self) -> String {
+        format!("{}/{}", self.dir.dir_name(), self.name)
+    }
+}
+
+impl std::fmt::Display for AssetId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AssetNode {
+    pub id: AssetId,
+    pub kind: AssetKind,
+    pub exists: bool,
+    pub dependencies: Vec<AssetId>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MissingAsset {
+    pub id: AssetId,
+    pub referenced_by: Vec<AssetId>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct AssetReport {
+    pub missing: Vec<MissingAsset>,
+    pub unused: Vec<AssetId>,
+    pub errors: Vec<(AssetId, String)>,
+}
+
+impl AssetReport {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.errors.is_empty()
+    }
+}
+
+impl std::fmt::Display for AssetReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for missing in &self.missing {
+            if missing.referenced_by.is_empty() {
+                writeln!(f, "missing: {}", missing.id)?;
+                continue;
+            }
+            let referenced_by: Vec<String> =
+                missing.referenced_by.iter().map(|id| id.path()).collect();
+            writeln!(
+                f,
+                "missing: {} (referenced by {})",
+                missing.id,
+                referenced_by.join(", ")
+            )?;
+        }
+        for (id, error) in &self.errors {
+            writeln!(f, "error: {}: {}", id, error)?;
+        }
+        for id in &self.unused {
+            writeln!(f, "unused: {}", id)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AssetGraph {
+    pub roots: Vec<AssetId>,
+    pub nodes: BTreeMap<AssetId, AssetNode>,
+}
+
+impl AssetGraph {
+    pub fn build(roots: Vec<AssetId>) -> Result<AssetGraph, AppError> {
+        let mut graph = AssetGraph {
+            roots: roots.clone(),
+            nodes: BTreeMap::new(),
+        };
+        let mut queue: VecDeque<AssetId> = roots.into_iter().collect();
+        while let Some(id) = queue.pop_front() {
+            if graph.nodes.contains_key(&id) {
+                continue;
+            }
+            let node = Self::visit(&id)?;
+            for dependency in &node.dependencies {
+                if !graph.nodes.contains_key(dependency) {
+                    queue.push_back(dependency.clone());
+                }
+            }
+            graph.nodes.insert(id, node);
+        }
+        Ok(graph)
+    }
+
+    pub fn from_assets_dir() -> Result<AssetGraph, AppError> {
+        let mut roots: Vec<AssetId> = BUILTIN_ASSETS
+            .iter()
+            .map(|name| AssetId::new(AssetDir::Res, name))
+            .collect();
+        for name in FileSystem::list_assets(AssetDir::Scenes)? {
+            if AssetKind::from_file_name(&name) == AssetKind::Scene {
+                roots.push(AssetId::new(AssetDir::Scenes, &name));
+            }
+        }
+        Self::build(roots)
+    }
+
+    fn visit(id: &AssetId) -> Result<AssetNode, AppError> {
+        let kind = id.kind();
+        let dir = id.dir;
+        let exists = FileSystem::asset_exists(dir, &id.name)?;
+        let mut node = AssetNode {
+            id: id.clone(),
+            kind,
+            exists,
+            dependencies: Vec::new(),
+            error: None,
+        };
+        if !exists {
+            return Ok(node);
+        }
+
+        let parsed = match kind {
+            AssetKind::Model if id.name.to_ascii_lowercase().ends_with(".obj") => {
+                FileSystem::load_asset_string(dir, &id.name).map(|src| obj_dependencies(&src))
+            }
+            AssetKind::Model => FileSystem::load_asset(dir, &id.name).and_then(|data| {
+                gltf_dependencies(&id.name, &data).map(|deps| {
+                    deps.iter()
+                        .map(|d| AssetId::new(AssetDir::Res, d))
+                        .collect()
+                })
+            }),
+            AssetKind::Material => {
+                FileSystem::load_asset_string(dir, &id.name).and_then(|src| mtl_dependencies(&src))
+            }
+            AssetKind::Scene => FileSystem::load_asset_string(dir, &id.name)
+                .and_then(|src| scene_dependencies(&id.name, &src)),
+            _ => Ok(Vec::new()),
+        };
+        match parsed {
+            Ok(dependencies) => node.dependencies = dependencies,
+            Err(e) => node.error = Some(e.to_string()),
+        }
+        Ok(node)
+    }
+
+    pub fn referenced_files(&self) -> Vec<&AssetId> {
+        self.nodes.keys().collect()
+    }
+
+    pub fn dependents(&self, id: &AssetId) -> Vec<AssetId> {
+        self.nodes
+            .values()
+            .filter(|node| node.dependencies.contains(id))
+            .map(|node| node.id.clone())
+            .collect()
+    }
+
+    pub fn report(&self) -> Result<AssetReport, AppError> {
+        let mut report = AssetReport::default();
+        for node in self.nodes.values() {
+            if !node.exists {
+                report.missing.push(MissingAsset {
+                    id: node.id.clone(),
+                    referenced_by: self.dependents(&node.id),
+                });
+            }
+            if let Some(error) = &node.error {
+                report.errors.push((node.id.clone(), error.clone()));
+            }
+        }
+
+        let referenced: BTreeSet<&AssetId> = self.nodes.keys().collect();
+        for name in FileSystem::list_assets(AssetDir::Res)? {
+            let id = AssetId::new(AssetDir::Res, &name);
+            if id.kind() != AssetKind::Other && !referenced.contains(&id) {
+                report.unused.push(id);
+            }
+        }
+        Ok(report)
+    }
+}
+
+pub fn obj_dependencies(src: &str) -> Vec<AssetId> {
+    let mut dependencies = Vec::new();
+    for line in src.lines() {
+        let mut words = line.split_whitespace();
+        if words.next() == Some("mtllib") {
+            for name in words {
+                dependencies.push(AssetId::new(AssetDir::Res, name));
+            }
+        }
+    }
+    dependencies
+}
+
+pub fn mtl_dependencies(src: &str) -> Result<Vec<AssetId>, AppError> {
+    let (materials, _) = tobj::load_mtl_buf(&mut BufReader::new(Cursor::new(src)))?;
+    let mut dependencies = Vec::new();
+    for material in materials {
+        for texture in [
+            &material.ambient_texture,
+            &material.diffuse_texture,
+            &material.specular_texture,
+            &material.normal_texture,
+            &material.shininess_texture,
+            &material.dissolve_texture,
+        ] {
+            let id = AssetId::new(AssetDir::Res, texture);
+            if !texture.is_empty() && !dependencies.contains(&id) {
+                dependencies.push(id);
+            }
+        }
+    }
+    Ok(dependencies)
+}
+
+pub fn scene_dependencies(file_name: &str, src: &str) -> Result<Vec<AssetId>, AppError> {
+    let mut strings = Vec::new();
+    if file_name.to_ascii_lowercase().ends_with(".toml") {
+        let value: toml::Value = toml::from_str(src)?;
+        collect_toml_strings(&value, &mut strings);
+    } else {
+        let value: serde_yaml::Value = serde_yaml::from_str(src)?;
+        collect_yaml_strings(&value, &mut strings);
+    }
+
+    let mut dependencies = Vec::new();
+    for s in strings {
+        let kind = AssetKind::from_file_name(&s);
+        if kind == AssetKind::Other || kind == AssetKind::Scene {
+            continue;
+        }
+        let id = AssetId::from_reference(&s);
+        if !dependencies.contains(&id) {
+            dependencies.push(id);
+        }
+    }
+    Ok(dependencies)
+}
+
+fn collect_yaml_strings(value: &serde_yaml::Value, out: &mut Vec<String>) {
+    match value {
+        serde_yaml::Value::String(s) => out.push(s.clone()),
+        serde_yaml::Value::Sequence(seq) => seq.iter().for_each(|v| collect_yaml_strings(v, out)),
+        serde_yaml::Value::Mapping(map) => map.values().for_each(|v| collect_yaml_strings(v, out)),
+        serde_yaml::Value::Tagged(tagged) => collect_yaml_strings(&tagged.value, out),
+        _ => {}
+    }
+}
+
+fn collect_toml_strings(value: &toml::Value, out: &mut Vec<String>) {
+    match value {
+        toml::Value::String(s) => out.push(s.clone()),
+        toml::Value::Array(array) => array.iter().for_each(|v| collect_toml_strings(v, out)),
+        toml::Value::Table(table) => table.values().for_each(|v| collect_toml_strings(v, out)),
+        _ => {}
+    }
+}
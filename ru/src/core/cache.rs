@@ -105,6 +105,15 @@ impl<R> HashCache<R> {
             cache: HashMap::new(),
         }
     }
+    pub fn iter(&self) -> impl Iterator<Item = (&CacheKey, &R)> {
+        self.cache.iter()
+    }
+    pub fn keys(&self) -> impl Iterator<Item = &CacheKey> {
+        self.cache.keys()
+    }
+    pub fn take(&mut self, id: &CacheKey) -> Option<R> {
+        self.cache.remove(id)
+    }
 }
 
 impl<R> Cache<R> for HashCache<R> {
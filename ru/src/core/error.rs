@@ -38,6 +38,8 @@ pub enum AppError {
     // Shader-related errors
     #[error("Failed to parse shader source: {0}")]
     ShaderParseError(#[from] naga::front::wgsl::ParseError),
+    #[error("Shader validation failed: {0}")]
+    ShaderValidationError(String),
 
     // I/O and file-related errors
     #[error("io::Error: {0}")]
@@ -50,6 +52,12 @@ pub enum AppError {
     WalkDirError(#[from] walkdir::Error),
     #[error("tobj::LoadError {0}")]
     TobjLoadError(#[from] tobj::LoadError),
+    #[error("gltf::Error: {0}")]
+    GltfError(#[from] gltf::Error),
+    #[error("Archive error: {0}")]
+    ArchiveError(String),
+    #[error("notify::Error: {0}")]
+    NotifyError(#[from] notify::Error),
 
     // Logging and system-related errors
     #[error("Logger setup error: {0}")]
@@ -71,6 +79,8 @@ pub enum AppError {
     RequestDeviceError(#[from] wgpu::RequestDeviceError),
     #[error("GPUResourceError: {0}")]
     GPUResourceError(String),
+    #[error("Render graph error: {0}")]
+    RenderGraphError(String),
 
     #[error("RenderError {0}")]
     RenderError(#[from] glyphon::RenderError),
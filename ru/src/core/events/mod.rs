@@ -89,6 +89,24 @@ pub enum RupyAppEvent {
         object_id: u64,
         object_type: String,
     },
+    AnimationEvent {
+        object_id: u64,
+        clip: String,
+        name: String,
+    },
+    ObjectPicked {
+        object_id: u64,
+        instance: usize,
+        distance: f32,
+    },
+    OverlapStarted {
+        first: u64,
+        second: u64,
+    },
+    OverlapEnded {
+        first: u64,
+        second: u64,
+    },
 
     InputCommand {
         command: String,
@@ -153,6 +171,10 @@ impl RupyAppEvent {
             RupyAppEvent::SceneUnloaded { .. } => "SceneUnloaded",
             RupyAppEvent::ObjectSpawned { .. } => "ObjectSpawned",
             RupyAppEvent::ObjectDestroyed { .. } => "ObjectDestroyed",
+            RupyAppEvent::AnimationEvent { .. } => "AnimationEvent",
+            RupyAppEvent::ObjectPicked { .. } => "ObjectPicked",
+            RupyAppEvent::OverlapStarted { .. } => "OverlapStarted",
+            RupyAppEvent::OverlapEnded { .. } => "OverlapEnded",
             RupyAppEvent::InputCommand { .. } => "InputCommand",
             RupyAppEvent::FrameRendered { .. } => "FrameRendered",
             RupyAppEvent::RenderError { .. } => "RenderError",
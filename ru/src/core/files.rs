@@ -6,7 +6,9 @@ use std::{ffi::OsStr, io::Write};
 
 use crate::{log_error, log_info};
 
+use super::archive::Archive;
 use super::error::AppError;
+use super::vfs::{AssetDir, MountPoint, VFS};
 
 #[derive(Debug, Clone)]
 pub struct PathBuilder {
@@ -37,60 +39,129 @@ pub struct FileSystem;
 
 impl FileSystem {
     pub fn get_assets_dir() -> Result<PathBuf, AppError> {
-        let base_dir = std::env::current_dir()?.join("src").join("assets");
-        Ok(base_dir)
+        VFS.read()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .base_dir()
+    }
+
+    pub fn get_dir(dir: AssetDir) -> Result<PathBuf, AppError> {
+        VFS.read()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .dir(dir)
+    }
+
+    pub fn get_dir_roots(dir: AssetDir) -> Result<Vec<PathBuf>, AppError> {
+        Ok(VFS
+            .read()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .dir_roots(dir))
+    }
+
+    pub fn resolve(dir: AssetDir, file_name: &str) -> Result<PathBuf, AppError> {
+        VFS.read()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .resolve(dir, file_name)
+    }
+
+    pub fn mount<P: AsRef<Path>>(name: &str, root: P, priority: i32) -> Result<(), AppError> {
+        VFS.write()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .mount(MountPoint::new(name, root.as_ref().to_path_buf(), priority));
+        Ok(())
+    }
+
+    pub fn unmount(name: &str) -> Result<bool, AppError> {
+        Ok(VFS
+            .write()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .unmount(name))
     }
 
     pub fn get_res_dir() -> Result<PathBuf, AppError> {
-        let path = Self::get_assets_dir()?.join("res");
-        Ok(path)
+        Self::get_dir(AssetDir::Res)
     }
 
     pub fn get_scenes_dir() -> Result<PathBuf, AppError> {
-        log_info!("get_scenes_dir");
-        let path = Self::get_assets_dir()?.join("scenes");
-        log_info!("scenes_dir {:?}", path);
-        Ok(path)
+        Self::get_dir(AssetDir::Scenes)
     }
 
     pub fn get_textures_dir() -> Result<PathBuf, AppError> {
-        let path = Self::get_assets_dir()?.join("textures");
-        Ok(path)
+        Self::get_dir(AssetDir::Textures)
     }
 
     pub fn get_shaders_dir() -> Result<PathBuf, AppError> {
-        let path = Self::get_assets_dir()?.join("shaders");
-        Ok(path)
+        Self::get_dir(AssetDir::Shaders)
     }
 
     pub fn get_texture_base_folder(file_name: &str) -> Result<PathBuf, AppError> {
         log_info!("get_texture_base_folder: {:?}", file_name);
-        let path = Self::get_textures_dir()?.join(file_name);
-        Ok(path)
+        Self::resolve(AssetDir::Textures, file_name)
     }
 
     pub fn get_shader_file_path(file_name: &str) -> Result<PathBuf, AppError> {
         log_info!("get_shader_file_path: {:?}", file_name);
-        let path = Self::get_shaders_dir()?.join(file_name);
-        Ok(path)
+        Self::resolve(AssetDir::Shaders, file_name)
     }
 
     pub fn get_scene_file_path(file_name: &str) -> Result<PathBuf, AppError> {
         log_info!("get_scene_file_path: {:?}", file_name);
-        let path = Self::get_scenes_dir()?.join(file_name);
-        Ok(path)
+        Self::resolve(AssetDir::Scenes, file_name)
+    }
+
+    pub fn get_res_file_path(file_name: &str) -> Result<PathBuf, AppError> {
+        Self::resolve(AssetDir::Res, file_name)
     }
 
     pub fn load_string(file_name: &str) -> Result<String, AppError> {
-        let path = Self::get_res_dir()?.join(file_name);
-        let txt = Self::read_to_string(path)?;
-        Ok(txt)
+        Self::load_asset_string(AssetDir::Res, file_name)
     }
 
     pub fn load_binary(file_name: &str) -> Result<Vec<u8>, AppError> {
-        let path = Self::get_res_dir()?.join(file_name);
-        let data = std::fs::read(path)?;
-        Ok(data)
+        Self::load_asset(AssetDir::Res, file_name)
+    }
+
+    pub fn load_asset(dir: AssetDir, file_name: &str) -> Result<Vec<u8>, AppError> {
+        let data = VFS
+            .read()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .read(dir, file_name);
+        if data.is_err() {
+            log_error!("Failed to read asset: {}/{}", dir.dir_name(), file_name);
+        }
+        data
+    }
+
+    pub fn load_asset_string(dir: AssetDir, file_name: &str) -> Result<String, AppError> {
+        let txt = VFS
+            .read()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .read_to_string(dir, file_name);
+        if txt.is_err() {
+            log_error!("Failed to read asset: {}/{}", dir.dir_name(), file_name);
+        }
+        txt
+    }
+
+    pub fn list_assets(dir: AssetDir) -> Result<Vec<String>, AppError> {
+        Ok(VFS
+            .read()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .list(dir))
+    }
+
+    pub fn asset_exists(dir: AssetDir, file_name: &str) -> Result<bool, AppError> {
+        Ok(VFS
+            .read()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .exists(dir, file_name))
+    }
+
+    pub fn mount_archive<P: AsRef<Path>>(path: P) -> Result<(), AppError> {
+        let archive = Archive::open(path)?;
+        VFS.write()
+            .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?
+            .mount_archive(archive);
+        Ok(())
     }
 
     pub fn image_open(path: &Path) -> Result<DynamicImage, image::ImageError> {
@@ -103,10 +174,9 @@ impl FileSystem {
         }
     }
     pub fn get_path_for_type(file_type: FileType, file_name: &str) -> Result<PathBuf, AppError> {
-        let base_dir = match file_type {
-            FileType::Image => Self::get_textures_dir()?,
-        };
-        Ok(base_dir.join(file_name))
+        match file_type {
+            FileType::Image => Self::resolve(AssetDir::Textures, file_name),
+        }
     }
 
     pub fn load_or_create<F>(
@@ -9,6 +9,7 @@ where
     match event {
         WindowEvent::CursorMoved { .. }
         | WindowEvent::KeyboardInput { .. }
+        | WindowEvent::MouseInput { .. }
         | WindowEvent::MouseWheel { .. } => {
             cb();
         }
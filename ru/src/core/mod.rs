@@ -1,3 +1,5 @@
+pub mod archive;
+pub mod asset_graph;
 pub mod audio;
 pub mod cache;
 pub mod error;
@@ -6,4 +8,6 @@ pub mod files;
 pub mod input;
 pub mod logging;
 pub mod surface;
+pub mod vfs;
+pub mod watcher;
 pub mod worker;
@@ -1,6 +1,11 @@
+use image::RgbaImage;
 use wgpu::{Surface, SurfaceConfiguration};
 use winit::dpi::PhysicalSize;
 
+use super::error::AppError;
+
+pub const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
 pub struct RenderSurface<'a> {
     pub surface: Surface<'a>,
     pub config: SurfaceConfiguration,
@@ -56,3 +61,190 @@ impl<'a> RenderSurface<'a> {
         self.surface.get_current_texture()
     }
 }
+
+pub struct OffscreenSurface {
+    pub texture: wgpu::Texture,
+    pub config: SurfaceConfiguration,
+}
+
+impl OffscreenSurface {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: OFFSCREEN_FORMAT,
+            width: width.max(1),
+            height: height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 1,
+        };
+        let texture = Self::create_texture(device, &config);
+        Self { texture, config }
+    }
+
+    fn create_texture(device: &wgpu::Device, config: &SurfaceConfiguration) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("texture:offscreen"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: config.usage,
+            view_formats: &[],
+        })
+    }
+
+    pub fn configure(&mut self, device: &wgpu::Device) {
+        self.texture = Self::create_texture(device, &self.config);
+    }
+
+    pub fn read_pixels(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<RgbaImage, AppError> {
+        let width = self.config.width;
+        let height = self.config.height;
+        let unpadded_bytes_per_row = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("buffer:offscreen_readback"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Offscreen Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        rx.recv()
+            .map_err(|e| AppError::GPUResourceError(e.to_string()))?
+            .map_err(|e| AppError::GPUResourceError(e.to_string()))?;
+
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        buffer.unmap();
+
+        if matches!(
+            self.config.format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            for pixel in pixels.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        RgbaImage::from_raw(width, height, pixels).ok_or(AppError::GPUResourceError(String::from(
+            "Offscreen readback size mismatch",
+        )))
+    }
+}
+
+pub enum RenderTarget<'a> {
+    Surface(RenderSurface<'a>),
+    Offscreen(OffscreenSurface),
+}
+
+pub struct RenderFrame {
+    pub view: wgpu::TextureView,
+    surface_texture: Option<wgpu::SurfaceTexture>,
+}
+
+impl RenderFrame {
+    pub fn present(self) {
+        if let Some(surface_texture) = self.surface_texture {
+            surface_texture.present();
+        }
+    }
+}
+
+impl<'a> RenderTarget<'a> {
+    pub fn config(&self) -> &SurfaceConfiguration {
+        match self {
+            RenderTarget::Surface(surface) => &surface.config,
+            RenderTarget::Offscreen(offscreen) => &offscreen.config,
+        }
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        let config = self.config();
+        PhysicalSize::new(config.width, config.height)
+    }
+
+    pub fn configure(&mut self, device: &wgpu::Device) {
+        match self {
+            RenderTarget::Surface(surface) => surface.configure(device),
+            RenderTarget::Offscreen(offscreen) => offscreen.configure(device),
+        }
+    }
+
+    pub fn update_config_size<P: winit::dpi::Pixel>(&mut self, new_size: PhysicalSize<P>) -> bool {
+        let width: u32 = new_size.width.cast();
+        let height: u32 = new_size.height.cast();
+        if width == 0 || height == 0 {
+            return false;
+        }
+        let config = match self {
+            RenderTarget::Surface(surface) => &mut surface.config,
+            RenderTarget::Offscreen(offscreen) => &mut offscreen.config,
+        };
+        config.width = width;
+        config.height = height;
+        true
+    }
+
+    pub fn acquire(&self) -> Result<RenderFrame, wgpu::SurfaceError> {
+        match self {
+            RenderTarget::Surface(surface) => {
+                let output = surface.get_current_texture()?;
+                Ok(RenderFrame {
+                    view: output
+                        .texture
+                        .create_view(&wgpu::TextureViewDescriptor::default()),
+                    surface_texture: Some(output),
+                })
+            }
+            RenderTarget::Offscreen(offscreen) => Ok(RenderFrame {
+                view: offscreen
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default()),
+                surface_texture: None,
+            }),
+        }
+    }
+}
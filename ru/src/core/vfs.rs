@@ -0,0 +1,617 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Deserialize};
+use walkdir::WalkDir;
+
+use super::archive::{Archive, RPAK_EXTENSION};
+use super::error::AppError;
+use crate::{log_error, log_info, log_warning};
+
+pub const CONFIG_ENV: &str = "RUPY_ENGINE_CONFIG";
+pub const STATIC_DIR_ENV: &str = "RUPY_ENGINE_STATIC_DIR";
+pub const RES_DIR_ENV: &str = "RUPY_ENGINE_RES_DIR";
+pub const TEXTURES_DIR_ENV: &str = "RUPY_ENGINE_TEXTURES_DIR";
+pub const SHADERS_DIR_ENV: &str = "RUPY_ENGINE_SHADERS_DIR";
+pub const SCENES_DIR_ENV: &str = "RUPY_ENGINE_SCENES_DIR";
+pub const ARCHIVE_ENV: &str = "RUPY_ENGINE_ARCHIVE";
+
+pub const BASE_MOUNT: &str = "base";
+pub const FALLBACK_STATIC_DIR: &str = "assets";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AssetDir {
+    Res,
+    Shaders,
+    Textures,
+    Scenes,
+}
+
+impl AssetDir {
+    pub const ALL: [AssetDir; 4] = [
+        AssetDir::Res,
+        AssetDir::Shaders,
+        AssetDir::Textures,
+        AssetDir::Scenes,
+    ];
+
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            AssetDir::Res => "res",
+            AssetDir::Shaders => "shaders",
+            AssetDir::Textures => "textures",
+            AssetDir::Scenes => "scenes",
+        }
+    }
+
+    pub fn env_var(&self) -> &'static str {
+        match self {
+            AssetDir::Res => RES_DIR_ENV,
+            AssetDir::Shaders => SHADERS_DIR_ENV,
+            AssetDir::Textures => TEXTURES_DIR_ENV,
+            AssetDir::Scenes => SCENES_DIR_ENV,
+        }
+    }
+
+    pub fn from_config_key(key: &str) -> Option<AssetDir> {
+        match key {
+            "res" => Some(AssetDir::Res),
+            "shaders" => Some(AssetDir::Shaders),
+            "images" | "textures" => Some(AssetDir::Textures),
+            "scenes" => Some(AssetDir::Scenes),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MountPoint {
+    pub name: String,
+    pub root: PathBuf,
+    pub dir: Option<AssetDir>,
+    pub priority: i32,
+}
+
+impl MountPoint {
+    pub fn new(name: &str, root: PathBuf, priority: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            root,
+            dir: None,
+            priority,
+        }
+    }
+
+    pub fn for_dir(name: &str, root: PathBuf, dir: AssetDir, priority: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            root,
+            dir: Some(dir),
+            priority,
+        }
+    }
+
+    pub fn dir_path(&self, dir: AssetDir) -> Option<PathBuf> {
+        match self.dir {
+            None => Some(self.root.join(dir.dir_name())),
+            Some(mounted) if mounted == dir => Some(self.root.clone()),
+            Some(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct VfsConfig {
+    #[serde(default)]
+    pub paths: HashMap<String, String>,
+    #[serde(default)]
+    pub mounts: Vec<MountConfig>,
+    /// Directory of the config file; relative paths in the config resolve against it.
+    #[serde(skip)]
+    pub base_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MountConfig {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl VfsConfig {
+    pub fn path() -> Result<PathBuf, AppError> {
+        match std::env::var(CONFIG_ENV) {
+            Ok(path) => Ok(PathBuf::from(path)),
+            Err(_) => Ok(std::env::current_dir()?.join("config.toml")),
+        }
+    }
+
+    pub fn load() -> Result<VfsConfig, AppError> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(VfsConfig::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let mut config: VfsConfig = toml::from_str(&content)?;
+        config.base_dir = path.parent().map(Path::to_path_buf);
+        Ok(config)
+    }
+
+    /// Joins a relative config path onto the config file's directory.
+    pub fn config_relative(&self, value: &str) -> PathBuf {
+        let path = PathBuf::from(value);
+        match &self.base_dir {
+            Some(base) if path.is_relative() => base.join(path),
+            _ => path,
+        }
+    }
+
+    /// An env var named by `value`, or `value` as a path if it exists.
+    fn resolve_setting(&self, value: &str) -> Option<PathBuf> {
+        match std::env::var(value) {
+            Ok(path) if !path.is_empty() => Some(PathBuf::from(path)),
+            _ => {
+                let path = self.config_relative(value);
+                if path.exists() {
+                    Some(path)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Reads the `[key]` table of the engine config file. A missing file or table yields
+/// `T::default()`.
+pub fn load_config_section<T: DeserializeOwned + Default>(key: &str) -> Result<T, AppError> {
+    let path = VfsConfig::path()?;
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    parse_config_section(&std::fs::read_to_string(&path)?, key)
+}
+
+pub fn parse_config_section<T: DeserializeOwned + Default>(
+    content: &str,
+    key: &str,
+) -> Result<T, AppError> {
+    let mut table: toml::Table = toml::from_str(content)?;
+    match table.remove(key) {
+        Some(section) => Ok(section.try_into()?),
+        None => Ok(T::default()),
+    }
+}
+
+fn default_static_dir() -> Result<PathBuf, AppError> {
+    let source_assets = std::env::current_dir()?.join("src").join("assets");
+    if source_assets.exists() {
+        return Ok(source_assets);
+    }
+    if let Some(exe_dir) = std::env::current_exe()?.parent() {
+        let exe_assets = exe_dir.join("assets");
+        if exe_assets.exists() {
+            return Ok(exe_assets);
+        }
+    }
+    Ok(source_assets)
+}
+
+fn default_archive() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let path = exe.parent()?.join("assets").with_extension(RPAK_EXTENSION);
+    if path.exists() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct VirtualFileSystem {
+    mounts: Vec<MountPoint>,
+    archives: Vec<Archive>,
+}
+
+impl VirtualFileSystem {
+    pub fn new() -> Self {
+        Self {
+            mounts: Vec::new(),
+            archives: Vec::new(),
+        }
+    }
+
+    pub fn from_config(config: &VfsConfig) -> Result<Self, AppError> {
+        let mut vfs = VirtualFileSystem::new();
+
+        let static_dir = config
+            .paths
+            .get("static")
+            .map(String::as_str)
+            .or(Some(STATIC_DIR_ENV))
+            .and_then(|value| config.resolve_setting(value));
+        let static_dir = match static_dir {
+            Some(dir) => dir,
+            None => default_static_dir()?,
+        };
+        vfs.mount(MountPoint::new(BASE_MOUNT, static_dir, 0));
+
+        for dir in AssetDir::ALL {
+            let setting = config
+                .paths
+                .iter()
+                .find(|(key, _)| AssetDir::from_config_key(key) == Some(dir))
+                .map(|(_, value)| value.as_str())
+                .unwrap_or(dir.env_var());
+            if let Some(root) = config.resolve_setting(setting) {
+                vfs.mount(MountPoint::for_dir(dir.dir_name(), root, dir, 1));
+            }
+        }
+
+        for mount in &config.mounts {
+            let root = config
+                .resolve_setting(&mount.path)
+                .unwrap_or_else(|| config.config_relative(&mount.path));
+            vfs.mount(MountPoint::new(&mount.name, root, mount.priority));
+        }
+
+        let archive = config
+            .paths
+            .get("archive")
+            .map(String::as_str)
+            .or(Some(ARCHIVE_ENV))
+            .and_then(|value| config.resolve_setting(value))
+            .or_else(default_archive);
+        if let Some(path) = archive {
+            match Archive::open(&path) {
+                Ok(archive) => vfs.mount_archive(archive),
+                Err(e) => {
+                    log_warning!("Skipping unreadable archive {:?}: {:?}", path, e);
+                }
+            }
+        }
+
+        Ok(vfs)
+    }
+
+    pub fn load() -> Result<Self, AppError> {
+        Self::from_config(&VfsConfig::load()?)
+    }
+
+    /// A VFS with only `assets/` mounted, used when the configured one can't be built.
+    pub fn fallback() -> Self {
+        let mut vfs = VirtualFileSystem::new();
+        vfs.mount(MountPoint::new(
+            BASE_MOUNT,
+            PathBuf::from(FALLBACK_STATIC_DIR),
+            0,
+        ));
+        vfs
+    }
+
+    pub fn mount(&mut self, mount: MountPoint) {
+        log_info!(
+            "Mounting {} at {:?} (priority {})",
+            mount.name,
+            mount.root,
+            mount.priority
+        );
+        self.mounts.retain(|m| m.name != mount.name);
+        let index = self
+            .mounts
+            .iter()
+            .position(|m| m.priority > mount.priority)
+            .unwrap_or(self.mounts.len());
+        self.mounts.insert(index, mount);
+    }
+
+    pub fn unmount(&mut self, name: &str) -> bool {
+        let len = self.mounts.len();
+        self.mounts.retain(|m| m.name != name);
+        self.mounts.len() != len
+    }
+
+    pub fn mount_archive(&mut self, archive: Archive) {
+        log_info!("Mounting archive {:?}", archive.path);
+        self.archives.retain(|a| a.path != archive.path);
+        self.archives.push(archive);
+    }
+
+    pub fn archives(&self) -> &[Archive] {
+        &self.archives
+    }
+
+    pub fn mounts(&self) -> &[MountPoint] {
+        &self.mounts
+    }
+
+    pub fn base_dir(&self) -> Result<PathBuf, AppError> {
+        self.mounts
+            .iter()
+            .find(|m| m.name == BASE_MOUNT)
+            .or_else(|| self.mounts.iter().find(|m| m.dir.is_none()))
+            .map(|m| m.root.clone())
+            .ok_or_else(|| AppError::ConfigError("No asset root mounted".to_string()))
+    }
+
+    pub fn dir_roots(&self, dir: AssetDir) -> Vec<PathBuf> {
+        self.mounts
+            .iter()
+            .rev()
+            .filter_map(|m| m.dir_path(dir))
+            .collect()
+    }
+
+    pub fn dir(&self, dir: AssetDir) -> Result<PathBuf, AppError> {
+        let roots = self.dir_roots(dir);
+        roots
+            .iter()
+            .find(|root| root.exists())
+            .or(roots.last())
+            .cloned()
+            .ok_or_else(|| AppError::ConfigError(format!("No mount provides {}", dir.dir_name())))
+    }
+
+    pub fn resolve(&self, dir: AssetDir, file_name: &str) -> Result<PathBuf, AppError> {
+        let roots = self.dir_roots(dir);
+        if let Some(path) = roots
+            .iter()
+            .map(|root| root.join(file_name))
+            .find(|path| path.exists())
+        {
+            return Ok(path);
+        }
+        match roots.last() {
+            Some(root) => Ok(root.join(file_name)),
+            None => Err(AppError::FileNotFoundError(format!(
+                "{}/{}",
+                dir.dir_name(),
+                file_name
+            ))),
+        }
+    }
+
+    pub fn archive_path(dir: AssetDir, file_name: &str) -> String {
+        format!("{}/{}", dir.dir_name(), file_name.replace('\\', "/"))
+    }
+
+    pub fn exists(&self, dir: AssetDir, file_name: &str) -> bool {
+        let archive_path = Self::archive_path(dir, file_name);
+        self.dir_roots(dir)
+            .iter()
+            .any(|root| root.join(file_name).exists())
+            || self.archives.iter().any(|a| a.contains(&archive_path))
+    }
+
+    pub fn read(&self, dir: AssetDir, file_name: &str) -> Result<Vec<u8>, AppError> {
+        if let Some(path) = self
+            .dir_roots(dir)
+            .iter()
+            .map(|root| root.join(file_name))
+            .find(|path| path.exists())
+        {
+            return Ok(std::fs::read(path)?);
+        }
+        let archive_path = Self::archive_path(dir, file_name);
+        match self
+            .archives
+            .iter()
+            .rev()
+            .find(|a| a.contains(&archive_path))
+        {
+            Some(archive) => archive.read(&archive_path),
+            None => Err(AppError::FileNotFoundError(archive_path)),
+        }
+    }
+
+    pub fn read_to_string(&self, dir: AssetDir, file_name: &str) -> Result<String, AppError> {
+        String::from_utf8(self.read(dir, file_name)?)
+            .map_err(|e| AppError::ResourceCreationFailed(e.to_string()))
+    }
+
+    pub fn list(&self, dir: AssetDir) -> Vec<String> {
+        let mut files = BTreeSet::new();
+        for root in self.dir_roots(dir) {
+            for entry in WalkDir::new(&root).into_iter().filter_map(|e| e.ok()) {
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                if let Some(name) = self.relative_name(dir, entry.path()) {
+                    files.insert(name);
+                }
+            }
+        }
+        let prefix = format!("{}/", dir.dir_name());
+        for archive in &self.archives {
+            for entry in archive.entries() {
+                if let Some(name) = entry.path.strip_prefix(&prefix) {
+                    files.insert(name.to_string());
+                }
+            }
+        }
+        files.into_iter().collect()
+    }
+
+    pub fn relative_name(&self, dir: AssetDir, path: &Path) -> Option<String> {
+        self.dir_roots(dir).iter().find_map(|root| {
+            let relative = path.strip_prefix(root).ok()?;
+            let components: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            Some(components.join("/"))
+        })
+    }
+}
+
+pub static VFS: Lazy<Arc<RwLock<VirtualFileSystem>>> = Lazy::new(|| {
+    let vfs = VirtualFileSystem::load()
+        .or_else(|e| {
+            log_warning!("Failed to load asset config, using defaults: {:?}", e);
+            VirtualFileSystem::from_config(&VfsConfig::default())
+        })
+        .unwrap_or_else(|e| {
+            log_error!(
+                "Failed to mount default assets, falling back to {:?}: {:?}",
+                FALLBACK_STATIC_DIR,
+                e
+            );
+            VirtualFileSystem::fallback()
+        });
+    Arc::new(RwLock::new(vfs))
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::archive::ArchiveBuilder;
+    use crate::graphics::{msaa::MsaaSettings, shadow::settings::ShadowSettings};
+
+    #[test]
+    fn config_section_reads_table_or_defaults() {
+        let content = "[msaa]\nsample_count = 2\n\n[shadows]\ntile_size = 1024\n";
+        let msaa: MsaaSettings = parse_config_section(content, "msaa").unwrap();
+        assert_eq!(msaa.sample_count, 2);
+
+        let shadows: ShadowSettings = parse_config_section(content, "shadows").unwrap();
+        assert_eq!(shadows.tile_size, 1024);
+        assert_eq!(shadows.pcf_radius, ShadowSettings::default().pcf_radius);
+
+        let missing: MsaaSettings = parse_config_section("[paths]\n", "msaa").unwrap();
+        assert_eq!(missing, MsaaSettings::default());
+    }
+
+    #[test]
+    fn config_section_reports_bad_values() {
+        let result =
+            parse_config_section::<MsaaSettings>("[msaa]\nsample_count = \"four\"\n", "msaa");
+        assert!(result.is_err());
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rupy-vfs-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_file(path: PathBuf, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn higher_priority_mount_shadows_lower() {
+        let root = scratch_dir("priority");
+        write_file(root.join("base/scenes/a.toml"), "base");
+        write_file(root.join("base/scenes/b.toml"), "base");
+        write_file(root.join("mod/scenes/a.toml"), "mod");
+
+        let mut vfs = VirtualFileSystem::new();
+        vfs.mount(MountPoint::new("mod", root.join("mod"), 5));
+        vfs.mount(MountPoint::new(BASE_MOUNT, root.join("base"), 0));
+
+        let read = |vfs: &VirtualFileSystem, name| vfs.read_to_string(AssetDir::Scenes, name);
+        assert_eq!(read(&vfs, "a.toml").unwrap(), "mod");
+        assert_eq!(read(&vfs, "b.toml").unwrap(), "base");
+        assert_eq!(vfs.list(AssetDir::Scenes), vec!["a.toml", "b.toml"]);
+
+        assert!(vfs.unmount("mod"));
+        assert_eq!(read(&vfs, "a.toml").unwrap(), "base");
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn dir_mount_overrides_base_for_its_dir_only() {
+        let root = scratch_dir("dir-mount");
+        write_file(root.join("base/shaders/s.wgsl"), "base");
+        write_file(root.join("base/scenes/s.wgsl"), "base");
+        write_file(root.join("shaders/s.wgsl"), "override");
+
+        let mut vfs = VirtualFileSystem::new();
+        vfs.mount(MountPoint::new(BASE_MOUNT, root.join("base"), 0));
+        vfs.mount(MountPoint::for_dir(
+            "shaders",
+            root.join("shaders"),
+            AssetDir::Shaders,
+            1,
+        ));
+
+        let shader = vfs.read_to_string(AssetDir::Shaders, "s.wgsl").unwrap();
+        let scene = vfs.read_to_string(AssetDir::Scenes, "s.wgsl").unwrap();
+        assert_eq!(shader, "override");
+        assert_eq!(scene, "base");
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn packed_archive_round_trips_through_vfs() {
+        let root = scratch_dir("archive");
+        let scene = "[scene]\n".repeat(64);
+        write_file(root.join("assets/scenes/main.toml"), &scene);
+        write_file(root.join("assets/shaders/nested/a.wgsl"), "fn main() {}");
+
+        for compress in [true, false] {
+            let mut builder = ArchiveBuilder::new(compress);
+            assert_eq!(builder.add_dir(root.join("assets")).unwrap(), 2);
+            let path = root.join("assets").with_extension(RPAK_EXTENSION);
+            builder.write(&path).unwrap();
+
+            let mut vfs = VirtualFileSystem::new();
+            vfs.mount_archive(Archive::open(&path).unwrap());
+            assert!(vfs.exists(AssetDir::Shaders, "nested/a.wgsl"));
+            assert_eq!(
+                vfs.read_to_string(AssetDir::Scenes, "main.toml").unwrap(),
+                scene
+            );
+            assert_eq!(vfs.list(AssetDir::Shaders), vec!["nested/a.wgsl"]);
+            assert!(vfs.read(AssetDir::Scenes, "missing.toml").is_err());
+        }
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn bad_archive_is_skipped() {
+        let root = scratch_dir("bad-archive");
+        write_file(root.join("static/res/cube.obj"), "base");
+        write_file(root.join("broken.rpak"), "not an archive");
+
+        let mut config: VfsConfig =
+            toml::from_str("[paths]\nstatic = \"static\"\narchive = \"broken.rpak\"\n").unwrap();
+        config.base_dir = Some(root.clone());
+        let vfs = VirtualFileSystem::from_config(&config).unwrap();
+
+        assert!(vfs.archives().is_empty());
+        assert_eq!(
+            vfs.read_to_string(AssetDir::Res, "cube.obj").unwrap(),
+            "base"
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn config_paths_resolve_against_config_dir() {
+        let root = scratch_dir("config-dir");
+        write_file(root.join("static/res/cube.obj"), "base");
+        write_file(root.join("extra/res/cube.obj"), "extra");
+
+        let mut config: VfsConfig = toml::from_str(
+            "[paths]\nstatic = \"static\"\n\n[[mounts]]\nname = \"extra\"\npath = \"extra\"\npriority = 3\n",
+        )
+        .unwrap();
+        config.base_dir = Some(root.clone());
+        let vfs = VirtualFileSystem::from_config(&config).unwrap();
+
+        assert_eq!(vfs.base_dir().unwrap(), root.join("static"));
+        let mount = vfs.mounts().iter().find(|m| m.name == "extra").unwrap();
+        assert_eq!(mount.root, root.join("extra"));
+        assert_eq!(
+            vfs.read_to_string(AssetDir::Res, "cube.obj").unwrap(),
+            "extra"
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crossbeam::channel::{self, Receiver};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::{
+    error::AppError,
+    vfs::{AssetDir, VFS},
+};
+use crate::log_warning;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AssetChange {
+    Shader(String),
+    Texture(String),
+    Model(String),
+    Material(String),
+}
+
+impl AssetChange {
+    pub fn name(&self) -> &str {
+        match self {
+            AssetChange::Shader(name)
+            | AssetChange::Texture(name)
+            | AssetChange::Model(name)
+            | AssetChange::Material(name) => name,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<AssetChange> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        let vfs = VFS.read().ok()?;
+        match extension.as_str() {
+            "wgsl" => vfs
+                .relative_name(AssetDir::Shaders, path)
+                .map(AssetChange::Shader),
+            "png" | "jpg" | "jpeg" => vfs
+                .relative_name(AssetDir::Res, path)
+                .map(AssetChange::Texture),
+            "obj" => vfs
+                .relative_name(AssetDir::Res, path)
+                .map(AssetChange::Model),
+            "mtl" => vfs
+                .relative_name(AssetDir::Res, path)
+                .map(AssetChange::Material),
+            _ => None,
+        }
+    }
+}
+
+pub struct AssetWatcher {
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<PathBuf>,
+}
+
+impl AssetWatcher {
+    pub fn new(roots: Vec<PathBuf>) -> Result<Self, AppError> {
+        let (tx, rx) = channel::unbounded();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) => {
+                    if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                        for path in event.paths {
+                            let _ = tx.send(path);
+                        }
+                    }
+                }
+                Err(e) => {
+                    log_warning!("AssetWatcher: {:?}", e);
+                }
+            })?;
+        for root in roots.iter().filter(|root| root.exists()) {
+            watcher.watch(root, RecursiveMode::Recursive)?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver: rx,
+        })
+    }
+
+    pub fn from_assets_dir() -> Result<Self, AppError> {
+        let roots = {
+            let vfs = VFS
+                .read()
+                .map_err(|e| AppError::LockAcquisitionFailure(e.to_string()))?;
+            let mut roots: Vec<PathBuf> = vfs.mounts().iter().map(|m| m.root.clone()).collect();
+            roots.sort();
+            roots.dedup();
+            roots
+        };
+        Self::new(roots)
+    }
+
+    pub fn poll(&self) -> Vec<AssetChange> {
+        let mut seen = HashSet::new();
+        let mut changes = Vec::new();
+        while let Ok(path) = self.receiver.try_recv() {
+            if let Some(change) = AssetChange::from_path(&path) {
+                if seen.insert(change.clone()) {
+                    changes.push(change);
+                }
+            }
+        }
+        changes
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    core::cache::{CacheKey, HasCacheKey, HashCache},
+    ecs::{
+        components::{instance::model::Instance, transform::Transform},
+        traits::Cache,
+    },
+};
+
+use super::{
+    clip::AnimationClip,
+    player::{blend_poses, AnimationEvent, AnimationPlayer, PlaybackMode},
+};
+
+/// Plays transform clips on an entity. Clip poses are relative: they are composed onto
+/// the entity's transform and onto each of its instances as they were when the animator
+/// first ran, so instanced copies keep their own placement.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Animator {
+    pub player: AnimationPlayer,
+    pub base: Option<Transform>,
+    pub instance_bases: Vec<Transform>,
+}
+
+impl Animator {
+    pub const LABEL: &'static str = "component:animator";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn playing(clip: CacheKey, mode: PlaybackMode) -> Self {
+        let mut animator = Self::new();
+        animator.play(clip, mode);
+        animator
+    }
+
+    pub fn play(&mut self, clip: CacheKey, mode: PlaybackMode) {
+        self.player.play(clip);
+        self.player.set_mode(clip, mode);
+    }
+
+    pub fn crossfade(&mut self, clip: CacheKey, mode: PlaybackMode, duration: f32) {
+        self.player.crossfade(clip, duration);
+        self.player.set_mode(clip, mode);
+    }
+
+    pub fn stop(&mut self, duration: f32) {
+        self.player.stop(duration);
+    }
+
+    pub fn set_speed(&mut self, clip: CacheKey, speed: f32) {
+        self.player.set_speed(clip, speed);
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.player.paused = paused;
+    }
+
+    pub fn is_playing(&self, clip: CacheKey) -> bool {
+        self.player.is_playing(clip)
+    }
+
+    pub fn is_finished(&self, clips: &HashCache<AnimationClip>) -> bool {
+        self.player.is_finished(clips)
+    }
+
+    pub fn advance(&mut self, dt: f32, clips: &HashCache<AnimationClip>) -> Vec<AnimationEvent> {
+        self.player.advance(dt, clips)
+    }
+
+    pub fn sample(&self, clips: &HashCache<AnimationClip>) -> Transform {
+        let rest = Transform::identity();
+        let poses: Vec<(Vec<Transform>, f32)> = self
+            .player
+            .layers
+            .iter()
+            .filter(|layer| layer.weight > 0.0)
+            .filter_map(|layer| {
+                let clip = clips.get(&layer.clip)?;
+                let mut pose = [rest];
+                clip.sample(layer.time, &mut pose);
+                Some((pose.to_vec(), layer.weight))
+            })
+            .collect();
+        blend_poses(&poses, &[rest])
+            .first()
+            .copied()
+            .unwrap_or(rest)
+    }
+
+    pub fn animate(&mut self, pose: Transform, current: Transform) -> Transform {
+        self.base.get_or_insert(current).compose(&pose)
+    }
+
+    pub fn animate_instances(&mut self, pose: Transform, instances: &mut [Instance]) {
+        self.instance_bases.truncate(instances.len());
+        let captured = self.instance_bases.len();
+        self.instance_bases.extend(
+            instances[captured..]
+                .iter()
+                .map(|instance| instance.transform),
+        );
+        for (instance, base) in instances.iter_mut().zip(&self.instance_bases) {
+            instance.transform = base.compose(&pose);
+        }
+    }
+
+    /// Forgets the captured bases so the next update re-anchors on the current transforms.
+    pub fn rebase(&mut self) {
+        self.base = None;
+        self.instance_bases.clear();
+    }
+}
+
+impl HasCacheKey for Animator {
+    fn key(suffixes: Vec<&str>) -> CacheKey {
+        let mut base = String::from(Self::LABEL);
+        for suffix in suffixes {
+            base.push_str(format!(":{}", suffix).as_ref());
+        }
+        CacheKey::from(&base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Quaternion, Rotation3, Vector3};
+
+    use super::*;
+    use crate::ecs::components::animation::clip::{AnimationChannel, Interpolation, Keyframes};
+
+    fn lift_clip() -> AnimationClip {
+        AnimationClip::new(
+            "lift".to_string(),
+            vec![AnimationChannel {
+                target: 0,
+                interpolation: Interpolation::Linear,
+                times: vec![0.0, 1.0],
+                keyframes: Keyframes::Translation(vec![
+                    Vector3::new(0.0, 0.0, 0.0),
+                    Vector3::new(0.0, 2.0, 0.0),
+                ]),
+            }],
+        )
+    }
+
+    fn placed(x: f32, yaw: f32) -> Transform {
+        Transform {
+            position: Vector3::new(x, 0.0, 0.0),
+            rotation: Quaternion::from_angle_y(cgmath::Deg(yaw)),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    fn assert_near(actual: Vector3<f32>, expected: Vector3<f32>) {
+        assert!(
+            (actual.x - expected.x).abs() < 1e-5
+                && (actual.y - expected.y).abs() < 1e-5
+                && (actual.z - expected.z).abs() < 1e-5,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn instances_keep_their_placement() {
+        let mut clips = HashCache::new();
+        let key = AnimationClip::key(vec!["lift"]);
+        clips.put(key, lift_clip());
+
+        let mut animator = Animator::playing(key, PlaybackMode::Loop);
+        let mut instances = vec![
+            Instance {
+                transform: placed(-3.0, 0.0),
+            },
+            Instance {
+                transform: placed(3.0, 90.0),
+            },
+        ];
+
+        for _ in 0..2 {
+            animator.advance(0.25, &clips);
+            let pose = animator.sample(&clips);
+            animator.animate_instances(pose, &mut instances);
+        }
+
+        assert_near(
+            instances[0].transform.position,
+            Vector3::new(-3.0, 1.0, 0.0),
+        );
+        assert_near(instances[1].transform.position, Vector3::new(3.0, 1.0, 0.0));
+        assert_eq!(instances[1].transform.rotation, placed(3.0, 90.0).rotation);
+    }
+
+    #[test]
+    fn entity_transform_is_animated_from_its_base() {
+        let mut clips = HashCache::new();
+        let key = AnimationClip::key(vec!["lift"]);
+        clips.put(key, lift_clip());
+
+        let mut animator = Animator::playing(key, PlaybackMode::Once);
+        let mut transform = placed(1.0, 0.0);
+        for _ in 0..4 {
+            animator.advance(0.5, &clips);
+            let pose = animator.sample(&clips);
+            transform = animator.animate(pose, transform);
+        }
+        assert_near(transform.position, Vector3::new(1.0, 2.0, 0.0));
+
+        animator.rebase();
+        let pose = animator.sample(&clips);
+        assert_near(
+            animator.animate(pose, transform).position,
+            Vector3::new(1.0, 4.0, 0.0),
+        );
+    }
+}
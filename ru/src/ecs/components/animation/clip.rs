@@ -0,0 +1,319 @@
+use std::ops::{Add, Mul};
+
+use cgmath::{InnerSpace, Quaternion, Vector3};
+
+use crate::{
+    core::cache::{CacheKey, HasCacheKey},
+    ecs::components::transform::Transform,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Step,
+    Linear,
+    CubicSpline,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Keyframes {
+    Translation(Vec<Vector3<f32>>),
+    Rotation(Vec<Quaternion<f32>>),
+    Scale(Vec<Vector3<f32>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationChannel {
+    pub target: usize,
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    pub keyframes: Keyframes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationMarker {
+    pub time: f32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationClip {
+    pub name: String,
+    pub duration: f32,
+    pub channels: Vec<AnimationChannel>,
+    pub markers: Vec<AnimationMarker>,
+}
+
+impl AnimationClip {
+    pub const LABEL: &'static str = "component:animation_clip";
+
+    pub fn new(name: String, channels: Vec<AnimationChannel>) -> Self {
+        let duration = channels
+            .iter()
+            .filter_map(|channel| channel.times.last().copied())
+            .fold(0.0, f32::max);
+        Self {
+            name,
+            duration,
+            channels,
+            markers: Vec::new(),
+        }
+    }
+
+    pub fn with_marker(mut self, time: f32, name: &str) -> Self {
+        self.markers.push(AnimationMarker {
+            time: time.clamp(0.0, self.duration),
+            name: name.to_string(),
+        });
+        self
+    }
+
+    pub fn sample(&self, time: f32, pose: &mut [Transform]) {
+        for channel in &self.channels {
+            if let Some(transform) = pose.get_mut(channel.target) {
+                channel.apply(time, transform);
+            }
+        }
+    }
+}
+
+impl HasCacheKey for AnimationClip {
+    fn key(suffixes: Vec<&str>) -> CacheKey {
+        let mut base = String::from(Self::LABEL);
+        for suffix in suffixes {
+            base.push_str(format!(":{}", suffix).as_ref());
+        }
+        CacheKey::from(&base)
+    }
+}
+
+impl AnimationChannel {
+    pub fn apply(&self, time: f32, transform: &mut Transform) {
+        match &self.keyframes {
+            Keyframes::Translation(values) => {
+                if let Some(value) = self.sample_values(values, time) {
+                    transform.position = value;
+                }
+            }
+            Keyframes::Scale(values) => {
+                if let Some(value) = self.sample_values(values, time) {
+                    transform.scale = value;
+                }
+            }
+            Keyframes::Rotation(values) => {
+                if let Some(value) = self.sample_rotation(values, time) {
+                    transform.rotation = value;
+                }
+            }
+        }
+    }
+
+    fn key_value<T: Copy>(&self, values: &[T], key: usize) -> Option<T> {
+        match self.interpolation {
+            Interpolation::CubicSpline => values.get(key * 3 + 1).copied(),
+            _ => values.get(key).copied(),
+        }
+    }
+
+    fn sample_values<T>(&self, values: &[T], time: f32) -> Option<T>
+    where
+        T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+    {
+        let (k0, k1, t, dt) = locate(&self.times, time)?;
+        let v0 = self.key_value(values, k0)?;
+        if k0 == k1 {
+            return Some(v0);
+        }
+        let v1 = self.key_value(values, k1)?;
+        match self.interpolation {
+            Interpolation::Step => Some(v0),
+            Interpolation::Linear => Some(v0 * (1.0 - t) + v1 * t),
+            Interpolation::CubicSpline => {
+                let out_tangent = *values.get(k0 * 3 + 2)?;
+                let in_tangent = *values.get(k1 * 3)?;
+                Some(hermite(v0, out_tangent, v1, in_tangent, t, dt))
+            }
+        }
+    }
+
+    fn sample_rotation(&self, values: &[Quaternion<f32>], time: f32) -> Option<Quaternion<f32>> {
+        let (k0, k1, t, _) = locate(&self.times, time)?;
+        match self.interpolation {
+            Interpolation::Linear if k0 != k1 => {
+                let v0 = self.key_value(values, k0)?;
+                let v1 = self.key_value(values, k1)?;
+                Some(slerp(v0, v1, t))
+            }
+            _ => self
+                .sample_values(values, time)
+                .map(|rotation| rotation.normalize()),
+        }
+    }
+}
+
+fn locate(times: &[f32], time: f32) -> Option<(usize, usize, f32, f32)> {
+    let last = times.len().checked_sub(1)?;
+    if time <= times[0] {
+        return Some((0, 0, 0.0, 0.0));
+    }
+    if time >= times[last] {
+        return Some((last, last, 0.0, 0.0));
+    }
+    let k1 = times.partition_point(|t| *t <= time);
+    let k0 = k1 - 1;
+    let dt = times[k1] - times[k0];
+    if dt <= f32::EPSILON {
+        return Some((k1, k1, 0.0, 0.0));
+    }
+    Some((k0, k1, (time - times[k0]) / dt, dt))
+}
+
+fn hermite<T>(v0: T, out_tangent: T, v1: T, in_tangent: T, t: f32, dt: f32) -> T
+where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+    let t2 = t * t;
+    let t3 = t2 * t;
+    v0 * (2.0 * t3 - 3.0 * t2 + 1.0)
+        + out_tangent * ((t3 - 2.0 * t2 + t) * dt)
+        + v1 * (-2.0 * t3 + 3.0 * t2)
+        + in_tangent * ((t3 - t2) * dt)
+}
+
+pub fn slerp(a: Quaternion<f32>, b: Quaternion<f32>, t: f32) -> Quaternion<f32> {
+    let b = if a.dot(b) < 0.0 { -b } else { b };
+    let dot = a.dot(b).min(1.0);
+    if dot > 0.9995 {
+        return (a * (1.0 - t) + b * t).normalize();
+    }
+    let theta = dot.acos();
+    let sin_theta = theta.sin();
+    (a * (((1.0 - t) * theta).sin() / sin_theta) + b * ((t * theta).sin() / sin_theta)).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, Rotation3};
+
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn translation(interpolation: Interpolation, values: Vec<Vector3<f32>>) -> AnimationChannel {
+        AnimationChannel {
+            target: 0,
+            interpolation,
+            times: vec![0.0, 1.0, 2.0],
+            keyframes: Keyframes::Translation(values),
+        }
+    }
+
+    fn sample_x(channel: &AnimationChannel, time: f32) -> f32 {
+        let mut transform = Transform::identity();
+        channel.apply(time, &mut transform);
+        transform.position.x
+    }
+
+    fn x(value: f32) -> Vector3<f32> {
+        Vector3::new(value, 0.0, 0.0)
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < EPSILON,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn step_holds_the_previous_key() {
+        let channel = translation(Interpolation::Step, vec![x(0.0), x(1.0), x(4.0)]);
+        for (time, expected) in [(-1.0, 0.0), (0.5, 0.0), (1.0, 1.0), (1.99, 1.0), (3.0, 4.0)] {
+            assert_close(sample_x(&channel, time), expected);
+        }
+    }
+
+    #[test]
+    fn linear_interpolates_and_clamps() {
+        let channel = translation(Interpolation::Linear, vec![x(0.0), x(1.0), x(4.0)]);
+        for (time, expected) in [
+            (-1.0, 0.0),
+            (0.25, 0.25),
+            (1.5, 2.5),
+            (2.0, 4.0),
+            (5.0, 4.0),
+        ] {
+            assert_close(sample_x(&channel, time), expected);
+        }
+    }
+
+    #[test]
+    fn cubic_spline_uses_tangents() {
+        // [in, value, out] per key.
+        let matching = translation(
+            Interpolation::CubicSpline,
+            vec![
+                x(1.0),
+                x(0.0),
+                x(1.0),
+                x(1.0),
+                x(1.0),
+                x(1.0),
+                x(1.0),
+                x(2.0),
+                x(1.0),
+            ],
+        );
+        assert_close(sample_x(&matching, 0.25), 0.25);
+        assert_close(sample_x(&matching, 1.5), 1.5);
+
+        let flat = translation(
+            Interpolation::CubicSpline,
+            vec![
+                x(0.0),
+                x(0.0),
+                x(0.0),
+                x(0.0),
+                x(1.0),
+                x(0.0),
+                x(0.0),
+                x(1.0),
+                x(0.0),
+            ],
+        );
+        assert_close(sample_x(&flat, 0.25), 0.15625);
+        assert_close(sample_x(&flat, 0.5), 0.5);
+        assert_close(sample_x(&flat, 1.0), 1.0);
+    }
+
+    #[test]
+    fn slerp_follows_the_short_arc() {
+        let start = Quaternion::from_angle_y(Deg(0.0));
+        let end = Quaternion::from_angle_y(Deg(90.0));
+        let half = Quaternion::from_angle_y(Deg(45.0));
+
+        assert_close(slerp(start, end, 0.0).dot(start).abs(), 1.0);
+        assert_close(slerp(start, end, 1.0).dot(end).abs(), 1.0);
+        assert_close(slerp(start, end, 0.5).dot(half).abs(), 1.0);
+        assert_close(slerp(start, -end, 0.5).dot(half).abs(), 1.0);
+        assert_close(slerp(start, end, 0.3).magnitude(), 1.0);
+    }
+
+    #[test]
+    fn linear_rotation_channels_slerp() {
+        let channel = AnimationChannel {
+            target: 0,
+            interpolation: Interpolation::Linear,
+            times: vec![0.0, 1.0],
+            keyframes: Keyframes::Rotation(vec![
+                Quaternion::from_angle_y(Deg(0.0)),
+                Quaternion::from_angle_y(Deg(120.0)),
+            ]),
+        };
+        let mut transform = Transform::identity();
+        channel.apply(0.25, &mut transform);
+        let expected = Quaternion::from_angle_y(Deg(30.0));
+        assert_close(transform.rotation.dot(expected).abs(), 1.0);
+    }
+}
@@ -0,0 +1,21 @@
+use crate::core::cache::HashCache;
+
+use super::clip::AnimationClip;
+
+pub struct AnimationManager {
+    pub clips: HashCache<AnimationClip>,
+}
+
+impl AnimationManager {
+    pub fn new() -> Self {
+        Self {
+            clips: HashCache::new(),
+        }
+    }
+}
+
+impl Default for AnimationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,5 @@
+pub mod animator;
+pub mod clip;
+pub mod manager;
+pub mod player;
+pub mod skeleton;
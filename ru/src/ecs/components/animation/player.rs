@@ -0,0 +1,505 @@
+use cgmath::InnerSpace;
+
+use crate::{
+    core::cache::{CacheKey, HasCacheKey, HashCache},
+    ecs::{components::transform::Transform, traits::Cache},
+};
+
+use super::clip::AnimationClip;
+
+pub const DEFAULT_CROSSFADE_SECONDS: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackMode {
+    Once,
+    #[default]
+    Loop,
+    PingPong,
+}
+
+impl PlaybackMode {
+    pub fn period(self, duration: f32) -> f32 {
+        match self {
+            PlaybackMode::PingPong => duration * 2.0,
+            _ => duration,
+        }
+    }
+
+    pub fn clip_time(self, elapsed: f32, duration: f32) -> f32 {
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match self {
+            PlaybackMode::Once => elapsed.clamp(0.0, duration),
+            PlaybackMode::Loop => elapsed.rem_euclid(duration),
+            PlaybackMode::PingPong => {
+                let phase = elapsed.rem_euclid(duration * 2.0);
+                if phase > duration {
+                    duration * 2.0 - phase
+                } else {
+                    phase
+                }
+            }
+        }
+    }
+
+    pub fn crossings(self, marker: f32, from: f32, to: f32, duration: f32, start: bool) -> usize {
+        if duration <= 0.0 || (from == to && !start) {
+            return 0;
+        }
+        match self {
+            PlaybackMode::Once => count_crossings(marker, f32::INFINITY, from, to, start),
+            PlaybackMode::Loop => {
+                let start = start && marker < duration;
+                count_crossings(marker, duration, from, to, start)
+            }
+            PlaybackMode::PingPong => {
+                let period = duration * 2.0;
+                let mut count = count_crossings(marker, period, from, to, start);
+                if marker > 0.0 && marker < duration {
+                    count += count_crossings(period - marker, period, from, to, start);
+                }
+                count
+            }
+        }
+    }
+}
+
+fn count_crossings(offset: f32, period: f32, from: f32, to: f32, start: bool) -> usize {
+    if period.is_infinite() {
+        let (low, high) = (from.min(to), from.max(to));
+        let inside = offset > low && offset < high;
+        let at_end = offset == to;
+        let at_start = start && offset == from;
+        return usize::from(inside || at_end || at_start);
+    }
+    let a = (from - offset) / period;
+    let b = (to - offset) / period;
+    let count = match (to >= from, start) {
+        (true, false) => b.floor() - a.floor(),
+        (true, true) => b.floor() - a.ceil() + 1.0,
+        (false, false) => a.ceil() - b.ceil(),
+        (false, true) => a.floor() - b.ceil() + 1.0,
+    };
+    count.max(0.0) as usize
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationEvent {
+    pub clip: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationLayer {
+    pub clip: CacheKey,
+    pub time: f32,
+    pub elapsed: f32,
+    pub speed: f32,
+    pub weight: f32,
+    pub target_weight: f32,
+    pub fade_rate: f32,
+    pub mode: PlaybackMode,
+    pub started: bool,
+}
+
+impl AnimationLayer {
+    pub fn new(clip: CacheKey, weight: f32) -> Self {
+        Self {
+            clip,
+            time: 0.0,
+            elapsed: 0.0,
+            speed: 1.0,
+            weight,
+            target_weight: weight,
+            fade_rate: 0.0,
+            mode: PlaybackMode::Loop,
+            started: false,
+        }
+    }
+
+    pub fn is_finished(&self, duration: f32) -> bool {
+        self.mode == PlaybackMode::Once
+            && self.started
+            && (self.elapsed >= duration && self.speed > 0.0
+                || self.elapsed <= 0.0 && self.speed < 0.0)
+    }
+
+    fn fade_to(&mut self, target_weight: f32, duration: f32) {
+        self.target_weight = target_weight;
+        if duration <= f32::EPSILON {
+            self.weight = target_weight;
+            self.fade_rate = 0.0;
+        } else {
+            self.fade_rate = (target_weight - self.weight).abs() / duration;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnimationPlayer {
+    pub layers: Vec<AnimationLayer>,
+    pub paused: bool,
+}
+
+impl AnimationPlayer {
+    pub const LABEL: &'static str = "component:animation_player";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn playing(clip: CacheKey) -> Self {
+        let mut player = Self::new();
+        player.play(clip);
+        player
+    }
+
+    pub fn play(&mut self, clip: CacheKey) {
+        self.layers = vec![AnimationLayer::new(clip, 1.0)];
+    }
+
+    pub fn crossfade(&mut self, clip: CacheKey, duration: f32) {
+        for layer in self.layers.iter_mut().filter(|layer| layer.clip != clip) {
+            layer.fade_to(0.0, duration);
+        }
+        match self.layer_mut(clip) {
+            Some(layer) => layer.fade_to(1.0, duration),
+            None => {
+                let mut layer = AnimationLayer::new(clip, 0.0);
+                layer.fade_to(1.0, duration);
+                self.layers.push(layer);
+            }
+        }
+    }
+
+    pub fn blend(&mut self, clip: CacheKey, weight: f32) {
+        match self.layer_mut(clip) {
+            Some(layer) => layer.fade_to(weight, 0.0),
+            None => self.layers.push(AnimationLayer::new(clip, weight)),
+        }
+    }
+
+    pub fn stop(&mut self, duration: f32) {
+        for layer in &mut self.layers {
+            layer.fade_to(0.0, duration);
+        }
+    }
+
+    pub fn set_speed(&mut self, clip: CacheKey, speed: f32) {
+        if let Some(layer) = self.layer_mut(clip) {
+            layer.speed = speed;
+        }
+    }
+
+    pub fn set_mode(&mut self, clip: CacheKey, mode: PlaybackMode) {
+        if let Some(layer) = self.layer_mut(clip) {
+            layer.mode = mode;
+        }
+    }
+
+    pub fn is_playing(&self, clip: CacheKey) -> bool {
+        self.layers
+            .iter()
+            .any(|layer| layer.clip == clip && layer.target_weight > 0.0)
+    }
+
+    pub fn layer_mut(&mut self, clip: CacheKey) -> Option<&mut AnimationLayer> {
+        self.layers.iter_mut().find(|layer| layer.clip == clip)
+    }
+
+    pub fn advance(&mut self, dt: f32, clips: &HashCache<AnimationClip>) -> Vec<AnimationEvent> {
+        let mut events = Vec::new();
+        if self.paused {
+            return events;
+        }
+        for layer in &mut self.layers {
+            let Some(clip) = clips.get(&layer.clip) else {
+                layer.time = 0.0;
+                continue;
+            };
+            let duration = clip.duration;
+            let from = layer.elapsed;
+            let mut to = from + dt * layer.speed;
+            if layer.mode == PlaybackMode::Once {
+                to = to.clamp(0.0, duration);
+            }
+
+            if layer.target_weight > 0.0 {
+                for marker in &clip.markers {
+                    let count =
+                        layer
+                            .mode
+                            .crossings(marker.time, from, to, duration, !layer.started);
+                    for _ in 0..count {
+                        events.push(AnimationEvent {
+                            clip: clip.name.clone(),
+                            name: marker.name.clone(),
+                        });
+                    }
+                }
+            }
+
+            layer.started = true;
+            layer.elapsed = match layer.mode {
+                PlaybackMode::Once => to,
+                mode if duration > 0.0 => to.rem_euclid(mode.period(duration)),
+                _ => 0.0,
+            };
+            layer.time = layer.mode.clip_time(layer.elapsed, duration);
+
+            let step = layer.fade_rate * dt;
+            if layer.weight < layer.target_weight {
+                layer.weight = (layer.weight + step).min(layer.target_weight);
+            } else if layer.weight > layer.target_weight {
+                layer.weight = (layer.weight - step).max(layer.target_weight);
+            }
+        }
+        self.layers
+            .retain(|layer| layer.weight > 0.0 || layer.target_weight > 0.0);
+        events
+    }
+
+    pub fn is_finished(&self, clips: &HashCache<AnimationClip>) -> bool {
+        self.layers.iter().all(|layer| {
+            clips
+                .get(&layer.clip)
+                .map(|clip| layer.is_finished(clip.duration))
+                .unwrap_or(true)
+        })
+    }
+
+    pub fn sample(
+        &self,
+        clips: &HashCache<AnimationClip>,
+        rest_pose: &[Transform],
+    ) -> Vec<Transform> {
+        let poses: Vec<(Vec<Transform>, f32)> = self
+            .layers
+            .iter()
+            .filter(|layer| layer.weight > 0.0)
+            .filter_map(|layer| {
+                let clip = clips.get(&layer.clip)?;
+                let mut pose = rest_pose.to_vec();
+                clip.sample(layer.time, &mut pose);
+                Some((pose, layer.weight))
+            })
+            .collect();
+        blend_poses(&poses, rest_pose)
+    }
+}
+
+pub fn blend_poses(poses: &[(Vec<Transform>, f32)], rest_pose: &[Transform]) -> Vec<Transform> {
+    let total: f32 = poses.iter().map(|(_, weight)| weight).sum();
+    let rest_weight = (1.0 - total).max(0.0);
+
+    rest_pose
+        .iter()
+        .enumerate()
+        .map(|(joint, rest)| {
+            let mut position = rest.position * rest_weight;
+            let mut scale = rest.scale * rest_weight;
+            let mut rotation = rest.rotation * rest_weight;
+            for (pose, weight) in poses {
+                let Some(transform) = pose.get(joint) else {
+                    continue;
+                };
+                position += transform.position * *weight;
+                scale += transform.scale * *weight;
+                let sign = if rest.rotation.dot(transform.rotation) < 0.0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+                rotation += transform.rotation * (*weight * sign);
+            }
+
+            let weight = total + rest_weight;
+            if weight <= f32::EPSILON || rotation.magnitude2() <= f32::EPSILON {
+                return *rest;
+            }
+            Transform {
+                position: position / weight,
+                rotation: rotation.normalize(),
+                scale: scale / weight,
+            }
+        })
+        .collect()
+}
+
+impl HasCacheKey for AnimationPlayer {
+    fn key(suffixes: Vec<&str>) -> CacheKey {
+        let mut base = String::from(Self::LABEL);
+        for suffix in suffixes {
+            base.push_str(format!(":{}", suffix).as_ref());
+        }
+        CacheKey::from(&base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Vector3;
+
+    use super::*;
+    use crate::ecs::components::animation::clip::{AnimationChannel, Interpolation, Keyframes};
+
+    const EPSILON: f32 = 1e-5;
+
+    fn clip_with_marker(marker: f32) -> AnimationClip {
+        AnimationClip::new(
+            "lift".to_string(),
+            vec![AnimationChannel {
+                target: 0,
+                interpolation: Interpolation::Linear,
+                times: vec![0.0, 1.0],
+                keyframes: Keyframes::Translation(vec![
+                    Vector3::new(0.0, 0.0, 0.0),
+                    Vector3::new(0.0, 2.0, 0.0),
+                ]),
+            }],
+        )
+        .with_marker(marker, "marker")
+    }
+
+    fn clips(clip: AnimationClip) -> (HashCache<AnimationClip>, CacheKey) {
+        let key = AnimationClip::key(vec![&clip.name]);
+        let mut clips = HashCache::new();
+        clips.put(key, clip);
+        (clips, key)
+    }
+
+    #[test]
+    fn count_crossings_once_counts_markers_inside_the_step() {
+        let infinite = f32::INFINITY;
+        assert_eq!(count_crossings(0.5, infinite, 0.0, 1.0, false), 1);
+        assert_eq!(count_crossings(0.5, infinite, 1.0, 0.0, false), 1);
+        assert_eq!(count_crossings(0.5, infinite, 0.0, 0.25, false), 0);
+        assert_eq!(count_crossings(0.5, infinite, 0.0, 0.5, false), 1);
+        assert_eq!(count_crossings(0.5, infinite, 0.5, 1.0, false), 0);
+        assert_eq!(count_crossings(0.5, infinite, 0.5, 1.0, true), 1);
+    }
+
+    #[test]
+    fn count_crossings_periodic_counts_every_wrap() {
+        assert_eq!(count_crossings(0.25, 1.0, 0.0, 2.5, false), 3);
+        assert_eq!(count_crossings(0.25, 1.0, 2.5, 0.0, false), 3);
+        assert_eq!(count_crossings(0.25, 1.0, 0.3, 1.2, false), 0);
+    }
+
+    #[test]
+    fn count_crossings_end_is_inclusive_and_start_only_when_starting() {
+        assert_eq!(count_crossings(0.5, 1.0, 0.0, 0.5, false), 1);
+        assert_eq!(count_crossings(0.5, 1.0, 0.5, 1.0, false), 0);
+        assert_eq!(count_crossings(0.5, 1.0, 0.5, 1.0, true), 1);
+        assert_eq!(count_crossings(0.5, 1.0, 1.5, 0.5, false), 1);
+        assert_eq!(count_crossings(0.5, 1.0, 1.5, 0.5, true), 2);
+    }
+
+    #[test]
+    fn clip_time_wraps_per_mode() {
+        let cases = [
+            (PlaybackMode::Once, 1.5, 1.0),
+            (PlaybackMode::Once, -0.5, 0.0),
+            (PlaybackMode::Once, 0.25, 0.25),
+            (PlaybackMode::Loop, 2.25, 0.25),
+            (PlaybackMode::Loop, -0.25, 0.75),
+            (PlaybackMode::PingPong, 0.5, 0.5),
+            (PlaybackMode::PingPong, 1.25, 0.75),
+            (PlaybackMode::PingPong, 2.25, 0.25),
+            (PlaybackMode::PingPong, -0.25, 0.25),
+        ];
+        for (mode, elapsed, expected) in cases {
+            let time = mode.clip_time(elapsed, 1.0);
+            assert!(
+                (time - expected).abs() < EPSILON,
+                "{:?} at {} gave {}, expected {}",
+                mode,
+                elapsed,
+                time,
+                expected
+            );
+        }
+        assert_eq!(PlaybackMode::Loop.clip_time(3.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn ping_pong_crosses_markers_both_ways() {
+        let mode = PlaybackMode::PingPong;
+        assert_eq!(mode.crossings(0.25, 0.0, 2.0, 1.0, false), 2);
+        assert_eq!(mode.crossings(0.25, 0.0, 1.0, 1.0, false), 1);
+        assert_eq!(mode.crossings(1.0, 0.0, 2.0, 1.0, false), 1);
+        assert_eq!(mode.crossings(0.0, 0.0, 2.0, 1.0, true), 2);
+    }
+
+    #[test]
+    fn advance_emits_events_for_each_loop() {
+        let (clips, key) = clips(clip_with_marker(0.5));
+        let mut player = AnimationPlayer::playing(key);
+        let events = player.advance(2.0, &clips);
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| event.name == "marker"));
+        assert!(player.layers[0].time.abs() < EPSILON);
+    }
+
+    #[test]
+    fn once_clamps_and_finishes() {
+        let (clips, key) = clips(clip_with_marker(1.0));
+        let mut player = AnimationPlayer::playing(key);
+        player.set_mode(key, PlaybackMode::Once);
+        assert!(!player.is_finished(&clips));
+
+        assert_eq!(player.advance(5.0, &clips).len(), 1);
+        assert!((player.layers[0].time - 1.0).abs() < EPSILON);
+        assert!(player.is_finished(&clips));
+        assert!(player.advance(1.0, &clips).is_empty());
+    }
+
+    #[test]
+    fn ping_pong_advance_bounces_back() {
+        let (clips, key) = clips(clip_with_marker(0.5));
+        let mut player = AnimationPlayer::playing(key);
+        player.set_mode(key, PlaybackMode::PingPong);
+        player.advance(1.25, &clips);
+        assert!((player.layers[0].time - 0.75).abs() < EPSILON);
+    }
+
+    fn at(x: f32) -> Transform {
+        Transform {
+            position: Vector3::new(x, 0.0, 0.0),
+            ..Transform::identity()
+        }
+    }
+
+    #[test]
+    fn blend_poses_weights_layers_and_fills_with_rest() {
+        let rest = vec![at(0.0), at(10.0)];
+        let walk = vec![at(2.0), at(20.0)];
+        let run = vec![at(4.0), at(30.0)];
+
+        let blended = blend_poses(&[(walk.clone(), 0.5), (run, 0.5)], &rest);
+        assert!((blended[0].position.x - 3.0).abs() < EPSILON);
+        assert!((blended[1].position.x - 25.0).abs() < EPSILON);
+
+        let partial = blend_poses(&[(walk, 0.25)], &rest);
+        assert!((partial[0].position.x - 0.5).abs() < EPSILON);
+        assert!((partial[1].position.x - 12.5).abs() < EPSILON);
+
+        assert_eq!(blend_poses(&[], &rest), rest);
+    }
+
+    #[test]
+    fn blend_poses_aligns_rotation_hemispheres() {
+        use cgmath::{Deg, Quaternion, Rotation3};
+
+        let rest = vec![Transform::identity()];
+        let turned = Transform {
+            rotation: -Quaternion::from_angle_y(Deg(90.0)),
+            ..Transform::identity()
+        };
+        let blended = blend_poses(&[(vec![turned], 0.5)], &rest);
+        let expected = Quaternion::from_angle_y(Deg(45.0));
+        assert!((blended[0].rotation.dot(expected).abs() - 1.0).abs() < EPSILON);
+        assert!((blended[0].rotation.magnitude() - 1.0).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use cgmath::{Matrix4, SquareMatrix};
+
+use crate::{
+    core::cache::{CacheKey, HasCacheKey},
+    ecs::{components::transform::Transform, entity::Entity},
+};
+
+pub const MAX_JOINTS: usize = 256;
+
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    pub joints: Vec<Entity>,
+    pub inverse_bind_matrices: Vec<Matrix4<f32>>,
+    pub rest_pose: Vec<Transform>,
+    pub clips: Vec<CacheKey>,
+}
+
+impl Skeleton {
+    pub const LABEL: &'static str = "component:skeleton";
+
+    pub fn new(
+        joints: Vec<Entity>,
+        inverse_bind_matrices: Vec<Matrix4<f32>>,
+        rest_pose: Vec<Transform>,
+    ) -> Self {
+        Self {
+            joints,
+            inverse_bind_matrices,
+            rest_pose,
+            clips: Vec::new(),
+        }
+    }
+
+    pub fn joint_count(&self) -> usize {
+        self.joints.len()
+    }
+
+    pub fn joint_index(&self, entity: Entity) -> Option<usize> {
+        self.joints.iter().position(|joint| *joint == entity)
+    }
+
+    pub fn joint_buffer_key(entity: Entity) -> CacheKey {
+        Skeleton::key(vec![
+            "joints",
+            &entity.id.to_string(),
+            &entity.generation.to_string(),
+        ])
+    }
+
+    pub fn joint_matrices(
+        &self,
+        mesh_world: &Transform,
+        world_transforms: &HashMap<Entity, Transform>,
+    ) -> Vec<[[f32; 4]; 4]> {
+        let inverse_mesh = mesh_world
+            .to_model_matrix()
+            .invert()
+            .unwrap_or_else(Matrix4::identity);
+        self.joints
+            .iter()
+            .enumerate()
+            .take(MAX_JOINTS)
+            .map(|(index, joint)| {
+                let joint_world = world_transforms
+                    .get(joint)
+                    .map(Transform::to_model_matrix)
+                    .unwrap_or_else(Matrix4::identity);
+                let inverse_bind = self
+                    .inverse_bind_matrices
+                    .get(index)
+                    .copied()
+                    .unwrap_or_else(Matrix4::identity);
+                (inverse_mesh * joint_world * inverse_bind).into()
+            })
+            .collect()
+    }
+}
+
+impl HasCacheKey for Skeleton {
+    fn key(suffixes: Vec<&str>) -> CacheKey {
+        let mut base = String::from(Self::LABEL);
+        for suffix in suffixes {
+            base.push_str(format!(":{}", suffix).as_ref());
+        }
+        CacheKey::from(&base)
+    }
+}
@@ -0,0 +1,280 @@
+use cgmath::{InnerSpace, Matrix3, Vector3, Zero};
+
+use crate::{
+    camera::frustum::BoundingVolume,
+    core::cache::{CacheKey, HasCacheKey},
+};
+
+use super::transform::Transform;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Self {
+            min: Vector3::zero(),
+            max: Vector3::zero(),
+            center: Vector3::zero(),
+            radius: 0.0,
+        }
+    }
+}
+
+impl Bounds {
+    pub const LABEL: &'static str = "component:bounds";
+
+    pub fn from_points<I: IntoIterator<Item = [f32; 3]>>(points: I) -> Option<Bounds> {
+        let points: Vec<Vector3<f32>> = points.into_iter().map(Vector3::from).collect();
+        let first = *points.first()?;
+        let (mut min, mut max) = (first, first);
+        for p in &points {
+            min = Vector3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = Vector3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+        let center = (min + max) * 0.5;
+        let radius = points
+            .iter()
+            .map(|p| (p - center).magnitude())
+            .fold(0.0, f32::max);
+        Some(Bounds {
+            min,
+            max,
+            center,
+            radius,
+        })
+    }
+
+    pub fn half_extents(&self) -> Vector3<f32> {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        let min = Vector3::new(
+            self.min.x.min(other.min.x),
+            self.min.y.min(other.min.y),
+            self.min.z.min(other.min.z),
+        );
+        let max = Vector3::new(
+            self.max.x.max(other.max.x),
+            self.max.y.max(other.max.y),
+            self.max.z.max(other.max.z),
+        );
+
+        let offset = other.center - self.center;
+        let distance = offset.magnitude();
+        let (center, radius) = if distance + other.radius <= self.radius {
+            (self.center, self.radius)
+        } else if distance + self.radius <= other.radius {
+            (other.center, other.radius)
+        } else {
+            let radius = (distance + self.radius + other.radius) * 0.5;
+            (
+                self.center + offset * ((radius - self.radius) / distance),
+                radius,
+            )
+        };
+
+        Bounds {
+            min,
+            max,
+            center,
+            radius,
+        }
+    }
+
+    pub fn transformed(&self, transform: &Transform) -> Bounds {
+        let rotation = Matrix3::from(transform.rotation);
+        let scale = transform.scale;
+        let scaled = |v: Vector3<f32>| Vector3::new(v.x * scale.x, v.y * scale.y, v.z * scale.z);
+
+        let center = transform.position + rotation * scaled((self.min + self.max) * 0.5);
+        let half = scaled(self.half_extents());
+        let extents = Vector3::new(
+            rotation.x.x.abs() * half.x.abs()
+                + rotation.y.x.abs() * half.y.abs()
+                + rotation.z.x.abs() * half.z.abs(),
+            rotation.x.y.abs() * half.x.abs()
+                + rotation.y.y.abs() * half.y.abs()
+                + rotation.z.y.abs() * half.z.abs(),
+            rotation.x.z.abs() * half.x.abs()
+                + rotation.y.z.abs() * half.y.abs()
+                + rotation.z.z.abs() * half.z.abs(),
+        );
+        let max_scale = scale.x.abs().max(scale.y.abs()).max(scale.z.abs());
+
+        Bounds {
+            min: center - extents,
+            max: center + extents,
+            center: transform.position + rotation * scaled(self.center),
+            radius: self.radius * max_scale,
+        }
+    }
+
+    pub fn sphere(&self) -> BoundingVolume {
+        BoundingVolume::Sphere {
+            center: self.center,
+            radius: self.radius,
+        }
+    }
+
+    pub fn aabb(&self) -> BoundingVolume {
+        BoundingVolume::AABB {
+            min: self.min,
+            max: self.max,
+        }
+    }
+
+    pub fn overlaps(&self, other: &Bounds) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+            && self.min.z <= other.max.z
+            && self.max.z >= other.min.z
+    }
+
+    pub fn intersect_ray(&self, origin: Vector3<f32>, direction: Vector3<f32>) -> Option<f32> {
+        let mut t_min = 0.0f32;
+        let mut t_max = f32::MAX;
+        for axis in 0..3 {
+            let (o, d) = (origin[axis], direction[axis]);
+            if d.abs() < f32::EPSILON {
+                if o < self.min[axis] || o > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let inv = 1.0 / d;
+            let (t0, t1) = ((self.min[axis] - o) * inv, (self.max[axis] - o) * inv);
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_min > t_max {
+                return None;
+            }
+        }
+        Some(t_min)
+    }
+}
+
+impl HasCacheKey for Bounds {
+    fn key(suffixes: Vec<&str>) -> CacheKey {
+        let mut base = String::from(Self::LABEL);
+        for suffix in suffixes {
+            base.push_str(format!(":{}", suffix).as_ref());
+        }
+        CacheKey::from(&base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Deg, Quaternion, Rotation3};
+
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn assert_vec_eq(actual: Vector3<f32>, expected: Vector3<f32>) {
+        assert!(
+            (actual - expected).magnitude() < EPSILON,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    fn unit_box() -> Bounds {
+        Bounds::from_points([[-1.0, -1.0, -1.0], [1.0, 1.0, 1.0]]).unwrap()
+    }
+
+    #[test]
+    fn transformed_moves_scales_and_rotates_bounds() {
+        let offset = Bounds::from_points([[1.0, 0.0, 0.0], [3.0, 2.0, 2.0]]).unwrap();
+        let transform = Transform {
+            position: Vector3::new(10.0, 0.0, 0.0),
+            rotation: Quaternion::from_angle_y(Deg(90.0)),
+            scale: Vector3::new(2.0, 1.0, 1.0),
+        };
+        let moved = offset.transformed(&transform);
+
+        assert_vec_eq(moved.center, Vector3::new(11.0, 1.0, -4.0));
+        assert_vec_eq(moved.min, Vector3::new(10.0, 0.0, -6.0));
+        assert_vec_eq(moved.max, Vector3::new(12.0, 2.0, -2.0));
+        assert!((moved.radius - offset.radius * 2.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn transformed_rotation_grows_aabb_to_enclose_box() {
+        let transform = Transform {
+            rotation: Quaternion::from_angle_z(Deg(45.0)),
+            ..Transform::identity()
+        };
+        let rotated = unit_box().transformed(&transform);
+        let diagonal = 2.0f32.sqrt();
+        assert_vec_eq(rotated.max, Vector3::new(diagonal, diagonal, 1.0));
+        assert_vec_eq(rotated.min, -rotated.max);
+    }
+
+    #[test]
+    fn union_encloses_both_boxes_and_spheres() {
+        let a = unit_box();
+        let b = Bounds::from_points([[4.0, -1.0, -1.0], [6.0, 1.0, 1.0]]).unwrap();
+        let union = a.union(&b);
+
+        assert_vec_eq(union.min, Vector3::new(-1.0, -1.0, -1.0));
+        assert_vec_eq(union.max, Vector3::new(6.0, 1.0, 1.0));
+        for bounds in [a, b] {
+            let reach = (bounds.center - union.center).magnitude() + bounds.radius;
+            assert!(reach <= union.radius + EPSILON);
+        }
+        assert_eq!(a.union(&a), a);
+
+        let inner = Bounds::from_points([[-0.1, -0.1, -0.1], [0.1, 0.1, 0.1]]).unwrap();
+        let nested = a.union(&inner);
+        assert_vec_eq(nested.center, a.center);
+        assert!((nested.radius - a.radius).abs() < EPSILON);
+    }
+
+    #[test]
+    fn intersect_ray_returns_entry_distance() {
+        let bounds = unit_box();
+        let hit = bounds.intersect_ray(Vector3::new(-5.0, 0.0, 0.0), Vector3::unit_x());
+        assert!((hit.unwrap() - 4.0).abs() < EPSILON);
+
+        let inside = bounds.intersect_ray(Vector3::new(0.0, 0.0, 0.0), Vector3::unit_y());
+        assert_eq!(inside, Some(0.0));
+
+        let diagonal = Vector3::new(1.0, 1.0, 0.0).normalize();
+        let hit = bounds.intersect_ray(Vector3::new(-3.0, -3.0, 0.0), diagonal);
+        assert!((hit.unwrap() - 2.0 * 2.0f32.sqrt()).abs() < EPSILON);
+    }
+
+    #[test]
+    fn intersect_ray_misses() {
+        let bounds = unit_box();
+        assert!(bounds
+            .intersect_ray(Vector3::new(-5.0, 0.0, 0.0), -Vector3::unit_x())
+            .is_none());
+        assert!(bounds
+            .intersect_ray(Vector3::new(-5.0, 2.0, 0.0), Vector3::unit_x())
+            .is_none());
+        assert!(bounds
+            .intersect_ray(Vector3::new(0.0, 5.0, 0.0), Vector3::unit_x())
+            .is_none());
+    }
+
+    #[test]
+    fn overlaps_includes_touching_faces() {
+        let a = unit_box();
+        let touching = Bounds::from_points([[1.0, -1.0, -1.0], [3.0, 1.0, 1.0]]).unwrap();
+        let apart = Bounds::from_points([[1.5, -1.0, -1.0], [3.0, 1.0, 1.0]]).unwrap();
+        assert!(a.overlaps(&touching));
+        assert!(!a.overlaps(&apart));
+    }
+}
@@ -0,0 +1,20 @@
+use crate::core::cache::{CacheKey, HasCacheKey};
+
+/// Marks the entity whose `Transform` drives the view camera, e.g. an animated fly-through.
+/// The camera looks down the entity's local -Z axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CameraTarget;
+
+impl CameraTarget {
+    pub const LABEL: &'static str = "component:camera_target";
+}
+
+impl HasCacheKey for CameraTarget {
+    fn key(suffixes: Vec<&str>) -> CacheKey {
+        let mut base = String::from(Self::LABEL);
+        for suffix in suffixes {
+            base.push_str(format!(":{}", suffix).as_ref());
+        }
+        CacheKey::from(&base)
+    }
+}
@@ -0,0 +1,28 @@
+use crate::{
+    core::cache::{CacheKey, HasCacheKey},
+    ecs::entity::Entity,
+};
+
+use super::transform::Transform;
+
+#[derive(Debug, Clone)]
+pub struct Hierarchy {
+    pub name: Option<String>,
+    pub parent: Option<Entity>,
+    pub children: Vec<Entity>,
+    pub local: Transform,
+}
+
+impl Hierarchy {
+    pub const LABEL: &'static str = "component:hierarchy";
+}
+
+impl HasCacheKey for Hierarchy {
+    fn key(suffixes: Vec<&str>) -> CacheKey {
+        let mut base = String::from(Self::LABEL);
+        for suffix in suffixes {
+            base.push_str(format!(":{}", suffix).as_ref());
+        }
+        CacheKey::from(&base)
+    }
+}
@@ -0,0 +1,119 @@
+use cgmath::{Deg, InnerSpace, Rad, Vector3};
+
+use crate::{
+    core::cache::{CacheKey, HasCacheKey},
+    graphics::uniform::cluster::{
+        LightRaw, LIGHT_DIRECTIONAL, LIGHT_POINT, LIGHT_SPOT, SHADOW_NONE,
+    },
+};
+
+use super::transform::Transform;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    Point,
+    Spot {
+        inner_angle: Deg<f32>,
+        outer_angle: Deg<f32>,
+    },
+    Directional,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub range: f32,
+    pub cast_shadows: bool,
+}
+
+impl Light {
+    pub const LABEL: &'static str = "component:light";
+
+    pub fn point(color: [f32; 3], intensity: f32, range: f32) -> Self {
+        Self {
+            kind: LightKind::Point,
+            color,
+            intensity,
+            range,
+            cast_shadows: true,
+        }
+    }
+
+    pub fn spot(
+        color: [f32; 3],
+        intensity: f32,
+        range: f32,
+        inner_angle: Deg<f32>,
+        outer_angle: Deg<f32>,
+    ) -> Self {
+        Self {
+            kind: LightKind::Spot {
+                inner_angle,
+                outer_angle,
+            },
+            color,
+            intensity,
+            range,
+            cast_shadows: false,
+        }
+    }
+
+    pub fn directional(color: [f32; 3], intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Directional,
+            color,
+            intensity,
+            range: f32::MAX,
+            cast_shadows: true,
+        }
+    }
+
+    pub fn with_shadows(mut self, cast_shadows: bool) -> Self {
+        self.cast_shadows = cast_shadows;
+        self
+    }
+
+    pub fn direction(transform: &Transform) -> Vector3<f32> {
+        (transform.rotation * -Vector3::unit_z()).normalize()
+    }
+
+    pub fn to_raw(&self, transform: &Transform) -> LightRaw {
+        let (kind, spot_scale, spot_offset) = match self.kind {
+            LightKind::Point => (LIGHT_POINT, 0.0, 1.0),
+            LightKind::Spot {
+                inner_angle,
+                outer_angle,
+            } => {
+                let cos_outer = Rad::from(outer_angle).0.cos();
+                let cos_inner = Rad::from(inner_angle).0.cos().max(cos_outer + 1e-4);
+                let scale = 1.0 / (cos_inner - cos_outer);
+                (LIGHT_SPOT, scale, -cos_outer * scale)
+            }
+            LightKind::Directional => (LIGHT_DIRECTIONAL, 0.0, 1.0),
+        };
+        LightRaw {
+            position: transform.position.into(),
+            range: self.range.max(f32::EPSILON),
+            color: self.color,
+            intensity: self.intensity,
+            direction: Self::direction(transform).into(),
+            kind,
+            spot_scale,
+            spot_offset,
+            shadow: SHADOW_NONE,
+            _padding: 0,
+        }
+    }
+}
+
+impl HasCacheKey for Light {
+    fn key(suffixes: Vec<&str>) -> CacheKey {
+        let mut base = String::from(Self::LABEL);
+        for suffix in suffixes {
+            base.push_str(format!(":{}", suffix).as_ref());
+        }
+        CacheKey::from(&base)
+    }
+}
@@ -0,0 +1,114 @@
+use cgmath::Rad;
+
+use crate::core::cache::{CacheKey, HasCacheKey};
+
+use super::mesh::simplify::LOD_RATIOS;
+
+pub const MAX_LOD_LEVELS: usize = LOD_RATIOS.len() + 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LodMetric {
+    ScreenSize,
+    Distance,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lod {
+    pub metric: LodMetric,
+    pub thresholds: Vec<f32>,
+    pub bias: f32,
+}
+
+impl Default for Lod {
+    fn default() -> Self {
+        Self::screen_size(vec![0.25, 0.1, 0.04])
+    }
+}
+
+impl Lod {
+    pub const LABEL: &'static str = "component:lod";
+
+    pub fn screen_size(thresholds: Vec<f32>) -> Self {
+        Self {
+            metric: LodMetric::ScreenSize,
+            thresholds,
+            bias: 1.0,
+        }
+    }
+
+    pub fn distance(thresholds: Vec<f32>) -> Self {
+        Self {
+            metric: LodMetric::Distance,
+            thresholds,
+            bias: 1.0,
+        }
+    }
+
+    pub fn with_bias(mut self, bias: f32) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    pub fn select(&self, distance: f32, radius: f32, fovy: Rad<f32>) -> usize {
+        let level = match self.metric {
+            LodMetric::ScreenSize => {
+                let size = screen_size(distance, radius, fovy) / self.bias.max(f32::EPSILON);
+                self.thresholds.iter().take_while(|t| size < **t).count()
+            }
+            LodMetric::Distance => {
+                let distance = distance * self.bias;
+                self.thresholds
+                    .iter()
+                    .take_while(|t| distance > **t)
+                    .count()
+            }
+        };
+        level.min(MAX_LOD_LEVELS - 1)
+    }
+}
+
+impl HasCacheKey for Lod {
+    fn key(suffixes: Vec<&str>) -> CacheKey {
+        let mut base = String::from(Self::LABEL);
+        for suffix in suffixes {
+            base.push_str(format!(":{}", suffix).as_ref());
+        }
+        CacheKey::from(&base)
+    }
+}
+
+pub fn screen_size(distance: f32, radius: f32, fovy: Rad<f32>) -> f32 {
+    if distance <= radius {
+        return f32::MAX;
+    }
+    radius / (distance * (fovy.0 * 0.5).tan())
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Deg;
+
+    use super::*;
+
+    #[test]
+    fn screen_size_selects_coarser_levels_further_away() {
+        let lod = Lod::default();
+        let fovy = Rad::from(Deg(45.0));
+        let levels: Vec<usize> = [0.5, 3.0, 15.0, 30.0, 100.0]
+            .into_iter()
+            .map(|distance| lod.select(distance, 1.0, fovy))
+            .collect();
+        assert_eq!(levels, vec![0, 0, 1, 2, 3]);
+        assert_eq!(lod.clone().with_bias(0.01).select(100.0, 1.0, fovy), 0);
+    }
+
+    #[test]
+    fn distance_thresholds_are_exclusive_and_capped() {
+        let lod = Lod::distance(vec![10.0, 20.0, 30.0, 40.0, 50.0]);
+        let fovy = Rad::from(Deg(45.0));
+        assert_eq!(lod.select(10.0, 1.0, fovy), 0);
+        assert_eq!(lod.select(15.0, 1.0, fovy), 1);
+        assert_eq!(lod.select(1000.0, 1.0, fovy), MAX_LOD_LEVELS - 1);
+        assert_eq!(lod.with_bias(2.0).select(15.0, 1.0, fovy), 2);
+    }
+}
@@ -1,14 +1,36 @@
+use super::manager::MaterialManager;
 use crate::core::cache::{CacheKey, HasCacheKey};
-use crate::graphics::binding::material::create_material_bind_group;
-use crate::graphics::binding::BindGroupManager;
+use crate::graphics::binding::material::{
+    create_material_bind_group, create_pbr_material_bind_group,
+};
+use crate::graphics::binding::{BindGroupLayouts, BindGroupManager};
 use crate::{
     core::{error::AppError, files::FileSystem},
-    ecs::traits::Cache,
-    graphics::textures::{manager::TextureManager, Texture},
+    ecs::{
+        components::ResourceContext,
+        systems::render::{BufferFactory, BufferManager},
+        traits::Cache,
+    },
+    graphics::{
+        pipelines::common::with_validation_scope,
+        textures::{
+            defaults::DefaultTexture, manager::TextureManager, mipmap::MipmapGenerator,
+            sampler::TextureOptions, Texture,
+        },
+        uniform::material::MaterialUniform,
+    },
+    log_warning,
 };
 use std::collections::HashMap;
 use std::sync::Arc;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ShadingModel {
+    #[default]
+    BlinnPhong,
+    MetallicRoughness,
+}
+
 #[derive(Debug)]
 pub struct Material {
     pub name: String,
@@ -27,6 +49,17 @@ pub struct Material {
     pub shininess_texture_key: Option<CacheKey>,
     pub dissolve_texture_key: Option<CacheKey>,
 
+    pub base_color_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub emissive_factor: [f32; 3],
+    pub occlusion_strength: f32,
+    pub normal_scale: f32,
+    pub metallic_roughness_texture_key: Option<CacheKey>,
+    pub occlusion_texture_key: Option<CacheKey>,
+    pub emissive_texture_key: Option<CacheKey>,
+
+    pub shading_model: ShadingModel,
     pub cache_key: CacheKey,
 }
 
@@ -63,16 +96,98 @@ impl Material {
             normal_texture_key,
             shininess_texture_key,
             dissolve_texture_key,
+            base_color_factor: [diffuse[0], diffuse[1], diffuse[2], dissolve],
+            metallic_factor: 0.0,
+            roughness_factor: Material::roughness_from_shininess(shininess),
+            emissive_factor: [0.0; 3],
+            occlusion_strength: 1.0,
+            normal_scale: 1.0,
+            metallic_roughness_texture_key: None,
+            occlusion_texture_key: None,
+            emissive_texture_key: None,
+            shading_model: ShadingModel::BlinnPhong,
             cache_key,
         }
     }
 
+    pub fn roughness_from_shininess(shininess: f32) -> f32 {
+        (2.0 / (shininess.max(0.0) + 2.0)).sqrt()
+    }
+
+    pub fn default_key() -> CacheKey {
+        Material::key(vec!["default"])
+    }
+
+    pub fn uniform_buffer_key(&self) -> CacheKey {
+        Material::key(vec!["uniform", &self.cache_key.value().to_string()])
+    }
+
+    pub fn texture_keys(&self) -> Vec<CacheKey> {
+        [
+            self.diffuse_texture_key,
+            self.normal_texture_key,
+            self.metallic_roughness_texture_key,
+            self.occlusion_texture_key,
+            self.emissive_texture_key,
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
     pub fn from_tobj_material(
         obj_material: tobj::Material,
         cache_key: CacheKey,
         texture_map: HashMap<String, (Option<CacheKey>, Option<CacheKey>)>,
     ) -> Self {
+        let param = |name: &str| {
+            obj_material
+                .unknown_param
+                .get(name)
+                .and_then(|value| value.trim().parse::<f32>().ok())
+        };
+        let roughness = param("Pr");
+        let metallic = param("Pm");
+        let emissive: Vec<f32> = obj_material
+            .unknown_param
+            .get("Ke")
+            .map(|value| {
+                value
+                    .split_whitespace()
+                    .filter_map(|v| v.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let shading_model = if roughness.is_some() || metallic.is_some() {
+            ShadingModel::MetallicRoughness
+        } else {
+            ShadingModel::BlinnPhong
+        };
+        let (diffuse_texture_key, normal_texture_key) = texture_map
+            .get(&obj_material.name)
+            .cloned()
+            .unwrap_or((None, None));
+
         Self {
+            base_color_factor: [
+                obj_material.diffuse[0],
+                obj_material.diffuse[1],
+                obj_material.diffuse[2],
+                obj_material.dissolve,
+            ],
+            metallic_factor: metallic.unwrap_or(0.0),
+            roughness_factor: roughness
+                .unwrap_or_else(|| Material::roughness_from_shininess(obj_material.shininess)),
+            emissive_factor: match emissive.as_slice() {
+                [r, g, b, ..] => [*r, *g, *b],
+                _ => [0.0; 3],
+            },
+            occlusion_strength: 1.0,
+            normal_scale: 1.0,
+            metallic_roughness_texture_key: None,
+            occlusion_texture_key: None,
+            emissive_texture_key: None,
+            shading_model,
             name: obj_material.name,
             ambient: obj_material.ambient,
             diffuse: obj_material.diffuse,
@@ -82,29 +197,12 @@ impl Material {
             optical_density: obj_material.optical_density,
             illumination_model: obj_material.illumination_model,
 
-            ambient_texture_key: texture_map
-                .get(&obj_material.ambient_texture)
-                .and_then(|(d, _)| d.clone()),
-
-            diffuse_texture_key: texture_map
-                .get(&obj_material.diffuse_texture)
-                .and_then(|(d, _)| d.clone()),
-
-            specular_texture_key: texture_map
-                .get(&obj_material.specular_texture)
-                .and_then(|(d, _)| d.clone()),
-
-            normal_texture_key: texture_map
-                .get(&obj_material.normal_texture)
-                .and_then(|(_, n)| n.clone()),
-
-            shininess_texture_key: texture_map
-                .get(&obj_material.shininess_texture)
-                .and_then(|(d, _)| d.clone()),
-
-            dissolve_texture_key: texture_map
-                .get(&obj_material.dissolve_texture)
-                .and_then(|(d, _)| d.clone()),
+            ambient_texture_key: None,
+            diffuse_texture_key,
+            specular_texture_key: None,
+            normal_texture_key,
+            shininess_texture_key: None,
+            dissolve_texture_key: None,
 
             cache_key,
         }
@@ -128,10 +226,49 @@ pub async fn load_texture(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
     file_name: &str,
-    is_normal_map: bool,
+    options: &TextureOptions,
+    mipmaps: &mut MipmapGenerator,
 ) -> Result<Texture, AppError> {
     let data = FileSystem::load_binary(file_name)?;
-    Texture::from_bytes(device, queue, &data, file_name, is_normal_map)
+    Texture::from_bytes_with_options(device, queue, &data, file_name, options, mipmaps)
+}
+
+async fn load_material_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    material_name: &str,
+    file_name: &str,
+    is_normal_map: bool,
+    texture_manager: &mut TextureManager,
+) -> Result<CacheKey, AppError> {
+    let key = CacheKey::from(file_name);
+    if texture_manager.textures.contains(&key) {
+        return Ok(key);
+    }
+    let options = texture_manager.options_for(file_name, is_normal_map);
+    match load_texture(
+        device,
+        queue,
+        file_name,
+        &options,
+        &mut texture_manager.mipmaps,
+    )
+    .await
+    {
+        Ok(texture) => {
+            texture_manager.textures.put(key, Arc::new(texture));
+            Ok(key)
+        }
+        Err(e) => {
+            log_warning!(
+                "Material {}: missing texture {} ({}), using placeholder",
+                material_name,
+                file_name,
+                e
+            );
+            texture_manager.insert_placeholder(device, queue, file_name, is_normal_map)
+        }
+    }
 }
 
 pub async fn load_material_textures(
@@ -144,26 +281,30 @@ pub async fn load_material_textures(
     for m in obj_materials {
         let mut textures = (None, None);
         if !m.diffuse_texture.is_empty() {
-            let diffuse_texture_key = CacheKey::from(m.diffuse_texture.as_str());
-
-            if !texture_manager.textures.contains(&diffuse_texture_key) {
-                let diffuse_texture =
-                    load_texture(device, queue, &m.diffuse_texture, false).await?;
-                texture_manager
-                    .textures
-                    .put(diffuse_texture_key, Arc::new(diffuse_texture));
-            }
-            textures.0 = Some(diffuse_texture_key);
+            textures.0 = Some(
+                load_material_texture(
+                    device,
+                    queue,
+                    &m.name,
+                    &m.diffuse_texture,
+                    false,
+                    texture_manager,
+                )
+                .await?,
+            );
         }
         if !m.normal_texture.is_empty() {
-            let normal_texture_key = CacheKey::from(m.normal_texture.as_str());
-            if !texture_manager.textures.contains(&normal_texture_key) {
-                let normal_texture = load_texture(device, queue, &m.normal_texture, true).await?;
-                texture_manager
-                    .textures
-                    .put(normal_texture_key, Arc::new(normal_texture))
-            }
-            textures.1 = Some(normal_texture_key);
+            textures.1 = Some(
+                load_material_texture(
+                    device,
+                    queue,
+                    &m.name,
+                    &m.normal_texture,
+                    true,
+                    texture_manager,
+                )
+                .await?,
+            );
         }
 
         texture_map.insert(m.name.clone(), textures);
@@ -173,36 +314,200 @@ pub async fn load_material_textures(
 pub async fn create_material(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
+    file_name: &str,
     obj_material: tobj::Material,
     texture_manager: &mut TextureManager,
+    buffer_manager: &mut BufferManager,
     bind_group_manager: &mut BindGroupManager,
 ) -> Result<Material, AppError> {
-    let bind_group_key = CacheKey::from(&obj_material.name);
+    let bind_group_key = Material::key(vec![file_name, &obj_material.name]);
     let texture_map =
         load_material_textures(device, queue, vec![&obj_material], texture_manager).await?;
-    let diffuse_texture_key = texture_map
-        .get(&obj_material.name)
-        .and_then(|(d, _)| d.as_ref());
-    let normal_texture_key = texture_map
-        .get(&obj_material.name)
-        .and_then(|(_, n)| n.as_ref());
-    let diffuse_texture =
-        diffuse_texture_key.and_then(|key| texture_manager.textures.get(&key).map(Arc::as_ref));
-    let normal_texture =
-        normal_texture_key.and_then(|key| texture_manager.textures.get(&key).map(Arc::as_ref));
-    let bind_group = create_material_bind_group(
+    let material = Material::from_tobj_material(obj_material, bind_group_key, texture_map);
+
+    let bind_group = create_bind_group_for_material(
         device,
-        &bind_group_manager
-            .bind_group_layouts
-            .texture_bind_group_layout,
-        diffuse_texture,
-        normal_texture,
+        queue,
+        &material,
+        texture_manager,
+        buffer_manager,
+        &bind_group_manager.bind_group_layouts,
     )?;
-
     bind_group_manager
         .bind_groups
         .put(bind_group_key, bind_group);
 
-    let material = Material::from_tobj_material(obj_material, bind_group_key, texture_map);
     Ok(material)
 }
+
+pub fn create_bind_group_for_material(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    material: &Material,
+    texture_manager: &mut TextureManager,
+    buffer_manager: &mut BufferManager,
+    layouts: &BindGroupLayouts,
+) -> Result<wgpu::BindGroup, AppError> {
+    let uniform = MaterialUniform::from_material(material);
+    let buffer_key = material.uniform_buffer_key();
+    let buffer = buffer_manager.get_or_create_buffer(buffer_key, || {
+        Ok(BufferFactory::create_material_uniform_buffer(
+            device, uniform,
+        ))
+    })?;
+    queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[uniform]));
+
+    let mut slot = |key: Option<CacheKey>, default: DefaultTexture| match key {
+        Some(key) => match texture_manager.textures.get(&key) {
+            Some(texture) => Ok(Arc::clone(texture)),
+            None => {
+                log_warning!(
+                    "Material {}: texture {:?} is not loaded, using placeholder",
+                    material.name,
+                    key
+                );
+                texture_manager.default_texture(device, queue, DefaultTexture::Missing)
+            }
+        },
+        None => texture_manager.default_texture(device, queue, default),
+    };
+
+    match material.shading_model {
+        ShadingModel::BlinnPhong => {
+            let diffuse_texture = slot(material.diffuse_texture_key, DefaultTexture::White)?;
+            let normal_texture = slot(material.normal_texture_key, DefaultTexture::FlatNormal)?;
+            with_validation_scope(device, || {
+                create_material_bind_group(
+                    device,
+                    &layouts.material_bind_group_layout,
+                    &diffuse_texture,
+                    &normal_texture,
+                    buffer,
+                )
+            })
+        }
+        ShadingModel::MetallicRoughness => {
+            let textures = [
+                slot(material.diffuse_texture_key, DefaultTexture::White)?,
+                slot(material.normal_texture_key, DefaultTexture::FlatNormal)?,
+                slot(
+                    material.metallic_roughness_texture_key,
+                    DefaultTexture::White,
+                )?,
+                slot(material.occlusion_texture_key, DefaultTexture::White)?,
+                slot(material.emissive_texture_key, DefaultTexture::White)?,
+            ];
+            with_validation_scope(device, || {
+                create_pbr_material_bind_group(
+                    device,
+                    &layouts.pbr_material_bind_group_layout,
+                    [
+                        &textures[0],
+                        &textures[1],
+                        &textures[2],
+                        &textures[3],
+                        &textures[4],
+                    ],
+                    buffer,
+                )
+            })
+        }
+    }
+    .map_err(|e| {
+        AppError::ResourceCreationFailed(format!(
+            "Invalid bind group for material {}: {}",
+            material.name, e
+        ))
+    })
+}
+
+pub fn create_default_material(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    resources: &mut ResourceContext,
+) -> Result<CacheKey, AppError> {
+    let key = Material::default_key();
+    let material = Material::new(
+        String::from("default"),
+        [1.0, 1.0, 1.0],
+        [0.8, 0.8, 0.8],
+        [0.5, 0.5, 0.5],
+        32.0,
+        1.0,
+        1.0,
+        Some(2),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        key,
+    );
+    let bind_group = create_bind_group_for_material(
+        device,
+        queue,
+        &material,
+        &mut resources.texture_manager,
+        &mut resources.buffer_manager,
+        &resources.bind_group_manager.bind_group_layouts,
+    )?;
+    resources
+        .bind_group_manager
+        .bind_groups
+        .put(key, bind_group);
+    resources.material_manager.materials.put(key, material);
+    Ok(key)
+}
+
+pub fn update_material_uniform(
+    queue: &wgpu::Queue,
+    material: &Material,
+    buffer_manager: &BufferManager,
+) -> Result<(), AppError> {
+    let buffer = buffer_manager
+        .buffers
+        .get(&material.uniform_buffer_key())
+        .ok_or_else(|| {
+            AppError::ResourceNotFound(format!(
+                "Uniform buffer for material {} not found",
+                material.name
+            ))
+        })?;
+    queue.write_buffer(
+        buffer,
+        0,
+        bytemuck::cast_slice(&[MaterialUniform::from_material(material)]),
+    );
+    Ok(())
+}
+
+pub fn rebuild_material_bind_groups(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture_key: CacheKey,
+    material_manager: &MaterialManager,
+    texture_manager: &mut TextureManager,
+    buffer_manager: &mut BufferManager,
+    bind_group_manager: &mut BindGroupManager,
+) -> Result<usize, AppError> {
+    let mut rebuilt = 0;
+    for (_, material) in material_manager.materials.iter() {
+        if !material.texture_keys().contains(&texture_key) {
+            continue;
+        }
+        let bind_group = create_bind_group_for_material(
+            device,
+            queue,
+            material,
+            texture_manager,
+            buffer_manager,
+            &bind_group_manager.bind_group_layouts,
+        )?;
+        bind_group_manager
+            .bind_groups
+            .put(material.cache_key, bind_group);
+        rebuilt += 1;
+    }
+    Ok(rebuilt)
+}
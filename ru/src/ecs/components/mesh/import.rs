@@ -0,0 +1,577 @@
+use std::io::{Cursor, Read, Write};
+use std::path::PathBuf;
+
+use crate::{
+    core::{error::AppError, files::FileSystem},
+    ecs::components::model::model::{load_material_lib, load_model_raw_from_string, ModelRaw},
+    graphics::vertex::{ModelVertex, SkinnedVertex, VertexSkin, VertexType},
+    log_info, log_warning,
+    utilities::helpers::content_hash,
+};
+
+use super::{model::Mesh, simplify::generate_lods};
+
+pub const MESH_CACHE_MAGIC: &[u8; 4] = b"RMSH";
+pub const MESH_CACHE_VERSION: u32 = 5;
+pub const MESH_CACHE_EXTENSION: &str = "rmesh";
+
+#[derive(Debug, Clone)]
+pub struct ImportedMesh {
+    pub name: String,
+    pub material_id: Option<usize>,
+    pub bounds_min: [f32; 3],
+    pub bounds_max: [f32; 3],
+    pub vertices: Vec<ModelVertex>,
+    pub skin: Vec<VertexSkin>,
+    pub indices: Vec<u32>,
+    pub lods: Vec<Vec<u32>>,
+}
+
+impl ImportedMesh {
+    pub fn from_tobj_model(model: tobj::Model) -> ImportedMesh {
+        let data = Mesh::process_tobj_mesh(&model.mesh);
+        let vertices = data.vertices;
+        let lods = generate_lods(&vertices, &data.indices);
+        let (bounds_min, bounds_max) = compute_bounds(&vertices);
+
+        ImportedMesh {
+            name: model.name,
+            material_id: model.mesh.material_id,
+            bounds_min,
+            bounds_max,
+            vertices,
+            skin: Vec::new(),
+            indices: data.indices,
+            lods,
+        }
+    }
+
+    pub fn is_skinned(&self) -> bool {
+        !self.skin.is_empty() && self.skin.len() == self.vertices.len()
+    }
+
+    pub fn vertex_types(&self) -> Vec<VertexType> {
+        if self.is_skinned() {
+            return self
+                .vertices
+                .iter()
+                .zip(self.skin.iter())
+                .map(|(v, skin)| VertexType::Skinned(SkinnedVertex::new(*v, *skin)))
+                .collect();
+        }
+        self.vertices
+            .iter()
+            .map(|v| VertexType::Modeled(*v))
+            .collect()
+    }
+}
+
+pub fn compute_bounds(vertices: &[ModelVertex]) -> ([f32; 3], [f32; 3]) {
+    if vertices.is_empty() {
+        return ([0.0; 3], [0.0; 3]);
+    }
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for vertex in vertices {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(vertex.position[axis]);
+            max[axis] = max[axis].max(vertex.position[axis]);
+        }
+    }
+    (min, max)
+}
+
+/// A material library referenced by an OBJ, with the number of materials it held at import.
+/// Mesh material ids index the libraries' materials laid end to end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaterialLib {
+    pub name: String,
+    pub material_count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedModel {
+    pub source_hash: u64,
+    pub material_libs: Vec<MaterialLib>,
+    pub meshes: Vec<ImportedMesh>,
+}
+
+impl ImportedModel {
+    pub fn from_raw(source_hash: u64, raw: ModelRaw) -> Self {
+        Self {
+            source_hash,
+            material_libs: raw.material_libs,
+            meshes: raw
+                .models
+                .into_iter()
+                .map(ImportedMesh::from_tobj_model)
+                .collect(),
+        }
+    }
+
+    /// Loads every referenced material library, one slot per material id. Each library keeps
+    /// the slots it had at import, so a library that is now missing or malformed leaves `None`
+    /// for its meshes to fall back to the default material without shifting the others.
+    pub fn load_materials(&self) -> Vec<Option<tobj::Material>> {
+        let mut materials = Vec::new();
+        for lib in &self.material_libs {
+            let count = lib.material_count as usize;
+            let mut slots: Vec<Option<tobj::Material>> = load_material_lib(&lib.name)
+                .map(|(lib_materials, _)| lib_materials.into_iter().map(Some).collect())
+                .unwrap_or_default();
+            if !slots.is_empty() && slots.len() != count {
+                log_warning!(
+                    "Material library {} has {} materials, {} when imported",
+                    lib.name,
+                    slots.len(),
+                    count
+                );
+            }
+            slots.resize_with(count, || None);
+            materials.append(&mut slots);
+        }
+        materials
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MESH_CACHE_MAGIC);
+        out.extend_from_slice(&MESH_CACHE_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.source_hash.to_le_bytes());
+
+        out.extend_from_slice(&(self.material_libs.len() as u32).to_le_bytes());
+        for lib in &self.material_libs {
+            write_string(&mut out, &lib.name);
+            out.extend_from_slice(&lib.material_count.to_le_bytes());
+        }
+
+        out.extend_from_slice(&(self.meshes.len() as u32).to_le_bytes());
+        for mesh in &self.meshes {
+            write_string(&mut out, &mesh.name);
+            let material_id = mesh.material_id.map(|id| id as i32).unwrap_or(-1);
+            out.extend_from_slice(&material_id.to_le_bytes());
+            out.extend_from_slice(bytemuck::cast_slice(&mesh.bounds_min));
+            out.extend_from_slice(bytemuck::cast_slice(&mesh.bounds_max));
+            out.extend_from_slice(&(mesh.vertices.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(mesh.indices.len() as u32).to_le_bytes());
+            out.extend_from_slice(bytemuck::cast_slice(&mesh.vertices));
+            out.extend_from_slice(bytemuck::cast_slice(&mesh.indices));
+            out.extend_from_slice(&(mesh.skin.len() as u32).to_le_bytes());
+            out.extend_from_slice(bytemuck::cast_slice(&mesh.skin));
+            out.extend_from_slice(&(mesh.lods.len() as u32).to_le_bytes());
+            for lod in &mesh.lods {
+                out.extend_from_slice(&(lod.len() as u32).to_le_bytes());
+                out.extend_from_slice(bytemuck::cast_slice(lod));
+            }
+        }
+        out
+    }
+
+    pub fn read_header(bytes: &[u8]) -> Result<(u32, u64), AppError> {
+        let mut reader = Cursor::new(bytes);
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MESH_CACHE_MAGIC {
+            return Err(AppError::ResourceCreationFailed(
+                "Not a mesh cache file".to_string(),
+            ));
+        }
+        let version = read_u32(&mut reader)?;
+        let source_hash = read_u64(&mut reader)?;
+        Ok((version, source_hash))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ImportedModel, AppError> {
+        let (version, source_hash) = Self::read_header(bytes)?;
+        if version != MESH_CACHE_VERSION {
+            return Err(AppError::ResourceCreationFailed(format!(
+                "Unsupported mesh cache version {}",
+                version
+            )));
+        }
+        let mut reader = Cursor::new(&bytes[16..]);
+
+        let lib_count = read_count(&mut reader, 8)?;
+        let mut material_libs = Vec::with_capacity(lib_count);
+        for _ in 0..lib_count {
+            material_libs.push(MaterialLib {
+                name: read_string(&mut reader)?,
+                material_count: read_u32(&mut reader)?,
+            });
+        }
+
+        let mesh_count = read_count(&mut reader, 48)?;
+        let mut meshes = Vec::with_capacity(mesh_count);
+        for _ in 0..mesh_count {
+            let name = read_string(&mut reader)?;
+            let material_id = read_i32(&mut reader)?;
+            let mut bounds = [0f32; 6];
+            reader.read_exact(bytemuck::cast_slice_mut(&mut bounds))?;
+            let vertex_count = read_u32(&mut reader)? as usize;
+            let index_count = read_u32(&mut reader)? as usize;
+
+            let vertices = read_items::<ModelVertex>(&mut reader, vertex_count)?;
+            let indices = read_items::<u32>(&mut reader, index_count)?;
+            let skin_count = read_u32(&mut reader)? as usize;
+            let skin = read_items::<VertexSkin>(&mut reader, skin_count)?;
+            let lod_count = read_count(&mut reader, 4)?;
+            let mut lods = Vec::with_capacity(lod_count);
+            for _ in 0..lod_count {
+                let lod_len = read_u32(&mut reader)? as usize;
+                lods.push(read_items::<u32>(&mut reader, lod_len)?);
+            }
+
+            meshes.push(ImportedMesh {
+                name,
+                material_id: if material_id < 0 {
+                    None
+                } else {
+                    Some(material_id as usize)
+                },
+                bounds_min: [bounds[0], bounds[1], bounds[2]],
+                bounds_max: [bounds[3], bounds[4], bounds[5]],
+                vertices,
+                skin,
+                indices,
+                lods,
+            });
+        }
+
+        Ok(ImportedModel {
+            source_hash,
+            material_libs,
+            meshes,
+        })
+    }
+}
+
+pub fn cache_file_name(file_name: &str) -> String {
+    format!("{}.{}", file_name, MESH_CACHE_EXTENSION)
+}
+
+pub fn cache_file_path(file_name: &str) -> Result<PathBuf, AppError> {
+    FileSystem::get_res_file_path(&cache_file_name(file_name))
+}
+
+pub fn load_cached_model(file_name: &str, source_hash: u64) -> Option<ImportedModel> {
+    let bytes = FileSystem::load_binary(&cache_file_name(file_name)).ok()?;
+    match ImportedModel::read_header(&bytes) {
+        Ok((MESH_CACHE_VERSION, hash)) if hash == source_hash => {}
+        _ => return None,
+    }
+    match ImportedModel::from_bytes(&bytes) {
+        Ok(model) => Some(model),
+        Err(e) => {
+            log_warning!("Ignoring corrupt mesh cache for {}: {}", file_name, e);
+            None
+        }
+    }
+}
+
+pub fn write_cached_model(file_name: &str, model: &ImportedModel) -> Result<(), AppError> {
+    let path = cache_file_path(file_name)?;
+    let mut file = std::fs::File::create(&path)?;
+    file.write_all(&model.to_bytes())?;
+    log_info!("Wrote mesh cache {:?}", path);
+    Ok(())
+}
+
+pub async fn import_model(file_name: &str) -> Result<ImportedModel, AppError> {
+    let source = FileSystem::load_string(file_name)?;
+    let source_hash = content_hash(source.as_bytes());
+
+    if let Some(model) = load_cached_model(file_name, source_hash) {
+        log_info!("Loaded {} from mesh cache", file_name);
+        return Ok(model);
+    }
+
+    let raw = load_model_raw_from_string(source).await?;
+    let model = ImportedModel::from_raw(source_hash, raw);
+
+    if let Err(e) = write_cached_model(file_name, &model) {
+        log_warning!("Failed to write mesh cache for {}: {}", file_name, e);
+    }
+    Ok(model)
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn read_string(reader: &mut Cursor<&[u8]>) -> Result<String, AppError> {
+    let len = read_u32(reader)? as usize;
+    let bytes = read_items::<u8>(reader, len)?;
+    String::from_utf8(bytes).map_err(|e| AppError::ResourceCreationFailed(e.to_string()))
+}
+
+fn remaining(reader: &Cursor<&[u8]>) -> usize {
+    reader
+        .get_ref()
+        .len()
+        .saturating_sub(reader.position() as usize)
+}
+
+/// Reads an entry count and rejects it if the rest of the file can't hold that many entries
+/// of at least `entry_size` bytes, so a corrupt count never drives an allocation.
+fn read_count(reader: &mut Cursor<&[u8]>, entry_size: usize) -> Result<usize, AppError> {
+    let count = read_u32(reader)? as usize;
+    if count.saturating_mul(entry_size) > remaining(reader) {
+        return Err(AppError::ResourceCreationFailed(format!(
+            "Mesh cache count {} exceeds the {} bytes left",
+            count,
+            remaining(reader)
+        )));
+    }
+    Ok(count)
+}
+
+/// Reads `count` plain values, checking the bytes are there before allocating.
+fn read_items<T: bytemuck::Pod>(
+    reader: &mut Cursor<&[u8]>,
+    count: usize,
+) -> Result<Vec<T>, AppError> {
+    let len = count.saturating_mul(std::mem::size_of::<T>());
+    if len > remaining(reader) {
+        return Err(AppError::ResourceCreationFailed(format!(
+            "Mesh cache needs {} bytes but only {} are left",
+            len,
+            remaining(reader)
+        )));
+    }
+    let mut items = vec![T::zeroed(); count];
+    reader.read_exact(bytemuck::cast_slice_mut(&mut items))?;
+    Ok(items)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, AppError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_i32<R: Read>(reader: &mut R) -> Result<i32, AppError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(i32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, AppError> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mounts a scratch asset root holding `files` under `res/`. An empty cache file is
+    /// written next to the OBJ so the importer's cache write lands in the scratch root.
+    fn mount_res(name: &str, files: &[(&str, &str)]) -> (String, PathBuf) {
+        let root =
+            std::env::temp_dir().join(format!("rupy-import-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&root);
+        let res = root.join("res");
+        std::fs::create_dir_all(&res).unwrap();
+        for (file, content) in files {
+            std::fs::write(res.join(file), content).unwrap();
+            if file.ends_with(".obj") {
+                std::fs::write(res.join(cache_file_name(file)), []).unwrap();
+            }
+        }
+        let mount = format!("import-test:{}", name);
+        FileSystem::mount(&mount, &root, i32::MAX).unwrap();
+        (mount, root)
+    }
+
+    fn vertex(position: [f32; 3]) -> ModelVertex {
+        ModelVertex {
+            position,
+            tex_coords: [position[0], position[1]],
+            normal: [0.0, 0.0, 1.0],
+            tangent: [1.0, 0.0, 0.0],
+            bitangent: [0.0, 1.0, 0.0],
+        }
+    }
+
+    fn sample_model() -> ImportedModel {
+        let vertices = vec![
+            vertex([0.0, 0.0, 0.0]),
+            vertex([1.0, 0.0, 0.0]),
+            vertex([1.0, 1.0, 0.0]),
+            vertex([0.0, 1.0, -2.0]),
+        ];
+        let (bounds_min, bounds_max) = compute_bounds(&vertices);
+        let skin = vec![
+            VertexSkin {
+                joints: [0, 1, 0, 0],
+                weights: [0.75, 0.25, 0.0, 0.0],
+            };
+            vertices.len()
+        ];
+        ImportedModel {
+            source_hash: 0xDEAD_BEEF_0123_4567,
+            material_libs: vec![
+                MaterialLib {
+                    name: "cube.mtl".to_string(),
+                    material_count: 1,
+                },
+                MaterialLib {
+                    name: "extra.mtl".to_string(),
+                    material_count: 2,
+                },
+            ],
+            meshes: vec![
+                ImportedMesh {
+                    name: "quad".to_string(),
+                    material_id: Some(1),
+                    bounds_min,
+                    bounds_max,
+                    vertices: vertices.clone(),
+                    skin,
+                    indices: vec![0, 1, 2, 0, 2, 3],
+                    lods: vec![vec![0, 1, 2, 0, 2, 3], vec![0, 1, 2]],
+                },
+                ImportedMesh {
+                    name: "untextured".to_string(),
+                    material_id: None,
+                    bounds_min,
+                    bounds_max,
+                    vertices,
+                    skin: Vec::new(),
+                    indices: vec![0, 1, 2],
+                    lods: Vec::new(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn cache_round_trip_preserves_model() {
+        let model = sample_model();
+        let decoded = ImportedModel::from_bytes(&model.to_bytes()).unwrap();
+
+        assert_eq!(decoded.source_hash, model.source_hash);
+        assert_eq!(decoded.material_libs, model.material_libs);
+        assert_eq!(decoded.meshes.len(), model.meshes.len());
+        for (decoded, original) in decoded.meshes.iter().zip(&model.meshes) {
+            assert_eq!(decoded.name, original.name);
+            assert_eq!(decoded.material_id, original.material_id);
+            assert_eq!(decoded.bounds_min, original.bounds_min);
+            assert_eq!(decoded.bounds_max, original.bounds_max);
+            assert_eq!(
+                bytemuck::cast_slice::<ModelVertex, u8>(&decoded.vertices),
+                bytemuck::cast_slice::<ModelVertex, u8>(&original.vertices)
+            );
+            assert_eq!(decoded.skin, original.skin);
+            assert_eq!(decoded.indices, original.indices);
+            assert_eq!(decoded.lods, original.lods);
+        }
+        assert!(decoded.meshes[0].is_skinned());
+        assert!(!decoded.meshes[1].is_skinned());
+    }
+
+    #[test]
+    fn read_header_reports_version_and_hash() {
+        let bytes = sample_model().to_bytes();
+        let (version, hash) = ImportedModel::read_header(&bytes).unwrap();
+        assert_eq!(version, MESH_CACHE_VERSION);
+        assert_eq!(hash, 0xDEAD_BEEF_0123_4567);
+    }
+
+    #[test]
+    fn from_bytes_rejects_bad_magic_version_and_truncation() {
+        let bytes = sample_model().to_bytes();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(ImportedModel::from_bytes(&bad_magic).is_err());
+
+        let mut bad_version = bytes.clone();
+        bad_version[4..8].copy_from_slice(&(MESH_CACHE_VERSION + 1).to_le_bytes());
+        assert!(ImportedModel::from_bytes(&bad_version).is_err());
+
+        assert!(ImportedModel::from_bytes(&bytes[..bytes.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn from_bytes_rejects_counts_larger_than_the_file() {
+        let bytes = sample_model().to_bytes();
+        // The first mesh's vertex count sits after the header, both libraries, the mesh
+        // count, the mesh name, its material id and its bounds.
+        let libs: usize = ["cube.mtl", "extra.mtl"].iter().map(|n| 8 + n.len()).sum();
+        let vertex_count_at = 16 + 4 + libs + 4 + (4 + "quad".len()) + 4 + 24;
+        for offset in [16, 16 + 4 + libs, vertex_count_at, vertex_count_at + 4] {
+            let mut corrupt = bytes.clone();
+            corrupt[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+            assert!(ImportedModel::from_bytes(&corrupt).is_err());
+        }
+    }
+
+    #[test]
+    fn missing_material_library_does_not_fail_a_cold_import() {
+        let obj = "mtllib import_missing_absent.mtl\nmtllib import_missing_present.mtl\n\
+                   v 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl present\nf 1 2 3\n";
+        let (mount, root) = mount_res(
+            "missing",
+            &[
+                ("import_missing.obj", obj),
+                ("import_missing_present.mtl", "newmtl present\nKd 1 0 0\n"),
+            ],
+        );
+
+        let cold = pollster::block_on(import_model("import_missing.obj"));
+        let warm = pollster::block_on(import_model("import_missing.obj"));
+        FileSystem::unmount(&mount).unwrap();
+        let _ = std::fs::remove_dir_all(root);
+
+        for model in [cold.unwrap(), warm.unwrap()] {
+            assert_eq!(
+                model.material_libs,
+                vec![
+                    MaterialLib {
+                        name: "import_missing_absent.mtl".to_string(),
+                        material_count: 0,
+                    },
+                    MaterialLib {
+                        name: "import_missing_present.mtl".to_string(),
+                        material_count: 1,
+                    },
+                ]
+            );
+            assert_eq!(model.meshes.len(), 1);
+            assert_eq!(model.meshes[0].material_id, Some(0));
+        }
+    }
+
+    #[test]
+    fn unloadable_library_keeps_later_material_ids() {
+        let (mount, root) = mount_res(
+            "offsets",
+            &[("import_offsets_late.mtl", "newmtl late\nKd 0 1 0\n")],
+        );
+        let model = ImportedModel {
+            source_hash: 0,
+            material_libs: vec![
+                MaterialLib {
+                    name: "import_offsets_gone.mtl".to_string(),
+                    material_count: 2,
+                },
+                MaterialLib {
+                    name: "import_offsets_late.mtl".to_string(),
+                    material_count: 1,
+                },
+            ],
+            meshes: Vec::new(),
+        };
+
+        let materials = model.load_materials();
+        FileSystem::unmount(&mount).unwrap();
+        let _ = std::fs::remove_dir_all(root);
+
+        assert_eq!(materials.len(), 3);
+        assert!(materials[0].is_none() && materials[1].is_none());
+        assert_eq!(materials[2].as_ref().map(|m| m.name.as_str()), Some("late"));
+    }
+}
@@ -36,6 +36,7 @@ impl MeshManager {
             indices,
             material,
             name,
+            Vec::new(),
         )
     }
 }
@@ -48,9 +49,13 @@ pub fn create_cached_mesh_with_buffers(
     indices: Vec<u32>,
     material: Option<usize>,
     name: String,
+    lods: Vec<Vec<u32>>,
 ) -> Result<CacheKey, AppError> {
     let cache_key = Mesh::key(vec![&name, &material.unwrap_or(0).to_string()]);
-    let mesh = Mesh::cache(Mesh::new(cache_key, material, vertices, indices), meshes);
+    let mesh = Mesh::cache(
+        Mesh::new(cache_key, material, vertices, indices).with_lods(lods),
+        meshes,
+    );
 
     Mesh::cache_index_buffer(&mesh, device, buffers);
     Mesh::cache_vertex_buffer(&mesh, device, buffers);
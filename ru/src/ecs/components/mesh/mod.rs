@@ -1,2 +1,5 @@
+pub mod import;
 pub mod manager;
 pub mod model;
+pub mod processing;
+pub mod simplify;
@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+
+use cgmath::{InnerSpace, Vector2, Vector3, Zero};
+
+use crate::graphics::vertex::ModelVertex;
+
+pub const DEFAULT_WELD_EPSILON: f32 = 1e-5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalMode {
+    Smooth,
+    Flat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshProcessingOptions {
+    pub normal_mode: NormalMode,
+    pub weld: bool,
+    pub weld_epsilon: f32,
+    pub generate_tangents: bool,
+}
+
+impl Default for MeshProcessingOptions {
+    fn default() -> Self {
+        Self {
+            normal_mode: NormalMode::Smooth,
+            weld: true,
+            weld_epsilon: DEFAULT_WELD_EPSILON,
+            generate_tangents: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MeshData {
+    pub vertices: Vec<ModelVertex>,
+    pub indices: Vec<u32>,
+    pub has_normals: bool,
+    pub has_tex_coords: bool,
+}
+
+impl MeshData {
+    pub fn from_attributes(
+        positions: &[f32],
+        tex_coords: &[f32],
+        normals: &[f32],
+        indices: &[u32],
+    ) -> MeshData {
+        let count = positions.len() / 3;
+        let has_normals = normals.len() >= count * 3 && count > 0;
+        let has_tex_coords = tex_coords.len() >= count * 2 && count > 0;
+
+        let vertices = (0..count)
+            .map(|i| ModelVertex {
+                position: [positions[i * 3], positions[i * 3 + 1], positions[i * 3 + 2]],
+                tex_coords: if has_tex_coords {
+                    [tex_coords[i * 2], 1.0 - tex_coords[i * 2 + 1]]
+                } else {
+                    [0.0, 0.0]
+                },
+                normal: if has_normals {
+                    [normals[i * 3], normals[i * 3 + 1], normals[i * 3 + 2]]
+                } else {
+                    [0.0, 0.0, 0.0]
+                },
+                ..Default::default()
+            })
+            .collect();
+
+        MeshData {
+            vertices,
+            indices: sanitize_indices(indices, count),
+            has_normals,
+            has_tex_coords,
+        }
+    }
+
+    pub fn process(mut self, options: &MeshProcessingOptions) -> MeshData {
+        if !self.has_tex_coords {
+            generate_default_tex_coords(&mut self.vertices);
+            self.has_tex_coords = true;
+        }
+        if !self.has_normals {
+            match options.normal_mode {
+                NormalMode::Smooth => generate_smooth_normals(&mut self.vertices, &self.indices),
+                NormalMode::Flat => {
+                    let (vertices, indices) = generate_flat_normals(&self.vertices, &self.indices);
+                    self.vertices = vertices;
+                    self.indices = indices;
+                }
+            }
+            self.has_normals = true;
+        }
+        if options.weld {
+            let (vertices, indices) =
+                weld_vertices(&self.vertices, &self.indices, options.weld_epsilon);
+            self.vertices = vertices;
+            self.indices = indices;
+        }
+        if options.generate_tangents {
+            generate_tangents(&mut self.vertices, &mut self.indices);
+        }
+        self
+    }
+}
+
+pub fn sanitize_indices(indices: &[u32], vertex_count: usize) -> Vec<u32> {
+    if indices.is_empty() {
+        return (0..(vertex_count - vertex_count % 3) as u32).collect();
+    }
+    indices
+        .chunks_exact(3)
+        .filter(|tri| tri.iter().all(|&i| (i as usize) < vertex_count))
+        .flatten()
+        .copied()
+        .collect()
+}
+
+pub fn generate_default_tex_coords(vertices: &mut [ModelVertex]) {
+    if vertices.is_empty() {
+        return;
+    }
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for vertex in vertices.iter() {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(vertex.position[axis]);
+            max[axis] = max[axis].max(vertex.position[axis]);
+        }
+    }
+    let extent = [max[0] - min[0], max[1] - min[1], max[2] - min[2]];
+    let mut axes = [0usize, 1, 2];
+    axes.sort_by(|a, b| extent[*b].total_cmp(&extent[*a]));
+    let (u_axis, v_axis) = (axes[0], axes[1]);
+
+    for vertex in vertices.iter_mut() {
+        let u = if extent[u_axis] > 0.0 {
+            (vertex.position[u_axis] - min[u_axis]) / extent[u_axis]
+        } else {
+            0.0
+        };
+        let v = if extent[v_axis] > 0.0 {
+            (vertex.position[v_axis] - min[v_axis]) / extent[v_axis]
+        } else {
+            0.0
+        };
+        vertex.tex_coords = [u, 1.0 - v];
+    }
+}
+
+fn face_normal(vertices: &[ModelVertex], tri: &[u32]) -> Vector3<f32> {
+    let p0 = Vector3::from(vertices[tri[0] as usize].position);
+    let p1 = Vector3::from(vertices[tri[1] as usize].position);
+    let p2 = Vector3::from(vertices[tri[2] as usize].position);
+    (p1 - p0).cross(p2 - p0)
+}
+
+fn position_key(position: [f32; 3], epsilon: f32) -> [i64; 3] {
+    let scale = 1.0 / epsilon.max(f32::EPSILON);
+    position.map(|c| (c * scale).round() as i64)
+}
+
+pub fn generate_smooth_normals(vertices: &mut [ModelVertex], indices: &[u32]) {
+    let mut groups: HashMap<[i64; 3], Vector3<f32>> = HashMap::new();
+    let keys: Vec<[i64; 3]> = vertices
+        .iter()
+        .map(|v| position_key(v.position, DEFAULT_WELD_EPSILON))
+        .collect();
+
+    for tri in indices.chunks_exact(3) {
+        let normal = face_normal(vertices, tri);
+        for &index in tri {
+            *groups
+                .entry(keys[index as usize])
+                .or_insert_with(Vector3::zero) += normal;
+        }
+    }
+
+    for (vertex, key) in vertices.iter_mut().zip(keys) {
+        let normal = groups.get(&key).copied().unwrap_or_else(Vector3::zero);
+        vertex.normal = if normal.magnitude2() > 0.0 {
+            normal.normalize().into()
+        } else {
+            [0.0, 1.0, 0.0]
+        };
+    }
+}
+
+pub fn generate_flat_normals(
+    vertices: &[ModelVertex],
+    indices: &[u32],
+) -> (Vec<ModelVertex>, Vec<u32>) {
+    let mut out_vertices = Vec::with_capacity(indices.len());
+    let mut out_indices = Vec::with_capacity(indices.len());
+    for tri in indices.chunks_exact(3) {
+        let normal = face_normal(vertices, tri);
+        let normal: [f32; 3] = if normal.magnitude2() > 0.0 {
+            normal.normalize().into()
+        } else {
+            [0.0, 1.0, 0.0]
+        };
+        for &index in tri {
+            let mut vertex = vertices[index as usize];
+            vertex.normal = normal;
+            out_indices.push(out_vertices.len() as u32);
+            out_vertices.push(vertex);
+        }
+    }
+    (out_vertices, out_indices)
+}
+
+fn weld_key(vertex: &ModelVertex, epsilon: f32) -> [i64; 8] {
+    let scale = 1.0 / epsilon.max(f32::EPSILON);
+    let q = |c: f32| (c * scale).round() as i64;
+    [
+        q(vertex.position[0]),
+        q(vertex.position[1]),
+        q(vertex.position[2]),
+        q(vertex.tex_coords[0]),
+        q(vertex.tex_coords[1]),
+        q(vertex.normal[0]),
+        q(vertex.normal[1]),
+        q(vertex.normal[2]),
+    ]
+}
+
+pub fn weld_vertices(
+    vertices: &[ModelVertex],
+    indices: &[u32],
+    epsilon: f32,
+) -> (Vec<ModelVertex>, Vec<u32>) {
+    let mut lookup: HashMap<[i64; 8], u32> = HashMap::with_capacity(vertices.len());
+    let mut remap = Vec::with_capacity(vertices.len());
+    let mut welded = Vec::with_capacity(vertices.len());
+
+    for vertex in vertices {
+        let index = *lookup.entry(weld_key(vertex, epsilon)).or_insert_with(|| {
+            welded.push(*vertex);
+            (welded.len() - 1) as u32
+        });
+        remap.push(index);
+    }
+
+    let indices = indices.iter().map(|&i| remap[i as usize]).collect();
+    (welded, indices)
+}
+
+pub fn generate_tangents(vertices: &mut Vec<ModelVertex>, indices: &mut [u32]) -> Vec<u32> {
+    let tangents = compute_tangents(vertices, indices);
+    let mut split: HashMap<(u32, bool), u32> = HashMap::new();
+    let mut sources: Vec<u32> = (0..vertices.len() as u32).collect();
+
+    for (corner, index) in indices.iter_mut().enumerate() {
+        let tangent = tangents.corners[corner];
+        let positive = tangent[3] >= 0.0;
+        let key = (*index, positive);
+        let target = match split.get(&key) {
+            Some(&target) => target,
+            None => {
+                let target = if split.contains_key(&(*index, !positive)) {
+                    vertices.push(vertices[*index as usize]);
+                    sources.push(*index);
+                    (vertices.len() - 1) as u32
+                } else {
+                    *index
+                };
+                split.insert(key, target);
+                target
+            }
+        };
+        *index = target;
+    }
+
+    for ((source, positive), target) in split {
+        let tangent = tangents.accumulated[&(source, positive)];
+        let vertex = &mut vertices[target as usize];
+        let normal = Vector3::from(vertex.normal);
+        let t = Vector3::new(tangent[0], tangent[1], tangent[2]);
+        vertex.tangent = t.into();
+        vertex.bitangent = (normal.cross(t) * tangent[3]).into();
+    }
+    sources
+}
+
+pub struct TangentFrames {
+    pub corners: Vec<[f32; 4]>,
+    pub accumulated: HashMap<(u32, bool), [f32; 4]>,
+}
+
+pub fn compute_tangents(vertices: &[ModelVertex], indices: &[u32]) -> TangentFrames {
+    let mut corners = Vec::with_capacity(indices.len());
+    let mut sums: HashMap<(u32, bool), (Vector3<f32>, Vector3<f32>)> = HashMap::new();
+
+    for tri in indices.chunks_exact(3) {
+        let tri = [tri[0], tri[1], tri[2]];
+        let p = tri.map(|i| Vector3::from(vertices[i as usize].position));
+        let uv = tri.map(|i| Vector2::from(vertices[i as usize].tex_coords));
+
+        let e1 = p[1] - p[0];
+        let e2 = p[2] - p[0];
+        let d1 = uv[1] - uv[0];
+        let d2 = uv[2] - uv[0];
+        let det = d1.x * d2.y - d1.y * d2.x;
+
+        // Texture coordinates have their origin at the top left, so the bitangent points
+        // towards decreasing v to keep normal maps "+Y up" as in glTF.
+        let (face_tangent, face_bitangent) = if det.abs() > 1e-12 {
+            let r = 1.0 / det;
+            ((e1 * d2.y - e2 * d1.y) * r, (e1 * d2.x - e2 * d1.x) * r)
+        } else {
+            (Vector3::zero(), Vector3::zero())
+        };
+
+        for corner in 0..3 {
+            let index = tri[corner];
+            let normal = Vector3::from(vertices[index as usize].normal);
+            let sign = if normal.cross(face_tangent).dot(face_bitangent) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+
+            let a = p[(corner + 1) % 3] - p[corner];
+            let b = p[(corner + 2) % 3] - p[corner];
+            let angle = if a.magnitude2() > 0.0 && b.magnitude2() > 0.0 {
+                a.normalize().dot(b.normalize()).clamp(-1.0, 1.0).acos()
+            } else {
+                0.0
+            };
+
+            let entry = sums
+                .entry((index, sign > 0.0))
+                .or_insert_with(|| (Vector3::zero(), Vector3::zero()));
+            entry.0 += face_tangent * angle;
+            entry.1 += face_bitangent * angle;
+            corners.push([0.0, 0.0, 0.0, sign]);
+        }
+    }
+
+    let mut accumulated = HashMap::with_capacity(sums.len());
+    for ((index, positive), (tangent, _)) in sums {
+        let normal = Vector3::from(vertices[index as usize].normal);
+        let tangent = orthonormal_tangent(normal, tangent);
+        let sign = if positive { 1.0 } else { -1.0 };
+        accumulated.insert((index, positive), [tangent.x, tangent.y, tangent.z, sign]);
+    }
+
+    for (corner, frame) in corners.iter_mut().enumerate() {
+        let index = indices[corner];
+        let positive = frame[3] > 0.0;
+        *frame = accumulated[&(index, positive)];
+    }
+
+    TangentFrames {
+        corners,
+        accumulated,
+    }
+}
+
+fn orthonormal_tangent(normal: Vector3<f32>, tangent: Vector3<f32>) -> Vector3<f32> {
+    let projected = tangent - normal * normal.dot(tangent);
+    if projected.magnitude2() > 1e-12 {
+        return projected.normalize();
+    }
+    let axis = if normal.x.abs() < 0.9 {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    };
+    let fallback = axis - normal * normal.dot(axis);
+    if fallback.magnitude2() > 1e-12 {
+        fallback.normalize()
+    } else {
+        Vector3::unit_x()
+    }
+}
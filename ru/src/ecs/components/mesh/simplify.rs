@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+
+use cgmath::{InnerSpace, Vector3};
+
+use crate::graphics::vertex::ModelVertex;
+
+use super::processing::DEFAULT_WELD_EPSILON;
+
+pub const LOD_RATIOS: [f32; 3] = [0.5, 0.25, 0.125];
+pub const LOD_MIN_TRIANGLES: usize = 256;
+pub const LOD_MAX_ERROR: f32 = 0.05;
+const LOD_MIN_REDUCTION: f32 = 0.9;
+const MAX_PASSES: usize = 64;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric {
+    m: [f64; 10],
+}
+
+impl Quadric {
+    fn from_plane(normal: Vector3<f64>, d: f64) -> Self {
+        let (a, b, c) = (normal.x, normal.y, normal.z);
+        Self {
+            m: [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ],
+        }
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (a, b) in self.m.iter_mut().zip(other.m.iter()) {
+            *a += b;
+        }
+    }
+
+    fn error(&self, p: Vector3<f64>) -> f64 {
+        let m = &self.m;
+        let (x, y, z) = (p.x, p.y, p.z);
+        let e = m[0] * x * x
+            + 2.0 * m[1] * x * y
+            + 2.0 * m[2] * x * z
+            + 2.0 * m[3] * x
+            + m[4] * y * y
+            + 2.0 * m[5] * y * z
+            + 2.0 * m[6] * y
+            + m[7] * z * z
+            + 2.0 * m[8] * z
+            + m[9];
+        e.max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Collapse {
+    cost: f64,
+    from: u32,
+    to: u32,
+}
+
+pub fn generate_lods(vertices: &[ModelVertex], indices: &[u32]) -> Vec<Vec<u32>> {
+    let triangle_count = indices.len() / 3;
+    if triangle_count < LOD_MIN_TRIANGLES {
+        return Vec::new();
+    }
+
+    let mut lods: Vec<Vec<u32>> = Vec::with_capacity(LOD_RATIOS.len());
+    for ratio in LOD_RATIOS {
+        let previous = lods.last().map(Vec::as_slice).unwrap_or(indices);
+        let target = ((triangle_count as f32 * ratio) as usize).max(1) * 3;
+        let (lod, _) = simplify(vertices, previous, target, LOD_MAX_ERROR);
+        if lod.is_empty() || lod.len() as f32 > previous.len() as f32 * LOD_MIN_REDUCTION {
+            break;
+        }
+        lods.push(lod);
+    }
+    lods
+}
+
+pub fn simplify(
+    vertices: &[ModelVertex],
+    indices: &[u32],
+    target_index_count: usize,
+    max_error: f32,
+) -> (Vec<u32>, f32) {
+    let positions: Vec<Vector3<f64>> = vertices
+        .iter()
+        .map(|v| {
+            Vector3::new(
+                v.position[0] as f64,
+                v.position[1] as f64,
+                v.position[2] as f64,
+            )
+        })
+        .collect();
+    let mut indices: Vec<u32> = indices
+        .chunks_exact(3)
+        .filter(|tri| tri.iter().all(|&i| (i as usize) < vertices.len()))
+        .flatten()
+        .copied()
+        .collect();
+    if indices.len() <= target_index_count {
+        return (indices, 0.0);
+    }
+
+    let extent = mesh_extent(&positions, &indices);
+    let max_cost = (max_error as f64 * extent).powi(2);
+    let locked = locked_vertices(vertices, &indices);
+
+    let mut quadrics = vec![Quadric::default(); vertices.len()];
+    for tri in indices.chunks_exact(3) {
+        let [p0, p1, p2] = [tri[0], tri[1], tri[2]].map(|i| positions[i as usize]);
+        let normal = (p1 - p0).cross(p2 - p0);
+        if normal.magnitude2() <= 0.0 {
+            continue;
+        }
+        let normal = normal.normalize();
+        let quadric = Quadric::from_plane(normal, -normal.dot(p0));
+        for &i in tri {
+            quadrics[i as usize].add(&quadric);
+        }
+    }
+
+    let mut result_error = 0.0f64;
+    for _ in 0..MAX_PASSES {
+        if indices.len() <= target_index_count {
+            break;
+        }
+
+        let mut adjacency: Vec<Vec<u32>> = vec![Vec::new(); vertices.len()];
+        let mut candidates: Vec<Collapse> = Vec::with_capacity(indices.len() * 2);
+        for (t, tri) in indices.chunks_exact(3).enumerate() {
+            for corner in 0..3 {
+                let from = tri[corner];
+                adjacency[from as usize].push(t as u32);
+                for to in [tri[(corner + 1) % 3], tri[(corner + 2) % 3]] {
+                    if locked[from as usize] {
+                        continue;
+                    }
+                    let mut quadric = quadrics[from as usize];
+                    quadric.add(&quadrics[to as usize]);
+                    let cost = quadric.error(positions[to as usize]);
+                    if cost <= max_cost {
+                        candidates.push(Collapse { cost, from, to });
+                    }
+                }
+            }
+        }
+        if candidates.is_empty() {
+            break;
+        }
+        candidates.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+
+        let mut touched = vec![false; vertices.len()];
+        let mut remap: Vec<u32> = (0..vertices.len() as u32).collect();
+        let mut remaining = indices.len() / 3;
+        let target_triangles = target_index_count / 3;
+        let mut collapsed = 0usize;
+
+        for collapse in candidates {
+            if remaining <= target_triangles {
+                break;
+            }
+            let (from, to) = (collapse.from as usize, collapse.to as usize);
+            if touched[from] || touched[to] {
+                continue;
+            }
+            if flips_triangles(&positions, &indices, &adjacency[from], from, to) {
+                continue;
+            }
+
+            let mut removed = 0;
+            for &t in &adjacency[from] {
+                let tri = &indices[t as usize * 3..t as usize * 3 + 3];
+                if tri.contains(&collapse.to) {
+                    removed += 1;
+                }
+                for &i in tri {
+                    touched[i as usize] = true;
+                }
+            }
+            remap[from] = collapse.to;
+            let source = quadrics[from];
+            quadrics[to].add(&source);
+            remaining = remaining.saturating_sub(removed);
+            result_error = result_error.max(collapse.cost);
+            collapsed += 1;
+        }
+
+        if collapsed == 0 {
+            break;
+        }
+
+        indices = indices
+            .chunks_exact(3)
+            .map(|tri| [tri[0], tri[1], tri[2]].map(|i| remap[i as usize]))
+            .filter(|tri| tri[0] != tri[1] && tri[1] != tri[2] && tri[0] != tri[2])
+            .flatten()
+            .collect();
+    }
+
+    (
+        indices,
+        (result_error.sqrt() / extent.max(f64::EPSILON)) as f32,
+    )
+}
+
+fn mesh_extent(positions: &[Vector3<f64>], indices: &[u32]) -> f64 {
+    let mut min = Vector3::new(f64::MAX, f64::MAX, f64::MAX);
+    let mut max = Vector3::new(f64::MIN, f64::MIN, f64::MIN);
+    for &i in indices {
+        let p = positions[i as usize];
+        min = Vector3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+        max = Vector3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+    }
+    (max - min).magnitude()
+}
+
+fn position_key(position: [f32; 3]) -> [i64; 3] {
+    position.map(|c| (c / DEFAULT_WELD_EPSILON).round() as i64)
+}
+
+fn locked_vertices(vertices: &[ModelVertex], indices: &[u32]) -> Vec<bool> {
+    let mut groups: HashMap<[i64; 3], u32> = HashMap::new();
+    let mut group_sizes: HashMap<u32, u32> = HashMap::new();
+    let mut canonical = vec![0u32; vertices.len()];
+    let mut used = vec![false; vertices.len()];
+    for &i in indices {
+        used[i as usize] = true;
+    }
+    for (i, vertex) in vertices.iter().enumerate() {
+        if !used[i] {
+            continue;
+        }
+        let group = *groups
+            .entry(position_key(vertex.position))
+            .or_insert(i as u32);
+        canonical[i] = group;
+        *group_sizes.entry(group).or_insert(0) += 1;
+    }
+
+    let mut edges: HashMap<(u32, u32), u32> = HashMap::new();
+    for tri in indices.chunks_exact(3) {
+        for corner in 0..3 {
+            let a = canonical[tri[corner] as usize];
+            let b = canonical[tri[(corner + 1) % 3] as usize];
+            *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+    }
+
+    let mut locked_groups = vec![false; vertices.len()];
+    for ((a, b), count) in edges {
+        if count != 2 {
+            locked_groups[a as usize] = true;
+            locked_groups[b as usize] = true;
+        }
+    }
+
+    (0..vertices.len())
+        .map(|i| {
+            let group = canonical[i];
+            locked_groups[group as usize] || group_sizes.get(&group).copied().unwrap_or(0) > 1
+        })
+        .collect()
+}
+
+fn flips_triangles(
+    positions: &[Vector3<f64>],
+    indices: &[u32],
+    adjacent: &[u32],
+    from: usize,
+    to: usize,
+) -> bool {
+    for &t in adjacent {
+        let tri = &indices[t as usize * 3..t as usize * 3 + 3];
+        if tri.contains(&(to as u32)) {
+            continue;
+        }
+        let [p0, p1, p2] = [tri[0], tri[1], tri[2]].map(|i| positions[i as usize]);
+        let before = (p1 - p0).cross(p2 - p0);
+        let [q0, q1, q2] = [tri[0], tri[1], tri[2]].map(|i| {
+            if i as usize == from {
+                positions[to]
+            } else {
+                positions[i as usize]
+            }
+        });
+        let after = (q1 - q0).cross(q2 - q0);
+        if after.magnitude2() <= 0.0 || before.dot(after) <= 0.0 {
+            return true;
+        }
+        if before.magnitude2() > 0.0 && before.normalize().dot(after.normalize()) < 0.2 {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(size: usize) -> (Vec<ModelVertex>, Vec<u32>) {
+        let mut vertices = Vec::new();
+        for y in 0..=size {
+            for x in 0..=size {
+                vertices.push(ModelVertex {
+                    position: [x as f32, y as f32, 0.0],
+                    normal: [0.0, 0.0, 1.0],
+                    ..Default::default()
+                });
+            }
+        }
+        let row = size as u32 + 1;
+        let mut indices = Vec::new();
+        for y in 0..size as u32 {
+            for x in 0..size as u32 {
+                let i = y * row + x;
+                indices.extend([i, i + 1, i + row + 1, i, i + row + 1, i + row]);
+            }
+        }
+        (vertices, indices)
+    }
+
+    fn bounds(vertices: &[ModelVertex], indices: &[u32]) -> ([f32; 3], [f32; 3]) {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for &i in indices {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(vertices[i as usize].position[axis]);
+                max[axis] = max[axis].max(vertices[i as usize].position[axis]);
+            }
+        }
+        (min, max)
+    }
+
+    #[test]
+    fn simplify_reduces_triangles_and_keeps_bounds() {
+        let (vertices, indices) = grid(16);
+        let (simplified, error) = simplify(&vertices, &indices, indices.len() / 4, 0.01);
+
+        assert_eq!(simplified.len() % 3, 0);
+        assert!(simplified.len() <= indices.len() / 2);
+        assert!(simplified.iter().all(|&i| (i as usize) < vertices.len()));
+        assert!(error < 1e-4);
+        assert_eq!(bounds(&vertices, &simplified), bounds(&vertices, &indices));
+    }
+
+    #[test]
+    fn simplify_returns_small_meshes_unchanged() {
+        let (vertices, indices) = grid(2);
+        let (simplified, error) = simplify(&vertices, &indices, indices.len(), 0.01);
+        assert_eq!(simplified, indices);
+        assert_eq!(error, 0.0);
+    }
+
+    #[test]
+    fn generate_lods_shrink_per_level() {
+        let (vertices, indices) = grid(32);
+        let lods = generate_lods(&vertices, &indices);
+
+        assert!(!lods.is_empty());
+        let mut previous = indices.len();
+        for lod in &lods {
+            assert!(lod.len() < previous);
+            assert_eq!(bounds(&vertices, lod), bounds(&vertices, &indices));
+            previous = lod.len();
+        }
+
+        let (vertices, indices) = grid(8);
+        assert!(indices.len() / 3 < LOD_MIN_TRIANGLES);
+        assert!(generate_lods(&vertices, &indices).is_empty());
+    }
+}
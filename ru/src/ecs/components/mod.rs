@@ -1,8 +1,15 @@
+pub mod animation;
+pub mod bounds;
+pub mod camera;
+pub mod hierarchy;
 pub mod instance;
+pub mod light;
+pub mod lod;
 pub mod material;
 pub mod mesh;
 pub mod model;
 pub mod transform;
+use animation::manager::AnimationManager;
 use instance::manager::InstanceManager;
 use material::manager::MaterialManager;
 use mesh::manager::MeshManager;
@@ -27,6 +34,7 @@ pub struct ResourceContext {
     pub instance_manager: InstanceManager,
     pub bind_group_manager: BindGroupManager,
     pub shader_manager: ShaderManager,
+    pub animation_manager: AnimationManager,
 }
 pub trait VertexData {
     fn vertices(&self) -> Vec<crate::graphics::vertex::VertexType>;
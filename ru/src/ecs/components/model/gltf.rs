@@ -0,0 +1,762 @@
+use std::{collections::HashMap, sync::Arc};
+
+use base64::Engine;
+use cgmath::{Matrix4, Quaternion, SquareMatrix, Vector3};
+
+use crate::{
+    core::{
+        cache::{CacheKey, HasCacheKey},
+        error::AppError,
+        files::FileSystem,
+    },
+    ecs::{
+        components::{
+            animation::{
+                clip::{AnimationChannel, AnimationClip, Interpolation, Keyframes},
+                player::AnimationPlayer,
+                skeleton::{Skeleton, MAX_JOINTS},
+            },
+            hierarchy::Hierarchy,
+            instance::model::Instance,
+            material::model::{create_bind_group_for_material, Material, ShadingModel},
+            mesh::{
+                import::{compute_bounds, ImportedMesh},
+                manager::create_cached_mesh_with_buffers,
+                processing::{
+                    generate_tangents, sanitize_indices, MeshData, MeshProcessingOptions,
+                },
+                simplify::generate_lods,
+            },
+            transform::Transform,
+            ResourceContext,
+        },
+        entity::Entity,
+        traits::Cache,
+        world::World,
+    },
+    graphics::{
+        textures::{
+            sampler::{SamplerConfig, TextureOptions},
+            Texture,
+        },
+        vertex::{ModelVertex, VertexSkin},
+    },
+    log_info, log_warning,
+};
+
+use super::model::Model;
+
+#[derive(Debug, Clone)]
+pub struct GltfScene {
+    pub model: Model,
+    pub entities: Vec<Entity>,
+    pub roots: Vec<Entity>,
+    pub clips: Vec<CacheKey>,
+}
+
+pub fn is_gltf_file(file_name: &str) -> bool {
+    let lower = file_name.to_ascii_lowercase();
+    lower.ends_with(".gltf") || lower.ends_with(".glb")
+}
+
+fn base_dir(file_name: &str) -> String {
+    match file_name.rsplit_once('/') {
+        Some((dir, _)) => format!("{}/", dir),
+        None => String::new(),
+    }
+}
+
+/// Percent-decodes a relative glTF URI. Malformed escapes are kept as written.
+pub fn decode_uri(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match (bytes[i], bytes.get(i + 1..i + 3)) {
+            (b'%', Some(hex)) => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn read_uri(base: &str, uri: &str) -> Result<Vec<u8>, AppError> {
+    if let Some(rest) = uri.strip_prefix("data:") {
+        let (_, data) = rest.split_once(";base64,").ok_or_else(|| {
+            AppError::ResourceCreationFailed(format!("Unsupported data uri in glTF: {}", uri))
+        })?;
+        return base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| AppError::ResourceCreationFailed(e.to_string()));
+    }
+    FileSystem::load_binary(&format!("{}{}", base, decode_uri(uri)))
+}
+
+pub fn load_buffers(document: &::gltf::Gltf, file_name: &str) -> Result<Vec<Vec<u8>>, AppError> {
+    let base = base_dir(file_name);
+    let mut buffers = Vec::new();
+    for buffer in document.buffers() {
+        let mut data = match buffer.source() {
+            ::gltf::buffer::Source::Bin => document.blob.clone().ok_or_else(|| {
+                AppError::ResourceCreationFailed(format!("{} has no binary chunk", file_name))
+            })?,
+            ::gltf::buffer::Source::Uri(uri) => read_uri(&base, uri)?,
+        };
+        if data.len() < buffer.length() {
+            return Err(AppError::ResourceCreationFailed(format!(
+                "Buffer {} of {} is truncated",
+                buffer.index(),
+                file_name
+            )));
+        }
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+        buffers.push(data);
+    }
+    Ok(buffers)
+}
+
+pub fn read_primitive(
+    primitive: &::gltf::Primitive,
+    buffers: &[Vec<u8>],
+    name: String,
+) -> Result<ImportedMesh, AppError> {
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|b| b.as_slice()));
+
+    let positions: Vec<[f32; 3]> = reader
+        .read_positions()
+        .ok_or_else(|| {
+            AppError::ResourceCreationFailed(format!("Primitive {} has no positions", name))
+        })?
+        .collect();
+    let normals: Option<Vec<[f32; 3]>> = reader.read_normals().map(|n| n.collect());
+    let tex_coords: Option<Vec<[f32; 2]>> =
+        reader.read_tex_coords(0).map(|t| t.into_f32().collect());
+    let tangents: Option<Vec<[f32; 4]>> = reader.read_tangents().map(|t| t.collect());
+    let indices: Vec<u32> = reader
+        .read_indices()
+        .map(|i| i.into_u32().collect())
+        .unwrap_or_default();
+    let joints: Option<Vec<[u16; 4]>> = reader.read_joints(0).map(|j| j.into_u16().collect());
+    let weights: Option<Vec<[f32; 4]>> = reader.read_weights(0).map(|w| w.into_f32().collect());
+    let skin: Vec<VertexSkin> = match (joints, weights) {
+        (Some(joints), Some(weights))
+            if joints.len() == positions.len() && weights.len() == positions.len() =>
+        {
+            joints
+                .iter()
+                .zip(weights.iter())
+                .map(|(joints, weights)| {
+                    VertexSkin {
+                        joints: joints.map(u32::from),
+                        weights: *weights,
+                    }
+                    .normalized()
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    };
+
+    let vertices: Vec<ModelVertex> = positions
+        .iter()
+        .enumerate()
+        .map(|(i, position)| {
+            let normal = normals
+                .as_ref()
+                .and_then(|n| n.get(i).copied())
+                .unwrap_or_default();
+            let (tangent, bitangent) = match tangents.as_ref().and_then(|t| t.get(i)) {
+                Some(t) => {
+                    let n = Vector3::from(normal);
+                    let tangent = Vector3::new(t[0], t[1], t[2]);
+                    (tangent.into(), (n.cross(tangent) * t[3]).into())
+                }
+                None => ([0.0; 3], [0.0; 3]),
+            };
+            ModelVertex {
+                position: *position,
+                tex_coords: tex_coords
+                    .as_ref()
+                    .and_then(|t| t.get(i).copied())
+                    .unwrap_or_default(),
+                normal,
+                tangent,
+                bitangent,
+            }
+        })
+        .collect();
+
+    let data = MeshData {
+        indices: sanitize_indices(&indices, vertices.len()),
+        has_normals: normals.is_some_and(|n| n.len() == vertices.len()),
+        has_tex_coords: tex_coords.is_some_and(|t| t.len() == vertices.len()),
+        vertices,
+    }
+    .process(&MeshProcessingOptions {
+        weld: false,
+        generate_tangents: false,
+        ..Default::default()
+    });
+    let (mut vertices, mut indices) = (data.vertices, data.indices);
+    let sources: Vec<u32> = if tangents.is_none() {
+        generate_tangents(&mut vertices, &mut indices)
+    } else {
+        (0..vertices.len() as u32).collect()
+    };
+    let skin = if skin.is_empty() {
+        skin
+    } else {
+        sources.iter().map(|&i| skin[i as usize]).collect()
+    };
+    let (bounds_min, bounds_max) = compute_bounds(&vertices);
+
+    Ok(ImportedMesh {
+        name,
+        material_id: primitive.material().index(),
+        bounds_min,
+        bounds_max,
+        lods: generate_lods(&vertices, &indices),
+        vertices,
+        skin,
+        indices,
+    })
+}
+
+fn sampler_config(sampler: &::gltf::texture::Sampler) -> SamplerConfig {
+    use ::gltf::texture::{MagFilter, MinFilter, WrappingMode};
+
+    let address_mode = match sampler.wrap_s() {
+        WrappingMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => wgpu::AddressMode::MirrorRepeat,
+        WrappingMode::Repeat => wgpu::AddressMode::Repeat,
+    };
+    let mut config = SamplerConfig::default().with_address_mode(address_mode);
+    if let Some(MagFilter::Nearest) = sampler.mag_filter() {
+        config.mag_filter = wgpu::FilterMode::Nearest;
+    }
+    match sampler.min_filter() {
+        Some(MinFilter::Nearest) | Some(MinFilter::NearestMipmapNearest) => {
+            config.min_filter = wgpu::FilterMode::Nearest;
+            config.mipmap_filter = wgpu::FilterMode::Nearest;
+        }
+        Some(MinFilter::LinearMipmapNearest) => {
+            config.mipmap_filter = wgpu::FilterMode::Nearest;
+        }
+        Some(MinFilter::NearestMipmapLinear) => {
+            config.min_filter = wgpu::FilterMode::Nearest;
+        }
+        _ => {}
+    }
+    config
+}
+
+fn load_gltf_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    file_name: &str,
+    texture: ::gltf::texture::Texture,
+    buffers: &[Vec<u8>],
+    linear: bool,
+    resources: &mut ResourceContext,
+) -> Result<CacheKey, AppError> {
+    let image = texture.source();
+    let (label, bytes) = match image.source() {
+        ::gltf::image::Source::View { view, .. } => {
+            let buffer = buffers.get(view.buffer().index()).ok_or_else(|| {
+                AppError::ResourceCreationFailed(format!(
+                    "Missing buffer for image in {}",
+                    file_name
+                ))
+            })?;
+            let start = view.offset();
+            let end = start + view.length();
+            (
+                format!("{}#image{}", file_name, image.index()),
+                buffer[start..end].to_vec(),
+            )
+        }
+        ::gltf::image::Source::Uri { uri, .. } => {
+            let base = base_dir(file_name);
+            if uri.starts_with("data:") {
+                (
+                    format!("{}#image{}", file_name, image.index()),
+                    read_uri(&base, uri)?,
+                )
+            } else {
+                (format!("{}{}", base, uri), read_uri(&base, uri)?)
+            }
+        }
+    };
+
+    let key =
+        CacheKey::from(format!("{}#{}", label, if linear { "linear" } else { "srgb" }).as_str());
+    if resources.texture_manager.textures.contains(&key) {
+        return Ok(key);
+    }
+
+    let mut options = resources.texture_manager.options_for(&label, linear);
+    if !resources
+        .texture_manager
+        .options
+        .contains_key(&CacheKey::from(label.as_str()))
+    {
+        options = TextureOptions {
+            sampler: sampler_config(&texture.sampler()),
+            ..options
+        };
+    }
+    let texture = Texture::from_bytes_with_options(
+        device,
+        queue,
+        &bytes,
+        &label,
+        &options,
+        &mut resources.texture_manager.mipmaps,
+    )?;
+    resources
+        .texture_manager
+        .textures
+        .put(key, Arc::new(texture));
+    Ok(key)
+}
+
+fn build_material(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    file_name: &str,
+    material: ::gltf::Material,
+    buffers: &[Vec<u8>],
+    resources: &mut ResourceContext,
+) -> Result<Material, AppError> {
+    let pbr = material.pbr_metallic_roughness();
+    let index = material
+        .index()
+        .map(|i| i.to_string())
+        .unwrap_or_else(|| "default".to_string());
+    let name = material
+        .name()
+        .map(String::from)
+        .unwrap_or_else(|| format!("material{}", index));
+    let cache_key = Material::key(vec![file_name, &index]);
+
+    let mut load = |texture: Option<::gltf::texture::Texture>, linear: bool| match texture {
+        Some(texture) => {
+            match load_gltf_texture(
+                device, queue, file_name, texture, buffers, linear, resources,
+            ) {
+                Ok(key) => Some(key),
+                Err(e) => {
+                    log_warning!("Failed to load texture for {}: {}", name, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+    let base_color_texture_key = load(pbr.base_color_texture().map(|t| t.texture()), false);
+    let normal_texture_key = load(material.normal_texture().map(|t| t.texture()), true);
+    let metallic_roughness_texture_key =
+        load(pbr.metallic_roughness_texture().map(|t| t.texture()), true);
+    let occlusion_texture_key = load(material.occlusion_texture().map(|t| t.texture()), true);
+    let emissive_texture_key = load(material.emissive_texture().map(|t| t.texture()), false);
+
+    let base_color = pbr.base_color_factor();
+    let mut result = Material::new(
+        name,
+        [base_color[0], base_color[1], base_color[2]],
+        [base_color[0], base_color[1], base_color[2]],
+        [0.04, 0.04, 0.04],
+        0.0,
+        base_color[3],
+        1.5,
+        None,
+        None,
+        base_color_texture_key,
+        None,
+        normal_texture_key,
+        None,
+        None,
+        cache_key,
+    );
+    result.base_color_factor = base_color;
+    result.metallic_factor = pbr.metallic_factor();
+    result.roughness_factor = pbr.roughness_factor();
+    result.emissive_factor = material.emissive_factor();
+    result.metallic_roughness_texture_key = metallic_roughness_texture_key;
+    result.occlusion_texture_key = occlusion_texture_key;
+    result.emissive_texture_key = emissive_texture_key;
+    result.occlusion_strength = material
+        .occlusion_texture()
+        .map(|t| t.strength())
+        .unwrap_or(1.0);
+    result.normal_scale = material.normal_texture().map(|t| t.scale()).unwrap_or(1.0);
+    result.shading_model = ShadingModel::MetallicRoughness;
+
+    let bind_group = create_bind_group_for_material(
+        device,
+        queue,
+        &result,
+        &mut resources.texture_manager,
+        &mut resources.buffer_manager,
+        &resources.bind_group_manager.bind_group_layouts,
+    )?;
+    resources
+        .bind_group_manager
+        .bind_groups
+        .put(cache_key, bind_group);
+
+    Ok(result)
+}
+
+fn node_transform(node: &::gltf::Node) -> Transform {
+    let (translation, rotation, scale) = node.transform().decomposed();
+    Transform {
+        position: Vector3::from(translation),
+        rotation: Quaternion::new(rotation[3], rotation[0], rotation[1], rotation[2]),
+        scale: Vector3::from(scale),
+    }
+}
+
+pub async fn load_gltf(
+    file_name: &str,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    resources: &mut ResourceContext,
+    world: &mut World,
+) -> Result<GltfScene, AppError> {
+    let data = FileSystem::load_binary(file_name)?;
+    let document = ::gltf::Gltf::from_slice(&data)?;
+    let buffers = load_buffers(&document, file_name)?;
+
+    let mut material_ids = Vec::new();
+    for material in document.materials() {
+        let material = build_material(device, queue, file_name, material, &buffers, resources)?;
+        material_ids.push(material.cache_key);
+        resources
+            .material_manager
+            .materials
+            .put(material.cache_key, material);
+    }
+
+    let mut mesh_primitives: Vec<Vec<CacheKey>> = Vec::new();
+    for mesh in document.meshes() {
+        let mut mesh_ids = Vec::new();
+        for primitive in mesh.primitives() {
+            if primitive.mode() != ::gltf::mesh::Mode::Triangles {
+                log_warning!(
+                    "Skipping non-triangle primitive {} of mesh {} in {}",
+                    primitive.index(),
+                    mesh.index(),
+                    file_name
+                );
+                continue;
+            }
+            let name = format!(
+                "{}:{}:{}",
+                file_name,
+                mesh.name()
+                    .map(String::from)
+                    .unwrap_or_else(|| mesh.index().to_string()),
+                primitive.index()
+            );
+            let imported = read_primitive(&primitive, &buffers, name)?;
+            let cache_id = create_cached_mesh_with_buffers(
+                device,
+                &mut resources.mesh_manager.meshes,
+                &mut resources.buffer_manager.buffers,
+                imported.vertex_types(),
+                imported.indices,
+                imported.material_id,
+                imported.name,
+                imported.lods,
+            )?;
+            mesh_ids.push(cache_id);
+        }
+        mesh_primitives.push(mesh_ids);
+    }
+
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| AppError::ResourceCreationFailed(format!("{} has no scene", file_name)))?;
+
+    let mut nodes: Vec<(Entity, Hierarchy, Transform, Option<usize>)> = Vec::new();
+    let mut node_entities: HashMap<usize, Entity> = HashMap::new();
+    let mut roots = Vec::new();
+    let mut stack: Vec<(::gltf::Node, Option<usize>, Transform)> = scene
+        .nodes()
+        .map(|node| (node, None, Transform::identity()))
+        .collect();
+    while let Some((node, parent, parent_world)) = stack.pop() {
+        let entity = world.create_entity();
+        node_entities.insert(node.index(), entity);
+        let local = node_transform(&node);
+        let world_transform = parent_world.compose(&local);
+
+        match parent {
+            Some(parent) => nodes[parent].1.children.push(entity),
+            None => roots.push(entity),
+        }
+        let hierarchy = Hierarchy {
+            name: node.name().map(String::from),
+            parent: parent.map(|p| nodes[p].0),
+            children: Vec::new(),
+            local,
+        };
+        nodes.push((
+            entity,
+            hierarchy,
+            world_transform,
+            node.mesh().map(|m| m.index()),
+        ));
+
+        let index = nodes.len() - 1;
+        for child in node.children() {
+            stack.push((child, Some(index), world_transform));
+        }
+    }
+
+    let rest_pose: HashMap<Entity, Transform> = nodes
+        .iter()
+        .map(|(entity, hierarchy, _, _)| (*entity, hierarchy.local))
+        .collect();
+    let mut skeletons = Vec::new();
+    let mut clips = Vec::new();
+    for node in document.nodes() {
+        let (Some(skin), Some(&entity)) = (node.skin(), node_entities.get(&node.index())) else {
+            continue;
+        };
+        match build_skeleton(
+            file_name,
+            &document,
+            &skin,
+            &buffers,
+            &node_entities,
+            &rest_pose,
+            resources,
+        ) {
+            Some(skeleton) => {
+                clips.extend(skeleton.clips.iter().copied());
+                skeletons.push((entity, skeleton));
+            }
+            None => {
+                log_warning!(
+                    "Skipping skin {} of {}: joints are outside the loaded scene",
+                    skin.index(),
+                    file_name
+                );
+            }
+        }
+    }
+    for (entity, skeleton) in skeletons {
+        let player = match skeleton.clips.first() {
+            Some(clip) => AnimationPlayer::playing(*clip),
+            None => AnimationPlayer::new(),
+        };
+        world.add_component(entity, skeleton)?;
+        world.add_component(entity, player)?;
+    }
+
+    let mut entities = Vec::with_capacity(nodes.len());
+    for (entity, hierarchy, world_transform, mesh) in nodes {
+        world.add_component(entity, hierarchy)?;
+        world.add_component(entity, world_transform)?;
+        if let Some(mesh) = mesh {
+            let model = Model::new(
+                mesh_primitives[mesh].clone(),
+                material_ids.clone(),
+                &resources.mesh_manager.meshes,
+            );
+            world.add_component(entity, model)?;
+            resources.instance_manager.add_instance(
+                &entity,
+                Instance {
+                    transform: world_transform,
+                },
+            )?;
+        }
+        entities.push(entity);
+    }
+
+    let model = Model::new(
+        mesh_primitives.into_iter().flatten().collect(),
+        material_ids,
+        &resources.mesh_manager.meshes,
+    );
+    resources
+        .model_manager
+        .models
+        .put(Model::key(vec![file_name]), model.clone());
+    log_info!(
+        "Loaded {} with {} meshes and {} nodes",
+        file_name,
+        model.mesh_ids.len(),
+        entities.len()
+    );
+
+    Ok(GltfScene {
+        model,
+        entities,
+        roots,
+        clips,
+    })
+}
+
+fn build_skeleton(
+    file_name: &str,
+    document: &::gltf::Gltf,
+    skin: &::gltf::Skin,
+    buffers: &[Vec<u8>],
+    node_entities: &HashMap<usize, Entity>,
+    rest_pose: &HashMap<Entity, Transform>,
+    resources: &mut ResourceContext,
+) -> Option<Skeleton> {
+    let joints: Vec<Entity> = skin
+        .joints()
+        .map(|joint| node_entities.get(&joint.index()).copied())
+        .collect::<Option<Vec<_>>>()?;
+    let joint_lookup: HashMap<usize, usize> = skin
+        .joints()
+        .enumerate()
+        .map(|(index, joint)| (joint.index(), index))
+        .collect();
+    if joints.len() > MAX_JOINTS {
+        log_warning!(
+            "Skin {} of {} has {} joints, only the first {} are skinned",
+            skin.index(),
+            file_name,
+            joints.len(),
+            MAX_JOINTS
+        );
+    }
+
+    let reader = skin.reader(|buffer| buffers.get(buffer.index()).map(|b| b.as_slice()));
+    let inverse_bind_matrices = match reader.read_inverse_bind_matrices() {
+        Some(matrices) => matrices.map(Matrix4::from).collect(),
+        None => vec![Matrix4::identity(); joints.len()],
+    };
+    let rest = joints
+        .iter()
+        .map(|joint| {
+            rest_pose
+                .get(joint)
+                .copied()
+                .unwrap_or_else(Transform::identity)
+        })
+        .collect();
+    let mut skeleton = Skeleton::new(joints, inverse_bind_matrices, rest);
+
+    for animation in document.animations() {
+        let channels = read_animation_channels(&animation, buffers, &joint_lookup);
+        if channels.is_empty() {
+            continue;
+        }
+        let name = animation
+            .name()
+            .map(String::from)
+            .unwrap_or_else(|| format!("animation{}", animation.index()));
+        let key = AnimationClip::key(vec![file_name, &skin.index().to_string(), &name]);
+        resources
+            .animation_manager
+            .clips
+            .put(key, AnimationClip::new(name, channels));
+        skeleton.clips.push(key);
+    }
+    Some(skeleton)
+}
+
+fn read_animation_channels(
+    animation: &::gltf::Animation,
+    buffers: &[Vec<u8>],
+    joint_lookup: &HashMap<usize, usize>,
+) -> Vec<AnimationChannel> {
+    use ::gltf::animation::util::ReadOutputs;
+
+    let mut channels = Vec::new();
+    for channel in animation.channels() {
+        let Some(&target) = joint_lookup.get(&channel.target().node().index()) else {
+            continue;
+        };
+        let reader = channel.reader(|buffer| buffers.get(buffer.index()).map(|b| b.as_slice()));
+        let Some(times) = reader.read_inputs().map(|t| t.collect::<Vec<f32>>()) else {
+            continue;
+        };
+        let interpolation = match channel.sampler().interpolation() {
+            ::gltf::animation::Interpolation::Step => Interpolation::Step,
+            ::gltf::animation::Interpolation::Linear => Interpolation::Linear,
+            ::gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+        };
+        let keyframes = match reader.read_outputs() {
+            Some(ReadOutputs::Translations(values)) => {
+                Keyframes::Translation(values.map(Vector3::from).collect())
+            }
+            Some(ReadOutputs::Rotations(values)) => Keyframes::Rotation(
+                values
+                    .into_f32()
+                    .map(|q| Quaternion::new(q[3], q[0], q[1], q[2]))
+                    .collect(),
+            ),
+            Some(ReadOutputs::Scales(values)) => {
+                Keyframes::Scale(values.map(Vector3::from).collect())
+            }
+            _ => continue,
+        };
+        channels.push(AnimationChannel {
+            target,
+            interpolation,
+            times,
+            keyframes,
+        });
+    }
+    channels
+}
+
+pub fn gltf_dependencies(file_name: &str, data: &[u8]) -> Result<Vec<String>, AppError> {
+    let document = ::gltf::Gltf::from_slice(data)?;
+    let base = base_dir(file_name);
+    let mut dependencies = Vec::new();
+    for buffer in document.buffers() {
+        if let ::gltf::buffer::Source::Uri(uri) = buffer.source() {
+            if !uri.starts_with("data:") {
+                dependencies.push(format!("{}{}", base, decode_uri(uri)));
+            }
+        }
+    }
+    for image in document.images() {
+        if let ::gltf::image::Source::Uri { uri, .. } = image.source() {
+            if !uri.starts_with("data:") {
+                dependencies.push(format!("{}{}", base, decode_uri(uri)));
+            }
+        }
+    }
+    Ok(dependencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_uri_handles_escapes_and_utf8() {
+        assert_eq!(decode_uri("my%20mesh.bin"), "my mesh.bin");
+        assert_eq!(decode_uri("a%2Bb%23c.png"), "a+b#c.png");
+        assert_eq!(decode_uri("caf%C3%A9.png"), "café.png");
+        assert_eq!(decode_uri("plain/path.bin"), "plain/path.bin");
+        assert_eq!(decode_uri("100%.png"), "100%.png");
+        assert_eq!(decode_uri("bad%zzname%2"), "bad%zzname%2");
+    }
+}
@@ -1,14 +1,23 @@
-use crate::{core::cache::HashCache, core::error::AppError, ecs::components::ResourceContext};
+use crate::{
+    core::cache::HashCache,
+    core::error::AppError,
+    ecs::{components::ResourceContext, world::World},
+};
 
-use super::model::{load_model, Model};
+use super::{
+    gltf::{load_gltf, GltfScene},
+    model::{load_model, reload_model, Model},
+};
 
 pub struct ModelManager {
     pub models: HashCache<Model>,
+    pub files: Vec<String>,
 }
 impl ModelManager {
     pub fn new() -> Self {
         Self {
             models: HashCache::new(),
+            files: Vec::new(),
         }
     }
     pub async fn load_model_from_file(
@@ -19,4 +28,22 @@ impl ModelManager {
     ) -> Result<Model, AppError> {
         load_model(file_name, device, queue, resources).await
     }
+    pub async fn reload_model_from_file(
+        file_name: &str,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resources: &mut ResourceContext,
+    ) -> Result<(Option<Model>, Model), AppError> {
+        reload_model(file_name, device, queue, resources).await
+    }
+
+    pub async fn load_gltf_from_file(
+        file_name: &str,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        resources: &mut ResourceContext,
+        world: &mut World,
+    ) -> Result<GltfScene, AppError> {
+        load_gltf(file_name, device, queue, resources, world).await
+    }
 }
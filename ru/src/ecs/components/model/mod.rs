@@ -1,2 +1,3 @@
+pub mod gltf;
 pub mod manager;
 pub mod model;
@@ -1,28 +1,61 @@
 use crate::{
     core::{
-        cache::{CacheKey, HasCacheKey},
+        cache::{CacheKey, HasCacheKey, HashCache},
         error::AppError,
         files::FileSystem,
     },
     ecs::{
         components::{
-            material::model::create_material,
-            mesh::{manager::create_cached_mesh_with_buffers, model::Mesh},
+            bounds::Bounds,
+            material::model::{create_material, Material},
+            mesh::{
+                import::{import_model, ImportedMesh, MaterialLib},
+                manager::{create_cached_mesh_with_buffers, MeshManager},
+                model::Mesh,
+            },
             ResourceContext,
         },
         traits::Cache,
     },
-    log_error,
+    log_error, log_warning,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{BufReader, Cursor},
 };
-use std::io::{BufReader, Cursor};
 
 #[derive(Debug, Clone)]
 pub struct Model {
     pub mesh_ids: Vec<CacheKey>,
     pub material_ids: Vec<CacheKey>,
+    pub bounds: Bounds,
 }
 impl Model {
     pub const LABEL: &'static str = "component:model";
+
+    pub fn new(
+        mesh_ids: Vec<CacheKey>,
+        material_ids: Vec<CacheKey>,
+        meshes: &HashCache<Mesh>,
+    ) -> Model {
+        let bounds = mesh_ids
+            .iter()
+            .filter_map(|id| meshes.get(id))
+            .map(|mesh| mesh.bounds)
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_default();
+        Model {
+            mesh_ids,
+            material_ids,
+            bounds,
+        }
+    }
+
+    pub fn material_key(&self, mesh: &Mesh) -> Option<CacheKey> {
+        mesh.material
+            .and_then(|index| self.material_ids.get(index).copied())
+    }
 }
 impl HasCacheKey for Model {
     fn key(suffixes: Vec<&str>) -> CacheKey {
@@ -38,6 +71,7 @@ impl HasCacheKey for Model {
 pub struct ModelRaw {
     pub models: Vec<tobj::Model>,
     pub materials: Vec<tobj::Material>,
+    pub material_libs: Vec<MaterialLib>,
 }
 impl ModelRaw {
     pub const LABEL: &'static str = "component:model_raw";
@@ -45,8 +79,15 @@ impl ModelRaw {
 
 pub async fn load_model_raw(file_name: &str) -> Result<ModelRaw, AppError> {
     let obj_text = load_obj_file_string(file_name).await?;
+    load_model_raw_from_string(obj_text).await
+}
+
+pub async fn load_model_raw_from_string(obj_text: String) -> Result<ModelRaw, AppError> {
     let mut obj_reader = read_object(obj_text).await?;
 
+    // Libraries are recorded in the order tobj appends them so material ids can be mapped
+    // back to their library; an unreadable one contributes no materials instead of failing.
+    let material_libs = RefCell::new(Vec::new());
     let (models, obj_materials) = tobj::load_obj_buf_async(
         &mut obj_reader,
         &tobj::LoadOptions {
@@ -54,19 +95,42 @@ pub async fn load_model_raw(file_name: &str) -> Result<ModelRaw, AppError> {
             single_index: true,
             ..Default::default()
         },
-        |p| async move {
-            let mat_text = FileSystem::load_string(&p).expect("Failed to load MTL file");
-            tobj::load_mtl_buf(&mut BufReader::new(Cursor::new(mat_text)))
+        |p| {
+            let loaded = load_material_lib(&p).unwrap_or_default();
+            material_libs.borrow_mut().push(MaterialLib {
+                name: p,
+                material_count: loaded.0.len() as u32,
+            });
+            async move { Ok(loaded) }
         },
     )
     .await?;
 
     Ok(ModelRaw {
         models,
-        materials: obj_materials?,
+        materials: obj_materials.unwrap_or_default(),
+        material_libs: material_libs.into_inner(),
     })
 }
 
+/// Loads and parses one MTL library, logging and returning `None` when it can't be used.
+pub fn load_material_lib(file_name: &str) -> Option<(Vec<tobj::Material>, HashMap<String, usize>)> {
+    let text = match FileSystem::load_string(file_name) {
+        Ok(text) => text,
+        Err(e) => {
+            log_warning!("Failed to load material library {}: {}", file_name, e);
+            return None;
+        }
+    };
+    match tobj::load_mtl_buf(&mut BufReader::new(Cursor::new(text))) {
+        Ok(materials) => Some(materials),
+        Err(e) => {
+            log_warning!("Failed to parse material library {}: {}", file_name, e);
+            None
+        }
+    }
+}
+
 pub async fn load_obj_file_string(file_name: &str) -> Result<String, AppError> {
     let result = FileSystem::load_string(file_name);
     match result {
@@ -84,20 +148,28 @@ pub async fn read_object(obj_text: String) -> Result<BufReader<Cursor<String>>,
 async fn build_material_ids(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
-    obj_materials: Vec<tobj::Material>,
+    file_name: &str,
+    obj_materials: Vec<Option<tobj::Material>>,
     resources: &mut ResourceContext,
 ) -> Result<Vec<CacheKey>, AppError> {
     let mut material_ids = Vec::new();
 
     let texture_manager = &mut resources.texture_manager;
+    let buffer_manager = &mut resources.buffer_manager;
     let bind_group_manager = &mut resources.bind_group_manager;
 
     for obj_material in obj_materials {
+        let Some(obj_material) = obj_material else {
+            material_ids.push(Material::default_key());
+            continue;
+        };
         let material = create_material(
             device,
             queue,
+            file_name,
             obj_material,
             texture_manager,
+            buffer_manager,
             bind_group_manager,
         )
         .await?;
@@ -116,28 +188,20 @@ async fn build_material_ids(
 fn build_mesh_ids(
     device: &wgpu::Device,
     resources: &mut ResourceContext,
-    chunk_size: usize,
-    models: Vec<tobj::Model>,
+    meshes: Vec<ImportedMesh>,
 ) -> Result<Vec<CacheKey>, AppError> {
     let mut mesh_ids = Vec::new();
 
-    for model in models {
-        let mut vertices = Mesh::generate_vertices(
-            &model.mesh.positions,
-            &model.mesh.texcoords,
-            &model.mesh.normals,
-            chunk_size,
-        );
-        Mesh::generate_tangent_space(&mut vertices, &model.mesh.indices, chunk_size);
-
+    for mesh in meshes {
         match create_cached_mesh_with_buffers(
             device,
             &mut resources.mesh_manager.meshes,
             &mut resources.buffer_manager.buffers,
-            vertices,
-            model.mesh.indices,
-            model.mesh.material_id,
-            model.name,
+            mesh.vertex_types(),
+            mesh.indices,
+            mesh.material_id,
+            mesh.name,
+            mesh.lods,
         ) {
             Ok(cache_id) => mesh_ids.push(cache_id),
             Err(e) => {
@@ -149,11 +213,12 @@ fn build_mesh_ids(
     Ok(mesh_ids)
 }
 
-pub fn assemble_model(mesh_ids: Vec<CacheKey>, material_ids: Vec<CacheKey>) -> Model {
-    Model {
-        mesh_ids,
-        material_ids,
-    }
+pub fn assemble_model(
+    mesh_ids: Vec<CacheKey>,
+    material_ids: Vec<CacheKey>,
+    mesh_manager: &MeshManager,
+) -> Model {
+    Model::new(mesh_ids, material_ids, &mesh_manager.meshes)
 }
 
 pub async fn load_model(
@@ -162,9 +227,55 @@ pub async fn load_model(
     queue: &wgpu::Queue,
     resources: &mut ResourceContext,
 ) -> Result<Model, AppError> {
-    let chunk_size: usize = 3;
-    let raw_model = load_model_raw(file_name).await?;
-    let material_ids = build_material_ids(device, queue, raw_model.materials, resources).await?;
-    let mesh_ids = build_mesh_ids(device, resources, chunk_size, raw_model.models)?;
-    Ok(assemble_model(mesh_ids, material_ids))
+    let imported = import_model(file_name).await?;
+    let materials = imported.load_materials();
+    let material_ids = build_material_ids(device, queue, file_name, materials, resources).await?;
+    let mesh_ids = build_mesh_ids(device, resources, imported.meshes)?;
+    let model = assemble_model(mesh_ids, material_ids, &resources.mesh_manager);
+    resources
+        .model_manager
+        .models
+        .put(Model::key(vec![file_name]), model.clone());
+    if !resources.model_manager.files.iter().any(|f| f == file_name) {
+        resources.model_manager.files.push(file_name.to_string());
+    }
+    Ok(model)
+}
+
+pub async fn reload_model(
+    file_name: &str,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    resources: &mut ResourceContext,
+) -> Result<(Option<Model>, Model), AppError> {
+    let imported = import_model(file_name).await?;
+    let materials = imported.load_materials();
+    let previous = resources
+        .model_manager
+        .models
+        .get(&Model::key(vec![file_name]))
+        .cloned();
+
+    if let Some(previous) = &previous {
+        for mesh_id in &previous.mesh_ids {
+            if let Some(mesh) = resources.mesh_manager.meshes.get(mesh_id) {
+                let (vertex_buffer_key, index_buffer_keys) =
+                    (mesh.vertex_buffer_key, mesh.index_buffer_keys());
+                resources.buffer_manager.buffers.remove(&vertex_buffer_key);
+                for index_buffer_key in &index_buffer_keys {
+                    resources.buffer_manager.buffers.remove(index_buffer_key);
+                }
+            }
+            resources.mesh_manager.meshes.remove(mesh_id);
+        }
+    }
+
+    let material_ids = build_material_ids(device, queue, file_name, materials, resources).await?;
+    let mesh_ids = build_mesh_ids(device, resources, imported.meshes)?;
+    let model = assemble_model(mesh_ids, material_ids, &resources.mesh_manager);
+    resources
+        .model_manager
+        .models
+        .put(Model::key(vec![file_name]), model.clone());
+    Ok((previous, model))
 }
@@ -3,7 +3,7 @@ use cgmath::{Matrix4, Quaternion, Vector3};
 use crate::core::cache::{CacheKey, HasCacheKey};
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Transform {
     pub position: Vector3<f32>,
     pub rotation: Quaternion<f32>,
@@ -24,6 +24,32 @@ impl HasCacheKey for Transform {
     }
 }
 impl Transform {
+    pub fn identity() -> Self {
+        use cgmath::One;
+        Self {
+            position: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn compose(&self, child: &Transform) -> Transform {
+        let scaled = Vector3::new(
+            child.position.x * self.scale.x,
+            child.position.y * self.scale.y,
+            child.position.z * self.scale.z,
+        );
+        Transform {
+            position: self.position + self.rotation * scaled,
+            rotation: self.rotation * child.rotation,
+            scale: Vector3::new(
+                self.scale.x * child.scale.x,
+                self.scale.y * child.scale.y,
+                self.scale.z * child.scale.z,
+            ),
+        }
+    }
+
     pub fn to_model_matrix(&self) -> Matrix4<f32> {
         let translation = Matrix4::from_translation(Vector3::new(
             self.position[0],
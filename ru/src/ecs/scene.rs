@@ -1,4 +1,40 @@
-use crate::ecs::entity::Entity;
+use std::collections::HashMap;
+
+use cgmath::{Deg, Euler, Quaternion, Vector3};
+use serde::Deserialize;
+
+use crate::{
+    core::{
+        cache::{CacheKey, HasCacheKey},
+        error::AppError,
+        files::FileSystem,
+        vfs::AssetDir,
+    },
+    ecs::{
+        components::{
+            animation::{
+                animator::Animator,
+                clip::{AnimationChannel, AnimationClip, Interpolation, Keyframes},
+                player::PlaybackMode,
+            },
+            camera::CameraTarget,
+            hierarchy::Hierarchy,
+            instance::{manager::InstanceManager, model::Instance},
+            light::Light,
+            lod::Lod,
+            model::{gltf::is_gltf_file, manager::ModelManager, model::Model},
+            transform::Transform,
+            ResourceContext,
+        },
+        entity::Entity,
+        systems::animation::world_transforms,
+        traits::Cache,
+        world::World,
+    },
+    graphics::geometry::GeometryId,
+    log_info,
+    utilities::helpers::content_hash,
+};
 
 #[derive(Debug)]
 pub struct Scene {
@@ -24,3 +60,690 @@ impl Scene {
         &self.entities
     }
 }
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SceneDescription {
+    pub name: String,
+    #[serde(default)]
+    pub animations: Vec<AnimationClipDescription>,
+    #[serde(default)]
+    pub entities: Vec<SceneEntityDescription>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SceneEntityDescription {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub geometry: Option<GeometryId>,
+    #[serde(default)]
+    pub instances: Vec<InstanceDescription>,
+    #[serde(default)]
+    pub light: Option<LightDescription>,
+    #[serde(default)]
+    pub camera: bool,
+    #[serde(default)]
+    pub animator: Option<AnimatorDescription>,
+    /// Screen-size thresholds for LOD switching; without them only LOD0 is drawn.
+    #[serde(default)]
+    pub lod: Option<Vec<f32>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LightKindDescription {
+    Point,
+    Spot,
+    Directional,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct LightDescription {
+    pub kind: LightKindDescription,
+    #[serde(default = "LightDescription::default_color")]
+    pub color: [f32; 3],
+    #[serde(default = "LightDescription::default_intensity")]
+    pub intensity: f32,
+    #[serde(default = "LightDescription::default_range")]
+    pub range: f32,
+    #[serde(default = "LightDescription::default_inner_angle")]
+    pub inner_angle: f32,
+    #[serde(default = "LightDescription::default_outer_angle")]
+    pub outer_angle: f32,
+    #[serde(default)]
+    pub shadows: Option<bool>,
+    #[serde(default)]
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub rotation: [f32; 3],
+}
+
+impl LightDescription {
+    fn default_color() -> [f32; 3] {
+        [1.0; 3]
+    }
+
+    fn default_intensity() -> f32 {
+        1.0
+    }
+
+    fn default_range() -> f32 {
+        10.0
+    }
+
+    fn default_inner_angle() -> f32 {
+        20.0
+    }
+
+    fn default_outer_angle() -> f32 {
+        30.0
+    }
+
+    pub fn to_light(&self) -> Light {
+        let light = match self.kind {
+            LightKindDescription::Point => Light::point(self.color, self.intensity, self.range),
+            LightKindDescription::Spot => Light::spot(
+                self.color,
+                self.intensity,
+                self.range,
+                Deg(self.inner_angle),
+                Deg(self.outer_angle),
+            ),
+            LightKindDescription::Directional => Light::directional(self.color, self.intensity),
+        };
+        match self.shadows {
+            Some(shadows) => light.with_shadows(shadows),
+            None => light,
+        }
+    }
+
+    pub fn to_transform(&self) -> Transform {
+        InstanceDescription {
+            position: self.position,
+            rotation: self.rotation,
+            ..Default::default()
+        }
+        .to_transform()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct InstanceDescription {
+    #[serde(default)]
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    #[serde(default = "InstanceDescription::default_scale")]
+    pub scale: [f32; 3],
+}
+
+impl Default for InstanceDescription {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            rotation: [0.0; 3],
+            scale: Self::default_scale(),
+        }
+    }
+}
+
+impl InstanceDescription {
+    fn default_scale() -> [f32; 3] {
+        [1.0; 3]
+    }
+
+    pub fn to_transform(&self) -> Transform {
+        let [x, y, z] = self.rotation;
+        Transform {
+            position: Vector3::from(self.position),
+            rotation: Quaternion::from(Euler::new(Deg(x), Deg(y), Deg(z))),
+            scale: Vector3::from(self.scale),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelPropertyDescription {
+    Translation,
+    Rotation,
+    Scale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterpolationDescription {
+    Step,
+    #[default]
+    Linear,
+    Cubic,
+}
+
+impl From<InterpolationDescription> for Interpolation {
+    fn from(description: InterpolationDescription) -> Self {
+        match description {
+            InterpolationDescription::Step => Interpolation::Step,
+            InterpolationDescription::Linear => Interpolation::Linear,
+            InterpolationDescription::Cubic => Interpolation::CubicSpline,
+        }
+    }
+}
+
+/// One keyframed property. Translation and scale keys are `[x, y, z]`; rotation keys are
+/// either Euler degrees `[x, y, z]` or a quaternion `[x, y, z, w]`. Cubic channels list
+/// three values per key (in-tangent, value, out-tangent) and need quaternion rotations.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationChannelDescription {
+    pub property: ChannelPropertyDescription,
+    #[serde(default)]
+    pub interpolation: InterpolationDescription,
+    pub times: Vec<f32>,
+    pub values: Vec<Vec<f32>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationMarkerDescription {
+    pub time: f32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationClipDescription {
+    pub name: String,
+    #[serde(default)]
+    pub channels: Vec<AnimationChannelDescription>,
+    #[serde(default)]
+    pub markers: Vec<AnimationMarkerDescription>,
+}
+
+impl AnimationChannelDescription {
+    fn vector(&self, value: &[f32]) -> Result<Vector3<f32>, String> {
+        match value {
+            [x, y, z] => Ok(Vector3::new(*x, *y, *z)),
+            _ => Err(format!("{:?} keys need 3 components", self.property)),
+        }
+    }
+
+    fn rotation(&self, value: &[f32]) -> Result<Quaternion<f32>, String> {
+        match value {
+            [x, y, z] if self.interpolation != InterpolationDescription::Cubic => {
+                Ok(Quaternion::from(Euler::new(Deg(*x), Deg(*y), Deg(*z))))
+            }
+            [x, y, z, w] => Ok(Quaternion::new(*w, *x, *y, *z)),
+            _ => Err("rotation keys need 4 quaternion components, or 3 Euler degrees for step and linear channels".to_string()),
+        }
+    }
+
+    pub fn to_channel(&self) -> Result<AnimationChannel, String> {
+        let per_key = match self.interpolation {
+            InterpolationDescription::Cubic => 3,
+            _ => 1,
+        };
+        if self.times.is_empty() {
+            return Err("channel has no keyframes".to_string());
+        }
+        if self.times.windows(2).any(|pair| pair[1] < pair[0]) {
+            return Err("keyframe times must be ascending".to_string());
+        }
+        if self.values.len() != self.times.len() * per_key {
+            return Err(format!(
+                "{} keyframe times need {} values, found {}",
+                self.times.len(),
+                self.times.len() * per_key,
+                self.values.len()
+            ));
+        }
+        let keyframes = match self.property {
+            ChannelPropertyDescription::Translation => Keyframes::Translation(
+                self.values
+                    .iter()
+                    .map(|value| self.vector(value))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ChannelPropertyDescription::Scale => Keyframes::Scale(
+                self.values
+                    .iter()
+                    .map(|value| self.vector(value))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ChannelPropertyDescription::Rotation => Keyframes::Rotation(
+                self.values
+                    .iter()
+                    .map(|value| self.rotation(value))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        Ok(AnimationChannel {
+            target: 0,
+            interpolation: self.interpolation.into(),
+            times: self.times.clone(),
+            keyframes,
+        })
+    }
+}
+
+impl AnimationClipDescription {
+    pub fn to_clip(&self) -> Result<AnimationClip, AppError> {
+        let channels = self
+            .channels
+            .iter()
+            .map(AnimationChannelDescription::to_channel)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                AppError::ResourceCreationFailed(format!("Animation {}: {}", self.name, e))
+            })?;
+        Ok(self.markers.iter().fold(
+            AnimationClip::new(self.name.clone(), channels),
+            |clip, marker| clip.with_marker(marker.time, &marker.name),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackModeDescription {
+    Once,
+    #[default]
+    Loop,
+    PingPong,
+}
+
+impl From<PlaybackModeDescription> for PlaybackMode {
+    fn from(description: PlaybackModeDescription) -> Self {
+        match description {
+            PlaybackModeDescription::Once => PlaybackMode::Once,
+            PlaybackModeDescription::Loop => PlaybackMode::Loop,
+            PlaybackModeDescription::PingPong => PlaybackMode::PingPong,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimatorDescription {
+    pub clip: String,
+    #[serde(default)]
+    pub mode: PlaybackModeDescription,
+    #[serde(default = "AnimatorDescription::default_speed")]
+    pub speed: f32,
+}
+
+impl AnimatorDescription {
+    fn default_speed() -> f32 {
+        1.0
+    }
+
+    pub fn to_animator(&self, clip: CacheKey) -> Animator {
+        let mut animator = Animator::playing(clip, self.mode.into());
+        animator.set_speed(clip, self.speed);
+        animator
+    }
+}
+
+impl SceneDescription {
+    pub fn parse(file_name: &str, src: &str) -> Result<SceneDescription, AppError> {
+        if file_name.to_ascii_lowercase().ends_with(".toml") {
+            Ok(toml::from_str(src)?)
+        } else {
+            Ok(serde_yaml::from_str(src)?)
+        }
+    }
+}
+
+pub async fn load_scene_file(
+    file_name: &str,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    world: &mut World,
+    resources: &mut ResourceContext,
+) -> Result<u64, AppError> {
+    let src = FileSystem::load_asset_string(AssetDir::Scenes, file_name)?;
+    let description = SceneDescription::parse(file_name, &src)?;
+    let id = content_hash(file_name.as_bytes());
+    world.new_scene(id, &description.name);
+
+    let mut clips = HashMap::new();
+    for animation in &description.animations {
+        let key = AnimationClip::key(vec![file_name, &animation.name]);
+        resources
+            .animation_manager
+            .clips
+            .put(key, animation.to_clip()?);
+        clips.insert(animation.name.as_str(), key);
+    }
+
+    let mut spawned = Vec::new();
+    for entry in &description.entities {
+        let label = entry.name.as_deref().unwrap_or("<unnamed>");
+        let animator = match &entry.animator {
+            Some(animator) => match clips.get(animator.clip.as_str()) {
+                Some(clip) => Some(animator.to_animator(*clip)),
+                None => {
+                    return Err(AppError::ResourceCreationFailed(format!(
+                        "Scene {} entity {} plays unknown animation {}",
+                        file_name, label, animator.clip
+                    )))
+                }
+            },
+            None => None,
+        };
+        let first = spawned.len();
+        if let Some(light) = &entry.light {
+            spawned.push(spawn_light(world, light)?);
+        }
+        if entry.camera {
+            spawned.push(spawn_camera_target(world, entry.instances.first())?);
+        }
+        let model = match (&entry.model, entry.geometry) {
+            (None, None) if spawned.len() > first => None,
+            (Some(file), None) if is_gltf_file(file) => {
+                if entry.instances.len() > 1 {
+                    return Err(AppError::ResourceCreationFailed(format!(
+                        "Scene {} entity {} places glTF {} more than once",
+                        file_name, label, file
+                    )));
+                }
+                let gltf = ModelManager::load_gltf_from_file(file, device, queue, resources, world)
+                    .await?;
+                if let Some(instance) = entry.instances.first() {
+                    spawned.push(place_gltf(
+                        world,
+                        &mut resources.instance_manager,
+                        &gltf.roots,
+                        &gltf.entities,
+                        instance.to_transform(),
+                    )?);
+                }
+                if let Some(thresholds) = &entry.lod {
+                    for entity in &gltf.entities {
+                        world.add_component(*entity, Lod::screen_size(thresholds.clone()))?;
+                    }
+                }
+                if let Some(animator) = &animator {
+                    for root in &gltf.roots {
+                        world.add_component(*root, animator.clone())?;
+                    }
+                }
+                spawned.extend(gltf.entities);
+                continue;
+            }
+            (Some(file), None) => {
+                Some(ModelManager::load_model_from_file(file, device, queue, resources).await?)
+            }
+            (None, Some(geometry)) => Some(geometry.create_model(device, resources)?),
+            _ => {
+                return Err(AppError::ResourceCreationFailed(format!(
+                    "Scene {} entity {} needs exactly one of model or geometry",
+                    file_name, label
+                )))
+            }
+        };
+        if let Some(model) = model {
+            let entity = spawn_model(world, resources, model, &entry.instances)?;
+            if let Some(thresholds) = &entry.lod {
+                world.add_component(entity, Lod::screen_size(thresholds.clone()))?;
+            }
+            spawned.push(entity);
+        }
+        if let (Some(animator), Some(entity)) = (animator, spawned.last()) {
+            world.add_component(*entity, animator)?;
+        }
+    }
+
+    if let Some(scene) = world.get_scene_mut(id) {
+        for entity in spawned {
+            scene.add_entity(entity);
+        }
+    }
+    log_info!(
+        "Loaded scene {} ({} entities) from {}",
+        description.name,
+        description.entities.len(),
+        file_name
+    );
+    Ok(id)
+}
+
+pub fn spawn_light(world: &mut World, description: &LightDescription) -> Result<Entity, AppError> {
+    let entity = world.create_entity();
+    world.add_component(entity, description.to_transform())?;
+    world.add_component(entity, description.to_light())?;
+    Ok(entity)
+}
+
+pub fn spawn_camera_target(
+    world: &mut World,
+    placement: Option<&InstanceDescription>,
+) -> Result<Entity, AppError> {
+    let entity = world.create_entity();
+    let transform = placement
+        .map(InstanceDescription::to_transform)
+        .unwrap_or_else(Transform::identity);
+    world.add_component(entity, transform)?;
+    world.add_component(entity, CameraTarget)?;
+    Ok(entity)
+}
+
+/// Parents the glTF roots under a new entity at `placement` and moves the loaded nodes and
+/// their instances to the resulting world transforms.
+pub fn place_gltf(
+    world: &mut World,
+    instance_manager: &mut InstanceManager,
+    roots: &[Entity],
+    entities: &[Entity],
+    placement: Transform,
+) -> Result<Entity, AppError> {
+    let root = world.create_entity();
+    world.add_component(root, placement)?;
+    world.add_component(
+        root,
+        Hierarchy {
+            name: None,
+            parent: None,
+            children: roots.to_vec(),
+            local: placement,
+        },
+    )?;
+    world.query_mut::<Hierarchy>(|entity, hierarchy| {
+        if roots.contains(&entity) {
+            hierarchy.parent = Some(root);
+        }
+    })?;
+
+    let world_transforms = world_transforms(world);
+    world.query_mut::<Transform>(|entity, transform| {
+        if !entities.contains(&entity) {
+            return;
+        }
+        if let Some(world_transform) = world_transforms.get(&entity) {
+            *transform = *world_transform;
+        }
+    })?;
+    for entity in entities {
+        let (Some(world_transform), Some(instances)) = (
+            world_transforms.get(entity),
+            instance_manager.instances.get_mut(&CacheKey::from(entity)),
+        ) else {
+            continue;
+        };
+        for instance in instances {
+            instance.transform = *world_transform;
+        }
+    }
+    Ok(root)
+}
+
+pub fn spawn_model(
+    world: &mut World,
+    resources: &mut ResourceContext,
+    model: Model,
+    instances: &[InstanceDescription],
+) -> Result<Entity, AppError> {
+    let entity = world.create_entity();
+    world.add_component(entity, model)?;
+    if instances.is_empty() {
+        resources.instance_manager.add_instance(
+            &entity,
+            Instance {
+                transform: Transform::identity(),
+            },
+        )?;
+    }
+    for instance in instances {
+        resources.instance_manager.add_instance(
+            &entity,
+            Instance {
+                transform: instance.to_transform(),
+            },
+        )?;
+    }
+    Ok(entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::InnerSpace;
+
+    use super::*;
+
+    const SCENE: &str = r#"
+name = "doors"
+
+[[animations]]
+name = "swing"
+markers = [{ time = 1.0, name = "opened" }]
+
+[[animations.channels]]
+property = "rotation"
+times = [0.0, 1.0]
+values = [[0.0, 0.0, 0.0], [0.0, 90.0, 0.0]]
+
+[[animations.channels]]
+property = "translation"
+interpolation = "step"
+times = [0.0, 0.5]
+values = [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+
+[[entities]]
+name = "flyby"
+camera = true
+instances = [{ position = [0.0, 2.0, 8.0] }]
+animator = { clip = "swing", mode = "ping_pong", speed = 0.5 }
+"#;
+
+    #[test]
+    fn parses_animation_clips_and_animators() {
+        let description = SceneDescription::parse("doors.toml", SCENE).unwrap();
+        let clip = description.animations[0].to_clip().unwrap();
+        assert_eq!(clip.duration, 1.0);
+        assert_eq!(clip.channels.len(), 2);
+        assert_eq!(clip.channels[1].interpolation, Interpolation::Step);
+        assert_eq!(clip.markers[0].name, "opened");
+
+        let entity = &description.entities[0];
+        assert!(entity.camera);
+        let key = AnimationClip::key(vec!["doors.toml", "swing"]);
+        let animator = entity.animator.as_ref().unwrap().to_animator(key);
+        assert_eq!(animator.player.layers[0].mode, PlaybackMode::PingPong);
+        assert_eq!(animator.player.layers[0].speed, 0.5);
+    }
+
+    #[test]
+    fn rejects_malformed_channels() {
+        let channel = |interpolation, times: Vec<f32>, values: Vec<Vec<f32>>| {
+            AnimationChannelDescription {
+                property: ChannelPropertyDescription::Rotation,
+                interpolation,
+                times,
+                values,
+            }
+            .to_channel()
+        };
+        let euler = vec![0.0, 90.0, 0.0];
+        let linear = InterpolationDescription::Linear;
+        let cubic = InterpolationDescription::Cubic;
+
+        assert!(channel(linear, vec![0.0], vec![euler.clone()]).is_ok());
+        assert!(channel(linear, vec![1.0, 0.0], vec![euler.clone(); 2]).is_err());
+        assert!(channel(linear, vec![0.0, 1.0], vec![euler.clone()]).is_err());
+        assert!(channel(linear, vec![0.0], vec![vec![1.0, 2.0]]).is_err());
+        assert!(channel(cubic, vec![0.0], vec![euler; 3]).is_err());
+        assert!(channel(cubic, vec![0.0], vec![vec![0.0, 0.0, 0.0, 1.0]; 3]).is_ok());
+    }
+
+    #[test]
+    fn place_gltf_moves_nodes_and_instances_under_the_placement() {
+        let mut world = World::new();
+        let mut instances = InstanceManager::new();
+        let node = |position: [f32; 3]| Transform {
+            position: Vector3::from(position),
+            ..Transform::identity()
+        };
+        let root = world.create_entity();
+        let child = world.create_entity();
+        let root_local = node([0.0, 1.0, 0.0]);
+        let child_local = node([2.0, 0.0, 0.0]);
+        for (entity, parent, children, local) in [
+            (root, None, vec![child], root_local),
+            (child, Some(root), Vec::new(), child_local),
+        ] {
+            let hierarchy = Hierarchy {
+                name: None,
+                parent,
+                children,
+                local,
+            };
+            world.add_component(entity, hierarchy).unwrap();
+        }
+        world.add_component(root, root_local).unwrap();
+        world
+            .add_component(child, root_local.compose(&child_local))
+            .unwrap();
+        instances
+            .add_instance(
+                &child,
+                Instance {
+                    transform: root_local.compose(&child_local),
+                },
+            )
+            .unwrap();
+
+        let placement = InstanceDescription {
+            position: [10.0, 0.0, 0.0],
+            rotation: [0.0, 90.0, 0.0],
+            scale: [2.0; 3],
+        }
+        .to_transform();
+        let placed = place_gltf(
+            &mut world,
+            &mut instances,
+            &[root],
+            &[root, child],
+            placement,
+        )
+        .unwrap();
+
+        let expected = placement.compose(&root_local).compose(&child_local);
+        let mut transform = None;
+        world
+            .query::<Transform>(|entity, current| {
+                if entity == child {
+                    transform = Some(*current);
+                }
+            })
+            .unwrap();
+        let transform = transform.unwrap();
+        assert!((transform.position - expected.position).magnitude() < 1e-5);
+        assert!((transform.position - Vector3::new(10.0, 2.0, -4.0)).magnitude() < 1e-5);
+        let instance = &instances.instances.get(&CacheKey::from(&child)).unwrap()[0];
+        assert_eq!(instance.transform, transform);
+        world
+            .query::<Hierarchy>(|entity, hierarchy| {
+                if entity == root {
+                    assert_eq!(hierarchy.parent, Some(placed));
+                }
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    core::{cache::CacheKey, events::RupyAppEvent},
+    ecs::{
+        components::{
+            animation::{
+                animator::Animator,
+                player::{AnimationEvent, AnimationPlayer},
+                skeleton::Skeleton,
+            },
+            camera::CameraTarget,
+            hierarchy::Hierarchy,
+            transform::Transform,
+            ResourceContext,
+        },
+        entity::Entity,
+        traits::Cache,
+        world::World,
+    },
+};
+
+fn to_app_events(entity: Entity, events: Vec<AnimationEvent>) -> Vec<RupyAppEvent> {
+    events
+        .into_iter()
+        .map(|event| RupyAppEvent::AnimationEvent {
+            object_id: entity.id as u64,
+            clip: event.clip,
+            name: event.name,
+        })
+        .collect()
+}
+
+pub fn update_animations(world: &World, resources: &ResourceContext, dt: f32) -> Vec<RupyAppEvent> {
+    let clips = &resources.animation_manager.clips;
+    let mut events = Vec::new();
+    let mut skeletons = HashMap::new();
+    let _ = world.query::<Skeleton>(|entity, skeleton| {
+        skeletons.insert(entity, skeleton.clone());
+    });
+
+    let mut locals: HashMap<Entity, Transform> = HashMap::new();
+    let _ = world.query_mut::<AnimationPlayer>(|entity, player| {
+        events.extend(to_app_events(entity, player.advance(dt, clips)));
+        let Some(skeleton) = skeletons.get(&entity) else {
+            return;
+        };
+        if player.layers.is_empty() {
+            return;
+        }
+        let pose = player.sample(clips, &skeleton.rest_pose);
+        for (joint, local) in skeleton.joints.iter().zip(pose) {
+            locals.insert(*joint, local);
+        }
+    });
+    if locals.is_empty() {
+        return events;
+    }
+
+    let _ = world.query_mut::<Hierarchy>(|entity, hierarchy| {
+        if let Some(local) = locals.get(&entity) {
+            hierarchy.local = *local;
+        }
+    });
+    let world_transforms = world_transforms(world);
+    let _ = world.query_mut::<Transform>(|entity, transform| {
+        if let Some(world_transform) = world_transforms.get(&entity) {
+            *transform = *world_transform;
+        }
+    });
+    events
+}
+
+pub fn update_animators(
+    world: &World,
+    resources: &mut ResourceContext,
+    dt: f32,
+) -> Vec<RupyAppEvent> {
+    let clips = &resources.animation_manager.clips;
+    let instances = &mut resources.instance_manager.instances;
+
+    let mut parents = HashMap::new();
+    let mut currents = HashMap::new();
+    let _ = world.query::<Hierarchy>(|entity, hierarchy| {
+        parents.insert(entity, hierarchy.parent);
+        currents.insert(entity, hierarchy.local);
+    });
+    let _ = world.query::<Transform>(|entity, transform| {
+        currents.entry(entity).or_insert(*transform);
+    });
+
+    let mut events = Vec::new();
+    let mut targets = HashMap::new();
+    let _ = world.query_mut::<Animator>(|entity, animator| {
+        events.extend(to_app_events(entity, animator.advance(dt, clips)));
+        if animator.player.layers.is_empty() {
+            return;
+        }
+        let pose = animator.sample(clips);
+        if let Some(current) = currents.get(&entity) {
+            targets.insert(entity, animator.animate(pose, *current));
+        }
+        if parents.contains_key(&entity) {
+            return;
+        }
+        if let Some(instances) = instances.get_mut(&CacheKey::from(&entity)) {
+            animator.animate_instances(pose, instances);
+        }
+    });
+    if targets.is_empty() {
+        return events;
+    }
+
+    let _ = world.query_mut::<Hierarchy>(|entity, hierarchy| {
+        if let Some(target) = targets.get(&entity) {
+            hierarchy.local = *target;
+        }
+    });
+    let _ = world.query_mut::<Transform>(|entity, transform| {
+        if parents.contains_key(&entity) {
+            return;
+        }
+        if let Some(target) = targets.get(&entity) {
+            *transform = *target;
+        }
+    });
+
+    let animated: HashSet<Entity> = parents
+        .keys()
+        .copied()
+        .filter(|entity| {
+            let mut current = Some(*entity);
+            let mut depth = 0;
+            while let Some(node) = current {
+                if targets.contains_key(&node) {
+                    return true;
+                }
+                depth += 1;
+                if depth > parents.len() {
+                    break;
+                }
+                current = parents.get(&node).copied().flatten();
+            }
+            false
+        })
+        .collect();
+    if animated.is_empty() {
+        return events;
+    }
+
+    let world_transforms = world_transforms(world);
+    let _ = world.query_mut::<Transform>(|entity, transform| {
+        if !animated.contains(&entity) {
+            return;
+        }
+        if let Some(world_transform) = world_transforms.get(&entity) {
+            *transform = *world_transform;
+        }
+    });
+    for entity in &animated {
+        let (Some(world_transform), Some(instances)) = (
+            world_transforms.get(entity),
+            instances.get_mut(&CacheKey::from(entity)),
+        ) else {
+            continue;
+        };
+        for instance in instances {
+            instance.transform = *world_transform;
+        }
+    }
+    events
+}
+
+/// Returns the world transform of the camera target with the lowest entity id, if any.
+pub fn camera_target(world: &World) -> Option<Transform> {
+    let mut target: Option<Entity> = None;
+    let _ = world.query::<CameraTarget>(|entity, _| {
+        if target.is_none_or(|current| entity.id < current.id) {
+            target = Some(entity);
+        }
+    });
+    let target = target?;
+    let mut transform = None;
+    let _ = world.query::<Transform>(|entity, current| {
+        if entity == target {
+            transform = Some(*current);
+        }
+    });
+    transform
+}
+
+pub fn update_skinning(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    world: &World,
+    resources: &mut ResourceContext,
+) {
+    let mut skeletons = Vec::new();
+    let _ = world.query::<Skeleton>(|entity, skeleton| {
+        skeletons.push((entity, skeleton.clone()));
+    });
+    if skeletons.is_empty() {
+        return;
+    }
+
+    let world_transforms = world_transforms(world);
+    for (entity, skeleton) in skeletons {
+        let mesh_world = world_transforms
+            .get(&entity)
+            .copied()
+            .unwrap_or_else(Transform::identity);
+        let matrices = skeleton.joint_matrices(&mesh_world, &world_transforms);
+        if matrices.is_empty() {
+            continue;
+        }
+        resources.buffer_manager.update_storage_buffer(
+            device,
+            queue,
+            &matrices,
+            Skeleton::joint_buffer_key(entity),
+        );
+    }
+}
+
+pub fn world_transforms(world: &World) -> HashMap<Entity, Transform> {
+    let mut hierarchies = HashMap::new();
+    let _ = world.query::<Hierarchy>(|entity, hierarchy| {
+        hierarchies.insert(entity, (hierarchy.parent, hierarchy.local));
+    });
+
+    let mut resolved: HashMap<Entity, Transform> = HashMap::with_capacity(hierarchies.len());
+    for &entity in hierarchies.keys() {
+        let mut chain = Vec::new();
+        let mut current = Some(entity);
+        while let Some(node) = current {
+            if resolved.contains_key(&node) || chain.len() > hierarchies.len() {
+                break;
+            }
+            let Some((parent, _)) = hierarchies.get(&node) else {
+                break;
+            };
+            chain.push(node);
+            current = *parent;
+        }
+
+        for node in chain.into_iter().rev() {
+            let (parent, local) = hierarchies[&node];
+            let world_transform = match parent.and_then(|parent| resolved.get(&parent)) {
+                Some(parent_world) => parent_world.compose(&local),
+                None => local,
+            };
+            resolved.insert(node, world_transform);
+        }
+    }
+    resolved
+}
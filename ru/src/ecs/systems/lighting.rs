@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use crate::{
+    ecs::{
+        components::{
+            light::{Light, LightKind},
+            transform::Transform,
+        },
+        entity::Entity,
+        world::World,
+    },
+    graphics::uniform::{
+        cluster::{LightRaw, SHADOW_POINT, SHADOW_SUN},
+        lighting::LightUniform,
+    },
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct SceneLights {
+    pub lights: Vec<LightRaw>,
+    pub primary: LightUniform,
+}
+
+pub fn collect_lights(world: &World) -> SceneLights {
+    let mut transforms: HashMap<Entity, Transform> = HashMap::new();
+    let _ = world.query::<Transform>(|entity, transform| {
+        transforms.insert(entity, *transform);
+    });
+    let mut entities: Vec<(Entity, Light, Transform)> = Vec::new();
+    let _ = world.query::<Light>(|entity, light| {
+        let transform = transforms
+            .get(&entity)
+            .copied()
+            .unwrap_or_else(Transform::identity);
+        entities.push((entity, *light, transform));
+    });
+    entities.sort_by_key(|(entity, _, _)| (entity.id, entity.generation));
+
+    let mut scene = SceneLights {
+        lights: Vec::with_capacity(entities.len()),
+        primary: LightUniform::default(),
+    };
+    let mut point_shadow = false;
+    let mut sun_shadow = false;
+    for (_, light, transform) in entities {
+        let mut raw = light.to_raw(&transform);
+        if light.cast_shadows {
+            match light.kind {
+                LightKind::Point if !point_shadow => {
+                    point_shadow = true;
+                    raw.shadow = SHADOW_POINT;
+                    scene.primary =
+                        scene
+                            .primary
+                            .with_point(raw.position, raw.color, raw.intensity, raw.range);
+                }
+                LightKind::Directional if !sun_shadow => {
+                    sun_shadow = true;
+                    raw.shadow = SHADOW_SUN;
+                    scene.primary = scene
+                        .primary
+                        .with_sun(raw.direction, raw.color, raw.intensity);
+                }
+                _ => {}
+            }
+        }
+        scene.lights.push(raw);
+    }
+    scene
+}
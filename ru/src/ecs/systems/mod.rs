@@ -1,2 +1,5 @@
+pub mod animation;
+pub mod lighting;
+pub mod passes;
 pub mod physics;
 pub mod render;
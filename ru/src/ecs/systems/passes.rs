@@ -0,0 +1,762 @@
+use std::collections::HashMap;
+
+use cgmath::InnerSpace;
+
+use crate::{
+    app::DebugMode,
+    camera::{frustum::Frustum, handler::CameraHandler},
+    core::error::AppError,
+    ecs::{
+        components::{
+            animation::skeleton::Skeleton,
+            instance::model::InstanceRaw,
+            lod::{Lod, MAX_LOD_LEVELS},
+            material::model::{Material, ShadingModel},
+            model::model::Model,
+            ResourceContext,
+        },
+        traits::{Cache, RenderPassDraw},
+        world::World,
+    },
+    graphics::{
+        binding::{light::create_light_bind_group, skin::SkinBindGroups},
+        cluster::LightClusters,
+        glyphon::GlyphonRender,
+        graph::{resource::ResourceId, PassContext, RenderGraph},
+        post::{
+            settings::PostEffect, PostProcessStack, PostStage, BLOOM_DOWNSAMPLE, BLOOM_UPSAMPLE,
+        },
+        shadow::{ShadowCaster, ShadowMaps, ATLAS_COLUMNS, ATLAS_ROWS},
+    },
+    log_warning,
+    prelude::cache::CacheKey,
+};
+
+use super::render::{RenderInfo, Renderer3D};
+
+pub const SURFACE: &str = "surface";
+pub const HDR_COLOR: &str = "hdr_color";
+pub const SCENE_DEPTH: &str = "scene_depth";
+pub const SCENE_COLOR_MSAA: &str = "scene_color_msaa";
+pub const ATLAS_VIEW_SCALE: f32 = 0.4;
+pub const ATLAS_VIEW_MARGIN: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy)]
+struct MeshDraw {
+    shading_model: ShadingModel,
+    material_key: CacheKey,
+    mesh_key: CacheKey,
+    lod: usize,
+    instance_key: CacheKey,
+    instance_start: u32,
+    instance_end: u32,
+    skin: Option<CacheKey>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ShadowDraw {
+    mesh_key: CacheKey,
+    instance_key: CacheKey,
+    instance_count: u32,
+    skin: Option<CacheKey>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SceneKeys {
+    pub environment: CacheKey,
+    pub light: CacheKey,
+    pub skybox: CacheKey,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SceneTargets {
+    pub color: ResourceId,
+    pub depth: ResourceId,
+    pub resolve: Option<ResourceId>,
+    pub shadow_atlas: Option<ResourceId>,
+}
+
+impl SceneTargets {
+    pub fn output(&self) -> ResourceId {
+        self.resolve.unwrap_or(self.color)
+    }
+}
+
+pub struct FrameContext<'f> {
+    pub ctx: &'f mut RenderInfo,
+    pub world: &'f mut World,
+    pub resources: &'f mut ResourceContext,
+    pub glyphon: &'f mut GlyphonRender,
+    pub camera_handler: &'f CameraHandler,
+    pub frustum: &'f Frustum,
+    pub shadows: &'f ShadowMaps,
+    pub clusters: &'f LightClusters,
+    pub config: &'f wgpu::SurfaceConfiguration,
+    pub color_format: wgpu::TextureFormat,
+    pub sample_count: u32,
+    pub camera_bind_group: &'f wgpu::BindGroup,
+    pub skin_bind_groups: &'f SkinBindGroups,
+}
+
+fn begin_scene_pass<'p>(
+    pass: &'p mut PassContext<'_>,
+    label: &str,
+    targets: SceneTargets,
+    clear: bool,
+    resolve: bool,
+) -> Result<wgpu::RenderPass<'p>, AppError> {
+    let color_view = pass.texture(targets.color)?;
+    let depth_view = pass.texture(targets.depth)?;
+    let resolve_target = match targets.resolve {
+        Some(id) if resolve => Some(pass.texture(id)?),
+        _ => None,
+    };
+    let (color_load, depth_load) = if clear {
+        (
+            wgpu::LoadOp::Clear(wgpu::Color {
+                r: 0.1,
+                g: 0.2,
+                b: 0.3,
+                a: 1.0,
+            }),
+            wgpu::LoadOp::Clear(1.0),
+        )
+    } else {
+        (wgpu::LoadOp::Load, wgpu::LoadOp::Load)
+    };
+    Ok(pass.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: color_view,
+            resolve_target,
+            ops: wgpu::Operations {
+                load: color_load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+            view: depth_view,
+            depth_ops: Some(wgpu::Operations {
+                load: depth_load,
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: None,
+        }),
+        occlusion_query_set: None,
+        timestamp_writes: None,
+    }))
+}
+
+pub fn light_cluster_pass(
+    pass: &mut PassContext<'_>,
+    frame: &mut FrameContext<'_>,
+) -> Result<(), AppError> {
+    frame.clusters.dispatch(pass.encoder);
+    Ok(())
+}
+
+pub fn opaque_pass(
+    pass: &mut PassContext<'_>,
+    frame: &mut FrameContext<'_>,
+    targets: SceneTargets,
+    keys: SceneKeys,
+) -> Result<(), AppError> {
+    let device = pass.device;
+    let debug_mode = frame.ctx.debug_mode();
+    let ctx = &mut *frame.ctx;
+    let world = &mut *frame.world;
+    let resources = &mut *frame.resources;
+    let camera_handler = frame.camera_handler;
+    let frustum = frame.frustum;
+    let camera_bind_group = frame.camera_bind_group;
+    let skin_bind_groups = frame.skin_bind_groups;
+
+    let bind_group_manager = &resources.bind_group_manager;
+    let environment_bind_group = bind_group_manager
+        .bind_groups
+        .get(&keys.environment)
+        .expect("Environment bind group not found");
+    let light_buffer = resources
+        .buffer_manager
+        .buffers
+        .get(&keys.light)
+        .expect("Light buffer not found");
+    let shadow_atlas = match targets.shadow_atlas {
+        Some(atlas) => Some(pass.texture(atlas)?),
+        None => None,
+    };
+    let light_bind_group = &create_light_bind_group(
+        device,
+        &bind_group_manager
+            .bind_group_layouts
+            .light_bind_group_layout,
+        light_buffer,
+        frame.shadows.bindings(shadow_atlas),
+        frame.clusters.bindings(),
+    );
+
+    let mut render_pass = begin_scene_pass(pass, "Opaque Pass", targets, true, false)?;
+
+    let pipeline_label = ctx.render_mode().label();
+
+    let light_pipeline = resources
+        .pipeline_manager
+        .pipelines
+        .get(&CacheKey::from(
+            format!("{}_light_pipeline", pipeline_label).as_str(),
+        ))
+        .expect("Light pipeline not found");
+
+    let normal_pipeline = resources
+        .pipeline_manager
+        .pipelines
+        .get(&CacheKey::from(
+            format!("{}_pipeline", pipeline_label).as_str(),
+        ))
+        .expect("Light pipeline not found");
+
+    let pbr_pipeline = resources
+        .pipeline_manager
+        .pipelines
+        .get(&CacheKey::from(
+            format!("{}_pbr_pipeline", pipeline_label).as_str(),
+        ))
+        .expect("PBR pipeline not found");
+
+    let skinned_pipeline = resources
+        .pipeline_manager
+        .pipelines
+        .get(&CacheKey::from(
+            format!("{}_skinned_pipeline", pipeline_label).as_str(),
+        ))
+        .expect("Skinned pipeline not found");
+
+    let skinned_pbr_pipeline = resources
+        .pipeline_manager
+        .pipelines
+        .get(&CacheKey::from(
+            format!("{}_skinned_pbr_pipeline", pipeline_label).as_str(),
+        ))
+        .expect("Skinned PBR pipeline not found");
+
+    let default_material_key = Material::default_key();
+    let mut entity_lods = HashMap::new();
+    let _ = world.query::<Lod>(|entity, lod| {
+        entity_lods.insert(entity, lod.clone());
+    });
+    let mut draws: Vec<MeshDraw> = Vec::new();
+    let _ = world.query::<Model>(|entity, model| {
+        let cache_id = CacheKey::from(entity);
+        render_pass.set_pipeline(light_pipeline);
+        render_pass.draw_model(
+            model,
+            &[camera_bind_group, light_bind_group],
+            &Some(0..1),
+            &resources.buffer_manager,
+            &resources.mesh_manager,
+        );
+
+        if let Some(instances) = resources
+            .instance_manager
+            .instances
+            .get(&CacheKey::from(entity))
+        {
+            let total_instances = instances.len() as u32;
+            let mut culled_instances = 0u32;
+
+            let fovy = camera_handler.projection.fovy;
+            let mut lod_buckets: Vec<Vec<InstanceRaw>> = vec![Vec::new(); MAX_LOD_LEVELS];
+            for instance in instances.iter() {
+                let bounds = model.bounds.transformed(&instance.transform);
+                let color =
+                    if frustum.contains(&bounds.sphere()) && frustum.contains(&bounds.aabb()) {
+                        [1.0, 1.0, 1.0, 1.0]
+                    } else {
+                        culled_instances += 1;
+                        if debug_mode == DebugMode::Verbose || debug_mode == DebugMode::Minimal {
+                            [1.0, 1.0, 1.0, 0.1]
+                        } else {
+                            continue;
+                        }
+                    };
+                let lod = entity_lods.get(&entity).map_or(0, |lod| {
+                    lod.select(
+                        (bounds.center - camera_handler.position()).magnitude(),
+                        bounds.radius,
+                        fovy,
+                    )
+                });
+                lod_buckets[lod].push(instance.to_raw(color));
+            }
+
+            ctx.update_instance_stats(total_instances, culled_instances);
+
+            let mut lod_offsets = Vec::with_capacity(MAX_LOD_LEVELS + 1);
+            let mut instance_raw_data = Vec::new();
+            for bucket in lod_buckets {
+                lod_offsets.push(instance_raw_data.len() as u32);
+                instance_raw_data.extend(bucket);
+            }
+            lod_offsets.push(instance_raw_data.len() as u32);
+
+            resources
+                .buffer_manager
+                .create_instance_buffer(device, &instance_raw_data, cache_id);
+
+            for mesh_id in &model.mesh_ids {
+                let Some(mesh) = resources.mesh_manager.meshes.get(mesh_id) else {
+                    continue;
+                };
+                let material_key = model
+                    .material_key(mesh)
+                    .filter(|key| resources.material_manager.materials.contains(key))
+                    .unwrap_or(default_material_key);
+                let shading_model = resources
+                    .material_manager
+                    .materials
+                    .get(&material_key)
+                    .map(|material| material.shading_model)
+                    .unwrap_or_default();
+                let skin = if mesh.is_skinned() {
+                    let key = Skeleton::joint_buffer_key(entity);
+                    if !skin_bind_groups.contains(&key) {
+                        continue;
+                    }
+                    Some(key)
+                } else {
+                    None
+                };
+                let lod_count = mesh.lod_count().min(MAX_LOD_LEVELS);
+                for lod in 0..lod_count {
+                    let instance_start = lod_offsets[lod];
+                    let instance_end = if lod + 1 == lod_count {
+                        lod_offsets[MAX_LOD_LEVELS]
+                    } else {
+                        lod_offsets[lod + 1]
+                    };
+                    if instance_start == instance_end {
+                        continue;
+                    }
+                    draws.push(MeshDraw {
+                        shading_model,
+                        material_key,
+                        mesh_key: *mesh_id,
+                        lod,
+                        instance_key: cache_id,
+                        instance_start,
+                        instance_end,
+                        skin,
+                    });
+                }
+            }
+        }
+    });
+
+    draws.sort_by_key(|draw| {
+        (
+            draw.skin.map(|key| key.value()),
+            draw.shading_model,
+            draw.material_key.value(),
+            draw.mesh_key.value(),
+            draw.lod,
+        )
+    });
+    render_pass.set_bind_group(1, camera_bind_group, &[]);
+    render_pass.set_bind_group(2, light_bind_group, &[]);
+    render_pass.set_bind_group(3, environment_bind_group, &[]);
+
+    let mut current_shading = None;
+    let mut current_material = None;
+    let mut current_skin = None;
+    for draw in &draws {
+        if current_skin != draw.skin {
+            let skin_bind_group = draw.skin.and_then(|key| skin_bind_groups.camera(&key));
+            render_pass.set_bind_group(1, skin_bind_group.unwrap_or(camera_bind_group), &[]);
+            if current_skin.is_none() || draw.skin.is_none() {
+                current_shading = None;
+            }
+            current_skin = draw.skin;
+        }
+        if current_shading != Some(draw.shading_model) {
+            match (draw.shading_model, draw.skin.is_some()) {
+                (ShadingModel::BlinnPhong, false) => render_pass.set_pipeline(normal_pipeline),
+                (ShadingModel::MetallicRoughness, false) => render_pass.set_pipeline(pbr_pipeline),
+                (ShadingModel::BlinnPhong, true) => render_pass.set_pipeline(skinned_pipeline),
+                (ShadingModel::MetallicRoughness, true) => {
+                    render_pass.set_pipeline(skinned_pbr_pipeline)
+                }
+            }
+            current_shading = Some(draw.shading_model);
+            current_material = None;
+        }
+        if current_material != Some(draw.material_key) {
+            let Some(material_bind_group) = bind_group_manager.bind_groups.get(&draw.material_key)
+            else {
+                log_warning!("No bind group for material {:?}", draw.material_key);
+                continue;
+            };
+            render_pass.set_bind_group(0, material_bind_group, &[]);
+            current_material = Some(draw.material_key);
+        }
+
+        let Some(mesh) = resources.mesh_manager.meshes.get(&draw.mesh_key) else {
+            continue;
+        };
+        let (index_buffer_key, num_elements) = mesh.lod(draw.lod);
+        if let (Ok(vertex_buffer), Ok(index_buffer), Some(instance_buffer)) = (
+            resources
+                .buffer_manager
+                .get_vertex_buffer(&mesh.vertex_buffer_key),
+            resources.buffer_manager.get_index_buffer(&index_buffer_key),
+            resources
+                .buffer_manager
+                .get_instance_buffer(draw.instance_key),
+        ) {
+            render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+            render_pass.draw_mesh_lod(
+                vertex_buffer,
+                index_buffer,
+                num_elements,
+                draw.instance_start..draw.instance_end,
+            );
+        } else {
+            log_warning!(
+                "Mesh with CacheId {:?} is missing vertex, index or instance buffer.",
+                draw.mesh_key
+            );
+        }
+    }
+    Ok(())
+}
+
+fn shadow_instance_key(entity: crate::ecs::entity::Entity) -> CacheKey {
+    CacheKey::from(format!("shadow:instances:{}:{}", entity.id, entity.generation).as_str())
+}
+
+pub fn shadow_pass(
+    pass: &mut PassContext<'_>,
+    frame: &mut FrameContext<'_>,
+    atlas: ResourceId,
+) -> Result<(), AppError> {
+    let device = pass.device;
+    let shadows = frame.shadows;
+    let skin_bind_groups = frame.skin_bind_groups;
+    let resources = &mut *frame.resources;
+
+    let mut draws: Vec<ShadowDraw> = Vec::new();
+    let _ = frame.world.query::<Model>(|entity, model| {
+        let Some(instances) = resources
+            .instance_manager
+            .instances
+            .get(&CacheKey::from(entity))
+        else {
+            return;
+        };
+        if instances.is_empty() {
+            return;
+        }
+        let instance_raw_data: Vec<InstanceRaw> = instances
+            .iter()
+            .map(|instance| instance.to_raw([1.0, 1.0, 1.0, 1.0]))
+            .collect();
+        let instance_key = shadow_instance_key(entity);
+        resources
+            .buffer_manager
+            .create_instance_buffer(device, &instance_raw_data, instance_key);
+
+        for mesh_id in &model.mesh_ids {
+            let Some(mesh) = resources.mesh_manager.meshes.get(mesh_id) else {
+                continue;
+            };
+            let skin = if mesh.is_skinned() {
+                let key = Skeleton::joint_buffer_key(entity);
+                if !skin_bind_groups.contains(&key) {
+                    continue;
+                }
+                Some(key)
+            } else {
+                None
+            };
+            draws.push(ShadowDraw {
+                mesh_key: *mesh_id,
+                instance_key,
+                instance_count: instance_raw_data.len() as u32,
+                skin,
+            });
+        }
+    });
+    draws.sort_by_key(|draw| draw.skin.map(|key| key.value()));
+
+    let atlas_view = pass.texture(atlas)?;
+    let mut render_pass = pass.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Shadow Pass"),
+        color_attachments: &[],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+            view: atlas_view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(1.0),
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: None,
+        }),
+        occlusion_query_set: None,
+        timestamp_writes: None,
+    });
+
+    for &view in shadows.active_views() {
+        let (x, y, size) = shadows.tile_viewport(view);
+        render_pass.set_viewport(x, y, size, size, 0.0, 1.0);
+        render_pass.set_scissor_rect(x as u32, y as u32, size as u32, size as u32);
+        render_pass.set_bind_group(
+            0,
+            shadows.caster_bind_group(),
+            &[ShadowMaps::caster_offset(view)],
+        );
+
+        let caster = ShadowCaster::for_view(view);
+        let mut current_skinned = None;
+        for draw in &draws {
+            let skinned = draw.skin.is_some();
+            if current_skinned != Some(skinned) {
+                render_pass.set_pipeline(shadows.caster_pipeline(caster, skinned));
+                current_skinned = Some(skinned);
+            }
+            if let Some(key) = draw.skin {
+                let Some(joint_bind_group) = skin_bind_groups.shadow(&key) else {
+                    continue;
+                };
+                render_pass.set_bind_group(1, joint_bind_group, &[]);
+            }
+
+            let Some(mesh) = resources.mesh_manager.meshes.get(&draw.mesh_key) else {
+                continue;
+            };
+            let (index_buffer_key, num_elements) = mesh.lod(0);
+            if let (Ok(vertex_buffer), Ok(index_buffer), Some(instance_buffer)) = (
+                resources
+                    .buffer_manager
+                    .get_vertex_buffer(&mesh.vertex_buffer_key),
+                resources.buffer_manager.get_index_buffer(&index_buffer_key),
+                resources
+                    .buffer_manager
+                    .get_instance_buffer(draw.instance_key),
+            ) {
+                render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+                render_pass.draw_mesh_lod(
+                    vertex_buffer,
+                    index_buffer,
+                    num_elements,
+                    0..draw.instance_count,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn shadow_atlas_pass(
+    pass: &mut PassContext<'_>,
+    frame: &mut FrameContext<'_>,
+    atlas: ResourceId,
+    output: ResourceId,
+) -> Result<(), AppError> {
+    let atlas_bind_group = frame.shadows.atlas_bind_group(
+        pass.device,
+        &frame.resources.bind_group_manager.bind_group_layouts,
+        pass.texture(atlas)?,
+    );
+    let output = pass.texture(output)?;
+    let mut render_pass = pass.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Shadow Atlas Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: output,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        occlusion_query_set: None,
+        timestamp_writes: None,
+    });
+
+    let (surface_width, surface_height) = (frame.config.width as f32, frame.config.height as f32);
+    let width = (surface_width * ATLAS_VIEW_SCALE).max(1.0);
+    let height = (width * ATLAS_ROWS as f32 / ATLAS_COLUMNS as f32)
+        .min(surface_height - ATLAS_VIEW_MARGIN)
+        .max(1.0);
+    let y = (surface_height - height - ATLAS_VIEW_MARGIN).max(0.0);
+    render_pass.set_viewport(ATLAS_VIEW_MARGIN, y, width, height, 0.0, 1.0);
+    frame
+        .shadows
+        .draw_atlas(&mut render_pass, &atlas_bind_group);
+    Ok(())
+}
+
+pub fn post_process_passes<'a>(
+    graph: &mut RenderGraph<'a, FrameContext<'_>>,
+    post: &'a PostProcessStack,
+    input: ResourceId,
+    output: ResourceId,
+) {
+    let effects = post.settings.active_effects();
+    if effects.is_empty() {
+        post_pass(graph, post, PostStage::Copy, [input, input], output, true);
+        return;
+    }
+    let mut current = input;
+    for (index, &effect) in effects.iter().enumerate() {
+        let secondary = match effect {
+            PostEffect::Bloom => bloom_passes(graph, post, current),
+            _ => current,
+        };
+        let last = index + 1 == effects.len();
+        let target = if last {
+            output
+        } else {
+            graph.create_texture(PostProcessStack::target_desc(index))
+        };
+        post_pass(
+            graph,
+            post,
+            PostStage::Effect(effect),
+            [current, secondary],
+            target,
+            last,
+        );
+        current = target;
+    }
+}
+
+fn bloom_passes<'a>(
+    graph: &mut RenderGraph<'a, FrameContext<'_>>,
+    post: &'a PostProcessStack,
+    input: ResourceId,
+) -> ResourceId {
+    let levels = post.settings.bloom_mip_count();
+    let downsampled: Vec<ResourceId> = (0..levels)
+        .map(|level| graph.create_texture(PostProcessStack::bloom_desc(BLOOM_DOWNSAMPLE, level)))
+        .collect();
+    post_pass(
+        graph,
+        post,
+        PostStage::BloomPrefilter,
+        [input, input],
+        downsampled[0],
+        false,
+    );
+    for pair in downsampled.windows(2) {
+        post_pass(
+            graph,
+            post,
+            PostStage::BloomDownsample,
+            [pair[0], pair[0]],
+            pair[1],
+            false,
+        );
+    }
+
+    let mut blurred = downsampled[downsampled.len() - 1];
+    for level in (0..levels - 1).rev() {
+        let target = graph.create_texture(PostProcessStack::bloom_desc(BLOOM_UPSAMPLE, level));
+        post_pass(
+            graph,
+            post,
+            PostStage::BloomUpsample,
+            [blurred, downsampled[level as usize]],
+            target,
+            false,
+        );
+        blurred = target;
+    }
+    blurred
+}
+
+fn post_pass<'a>(
+    graph: &mut RenderGraph<'a, FrameContext<'_>>,
+    post: &'a PostProcessStack,
+    stage: PostStage,
+    inputs: [ResourceId; 2],
+    output: ResourceId,
+    to_surface: bool,
+) {
+    let format = if to_surface {
+        post.output_format()
+    } else {
+        post.format()
+    };
+    graph
+        .add_pass(stage.label())
+        .read(inputs[0])
+        .read(inputs[1])
+        .write(output)
+        .execute(move |pass, frame| {
+            let bind_group = post.bind_group(
+                pass.device,
+                &frame.resources.bind_group_manager.bind_group_layouts,
+                pass.texture(inputs[0])?,
+                pass.texture(inputs[1])?,
+            );
+            let output = pass.texture(output)?;
+            post.draw(pass.encoder, stage, format, &bind_group, output)
+        });
+}
+
+pub fn sky_pass(
+    pass: &mut PassContext<'_>,
+    frame: &mut FrameContext<'_>,
+    targets: SceneTargets,
+    keys: SceneKeys,
+) -> Result<(), AppError> {
+    let resources = &*frame.resources;
+    let environment_bind_group = resources
+        .bind_group_manager
+        .bind_groups
+        .get(&keys.environment)
+        .expect("Environment bind group not found");
+    let mut render_pass = begin_scene_pass(pass, "Sky Pass", targets, false, false)?;
+    render_pass.set_pipeline(
+        resources
+            .pipeline_manager
+            .pipelines
+            .get(&keys.skybox)
+            .expect("Sky pipeline not found"),
+    );
+    render_pass.draw_vertices(
+        &[frame.camera_bind_group, environment_bind_group],
+        0..3,
+        Some(0..1),
+    );
+    Ok(())
+}
+
+pub fn overlay_pass(
+    pass: &mut PassContext<'_>,
+    frame: &mut FrameContext<'_>,
+    targets: SceneTargets,
+    depth_stencil: &wgpu::DepthStencilState,
+) -> Result<(), AppError> {
+    let device = pass.device;
+    let queue = pass.queue;
+    let mut render_pass = begin_scene_pass(pass, "Overlay Pass", targets, false, true)?;
+    frame.glyphon.render(
+        &mut render_pass,
+        device,
+        queue,
+        frame.config,
+        depth_stencil.depth_write_enabled,
+    );
+
+    if frame.ctx.debug_mode() == DebugMode::Verbose {
+        Renderer3D::debug_render_pass(
+            device,
+            frame.color_format,
+            frame.sample_count,
+            &Some(depth_stencil.format),
+            &mut render_pass,
+            frame.camera_handler,
+        );
+    }
+    Ok(())
+}
@@ -1 +1,153 @@
+use std::collections::HashSet;
 
+use cgmath::{InnerSpace, Vector3};
+
+use crate::{
+    core::cache::CacheKey,
+    ecs::{
+        components::{bounds::Bounds, model::model::Model, ResourceContext},
+        entity::Entity,
+        traits::Cache,
+        world::World,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstanceRef {
+    pub entity: Entity,
+    pub instance: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PickHit {
+    pub target: InstanceRef,
+    pub distance: f32,
+}
+
+/// World-space bounds of every model instance: the model's local bounds moved by the
+/// instance transform.
+pub fn world_bounds(world: &World, resources: &ResourceContext) -> Vec<(InstanceRef, Bounds)> {
+    let mut bounds = Vec::new();
+    let _ = world.query::<Model>(|entity, model| {
+        let Some(instances) = resources
+            .instance_manager
+            .instances
+            .get(&CacheKey::from(&entity))
+        else {
+            return;
+        };
+        for (instance, placed) in instances.iter().enumerate() {
+            bounds.push((
+                InstanceRef { entity, instance },
+                model.bounds.transformed(&placed.transform),
+            ));
+        }
+    });
+    bounds
+}
+
+/// Sweep-and-prune along X: returns every pair of instances whose world AABBs overlap.
+pub fn broadphase(bounds: &[(InstanceRef, Bounds)]) -> Vec<(InstanceRef, InstanceRef)> {
+    let mut order: Vec<usize> = (0..bounds.len()).collect();
+    order.sort_by(|a, b| bounds[*a].1.min.x.total_cmp(&bounds[*b].1.min.x));
+
+    let mut active: Vec<usize> = Vec::new();
+    let mut pairs = Vec::new();
+    for index in order {
+        let (target, current) = &bounds[index];
+        active.retain(|other| bounds[*other].1.max.x >= current.min.x);
+        for other in &active {
+            if bounds[*other].1.overlaps(current) {
+                pairs.push((bounds[*other].0, *target));
+            }
+        }
+        active.push(index);
+    }
+    pairs
+}
+
+/// Collapses instance pairs to distinct entity pairs, ordered by entity id.
+pub fn entity_pairs(pairs: &[(InstanceRef, InstanceRef)]) -> HashSet<(Entity, Entity)> {
+    pairs
+        .iter()
+        .filter(|(a, b)| a.entity != b.entity)
+        .map(|(a, b)| {
+            if a.entity.id <= b.entity.id {
+                (a.entity, b.entity)
+            } else {
+                (b.entity, a.entity)
+            }
+        })
+        .collect()
+}
+
+/// Returns the closest instance whose world AABB the ray hits.
+pub fn pick(
+    bounds: &[(InstanceRef, Bounds)],
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+) -> Option<PickHit> {
+    if direction.magnitude2() <= f32::EPSILON {
+        return None;
+    }
+    let direction = direction.normalize();
+    bounds
+        .iter()
+        .filter_map(|(target, bounds)| {
+            bounds
+                .intersect_ray(origin, direction)
+                .map(|distance| PickHit {
+                    target: *target,
+                    distance,
+                })
+        })
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(id: u32) -> InstanceRef {
+        InstanceRef {
+            entity: Entity { id, generation: 0 },
+            instance: 0,
+        }
+    }
+
+    fn cube(id: u32, center: [f32; 3], half: f32) -> (InstanceRef, Bounds) {
+        let [x, y, z] = center;
+        let bounds = Bounds::from_points([
+            [x - half, y - half, z - half],
+            [x + half, y + half, z + half],
+        ])
+        .unwrap();
+        (target(id), bounds)
+    }
+
+    #[test]
+    fn broadphase_reports_only_overlapping_pairs() {
+        let bounds = [
+            cube(0, [0.0, 0.0, 0.0], 1.0),
+            cube(1, [1.5, 0.0, 0.0], 1.0),
+            cube(2, [1.5, 5.0, 0.0], 1.0),
+            cube(3, [10.0, 0.0, 0.0], 1.0),
+        ];
+        let pairs = broadphase(&bounds);
+        assert_eq!(pairs, vec![(target(0), target(1))]);
+    }
+
+    #[test]
+    fn pick_returns_the_closest_hit() {
+        let bounds = [
+            cube(0, [0.0, 0.0, -10.0], 1.0),
+            cube(1, [0.0, 0.0, -5.0], 1.0),
+            cube(2, [3.0, 0.0, -2.0], 1.0),
+        ];
+        let hit = pick(&bounds, Vector3::new(0.0, 0.0, 0.0), -Vector3::unit_z()).unwrap();
+        assert_eq!(hit.target, target(1));
+        assert!((hit.distance - 4.0).abs() < 1e-5);
+
+        assert!(pick(&bounds, Vector3::new(0.0, 0.0, 0.0), Vector3::unit_z()).is_none());
+    }
+}
@@ -1,12 +1,10 @@
 use crate::{
     app::DebugMode,
-    camera::{
-        frustum::{BoundingVolume, Frustum},
-        handler::CameraHandler,
-    },
-    core::{cache::HashCache, error::AppError, surface::RenderSurface},
+    camera::{frustum::Frustum, handler::CameraHandler},
+    core::{cache::HashCache, error::AppError, surface::RenderTarget},
     ecs::{
         components::{
+            animation::skeleton::Skeleton,
             instance::model::InstanceRaw,
             mesh::{manager::MeshManager, model::Mesh},
             model::model::Model,
@@ -16,11 +14,15 @@ use crate::{
         world::World,
     },
     graphics::{
-        binding::camera::create_camera_bind_group,
+        binding::{camera::create_camera_bind_group, skin::SkinBindGroups},
+        cluster::{LightClusters, LIGHT_CLUSTERS},
         glyphon::GlyphonRender,
-        pipelines::{common::PipelineBase, hdr},
-        textures::depth_texture::DepthTexture,
-        uniform::{camera::CameraUniform, lighting::LightUniform, Uniforms},
+        graph::{pool::TransientPool, resource::TextureDesc, RenderGraph},
+        post::PostProcessStack,
+        shadow::ShadowMaps,
+        uniform::{
+            camera::CameraUniform, lighting::LightUniform, material::MaterialUniform, Uniforms,
+        },
         vertex::VertexType,
         PrimitiveTopology,
     },
@@ -33,6 +35,8 @@ use std::ops::Range;
 use wgpu::{util::DeviceExt, Buffer, BufferUsages};
 use winit::dpi::PhysicalSize;
 
+use super::passes::{self, FrameContext, SceneKeys, SceneTargets};
+
 #[repr(C)]
 #[derive(Debug, Pod, Zeroable, Clone, Copy)]
 struct DebugLineVertex {
@@ -41,20 +45,28 @@ struct DebugLineVertex {
 }
 pub struct Renderer3D {
     pub ctx: RenderInfo,
-    pub hdr: hdr::HdrPipeline,
-    pub depth_texture: DepthTexture,
+    pub post: PostProcessStack,
+    pub depth_stencil: wgpu::DepthStencilState,
+    pub transients: TransientPool,
+    pub shadows: ShadowMaps,
+    pub clusters: LightClusters,
     pub glyphon: GlyphonRender,
     pub camera_bg_cache_key: CacheKey,
     environment_bg_cache_key: CacheKey,
     light_bg_cache_key: CacheKey,
     skybox_cache_key: CacheKey,
+    frame_camera: Option<(wgpu::Buffer, wgpu::BindGroup)>,
+    skin_bind_groups: SkinBindGroups,
 }
 
 impl Renderer3D {
     pub fn new(
         ctx: RenderInfo,
-        hdr: hdr::HdrPipeline,
-        depth_texture: DepthTexture,
+        post: PostProcessStack,
+        depth_stencil: wgpu::DepthStencilState,
+        transients: TransientPool,
+        shadows: ShadowMaps,
+        clusters: LightClusters,
         glyphon: GlyphonRender,
         camera_bg_cache_key: CacheKey,
         environment_bg_cache_key: CacheKey,
@@ -63,13 +75,18 @@ impl Renderer3D {
     ) -> Self {
         Self {
             ctx,
-            hdr,
-            depth_texture,
+            post,
+            depth_stencil,
+            transients,
+            shadows,
+            clusters,
             glyphon,
             camera_bg_cache_key,
             environment_bg_cache_key,
             light_bg_cache_key,
             skybox_cache_key,
+            frame_camera: None,
+            skin_bind_groups: SkinBindGroups::new(),
         }
     }
 
@@ -77,207 +94,160 @@ impl Renderer3D {
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        target: &RenderSurface,
+        target: &RenderTarget,
         world: &mut World,
         resources: &mut ResourceContext,
         camera_handler: &CameraHandler,
         frustum: &Frustum,
         uniforms: &Uniforms,
-    ) -> Result<(), wgpu::SurfaceError> {
-        let debug_mode = self.ctx.debug_mode();
-        let ctx = &mut self.ctx;
-
-        let output = target.get_current_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Render Encoder"),
+    ) -> Result<(), AppError> {
+        let frame = target.acquire()?;
+        let config = target.config();
+        self.transients.resize(config.width, config.height);
+        self.shadows
+            .update(queue, camera_handler, &uniforms.lighting);
+        self.clusters
+            .update(queue, camera_handler, config.width, config.height);
+        self.post.update(queue);
+
+        let (camera_buffer, camera_bind_group) = &*self.frame_camera.get_or_insert_with(|| {
+            let buffer = BufferFactory::create_camera_uniform_buffer(device, uniforms.camera);
+            let bind_group = create_camera_bind_group(device, &buffer);
+            (buffer, bind_group)
         });
+        queue.write_buffer(camera_buffer, 0, bytemuck::cast_slice(&[uniforms.camera]));
+        let mut skeletons = Vec::new();
+        let _ = world.query::<Skeleton>(|entity, _| {
+            skeletons.push(Skeleton::joint_buffer_key(entity));
+        });
+        self.skin_bind_groups.update(
+            device,
+            &resources.bind_group_manager.bind_group_layouts,
+            camera_buffer,
+            &resources.buffer_manager.buffers,
+            &skeletons,
+        );
 
-        let bind_group_manager = &mut resources.bind_group_manager;
-
-        let environment_bind_group = bind_group_manager
-            .bind_groups
-            .get(&self.environment_bg_cache_key)
-            .expect("Environment bind group not found");
-        let light_bind_group = bind_group_manager
-            .bind_groups
-            .get(&self.light_bg_cache_key)
-            .expect("Light bind group not found");
-        let texture_bind_group = bind_group_manager
-            .bind_groups
-            .get(&CacheKey::from("Material.001"))
-            .expect("Texture bind group not found");
-
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &self.hdr.view(),
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_texture.current.view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
-
-            let uniform_buffer =
-                BufferFactory::create_camera_uniform_buffer(&device, uniforms.camera);
-
-            let camera_bind_group = create_camera_bind_group(&device, &uniform_buffer);
-            let pipeline_label = ctx.render_mode().label();
-
-            let light_pipeline = resources
-                .pipeline_manager
-                .pipelines
-                .get(&CacheKey::from(
-                    format!("{}_light_pipeline", pipeline_label).as_str(),
-                ))
-                .expect("Light pipeline not found");
-
-            let normal_pipeline = resources
-                .pipeline_manager
-                .pipelines
-                .get(&CacheKey::from(
-                    format!("{}_pipeline", pipeline_label).as_str(),
-                ))
-                .expect("Light pipeline not found");
-
-            let _ = world.query::<Model>(|entity, model| {
-                let cache_id = CacheKey::from(entity);
-                render_pass.set_pipeline(&light_pipeline);
-                render_pass.draw_model(
-                    &model,
-                    &[&camera_bind_group, &light_bind_group],
-                    &Some(0..1),
-                    &resources.buffer_manager,
-                    &resources.mesh_manager,
-                );
-
-                if let Some(instances) = resources
-                    .instance_manager
-                    .instances
-                    .get(&CacheKey::from(entity))
-                {
-                    let total_instances = instances.len() as u32;
-                    let mut culled_instances = 0u32;
-
-                    let instance_raw_data: Vec<_> = instances
-                        .iter()
-                        .filter_map(|instance| {
-                            let center = instance.transform.position - camera_handler.position();
-                            let radius =
-                                Frustum::calculate_instance_radius(instance.transform.scale);
-                            if frustum.contains(&BoundingVolume::Sphere { center, radius }) {
-                                Some(instance.to_raw([1.0, 1.0, 1.0, 1.0]))
-                            } else {
-                                culled_instances += 1;
-                                if debug_mode == DebugMode::Verbose
-                                    || debug_mode == DebugMode::Minimal
-                                {
-                                    Some(instance.to_raw([1.0, 1.0, 1.0, 0.1]))
-                                } else {
-                                    None
-                                }
-                            }
-                        })
-                        .collect();
-
-                    ctx.frame_metrics
-                        .update_instance_stats(total_instances, culled_instances);
-
-                    resources.buffer_manager.create_instance_buffer(
-                        &device,
-                        &instance_raw_data,
-                        cache_id,
-                    );
-
-                    render_pass.set_vertex_buffer(
-                        1,
-                        resources
-                            .buffer_manager
-                            .get_instance_buffer(cache_id)
-                            .unwrap()
-                            .slice(..),
-                    );
+        let keys = SceneKeys {
+            environment: self.environment_bg_cache_key,
+            light: self.light_bg_cache_key,
+            skybox: self.skybox_cache_key,
+        };
+        let depth_stencil = &self.depth_stencil;
+        let post = &self.post;
+        let shadows = &self.shadows;
+        let clusters = &self.clusters;
+
+        let mut graph: RenderGraph<FrameContext> = RenderGraph::new();
+        let output = graph.import_texture(passes::SURFACE, &frame.view);
+        let sample_count = resources.pipeline_manager.sample_count();
+        let multisampled = sample_count > 1;
+        let scene_usage =
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+        let attachment_usage = if multisampled {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        } else {
+            scene_usage
+        };
+        let hdr_color = graph.create_texture(TextureDesc::surface(
+            passes::HDR_COLOR,
+            post.format(),
+            scene_usage,
+        ));
+        let targets = SceneTargets {
+            color: if multisampled {
+                graph.create_texture(
+                    TextureDesc::surface(passes::SCENE_COLOR_MSAA, post.format(), attachment_usage)
+                        .with_sample_count(sample_count),
+                )
+            } else {
+                hdr_color
+            },
+            depth: graph.create_texture(
+                TextureDesc::surface(passes::SCENE_DEPTH, depth_stencil.format, attachment_usage)
+                    .with_sample_count(sample_count),
+            ),
+            resolve: multisampled.then_some(hdr_color),
+            shadow_atlas: shadows
+                .enabled()
+                .then(|| graph.create_texture(shadows.atlas_desc())),
+        };
+
+        if let Some(atlas) = targets.shadow_atlas {
+            graph
+                .add_pass("shadows")
+                .write(atlas)
+                .execute(move |pass, frame| passes::shadow_pass(pass, frame, atlas));
+        }
 
-                    render_pass.set_pipeline(&normal_pipeline);
-
-                    render_pass.draw_model(
-                        &model,
-                        &[
-                            texture_bind_group,
-                            &camera_bind_group,
-                            light_bind_group,
-                            environment_bind_group,
-                        ],
-                        &Some(0..instance_raw_data.len() as u32),
-                        &resources.buffer_manager,
-                        &resources.mesh_manager,
-                    );
-                }
-            });
+        let light_clusters = graph.import_buffer(LIGHT_CLUSTERS, clusters.cluster_buffer());
+        graph
+            .add_pass(LIGHT_CLUSTERS)
+            .write(light_clusters)
+            .execute(passes::light_cluster_pass);
 
-            render_pass.set_pipeline(
-                &resources
-                    .pipeline_manager
-                    .pipelines
-                    .get(&self.skybox_cache_key)
-                    .expect("Sky pipeline not found"),
-            );
-            render_pass.draw_vertices(
-                &[&camera_bind_group, environment_bind_group],
-                0..3,
-                Some(0..1),
-            );
-
-            self.glyphon.render(
-                &mut render_pass,
-                &device,
-                &queue,
-                &target.config,
-                self.depth_texture.stencil_state.depth_write_enabled,
-            );
-
-            if self.ctx.debug_mode() == DebugMode::Verbose {
-                Self::debug_render_pass(
-                    &device,
-                    &Some(self.depth_texture.current.texture.format()),
-                    &mut render_pass,
-                    camera_handler,
-                );
-            }
+        let mut opaque = graph.add_pass("opaque").read(light_clusters);
+        if let Some(atlas) = targets.shadow_atlas {
+            opaque = opaque.read(atlas);
+        }
+        opaque
+            .write(targets.color)
+            .write(targets.depth)
+            .execute(move |pass, frame| passes::opaque_pass(pass, frame, targets, keys));
+        graph
+            .add_pass("sky")
+            .write(targets.color)
+            .write(targets.depth)
+            .execute(move |pass, frame| passes::sky_pass(pass, frame, targets, keys));
+        let mut overlay = graph
+            .add_pass("overlay")
+            .write(targets.color)
+            .write(targets.depth);
+        if let Some(resolve) = targets.resolve {
+            overlay = overlay.write(resolve);
+        }
+        overlay
+            .execute(move |pass, frame| passes::overlay_pass(pass, frame, targets, depth_stencil));
+        passes::post_process_passes(&mut graph, post, targets.output(), output);
+        if let (Some(atlas), true) = (targets.shadow_atlas, shadows.show_atlas()) {
+            graph
+                .add_pass("shadow_atlas")
+                .read(atlas)
+                .write(output)
+                .execute(move |pass, frame| passes::shadow_atlas_pass(pass, frame, atlas, output));
         }
 
-        self.hdr.process(&mut encoder, &view);
-
-        queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        let mut frame_context = FrameContext {
+            ctx: &mut self.ctx,
+            world,
+            resources,
+            glyphon: &mut self.glyphon,
+            camera_handler,
+            frustum,
+            shadows,
+            clusters,
+            config,
+            color_format: post.format(),
+            sample_count,
+            camera_bind_group,
+            skin_bind_groups: &self.skin_bind_groups,
+        };
+        graph.execute(device, queue, &mut self.transients, &mut frame_context)?;
+
+        frame.present();
         self.glyphon.clear_buffer();
 
         Ok(())
     }
+    pub fn set_sample_count(&mut self, device: &wgpu::Device, sample_count: u32) {
+        self.glyphon
+            .set_sample_count(device, &self.depth_stencil, sample_count);
+    }
+
     pub fn debug_render_pass(
         device: &wgpu::Device,
+        color_format: wgpu::TextureFormat,
+        sample_count: u32,
         depth_format: &Option<wgpu::TextureFormat>,
         render_pass: &mut wgpu::RenderPass,
         camera: &CameraHandler,
@@ -331,7 +301,7 @@ impl Renderer3D {
                 module: &shader_module,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                    format: color_format,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
@@ -348,7 +318,10 @@ impl Renderer3D {
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
-            multisample: wgpu::MultisampleState::default(),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
             multiview: None,
             cache: Default::default(),
         });
@@ -357,18 +330,9 @@ impl Renderer3D {
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
         render_pass.draw(0..debug_line_vertices.len() as u32, 0..1);
     }
-    pub fn resize_textures<P: winit::dpi::Pixel>(
-        &mut self,
-        device: &wgpu::Device,
-        size: PhysicalSize<P>,
-        resources: &ResourceContext,
-    ) {
-        self.hdr.resize(
-            device,
-            size,
-            &resources.bind_group_manager.bind_group_layouts,
-        );
-        self.depth_texture.resize(device, size);
+    pub fn resize_textures<P: winit::dpi::Pixel>(&mut self, size: PhysicalSize<P>) {
+        self.transients
+            .resize(size.width.cast(), size.height.cast());
     }
 }
 
@@ -468,6 +432,10 @@ impl RenderInfo {
         self.frame_metrics = frame_metrics;
     }
 
+    pub fn update_instance_stats(&mut self, total: u32, culled: u32) {
+        self.frame_metrics.update_instance_stats(total, culled);
+    }
+
     pub fn debug_mode(&self) -> DebugMode {
         self.debug_mode
     }
@@ -603,6 +571,32 @@ impl BufferManager {
             AppError::ResourceNotFound(format!("Index buffer with ID {:?} not found", id))
         })
     }
+    pub fn update_storage_buffer<T: bytemuck::Pod>(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &[T],
+        id: CacheKey,
+    ) {
+        let size = std::mem::size_of_val(data) as wgpu::BufferAddress;
+        if self
+            .buffers
+            .get(&id)
+            .is_some_and(|buffer| buffer.size() >= size)
+        {
+            if let Some(buffer) = self.buffers.get(&id) {
+                queue.write_buffer(buffer, 0, bytemuck::cast_slice(data));
+            }
+            return;
+        }
+        let buffer = Self::create_buffer(
+            device,
+            data,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            &format!("Storage buffer: {:?}", id),
+        );
+        self.buffers.put(id, buffer);
+    }
     pub fn get_or_create_buffer(
         &mut self,
         cache_id: CacheKey,
@@ -648,6 +642,17 @@ impl BufferFactory {
             "CameraUniformBuffer",
         )
     }
+    pub fn create_material_uniform_buffer(
+        device: &wgpu::Device,
+        uniform: MaterialUniform,
+    ) -> wgpu::Buffer {
+        Self::create_buffer(
+            device,
+            &[uniform],
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            "MaterialUniformBuffer",
+        )
+    }
     pub fn create_light_buffer(device: &wgpu::Device) -> Buffer {
         let data = &[LightUniform::new([2.0, 2.0, 2.0], [1.0, 1.0, 1.0])];
         Self::create_buffer(
@@ -694,6 +699,9 @@ where
         set_bind_groups(self, bind_groups);
         for mesh_id in &model.mesh_ids {
             if let Some(mesh) = mesh_manager.meshes.get(&mesh_id) {
+                if mesh.is_skinned() {
+                    continue;
+                }
                 if let (Ok(vertex_buffer), Ok(index_buffer)) = (
                     buffer_manager.get_vertex_buffer(&mesh.vertex_buffer_key),
                     buffer_manager.get_index_buffer(&mesh.index_buffer_key),
@@ -752,6 +760,18 @@ where
         }
     }
 
+    fn draw_mesh_lod(
+        &mut self,
+        vertex_buffer: &wgpu::Buffer,
+        index_buffer: &wgpu::Buffer,
+        num_elements: u32,
+        instances: Range<u32>,
+    ) {
+        self.set_vertex_buffer(0, vertex_buffer.slice(..));
+        self.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        self.draw_indexed(0..num_elements, 0, instances);
+    }
+
     fn draw_vertices(
         &mut self,
         bind_groups: &[&'b wgpu::BindGroup],
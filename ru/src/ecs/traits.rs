@@ -43,6 +43,14 @@ pub trait RenderPassDraw<'a> {
         instances: Option<Range<u32>>,
     );
 
+    fn draw_mesh_lod(
+        &mut self,
+        vertex_buffer: &wgpu::Buffer,
+        index_buffer: &wgpu::Buffer,
+        num_elements: u32,
+        instances: Range<u32>,
+    );
+
     fn draw_vertices(
         &mut self,
         bind_groups: &[&'a wgpu::BindGroup],
@@ -29,3 +29,53 @@ pub fn create_camera_bind_group(device: &Device, camera_buffer: &Buffer) -> Bind
     });
     bind_group
 }
+
+pub fn create_skinned_camera_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+        label: Some("skinned_camera_bind_group_layout"),
+    })
+}
+
+pub fn create_skinned_camera_bind_group(
+    device: &Device,
+    layout: &wgpu::BindGroupLayout,
+    camera_buffer: &Buffer,
+    joint_buffer: &Buffer,
+) -> BindGroup {
+    device.create_bind_group(&BindGroupDescriptor {
+        layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: joint_buffer.as_entire_binding(),
+            },
+        ],
+        label: Some("skinned_camera_bind_group"),
+    })
+}
@@ -0,0 +1,70 @@
+use wgpu::{BindGroup, BindGroupLayout, Device};
+
+use crate::graphics::pipelines::ibl::EnvironmentMaps;
+
+pub fn create_environment_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    environment: &EnvironmentMaps,
+) -> BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("environment_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(environment.sky.view()),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(environment.sky.sampler()),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(environment.irradiance.view()),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(environment.prefiltered.view()),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: wgpu::BindingResource::TextureView(&environment.brdf_lut.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: wgpu::BindingResource::Sampler(&environment.sampler),
+            },
+        ],
+    })
+}
+
+pub fn create_environment_bind_group_layout(device: &Device) -> BindGroupLayout {
+    let texture_entry = |binding, filterable, view_dimension| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable },
+            view_dimension,
+            multisampled: false,
+        },
+        count: None,
+    };
+    let sampler_entry = |binding, sampler_type| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Sampler(sampler_type),
+        count: None,
+    };
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("environment_bind_group_layout"),
+        entries: &[
+            texture_entry(0, false, wgpu::TextureViewDimension::Cube),
+            sampler_entry(1, wgpu::SamplerBindingType::NonFiltering),
+            texture_entry(2, true, wgpu::TextureViewDimension::Cube),
+            texture_entry(3, true, wgpu::TextureViewDimension::Cube),
+            texture_entry(4, true, wgpu::TextureViewDimension::D2),
+            sampler_entry(5, wgpu::SamplerBindingType::Filtering),
+        ],
+    })
+}
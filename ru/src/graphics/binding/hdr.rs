@@ -1,7 +1,5 @@
 use wgpu::BindGroupLayout;
 
-use crate::graphics::textures::Texture;
-
 pub fn create_hdr_pipeline_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
     let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         label: Some("hdr_pipeline_bind_group_layout"),
@@ -22,6 +20,26 @@ pub fn create_hdr_pipeline_bind_group_layout(device: &wgpu::Device) -> wgpu::Bin
                 ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                 count: None,
             },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
         ],
     });
 
@@ -30,7 +48,10 @@ pub fn create_hdr_pipeline_bind_group_layout(device: &wgpu::Device) -> wgpu::Bin
 pub fn create_hdr_pipeline_bind_group(
     device: &wgpu::Device,
     layout: &BindGroupLayout,
-    texture: &Texture,
+    view: &wgpu::TextureView,
+    bloom: &wgpu::TextureView,
+    sampler: &wgpu::Sampler,
+    post_buffer: &wgpu::Buffer,
 ) -> wgpu::BindGroup {
     let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
         label: Some("hdr_pipeline_bind_group"),
@@ -38,11 +59,19 @@ pub fn create_hdr_pipeline_bind_group(
         entries: &[
             wgpu::BindGroupEntry {
                 binding: 0,
-                resource: wgpu::BindingResource::TextureView(&texture.view),
+                resource: wgpu::BindingResource::TextureView(view),
             },
             wgpu::BindGroupEntry {
                 binding: 1,
-                resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: post_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(bloom),
             },
         ],
     });
@@ -1,43 +1,106 @@
-use crate::{
-    core::error::AppError,
-    graphics::textures::{BindableTexture, Texture},
-};
+use crate::graphics::textures::{BindableTexture, Texture};
+
+pub const MATERIAL_TEXTURE_SLOTS: usize = 2;
+pub const MATERIAL_UNIFORM_BINDING: u32 = MATERIAL_TEXTURE_SLOTS as u32 * 2;
+
+pub fn create_material_bind_group_layout(
+    device: &wgpu::Device,
+    texture_slots: usize,
+    label: &str,
+) -> wgpu::BindGroupLayout {
+    let mut entries = Vec::with_capacity(texture_slots * 2 + 1);
+    for slot in 0..texture_slots as u32 {
+        entries.push(wgpu::BindGroupLayoutEntry {
+            binding: slot * 2,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            count: None,
+        });
+        entries.push(wgpu::BindGroupLayoutEntry {
+            binding: slot * 2 + 1,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        });
+    }
+    entries.push(wgpu::BindGroupLayoutEntry {
+        binding: texture_slots as u32 * 2,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    });
+
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &entries,
+        label: Some(label),
+    })
+}
 
 pub fn create_material_bind_group(
     device: &wgpu::Device,
     layout: &wgpu::BindGroupLayout,
-    diffuse_texture: Option<&Texture>,
-    normal_texture: Option<&Texture>,
-) -> Result<wgpu::BindGroup, AppError> {
-    let mut entries = Vec::new();
-
-    if let Some(diffuse) = diffuse_texture {
+    diffuse_texture: &Texture,
+    normal_texture: &Texture,
+    material_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    let mut entries = Vec::with_capacity(MATERIAL_TEXTURE_SLOTS * 2 + 1);
+    for texture in [diffuse_texture, normal_texture] {
         entries.push(wgpu::BindGroupEntry {
             binding: entries.len() as u32,
-            resource: wgpu::BindingResource::TextureView(diffuse.view()),
+            resource: wgpu::BindingResource::TextureView(texture.view()),
         });
         entries.push(wgpu::BindGroupEntry {
             binding: entries.len() as u32,
-            resource: wgpu::BindingResource::Sampler(diffuse.sampler()),
+            resource: wgpu::BindingResource::Sampler(texture.sampler()),
         });
     }
+    entries.push(wgpu::BindGroupEntry {
+        binding: MATERIAL_UNIFORM_BINDING,
+        resource: material_buffer.as_entire_binding(),
+    });
 
-    if let Some(normal) = normal_texture {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &entries,
+        label: Some("material_bind_group"),
+    })
+}
+
+pub const PBR_TEXTURE_SLOTS: usize = 5;
+
+pub fn create_pbr_material_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    textures: [&Texture; PBR_TEXTURE_SLOTS],
+    material_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    let mut entries = Vec::with_capacity(PBR_TEXTURE_SLOTS * 2 + 1);
+    for texture in textures {
         entries.push(wgpu::BindGroupEntry {
             binding: entries.len() as u32,
-            resource: wgpu::BindingResource::TextureView(normal.view()),
+            resource: wgpu::BindingResource::TextureView(texture.view()),
         });
         entries.push(wgpu::BindGroupEntry {
             binding: entries.len() as u32,
-            resource: wgpu::BindingResource::Sampler(normal.sampler()),
+            resource: wgpu::BindingResource::Sampler(texture.sampler()),
         });
     }
+    entries.push(wgpu::BindGroupEntry {
+        binding: entries.len() as u32,
+        resource: material_buffer.as_entire_binding(),
+    });
 
-    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
         layout,
         entries: &entries,
-        label: Some("material_bind_group"),
-    });
-
-    Ok(bind_group)
+        label: Some("pbr_material_bind_group"),
+    })
 }
@@ -1,17 +1,28 @@
 pub mod camera;
+pub mod cluster;
+pub mod environment;
 pub mod equirect;
 pub mod hdr;
 pub mod light;
 pub mod material;
-pub mod skybox;
+pub mod shadow;
+pub mod skin;
 pub mod texture;
-use camera::{create_camera_bind_group, create_camera_bind_group_layout};
+use camera::{
+    create_camera_bind_group, create_camera_bind_group_layout,
+    create_skinned_camera_bind_group_layout,
+};
+use cluster::create_light_cluster_bind_group_layout;
+use environment::{create_environment_bind_group, create_environment_bind_group_layout};
 use equirect::create_equirect_bind_group_layout;
 use hdr::create_hdr_pipeline_bind_group_layout;
-use light::{create_light_bind_group, create_light_bind_group_layout};
-use skybox::{create_skybox_bind_group, create_skybox_bind_group_layout};
+use light::create_light_bind_group_layout;
+use material::{create_material_bind_group_layout, MATERIAL_TEXTURE_SLOTS, PBR_TEXTURE_SLOTS};
+use shadow::{
+    create_shadow_atlas_bind_group_layout, create_shadow_caster_bind_group_layout,
+    create_shadow_joints_bind_group_layout,
+};
 
-use texture::create_texture_bind_group_layout;
 use wgpu::{BindGroup, BindGroupLayout, Device, TextureFormat};
 
 use crate::{
@@ -23,7 +34,7 @@ use crate::{
     prelude::cache::{CacheKey, HashCache},
 };
 
-use super::{textures::cube_texture::CubeTexture, uniform::Uniforms};
+use super::{pipelines::ibl::EnvironmentMaps, uniform::Uniforms};
 
 pub const INDEX_LIGHT_BIND_GROUP: isize = 0;
 pub const INDEX_CAMERA_BIND_GROUP: isize = 1;
@@ -37,23 +48,16 @@ pub struct BindGroupManager {
 pub fn initialize_common_bind_groups(
     device: &wgpu::Device,
     uniforms: &Uniforms,
-    sky_texture: CubeTexture,
+    environment: &EnvironmentMaps,
     environment_bg_cache_key: CacheKey,
     light_bg_cache_key: CacheKey,
     camera_bg_cache_key: CacheKey,
     buffer_manager: &mut BufferManager,
     bind_group_manager: &mut BindGroupManager,
 ) -> Result<(), AppError> {
-    bind_group_manager
-        .bind_groups
-        .get_or_create(light_bg_cache_key, || {
-            Ok(create_light_bind_group(
-                device,
-                buffer_manager.get_or_create_buffer(light_bg_cache_key, || {
-                    Ok(BufferFactory::create_light_buffer(device))
-                })?,
-            ))
-        })?;
+    buffer_manager.get_or_create_buffer(light_bg_cache_key, || {
+        Ok(BufferFactory::create_light_buffer(device))
+    })?;
     bind_group_manager
         .bind_groups
         .get_or_create(camera_bg_cache_key, || {
@@ -71,7 +75,13 @@ pub fn initialize_common_bind_groups(
     bind_group_manager
         .bind_groups
         .get_or_create(environment_bg_cache_key, || {
-            Ok(create_skybox_bind_group(device, &sky_texture))
+            Ok(create_environment_bind_group(
+                device,
+                &bind_group_manager
+                    .bind_group_layouts
+                    .environment_bind_group_layout,
+                environment,
+            ))
         })?;
 
     Ok(())
@@ -90,30 +100,48 @@ impl BindGroupManager {
 
 #[derive(Debug)]
 pub struct BindGroupLayouts {
-    pub texture_bind_group_layout: BindGroupLayout,
+    pub material_bind_group_layout: BindGroupLayout,
+    pub pbr_material_bind_group_layout: BindGroupLayout,
     pub camera_bind_group_layout: BindGroupLayout,
+    pub skinned_camera_bind_group_layout: BindGroupLayout,
     pub light_bind_group_layout: BindGroupLayout,
-    pub skybox_bind_group_layout: BindGroupLayout,
+    pub environment_bind_group_layout: BindGroupLayout,
     pub equirect_bind_group_layout: BindGroupLayout,
     pub hdr_pipeline_bind_group_layout: BindGroupLayout,
+    pub shadow_caster_bind_group_layout: BindGroupLayout,
+    pub shadow_joints_bind_group_layout: BindGroupLayout,
+    pub shadow_atlas_bind_group_layout: BindGroupLayout,
+    pub light_cluster_bind_group_layout: BindGroupLayout,
 }
 
 impl BindGroupLayouts {
     pub fn new(
-        texture_bind_group_layout: BindGroupLayout,
+        material_bind_group_layout: BindGroupLayout,
+        pbr_material_bind_group_layout: BindGroupLayout,
         camera_bind_group_layout: BindGroupLayout,
+        skinned_camera_bind_group_layout: BindGroupLayout,
         light_bind_group_layout: BindGroupLayout,
-        skybox_bind_group_layout: BindGroupLayout,
+        environment_bind_group_layout: BindGroupLayout,
         equirect_bind_group_layout: BindGroupLayout,
         hdr_pipeline_bind_group_layout: BindGroupLayout,
+        shadow_caster_bind_group_layout: BindGroupLayout,
+        shadow_joints_bind_group_layout: BindGroupLayout,
+        shadow_atlas_bind_group_layout: BindGroupLayout,
+        light_cluster_bind_group_layout: BindGroupLayout,
     ) -> Self {
         Self {
-            texture_bind_group_layout,
+            material_bind_group_layout,
+            pbr_material_bind_group_layout,
             camera_bind_group_layout,
+            skinned_camera_bind_group_layout,
             light_bind_group_layout,
-            skybox_bind_group_layout,
+            environment_bind_group_layout,
             equirect_bind_group_layout,
             hdr_pipeline_bind_group_layout,
+            shadow_caster_bind_group_layout,
+            shadow_joints_bind_group_layout,
+            shadow_atlas_bind_group_layout,
+            light_cluster_bind_group_layout,
         }
     }
 }
@@ -122,20 +150,40 @@ pub fn setup_bind_group_layouts(
     device: &Device,
     equirect_texture_format: TextureFormat,
 ) -> BindGroupLayouts {
-    let texture_bind_group_layout = create_texture_bind_group_layout(device, true);
+    let material_bind_group_layout = create_material_bind_group_layout(
+        device,
+        MATERIAL_TEXTURE_SLOTS,
+        "material_bind_group_layout",
+    );
+    let pbr_material_bind_group_layout = create_material_bind_group_layout(
+        device,
+        PBR_TEXTURE_SLOTS,
+        "pbr_material_bind_group_layout",
+    );
     let camera_bind_group_layout = create_camera_bind_group_layout(device);
+    let skinned_camera_bind_group_layout = create_skinned_camera_bind_group_layout(device);
     let light_bind_group_layout = create_light_bind_group_layout(device);
-    let skybox_bind_group_layout = create_skybox_bind_group_layout(device);
+    let environment_bind_group_layout = create_environment_bind_group_layout(device);
     let hdr_pipeline_bind_group_layout = create_hdr_pipeline_bind_group_layout(device);
     let equirect_bind_group_layout =
         create_equirect_bind_group_layout(device, equirect_texture_format);
+    let shadow_caster_bind_group_layout = create_shadow_caster_bind_group_layout(device);
+    let shadow_joints_bind_group_layout = create_shadow_joints_bind_group_layout(device);
+    let shadow_atlas_bind_group_layout = create_shadow_atlas_bind_group_layout(device);
+    let light_cluster_bind_group_layout = create_light_cluster_bind_group_layout(device);
 
     BindGroupLayouts::new(
-        texture_bind_group_layout,
+        material_bind_group_layout,
+        pbr_material_bind_group_layout,
         camera_bind_group_layout,
+        skinned_camera_bind_group_layout,
         light_bind_group_layout,
-        skybox_bind_group_layout,
+        environment_bind_group_layout,
         equirect_bind_group_layout,
         hdr_pipeline_bind_group_layout,
+        shadow_caster_bind_group_layout,
+        shadow_joints_bind_group_layout,
+        shadow_atlas_bind_group_layout,
+        light_cluster_bind_group_layout,
     )
 }
@@ -0,0 +1,102 @@
+use wgpu::{BindGroup, BindGroupLayout, Buffer, Device};
+
+use crate::graphics::uniform::shadow::ShadowCasterUniform;
+
+pub fn create_shadow_caster_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: wgpu::BufferSize::new(
+                    std::mem::size_of::<ShadowCasterUniform>() as u64
+                ),
+            },
+            count: None,
+        }],
+        label: Some("shadow_caster_bind_group_layout"),
+    })
+}
+
+pub fn create_shadow_caster_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    caster_buffer: &Buffer,
+) -> BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: caster_buffer,
+                offset: 0,
+                size: wgpu::BufferSize::new(std::mem::size_of::<ShadowCasterUniform>() as u64),
+            }),
+        }],
+        label: Some("shadow_caster_bind_group"),
+    })
+}
+
+pub fn create_shadow_joints_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }],
+        label: Some("shadow_joints_bind_group_layout"),
+    })
+}
+
+pub fn create_shadow_joints_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    joint_buffer: &Buffer,
+) -> BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: joint_buffer.as_entire_binding(),
+        }],
+        label: Some("shadow_joints_bind_group"),
+    })
+}
+
+pub fn create_shadow_atlas_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        }],
+        label: Some("shadow_atlas_bind_group_layout"),
+    })
+}
+
+pub fn create_shadow_atlas_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    atlas: &wgpu::TextureView,
+) -> BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(atlas),
+        }],
+        label: Some("shadow_atlas_bind_group"),
+    })
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use wgpu::{BindGroup, Buffer, Device};
+
+use crate::{
+    ecs::traits::Cache,
+    prelude::cache::{CacheKey, HashCache},
+};
+
+use super::{
+    camera::create_skinned_camera_bind_group, shadow::create_shadow_joints_bind_group,
+    BindGroupLayouts,
+};
+
+struct SkinBindGroup {
+    joint_buffer: wgpu::Id<Buffer>,
+    camera: BindGroup,
+    shadow: BindGroup,
+}
+
+/// Bind groups over each skeleton's joint buffer, kept across frames. Joint matrices are
+/// written into the existing buffer, so a group is only rebuilt when that buffer is replaced.
+#[derive(Default)]
+pub struct SkinBindGroups {
+    groups: HashMap<CacheKey, SkinBindGroup>,
+}
+
+impl SkinBindGroups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ensures a bind group for every joint buffer in `keys` and drops the rest.
+    pub fn update(
+        &mut self,
+        device: &Device,
+        layouts: &BindGroupLayouts,
+        camera_buffer: &Buffer,
+        buffers: &HashCache<Buffer>,
+        keys: &[CacheKey],
+    ) {
+        self.groups.retain(|key, _| keys.contains(key));
+        for key in keys {
+            let Some(joint_buffer) = buffers.get(key) else {
+                self.groups.remove(key);
+                continue;
+            };
+            if self
+                .groups
+                .get(key)
+                .is_some_and(|group| group.joint_buffer == joint_buffer.global_id())
+            {
+                continue;
+            }
+            self.groups.insert(
+                *key,
+                SkinBindGroup {
+                    joint_buffer: joint_buffer.global_id(),
+                    camera: create_skinned_camera_bind_group(
+                        device,
+                        &layouts.skinned_camera_bind_group_layout,
+                        camera_buffer,
+                        joint_buffer,
+                    ),
+                    shadow: create_shadow_joints_bind_group(
+                        device,
+                        &layouts.shadow_joints_bind_group_layout,
+                        joint_buffer,
+                    ),
+                },
+            );
+        }
+    }
+
+    pub fn contains(&self, key: &CacheKey) -> bool {
+        self.groups.contains_key(key)
+    }
+
+    pub fn camera(&self, key: &CacheKey) -> Option<&BindGroup> {
+        self.groups.get(key).map(|group| &group.camera)
+    }
+
+    pub fn shadow(&self, key: &CacheKey) -> Option<&BindGroup> {
+        self.groups.get(key).map(|group| &group.shadow)
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
@@ -1,47 +0,0 @@
-use wgpu::Device;
-
-use crate::graphics::textures::{cube_texture::CubeTexture, BindableTexture, Texture};
-
-pub fn create_skybox_bind_group(device: &Device, sky_texture: &CubeTexture) -> wgpu::BindGroup {
-    let bind_group_layout = create_skybox_bind_group_layout(device);
-    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-        label: Some("skybox_bind_group"),
-        layout: &bind_group_layout,
-        entries: &[
-            wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(&sky_texture.view()),
-            },
-            wgpu::BindGroupEntry {
-                binding: 1,
-                resource: wgpu::BindingResource::Sampler(&sky_texture.sampler()),
-            },
-        ],
-    });
-    bind_group
-}
-
-pub fn create_skybox_bind_group_layout(device: &Device) -> wgpu::BindGroupLayout {
-    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        label: Some("skybox_layout"),
-        entries: &[
-            wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Texture {
-                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
-                    view_dimension: wgpu::TextureViewDimension::Cube,
-                    multisampled: false,
-                },
-                count: None,
-            },
-            wgpu::BindGroupLayoutEntry {
-                binding: 1,
-                visibility: wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering),
-                count: None,
-            },
-        ],
-    });
-    bind_group_layout
-}
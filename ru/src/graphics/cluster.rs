@@ -0,0 +1,173 @@
+use wgpu::util::DeviceExt;
+
+use crate::{
+    camera::handler::CameraHandler,
+    core::error::AppError,
+    ecs::traits::Cache,
+    graphics::{
+        binding::{
+            cluster::create_light_cluster_bind_group, light::ClusterBindings, BindGroupLayouts,
+        },
+        pipelines::common::with_validation_scope,
+        shaders::{manager::ShaderManager, module::RupyShader},
+        uniform::cluster::{
+            ClusterUniform, LightRaw, CLUSTER_COUNT, CLUSTER_GRID, CLUSTER_STRIDE, MAX_LIGHTS,
+        },
+    },
+    log_warning,
+    prelude::cache::CacheKey,
+};
+
+pub const LIGHT_CLUSTERS: &str = "light_clusters";
+const WORKGROUP_SIZE: [u32; 3] = [4, 3, 4];
+
+pub struct LightClusters {
+    info: ClusterUniform,
+    info_buffer: wgpu::Buffer,
+    light_buffer: wgpu::Buffer,
+    cluster_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl LightClusters {
+    pub const SHADER_PATH: &'static str = "compute/light_clusters.wgsl";
+
+    pub fn new(
+        device: &wgpu::Device,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<Self, AppError> {
+        let info = ClusterUniform::default();
+        let info_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("ClusterInfoBuffer"),
+            contents: bytemuck::cast_slice(&[info]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let light_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ClusterLightBuffer"),
+            size: (MAX_LIGHTS * std::mem::size_of::<LightRaw>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let cluster_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ClusterIndexBuffer"),
+            size: (CLUSTER_COUNT * CLUSTER_STRIDE) as u64 * std::mem::size_of::<u32>() as u64,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let bind_group = create_light_cluster_bind_group(
+            device,
+            &bind_group_layouts.light_cluster_bind_group_layout,
+            &info_buffer,
+            &light_buffer,
+            &cluster_buffer,
+        );
+        let pipeline = Self::create_pipeline(device, shader_manager, bind_group_layouts)?;
+
+        Ok(Self {
+            info,
+            info_buffer,
+            light_buffer,
+            cluster_buffer,
+            bind_group,
+            pipeline,
+        })
+    }
+
+    pub fn light_count(&self) -> u32 {
+        self.info.light_count
+    }
+
+    pub fn upload_lights(&mut self, queue: &wgpu::Queue, lights: &[LightRaw]) {
+        if lights.len() > MAX_LIGHTS {
+            log_warning!(
+                "{} lights exceed the cluster limit, only the first {} are shaded",
+                lights.len(),
+                MAX_LIGHTS
+            );
+        }
+        let lights = &lights[..lights.len().min(MAX_LIGHTS)];
+        if !lights.is_empty() {
+            queue.write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(lights));
+        }
+        self.info.light_count = lights.len() as u32;
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, camera: &CameraHandler, width: u32, height: u32) {
+        let projection = &camera.projection;
+        let tan_half_fov = (projection.fovy.0 * 0.5).tan();
+        self.info.view = camera.view.calc_view_matrix().into();
+        self.info.screen_size = [width.max(1) as f32, height.max(1) as f32];
+        self.info.z_near = projection.znear;
+        self.info.z_far = projection.zfar;
+        self.info.tan_half_fov = [tan_half_fov * projection.aspect as f32, tan_half_fov];
+        queue.write_buffer(&self.info_buffer, 0, bytemuck::cast_slice(&[self.info]));
+    }
+
+    pub fn dispatch(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some(LIGHT_CLUSTERS),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.dispatch_workgroups(
+            CLUSTER_GRID[0].div_ceil(WORKGROUP_SIZE[0]),
+            CLUSTER_GRID[1].div_ceil(WORKGROUP_SIZE[1]),
+            CLUSTER_GRID[2].div_ceil(WORKGROUP_SIZE[2]),
+        );
+    }
+
+    pub fn bindings(&self) -> ClusterBindings<'_> {
+        ClusterBindings {
+            info: &self.info_buffer,
+            lights: &self.light_buffer,
+            clusters: &self.cluster_buffer,
+        }
+    }
+
+    pub fn cluster_buffer(&self) -> &wgpu::Buffer {
+        &self.cluster_buffer
+    }
+
+    pub fn reload_pipeline(
+        &mut self,
+        device: &wgpu::Device,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<(), AppError> {
+        self.pipeline = with_validation_scope(device, || {
+            Self::create_pipeline(device, shader_manager, bind_group_layouts)
+        })??;
+        Ok(())
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<wgpu::ComputePipeline, AppError> {
+        let shader = shader_manager
+            .shaders
+            .get_or_create(CacheKey::from(Self::SHADER_PATH), || {
+                RupyShader::load(device, Self::SHADER_PATH)
+            })?;
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Light Cluster Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layouts.light_cluster_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        Ok(
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(LIGHT_CLUSTERS),
+                layout: Some(&layout),
+                module: &shader.module,
+                entry_point: "assign_lights",
+                compilation_options: Default::default(),
+                cache: None,
+            }),
+        )
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{
+    core::{cache::CacheKey, error::AppError},
+    ecs::components::{
+        mesh::{
+            manager::create_cached_mesh_with_buffers,
+            processing::{sanitize_indices, MeshData, MeshProcessingOptions, NormalMode},
+        },
+        ResourceContext,
+    },
+    graphics::vertex::{ModelVertex, VertexType},
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct MeshBuilder {
+    vertices: Vec<ModelVertex>,
+    indices: Vec<u32>,
+    normal_mode: Option<NormalMode>,
+}
+
+impl MeshBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn vertex(&mut self, position: [f32; 3], normal: [f32; 3], tex_coords: [f32; 2]) -> u32 {
+        self.vertices.push(ModelVertex {
+            position,
+            tex_coords,
+            normal,
+            ..Default::default()
+        });
+        (self.vertices.len() - 1) as u32
+    }
+
+    pub fn triangle(&mut self, a: u32, b: u32, c: u32) -> &mut Self {
+        self.indices.extend_from_slice(&[a, b, c]);
+        self
+    }
+
+    pub fn quad(&mut self, a: u32, b: u32, c: u32, d: u32) -> &mut Self {
+        self.triangle(a, b, c).triangle(a, c, d)
+    }
+
+    pub fn generate_normals(&mut self, mode: NormalMode) -> &mut Self {
+        self.normal_mode = Some(mode);
+        self
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn build(self) -> MeshData {
+        MeshData {
+            indices: sanitize_indices(&self.indices, self.vertices.len()),
+            has_normals: self.normal_mode.is_none(),
+            has_tex_coords: true,
+            vertices: self.vertices,
+        }
+        .process(&MeshProcessingOptions {
+            normal_mode: self.normal_mode.unwrap_or(NormalMode::Smooth),
+            weld: false,
+            ..Default::default()
+        })
+    }
+
+    pub fn build_mesh(
+        self,
+        device: &wgpu::Device,
+        resources: &mut ResourceContext,
+        name: &str,
+    ) -> Result<CacheKey, AppError> {
+        let data = self.build();
+        create_cached_mesh_with_buffers(
+            device,
+            &mut resources.mesh_manager.meshes,
+            &mut resources.buffer_manager.buffers,
+            data.vertices.into_iter().map(VertexType::Modeled).collect(),
+            data.indices,
+            None,
+            name.to_string(),
+            Vec::new(),
+        )
+    }
+}
@@ -1,30 +1,107 @@
 use serde::{Deserialize, Serialize};
 
-// pub mod cube;
-// pub mod hexagon;
-// pub mod plane;
-// pub mod rectangle;
-// pub mod sphere;
-// pub mod triangle;
+use crate::{
+    core::{
+        cache::{CacheKey, HasCacheKey},
+        error::AppError,
+    },
+    ecs::{
+        components::{model::model::Model, ResourceContext},
+        traits::Cache,
+    },
+};
+
+pub use crate::ecs::components::mesh::processing::NormalMode;
+pub use builder::MeshBuilder;
+
+pub mod builder;
+pub mod cube;
+pub mod hexagon;
+pub mod plane;
+pub mod rectangle;
+pub mod sphere;
+pub mod triangle;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, PartialOrd)]
 pub enum GeometryDimension {
     D2,
     D3,
 }
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum GeometryId {
     Triangle,
     Cube,
+    Sphere,
+    Plane,
+    Rectangle,
+    Hexagon,
 }
 impl Serialize for GeometryId {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        Ok(match self {
-            GeometryId::Triangle => serializer.collect_str("Triangle")?,
-            GeometryId::Cube => serializer.collect_str("Cube")?,
-        })
+        serializer.collect_str(self.name())
+    }
+}
+
+impl GeometryId {
+    pub const ALL: [GeometryId; 6] = [
+        GeometryId::Triangle,
+        GeometryId::Cube,
+        GeometryId::Sphere,
+        GeometryId::Plane,
+        GeometryId::Rectangle,
+        GeometryId::Hexagon,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeometryId::Triangle => "Triangle",
+            GeometryId::Cube => "Cube",
+            GeometryId::Sphere => "Sphere",
+            GeometryId::Plane => "Plane",
+            GeometryId::Rectangle => "Rectangle",
+            GeometryId::Hexagon => "Hexagon",
+        }
+    }
+
+    pub fn dimension(&self) -> GeometryDimension {
+        match self {
+            GeometryId::Rectangle | GeometryId::Hexagon => GeometryDimension::D2,
+            _ => GeometryDimension::D3,
+        }
+    }
+
+    pub fn builder(&self) -> MeshBuilder {
+        match self {
+            GeometryId::Triangle => triangle::triangle(1.0),
+            GeometryId::Cube => cube::cube(1.0),
+            GeometryId::Sphere => sphere::sphere(0.5, 32, 16),
+            GeometryId::Plane => plane::plane(1.0, 1.0, 1),
+            GeometryId::Rectangle => rectangle::rectangle(1.0, 1.0),
+            GeometryId::Hexagon => hexagon::hexagon(0.5),
+        }
+    }
+
+    pub fn model_key(&self) -> CacheKey {
+        Model::key(vec!["geometry", self.name()])
+    }
+
+    pub fn create_model(
+        &self,
+        device: &wgpu::Device,
+        resources: &mut ResourceContext,
+    ) -> Result<Model, AppError> {
+        let key = self.model_key();
+        if let Some(model) = resources.model_manager.models.get(&key) {
+            return Ok(model.clone());
+        }
+        let mesh_id =
+            self.builder()
+                .build_mesh(device, resources, &format!("geometry:{}", self.name()))?;
+        let model = Model::new(vec![mesh_id], Vec::new(), &resources.mesh_manager.meshes);
+        resources.model_manager.models.put(key, model.clone());
+        Ok(model)
     }
 }
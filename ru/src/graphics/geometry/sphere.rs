@@ -1,66 +1,35 @@
-use nalgebra::{Matrix4, Vector3};
-
-use crate::graphics::data::VertexStruct;
-pub struct Sphere {
-    pub vertices: Vec<VertexStruct>,
-    pub indices: Vec<u16>,
-    pub model_matrix: Matrix4<f32>,
-}
-
-impl Sphere {
-    pub fn new(
-        radius: f32,
-        sectors: usize,
-        stacks: usize,
-        position: Vector3<f32>,
-        scale: f32,
-    ) -> Self {
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
-
-        for stack in 0..=stacks {
-            let stack_angle =
-                std::f32::consts::PI / 2.0 - (stack as f32 * std::f32::consts::PI / stacks as f32);
-            let xy = radius * stack_angle.cos();
-
-            for sector in 0..=sectors {
-                let sector_angle = 2.0 * std::f32::consts::PI * sector as f32 / sectors as f32;
-                let x = xy * sector_angle.cos();
-                let y = xy * sector_angle.sin();
-
-                vertices.push(VertexStruct {
-                    position: [x, y, 1.0],
-                    normal: [0.0, 0.0, 0.0],
-                    color: [1.0, 1.0, 1.0, 1.0],
-                    tex_coords: [sector as f32 / sectors as f32, stack as f32 / stacks as f32],
-                });
-            }
+use std::f32::consts::PI;
+
+use super::builder::MeshBuilder;
+
+pub fn sphere(radius: f32, sectors: u32, stacks: u32) -> MeshBuilder {
+    let sectors = sectors.max(3);
+    let stacks = stacks.max(2);
+    let mut builder = MeshBuilder::new();
+
+    for stack in 0..=stacks {
+        let v = stack as f32 / stacks as f32;
+        let phi = v * PI;
+        for sector in 0..=sectors {
+            let u = sector as f32 / sectors as f32;
+            let theta = u * 2.0 * PI;
+            let normal = [phi.sin() * theta.cos(), phi.cos(), -phi.sin() * theta.sin()];
+            builder.vertex(normal.map(|c| c * radius), normal, [u, v]);
         }
+    }
 
-        for stack in 0..stacks {
-            let k1 = stack * (sectors + 1);
-            let k2 = k1 + sectors + 1;
-
-            for sector in 0..sectors {
-                if stack != 0 {
-                    indices.push(k1 as u16 + sector as u16);
-                    indices.push(k2 as u16 + sector as u16);
-                    indices.push(k1 as u16 + sector as u16 + 1);
-                }
-
-                if stack != stacks - 1 {
-                    indices.push(k1 as u16 + sector as u16 + 1);
-                    indices.push(k2 as u16 + sector as u16);
-                    indices.push(k2 as u16 + sector as u16 + 1);
-                }
+    let row = sectors + 1;
+    for stack in 0..stacks {
+        for sector in 0..sectors {
+            let top = stack * row + sector;
+            let bottom = top + row;
+            if stack != 0 {
+                builder.triangle(top, bottom, top + 1);
+            }
+            if stack != stacks - 1 {
+                builder.triangle(top + 1, bottom, bottom + 1);
             }
-        }
-        Self {
-            vertices,
-            indices,
-            model_matrix: (Matrix4::new_translation(&position)
-                * Matrix4::new_nonuniform_scaling(&Vector3::new(scale, scale, scale)))
-            .into(),
         }
     }
+    builder
 }
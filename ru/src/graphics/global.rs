@@ -1,6 +1,6 @@
 use std::sync::{Arc, RwLock};
 
-use crate::core::error::AppError;
+use crate::{core::error::AppError, log_info};
 use once_cell::sync::Lazy;
 use wgpu::{Adapter, Device, Instance, InstanceDescriptor};
 use wgpu::{Features, Queue};
@@ -20,11 +20,14 @@ fn new_instance() -> wgpu::Instance {
     Instance::new(InstanceDescriptor::default())
 }
 
-async fn request_adapter(instance: &wgpu::Instance) -> Result<wgpu::Adapter, AppError> {
+async fn request_adapter(
+    instance: &wgpu::Instance,
+    force_fallback_adapter: bool,
+) -> Result<wgpu::Adapter, AppError> {
     let options = wgpu::RequestAdapterOptions {
         power_preference: wgpu::PowerPreference::HighPerformance,
         compatible_surface: None,
-        force_fallback_adapter: false,
+        force_fallback_adapter,
     };
     instance
         .request_adapter(&options)
@@ -36,7 +39,8 @@ async fn request_adapter(instance: &wgpu::Instance) -> Result<wgpu::Adapter, App
 
 async fn request_device(adapter: &wgpu::Adapter) -> Result<(wgpu::Device, wgpu::Queue), AppError> {
     let adapter_features = adapter.features();
-    let desired_features = Features::all_webgpu_mask();
+    let desired_features =
+        Features::all_webgpu_mask() | Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
     let supported_features = adapter_features & desired_features;
     let desc = wgpu::DeviceDescriptor {
         label: Some("Device"),
@@ -56,6 +60,14 @@ pub static GPU_QUEUE: Lazy<Arc<RwLock<Option<GpuQueue>>>> =
     Lazy::new(|| Arc::new(RwLock::new(None)));
 
 pub async fn initialize_instance() -> Result<(), AppError> {
+    initialize_gpu(false).await
+}
+
+pub async fn initialize_headless_instance() -> Result<(), AppError> {
+    initialize_gpu(true).await
+}
+
+async fn initialize_gpu(headless: bool) -> Result<(), AppError> {
     {
         let gpu_instance = GPU_INSTANCE
             .read()
@@ -66,7 +78,15 @@ pub async fn initialize_instance() -> Result<(), AppError> {
     }
 
     let instance = new_instance();
-    let adapter = request_adapter(&instance).await?;
+    let adapter = if headless {
+        match request_adapter(&instance, true).await {
+            Ok(adapter) => adapter,
+            Err(_) => request_adapter(&instance, false).await?,
+        }
+    } else {
+        request_adapter(&instance, false).await?
+    };
+    log_info!("Using GPU adapter {:?}", adapter.get_info());
     let (device, queue) = request_device(&adapter).await?;
 
     {
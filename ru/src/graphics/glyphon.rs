@@ -24,21 +24,15 @@ impl GlyphonRender {
         queue: &Queue,
         swapchain_format: wgpu::TextureFormat,
         depth_stencil: &wgpu::DepthStencilState,
+        sample_count: u32,
     ) -> Self {
         let swash_cache = SwashCache::new();
         let cache = Cache::new(device);
         let viewport = Viewport::new(device, &cache);
         let mut atlas = TextAtlas::new(device, queue, &cache, swapchain_format);
 
-        let renderer_2d =
-            TextRenderer::new(&mut atlas, device, wgpu::MultisampleState::default(), None);
-
-        let renderer_3d = TextRenderer::new(
-            &mut atlas,
-            device,
-            wgpu::MultisampleState::default(),
-            Some(depth_stencil.clone()),
-        );
+        let (renderer_2d, renderer_3d) =
+            Self::create_renderers(&mut atlas, device, depth_stencil, sample_count);
 
         let font_system = FontSystem::new();
         let glyphon_buffer = glyphon::Buffer::new_empty(glyphon::Metrics {
@@ -58,6 +52,32 @@ impl GlyphonRender {
         }
     }
 
+    fn create_renderers(
+        atlas: &mut TextAtlas,
+        device: &Device,
+        depth_stencil: &wgpu::DepthStencilState,
+        sample_count: u32,
+    ) -> (TextRenderer, TextRenderer) {
+        let multisample = wgpu::MultisampleState {
+            count: sample_count,
+            ..Default::default()
+        };
+        let renderer_2d = TextRenderer::new(atlas, device, multisample, None);
+        let renderer_3d =
+            TextRenderer::new(atlas, device, multisample, Some(depth_stencil.clone()));
+        (renderer_2d, renderer_3d)
+    }
+
+    pub fn set_sample_count(
+        &mut self,
+        device: &Device,
+        depth_stencil: &wgpu::DepthStencilState,
+        sample_count: u32,
+    ) {
+        (self.renderer_2d, self.renderer_3d) =
+            Self::create_renderers(&mut self.atlas, device, depth_stencil, sample_count);
+    }
+
     pub fn reconfigure(&mut self, queue: &wgpu::Queue, resolution: glyphon::Resolution) {
         self.viewport.update(queue, resolution);
     }
@@ -240,6 +260,27 @@ impl GlyphonRender {
         );
     }
 
+    pub fn errors(&mut self, position: [f32; 2], errors: &[String]) {
+        for error in errors {
+            for line in error.lines() {
+                self.push_buffer_lines(
+                    line,
+                    [position[0], position[1]],
+                    None,
+                    Some(LineEnding::Lf),
+                    Some(AttrsList::new(
+                        Attrs::new()
+                            .family(Family::Monospace)
+                            .color(glyphon::Color::rgb(255, 80, 80)),
+                    )),
+                    None,
+                );
+            }
+        }
+        self.glyphon_buffer
+            .shape_until_scroll(&mut self.font_system, false);
+    }
+
     pub fn frame_metrics(&mut self, position: [f32; 2], frame_metrics: &FrameMetrics) {
         self.push_buffer_lines(
             &format!("Delta: {}", frame_metrics.delta_time),
@@ -0,0 +1,604 @@
+pub mod pool;
+pub mod resource;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::core::error::AppError;
+use pool::TransientPool;
+use resource::{Allocation, BufferDesc, BufferKey, ResourceId, TextureDesc, TextureKey};
+
+enum ResourceKind<'a> {
+    Texture(TextureDesc),
+    Buffer(BufferDesc),
+    ImportedTexture(&'a wgpu::TextureView),
+    ImportedBuffer(&'a wgpu::Buffer),
+}
+
+struct GraphResource<'a> {
+    name: String,
+    kind: ResourceKind<'a>,
+}
+
+impl GraphResource<'_> {
+    fn is_imported(&self) -> bool {
+        matches!(
+            self.kind,
+            ResourceKind::ImportedTexture(_) | ResourceKind::ImportedBuffer(_)
+        )
+    }
+}
+
+type PassExecute<'a, F> =
+    Box<dyn FnOnce(&mut PassContext<'_>, &mut F) -> Result<(), AppError> + 'a>;
+
+struct GraphPass<'a, F> {
+    name: String,
+    reads: Vec<ResourceId>,
+    writes: Vec<ResourceId>,
+    side_effects: bool,
+    execute: PassExecute<'a, F>,
+}
+
+impl<F> GraphPass<'_, F> {
+    fn accesses(&self) -> impl Iterator<Item = &ResourceId> {
+        self.reads.iter().chain(self.writes.iter())
+    }
+}
+
+pub struct PassBuilder<'g, 'a, F> {
+    graph: &'g mut RenderGraph<'a, F>,
+    name: String,
+    reads: Vec<ResourceId>,
+    writes: Vec<ResourceId>,
+    side_effects: bool,
+}
+
+impl<'g, 'a, F> PassBuilder<'g, 'a, F> {
+    pub fn read(mut self, resource: ResourceId) -> Self {
+        if !self.reads.contains(&resource) {
+            self.reads.push(resource);
+        }
+        self
+    }
+
+    pub fn write(mut self, resource: ResourceId) -> Self {
+        if !self.writes.contains(&resource) {
+            self.writes.push(resource);
+        }
+        self
+    }
+
+    pub fn side_effects(mut self) -> Self {
+        self.side_effects = true;
+        self
+    }
+
+    pub fn execute<E>(self, execute: E)
+    where
+        E: FnOnce(&mut PassContext<'_>, &mut F) -> Result<(), AppError> + 'a,
+    {
+        let reads = self
+            .reads
+            .into_iter()
+            .filter(|resource| !self.writes.contains(resource))
+            .collect();
+        self.graph.passes.push(GraphPass {
+            name: self.name,
+            reads,
+            writes: self.writes,
+            side_effects: self.side_effects,
+            execute: Box::new(execute),
+        });
+    }
+}
+
+pub struct PassContext<'p> {
+    pub device: &'p wgpu::Device,
+    pub queue: &'p wgpu::Queue,
+    pub encoder: &'p mut wgpu::CommandEncoder,
+    pass: &'p str,
+    declared: &'p [ResourceId],
+    textures: &'p [Option<&'p wgpu::TextureView>],
+    buffers: &'p [Option<&'p wgpu::Buffer>],
+}
+
+impl<'p> PassContext<'p> {
+    pub fn pass_name(&self) -> &str {
+        self.pass
+    }
+
+    fn check_declared(&self, resource: ResourceId) -> Result<(), AppError> {
+        if self.declared.contains(&resource) {
+            Ok(())
+        } else {
+            Err(AppError::RenderGraphError(format!(
+                "Pass {} accessed resource {} without declaring it",
+                self.pass,
+                resource.index()
+            )))
+        }
+    }
+
+    pub fn texture(&self, resource: ResourceId) -> Result<&'p wgpu::TextureView, AppError> {
+        self.check_declared(resource)?;
+        self.textures
+            .get(resource.index())
+            .copied()
+            .flatten()
+            .ok_or_else(|| {
+                AppError::RenderGraphError(format!(
+                    "Resource {} is not a texture in pass {}",
+                    resource.index(),
+                    self.pass
+                ))
+            })
+    }
+
+    pub fn buffer(&self, resource: ResourceId) -> Result<&'p wgpu::Buffer, AppError> {
+        self.check_declared(resource)?;
+        self.buffers
+            .get(resource.index())
+            .copied()
+            .flatten()
+            .ok_or_else(|| {
+                AppError::RenderGraphError(format!(
+                    "Resource {} is not a buffer in pass {}",
+                    resource.index(),
+                    self.pass
+                ))
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphPlan {
+    pub order: Vec<usize>,
+    names: Vec<String>,
+    allocations: Vec<Option<Allocation>>,
+}
+
+impl GraphPlan {
+    pub fn pass_names(&self) -> Vec<&str> {
+        self.order
+            .iter()
+            .map(|&pass| self.names[pass].as_str())
+            .collect()
+    }
+
+    pub fn allocation(&self, resource: ResourceId) -> Option<Allocation> {
+        self.allocations.get(resource.index()).copied().flatten()
+    }
+
+    pub fn shares_memory(&self, a: ResourceId, b: ResourceId) -> bool {
+        match (self.allocation(a), self.allocation(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    pub fn physical_count(&self) -> usize {
+        self.allocations
+            .iter()
+            .flatten()
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    fn counts(&self) -> (HashMap<TextureKey, usize>, HashMap<BufferKey, usize>) {
+        let mut textures: HashMap<TextureKey, usize> = HashMap::new();
+        let mut buffers: HashMap<BufferKey, usize> = HashMap::new();
+        for allocation in self.allocations.iter().flatten() {
+            match *allocation {
+                Allocation::Texture(key, slot) => {
+                    let count = textures.entry(key).or_default();
+                    *count = (*count).max(slot + 1);
+                }
+                Allocation::Buffer(key, slot) => {
+                    let count = buffers.entry(key).or_default();
+                    *count = (*count).max(slot + 1);
+                }
+            }
+        }
+        (textures, buffers)
+    }
+}
+
+pub struct RenderGraph<'a, F> {
+    resources: Vec<GraphResource<'a>>,
+    passes: Vec<GraphPass<'a, F>>,
+}
+
+impl<'a, F> Default for RenderGraph<'a, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, F> RenderGraph<'a, F> {
+    pub fn new() -> Self {
+        Self {
+            resources: Vec::new(),
+            passes: Vec::new(),
+        }
+    }
+
+    fn add_resource(&mut self, name: &str, kind: ResourceKind<'a>) -> ResourceId {
+        self.resources.push(GraphResource {
+            name: name.to_string(),
+            kind,
+        });
+        ResourceId(self.resources.len() - 1)
+    }
+
+    pub fn create_texture(&mut self, desc: TextureDesc) -> ResourceId {
+        let name = desc.label.clone();
+        self.add_resource(&name, ResourceKind::Texture(desc))
+    }
+
+    pub fn create_buffer(&mut self, desc: BufferDesc) -> ResourceId {
+        let name = desc.label.clone();
+        self.add_resource(&name, ResourceKind::Buffer(desc))
+    }
+
+    pub fn import_texture(&mut self, name: &str, view: &'a wgpu::TextureView) -> ResourceId {
+        self.add_resource(name, ResourceKind::ImportedTexture(view))
+    }
+
+    pub fn import_buffer(&mut self, name: &str, buffer: &'a wgpu::Buffer) -> ResourceId {
+        self.add_resource(name, ResourceKind::ImportedBuffer(buffer))
+    }
+
+    pub fn add_pass(&mut self, name: &str) -> PassBuilder<'_, 'a, F> {
+        PassBuilder {
+            graph: self,
+            name: name.to_string(),
+            reads: Vec::new(),
+            writes: Vec::new(),
+            side_effects: false,
+        }
+    }
+
+    pub fn resource_name(&self, resource: ResourceId) -> Option<&str> {
+        self.resources
+            .get(resource.index())
+            .map(|resource| resource.name.as_str())
+    }
+
+    fn resource(&self, resource: ResourceId) -> Result<&GraphResource<'a>, AppError> {
+        self.resources.get(resource.index()).ok_or_else(|| {
+            AppError::RenderGraphError(format!("Unknown resource {}", resource.index()))
+        })
+    }
+
+    fn dependencies(&self) -> Result<Vec<HashSet<usize>>, AppError> {
+        let mut writers: Vec<Vec<usize>> = vec![Vec::new(); self.resources.len()];
+        for (index, pass) in self.passes.iter().enumerate() {
+            for &resource in pass.accesses() {
+                self.resource(resource)?;
+            }
+            for resource in &pass.writes {
+                writers[resource.index()].push(index);
+            }
+        }
+
+        let mut dependencies = vec![HashSet::new(); self.passes.len()];
+        for resource_writers in &writers {
+            for pair in resource_writers.windows(2) {
+                dependencies[pair[1]].insert(pair[0]);
+            }
+        }
+        for (index, pass) in self.passes.iter().enumerate() {
+            for resource in &pass.reads {
+                match writers[resource.index()].last() {
+                    Some(&writer) => {
+                        dependencies[index].insert(writer);
+                    }
+                    None if !self.resources[resource.index()].is_imported() => {
+                        return Err(AppError::RenderGraphError(format!(
+                            "Pass {} reads {} but no pass writes it",
+                            pass.name,
+                            self.resources[resource.index()].name
+                        )));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok(dependencies)
+    }
+
+    fn sort(&self, dependencies: &[HashSet<usize>]) -> Result<Vec<usize>, AppError> {
+        let mut remaining: Vec<usize> = dependencies.iter().map(HashSet::len).collect();
+        let mut scheduled = vec![false; self.passes.len()];
+        let mut order = Vec::with_capacity(self.passes.len());
+        while order.len() < self.passes.len() {
+            let Some(next) =
+                (0..self.passes.len()).find(|&pass| !scheduled[pass] && remaining[pass] == 0)
+            else {
+                let cycle: Vec<&str> = (0..self.passes.len())
+                    .filter(|&pass| !scheduled[pass])
+                    .map(|pass| self.passes[pass].name.as_str())
+                    .collect();
+                return Err(AppError::RenderGraphError(format!(
+                    "Cycle between passes {:?}",
+                    cycle
+                )));
+            };
+            scheduled[next] = true;
+            order.push(next);
+            for (pass, pass_dependencies) in dependencies.iter().enumerate() {
+                if pass_dependencies.contains(&next) {
+                    remaining[pass] -= 1;
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    fn cull(&self, order: Vec<usize>) -> Vec<usize> {
+        let mut needed: HashSet<ResourceId> = HashSet::new();
+        let mut kept = Vec::with_capacity(order.len());
+        for &index in order.iter().rev() {
+            let pass = &self.passes[index];
+            let required = pass.side_effects
+                || pass.writes.iter().any(|resource| {
+                    needed.contains(resource) || self.resources[resource.index()].is_imported()
+                });
+            if required {
+                needed.extend(pass.accesses().copied());
+                kept.push(index);
+            }
+        }
+        kept.reverse();
+        kept
+    }
+
+    fn allocate(&self, order: &[usize]) -> Vec<Option<Allocation>> {
+        let mut last_use: HashMap<ResourceId, usize> = HashMap::new();
+        for (position, &index) in order.iter().enumerate() {
+            for &resource in self.passes[index].accesses() {
+                last_use.insert(resource, position);
+            }
+        }
+
+        let mut allocations = vec![None; self.resources.len()];
+        let mut free_textures: HashMap<TextureKey, Vec<usize>> = HashMap::new();
+        let mut free_buffers: HashMap<BufferKey, Vec<usize>> = HashMap::new();
+        let mut texture_slots: HashMap<TextureKey, usize> = HashMap::new();
+        let mut buffer_slots: HashMap<BufferKey, usize> = HashMap::new();
+
+        for (position, &index) in order.iter().enumerate() {
+            let pass = &self.passes[index];
+            for &resource in pass.accesses() {
+                if allocations[resource.index()].is_some() {
+                    continue;
+                }
+                allocations[resource.index()] = match &self.resources[resource.index()].kind {
+                    ResourceKind::Texture(desc) => {
+                        let key = desc.key();
+                        let slot = free_textures
+                            .get_mut(&key)
+                            .and_then(|free| free.pop())
+                            .unwrap_or_else(|| {
+                                let next = texture_slots.entry(key).or_default();
+                                *next += 1;
+                                *next - 1
+                            });
+                        Some(Allocation::Texture(key, slot))
+                    }
+                    ResourceKind::Buffer(desc) => {
+                        let key = desc.key();
+                        let slot = free_buffers
+                            .get_mut(&key)
+                            .and_then(|free| free.pop())
+                            .unwrap_or_else(|| {
+                                let next = buffer_slots.entry(key).or_default();
+                                *next += 1;
+                                *next - 1
+                            });
+                        Some(Allocation::Buffer(key, slot))
+                    }
+                    _ => None,
+                };
+            }
+
+            for &resource in pass.accesses() {
+                if last_use.get(&resource) != Some(&position) {
+                    continue;
+                }
+                match allocations[resource.index()] {
+                    Some(Allocation::Texture(key, slot)) => {
+                        free_textures.entry(key).or_default().push(slot)
+                    }
+                    Some(Allocation::Buffer(key, slot)) => {
+                        free_buffers.entry(key).or_default().push(slot)
+                    }
+                    None => {}
+                }
+            }
+        }
+        allocations
+    }
+
+    pub fn compile(&self) -> Result<GraphPlan, AppError> {
+        let dependencies = self.dependencies()?;
+        let order = self.cull(self.sort(&dependencies)?);
+        let allocations = self.allocate(&order);
+        Ok(GraphPlan {
+            order,
+            names: self.passes.iter().map(|pass| pass.name.clone()).collect(),
+            allocations,
+        })
+    }
+
+    pub fn execute(
+        self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pool: &mut TransientPool,
+        frame: &mut F,
+    ) -> Result<(), AppError> {
+        let plan = self.compile()?;
+        let (texture_counts, buffer_counts) = plan.counts();
+        pool.retain_counts(&texture_counts, &buffer_counts);
+        for (resource, allocation) in self.resources.iter().zip(plan.allocations.iter()) {
+            match (&resource.kind, allocation) {
+                (ResourceKind::Texture(desc), Some(Allocation::Texture(_, slot))) => {
+                    pool.ensure_texture(device, desc, *slot)
+                }
+                (ResourceKind::Buffer(desc), Some(Allocation::Buffer(_, slot))) => {
+                    pool.ensure_buffer(device, desc, *slot)
+                }
+                _ => {}
+            }
+        }
+
+        let pool: &TransientPool = pool;
+        let mut textures: Vec<Option<&wgpu::TextureView>> =
+            Vec::with_capacity(self.resources.len());
+        let mut buffers: Vec<Option<&wgpu::Buffer>> = Vec::with_capacity(self.resources.len());
+        for (resource, allocation) in self.resources.iter().zip(plan.allocations.iter()) {
+            let (texture, buffer) = match (&resource.kind, allocation) {
+                (ResourceKind::ImportedTexture(view), _) => (Some(*view), None),
+                (ResourceKind::ImportedBuffer(buffer), _) => (None, Some(*buffer)),
+                (_, Some(Allocation::Texture(key, slot))) => (
+                    pool.texture(key, *slot).map(|physical| &physical.view),
+                    None,
+                ),
+                (_, Some(Allocation::Buffer(key, slot))) => (None, pool.buffer(key, *slot)),
+                _ => (None, None),
+            };
+            textures.push(texture);
+            buffers.push(buffer);
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Graph Encoder"),
+        });
+        let mut passes: Vec<Option<GraphPass<'a, F>>> = self.passes.into_iter().map(Some).collect();
+        for &index in &plan.order {
+            let Some(pass) = passes[index].take() else {
+                continue;
+            };
+            let declared: Vec<ResourceId> = pass.accesses().copied().collect();
+            let mut ctx = PassContext {
+                device,
+                queue,
+                encoder: &mut encoder,
+                pass: &pass.name,
+                declared: &declared,
+                textures: &textures,
+                buffers: &buffers,
+            };
+            (pass.execute)(&mut ctx, frame)?;
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use resource::TextureSize;
+
+    fn texture(graph: &mut RenderGraph<'_, ()>, label: &str) -> ResourceId {
+        graph.create_texture(TextureDesc::new(
+            label,
+            TextureSize::Surface,
+            wgpu::TextureFormat::Rgba16Float,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        ))
+    }
+
+    fn pass(
+        graph: &mut RenderGraph<'_, ()>,
+        name: &str,
+        reads: &[ResourceId],
+        writes: &[ResourceId],
+        side_effects: bool,
+    ) {
+        let mut builder = graph.add_pass(name);
+        for &resource in reads {
+            builder = builder.read(resource);
+        }
+        for &resource in writes {
+            builder = builder.write(resource);
+        }
+        if side_effects {
+            builder = builder.side_effects();
+        }
+        builder.execute(|_, _| Ok(()));
+    }
+
+    #[test]
+    fn orders_passes_by_dependencies() {
+        let mut graph = RenderGraph::new();
+        let hdr = texture(&mut graph, "hdr");
+        let bloom = texture(&mut graph, "bloom");
+        let ldr = texture(&mut graph, "ldr");
+        pass(&mut graph, "post", &[hdr, bloom], &[ldr], true);
+        pass(&mut graph, "bloom", &[hdr], &[bloom], false);
+        pass(&mut graph, "scene", &[], &[hdr], false);
+        pass(&mut graph, "overlay", &[], &[hdr], false);
+
+        let plan = graph.compile().unwrap();
+        assert_eq!(plan.pass_names(), vec!["scene", "overlay", "bloom", "post"]);
+    }
+
+    #[test]
+    fn culls_passes_without_consumers() {
+        let mut graph = RenderGraph::new();
+        let used = texture(&mut graph, "used");
+        let unused = texture(&mut graph, "unused");
+        pass(&mut graph, "producer", &[], &[used], false);
+        pass(&mut graph, "orphan", &[], &[unused], false);
+        pass(&mut graph, "present", &[used], &[], true);
+        pass(&mut graph, "capture", &[], &[], true);
+
+        let plan = graph.compile().unwrap();
+        assert_eq!(plan.pass_names(), vec!["producer", "present", "capture"]);
+        assert!(plan.allocation(unused).is_none());
+    }
+
+    #[test]
+    fn aliases_transients_with_disjoint_lifetimes() {
+        let mut graph = RenderGraph::new();
+        let first = texture(&mut graph, "first");
+        let second = texture(&mut graph, "second");
+        let third = texture(&mut graph, "third");
+        let depth = graph.create_texture(TextureDesc::surface(
+            "depth",
+            wgpu::TextureFormat::Depth32Float,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        ));
+        pass(&mut graph, "a", &[], &[first, depth], false);
+        pass(&mut graph, "b", &[first], &[second], false);
+        pass(&mut graph, "c", &[second], &[third], false);
+        pass(&mut graph, "d", &[third, depth], &[], true);
+
+        let plan = graph.compile().unwrap();
+        assert!(plan.shares_memory(first, third));
+        assert!(!plan.shares_memory(first, second));
+        assert!(!plan.shares_memory(second, third));
+        assert!(!plan.shares_memory(first, depth));
+        assert_eq!(plan.physical_count(), 3);
+    }
+
+    #[test]
+    fn rejects_cycles_and_unwritten_reads() {
+        let mut graph = RenderGraph::new();
+        let x = texture(&mut graph, "x");
+        let y = texture(&mut graph, "y");
+        pass(&mut graph, "a", &[x], &[y], true);
+        pass(&mut graph, "b", &[y], &[x], true);
+        let error = graph.compile().unwrap_err().to_string();
+        assert!(error.contains("Cycle"), "{}", error);
+
+        let mut graph = RenderGraph::new();
+        let missing = texture(&mut graph, "missing");
+        pass(&mut graph, "reader", &[missing], &[], true);
+        let error = graph.compile().unwrap_err().to_string();
+        assert!(error.contains("no pass writes"), "{}", error);
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use crate::log_debug;
+
+use super::resource::{BufferDesc, BufferKey, TextureDesc, TextureKey};
+
+pub struct PhysicalTexture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+}
+
+pub struct TransientPool {
+    width: u32,
+    height: u32,
+    textures: HashMap<TextureKey, Vec<PhysicalTexture>>,
+    buffers: HashMap<BufferKey, Vec<wgpu::Buffer>>,
+}
+
+impl TransientPool {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width: width.max(1),
+            height: height.max(1),
+            textures: HashMap::new(),
+            buffers: HashMap::new(),
+        }
+    }
+
+    pub fn surface_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) -> bool {
+        let (width, height) = (width.max(1), height.max(1));
+        if (width, height) == (self.width, self.height) {
+            return false;
+        }
+        self.width = width;
+        self.height = height;
+        self.textures
+            .retain(|key, _| !key.size.is_surface_relative());
+        true
+    }
+
+    pub fn texture_count(&self) -> usize {
+        self.textures.values().map(Vec::len).sum()
+    }
+
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.values().map(Vec::len).sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.textures.clear();
+        self.buffers.clear();
+    }
+
+    pub(crate) fn retain_counts(
+        &mut self,
+        textures: &HashMap<TextureKey, usize>,
+        buffers: &HashMap<BufferKey, usize>,
+    ) {
+        self.textures.retain(|key, pooled| {
+            pooled.truncate(textures.get(key).copied().unwrap_or(0));
+            !pooled.is_empty()
+        });
+        self.buffers.retain(|key, pooled| {
+            pooled.truncate(buffers.get(key).copied().unwrap_or(0));
+            !pooled.is_empty()
+        });
+    }
+
+    pub(crate) fn ensure_texture(
+        &mut self,
+        device: &wgpu::Device,
+        desc: &TextureDesc,
+        slot: usize,
+    ) {
+        let (width, height) = desc.size.resolve(self.width, self.height);
+        let pooled = self.textures.entry(desc.key()).or_default();
+        while pooled.len() <= slot {
+            log_debug!(
+                "Allocating transient texture {} ({}x{}, {:?})",
+                desc.label,
+                width,
+                height,
+                desc.format
+            );
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(&desc.label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: desc.layers,
+                },
+                mip_level_count: 1,
+                sample_count: desc.sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: desc.format,
+                usage: desc.usage,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(if desc.layers > 1 {
+                    wgpu::TextureViewDimension::D2Array
+                } else {
+                    wgpu::TextureViewDimension::D2
+                }),
+                ..Default::default()
+            });
+            pooled.push(PhysicalTexture { texture, view });
+        }
+    }
+
+    pub(crate) fn ensure_buffer(&mut self, device: &wgpu::Device, desc: &BufferDesc, slot: usize) {
+        let pooled = self.buffers.entry(desc.key()).or_default();
+        while pooled.len() <= slot {
+            log_debug!(
+                "Allocating transient buffer {} ({} bytes)",
+                desc.label,
+                desc.size
+            );
+            pooled.push(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&desc.label),
+                size: desc.size,
+                usage: desc.usage,
+                mapped_at_creation: false,
+            }));
+        }
+    }
+
+    pub(crate) fn texture(&self, key: &TextureKey, slot: usize) -> Option<&PhysicalTexture> {
+        self.textures.get(key).and_then(|pooled| pooled.get(slot))
+    }
+
+    pub(crate) fn buffer(&self, key: &BufferKey, slot: usize) -> Option<&wgpu::Buffer> {
+        self.buffers.get(key).and_then(|pooled| pooled.get(slot))
+    }
+}
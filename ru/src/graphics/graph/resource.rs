@@ -0,0 +1,130 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ResourceId(pub(crate) usize);
+
+impl ResourceId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureSize {
+    Surface,
+    Scaled(u32),
+    Fixed { width: u32, height: u32 },
+}
+
+impl TextureSize {
+    pub fn resolve(&self, surface_width: u32, surface_height: u32) -> (u32, u32) {
+        let (width, height) = match *self {
+            TextureSize::Surface => (surface_width, surface_height),
+            TextureSize::Scaled(divisor) => {
+                let divisor = divisor.max(1);
+                (surface_width / divisor, surface_height / divisor)
+            }
+            TextureSize::Fixed { width, height } => (width, height),
+        };
+        (width.max(1), height.max(1))
+    }
+
+    pub fn is_surface_relative(&self) -> bool {
+        !matches!(self, TextureSize::Fixed { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextureDesc {
+    pub label: String,
+    pub size: TextureSize,
+    pub layers: u32,
+    pub format: wgpu::TextureFormat,
+    pub usage: wgpu::TextureUsages,
+    pub sample_count: u32,
+}
+
+impl TextureDesc {
+    pub fn new(
+        label: &str,
+        size: TextureSize,
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsages,
+    ) -> Self {
+        Self {
+            label: label.to_string(),
+            size,
+            layers: 1,
+            format,
+            usage,
+            sample_count: 1,
+        }
+    }
+
+    pub fn surface(label: &str, format: wgpu::TextureFormat, usage: wgpu::TextureUsages) -> Self {
+        Self::new(label, TextureSize::Surface, format, usage)
+    }
+
+    pub fn with_layers(mut self, layers: u32) -> Self {
+        self.layers = layers.max(1);
+        self
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count.max(1);
+        self
+    }
+
+    pub fn key(&self) -> TextureKey {
+        TextureKey {
+            size: self.size,
+            layers: self.layers,
+            format: self.format,
+            usage: self.usage,
+            sample_count: self.sample_count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureKey {
+    pub size: TextureSize,
+    pub layers: u32,
+    pub format: wgpu::TextureFormat,
+    pub usage: wgpu::TextureUsages,
+    pub sample_count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BufferDesc {
+    pub label: String,
+    pub size: u64,
+    pub usage: wgpu::BufferUsages,
+}
+
+impl BufferDesc {
+    pub fn new(label: &str, size: u64, usage: wgpu::BufferUsages) -> Self {
+        Self {
+            label: label.to_string(),
+            size,
+            usage,
+        }
+    }
+
+    pub fn key(&self) -> BufferKey {
+        BufferKey {
+            size: self.size,
+            usage: self.usage,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BufferKey {
+    pub size: u64,
+    pub usage: wgpu::BufferUsages,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Allocation {
+    Texture(TextureKey, usize),
+    Buffer(BufferKey, usize),
+}
@@ -1,10 +1,16 @@
 pub mod binding;
+pub mod cluster;
 pub mod context;
 pub mod geometry;
 pub mod global;
 pub mod glyphon;
+pub mod graph;
+pub mod msaa;
 pub mod pipelines;
+pub mod post;
+pub mod settings;
 pub mod shaders;
+pub mod shadow;
 pub mod textures;
 pub mod uniform;
 pub mod vertex;
@@ -0,0 +1,55 @@
+use serde::Deserialize;
+
+use crate::core::{error::AppError, vfs::load_config_section};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MsaaSettings {
+    pub sample_count: u32,
+}
+
+impl Default for MsaaSettings {
+    fn default() -> Self {
+        Self { sample_count: 4 }
+    }
+}
+
+impl MsaaSettings {
+    pub const SAMPLE_COUNTS: [u32; 4] = [1, 2, 4, 8];
+
+    pub fn load() -> Result<MsaaSettings, AppError> {
+        load_config_section("msaa")
+    }
+
+    pub fn supported_sample_counts(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        formats: &[wgpu::TextureFormat],
+    ) -> Vec<u32> {
+        let features = device.features();
+        let adapter_specific =
+            features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+        Self::SAMPLE_COUNTS
+            .into_iter()
+            .filter(|count| {
+                formats.iter().all(|format| {
+                    let flags = if adapter_specific {
+                        adapter.get_texture_format_features(*format).flags
+                    } else {
+                        format.guaranteed_format_features(features).flags
+                    };
+                    flags.sample_count_supported(*count)
+                })
+            })
+            .collect()
+    }
+
+    pub fn resolve_sample_count(requested: u32, supported: &[u32]) -> u32 {
+        supported
+            .iter()
+            .copied()
+            .filter(|count| *count <= requested)
+            .max()
+            .unwrap_or(1)
+    }
+}
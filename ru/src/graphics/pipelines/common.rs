@@ -10,27 +10,19 @@ use crate::{
         constant::{WGSL_FS_MAIN, WGSL_VS_MAIN},
     },
 };
-use wgpu::{BlendState, CommandEncoder, Device, PipelineLayout, TextureFormat, TextureView};
-use winit::dpi::PhysicalSize;
+use wgpu::{
+    BindGroup, BlendState, CommandEncoder, Device, PipelineLayout, TextureFormat, TextureView,
+};
 pub trait PipelineBase {
     fn create_layout(device: &Device, bind_group_layouts: &BindGroupLayouts) -> PipelineLayout;
     fn new(
         device: &wgpu::Device,
         format: TextureFormat,
-        width: u32,
-        height: u32,
         shader_manager: &mut ShaderManager,
         bind_group_layouts: &BindGroupLayouts,
     ) -> Self;
-    fn resize<P: winit::dpi::Pixel>(
-        &mut self,
-        device: &Device,
-        new_size: PhysicalSize<P>,
-        bind_group_layouts: &BindGroupLayouts,
-    );
-    fn view(&self) -> &TextureView;
     fn format(&self) -> TextureFormat;
-    fn process(&self, encoder: &mut CommandEncoder, output: &TextureView);
+    fn process(&self, encoder: &mut CommandEncoder, input: &BindGroup, output: &TextureView);
 }
 
 pub fn create_render_pipeline(
@@ -40,6 +32,7 @@ pub fn create_render_pipeline(
     depth_format: Option<wgpu::TextureFormat>,
     vertex_layouts: &[wgpu::VertexBufferLayout],
     topology: wgpu::PrimitiveTopology,
+    sample_count: u32,
     shader_path: &str,
     shader_manager: &mut ShaderManager,
 ) -> Result<wgpu::RenderPipeline, AppError> {
@@ -92,7 +85,7 @@ pub fn create_render_pipeline(
             bias: wgpu::DepthBiasState::default(),
         }),
         multisample: wgpu::MultisampleState {
-            count: 1,
+            count: sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -102,3 +95,15 @@ pub fn create_render_pipeline(
 
     Ok(pipeline)
 }
+
+pub fn with_validation_scope<T>(
+    device: &wgpu::Device,
+    f: impl FnOnce() -> T,
+) -> Result<T, AppError> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let value = f();
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(e) => Err(AppError::GPUResourceError(e.to_string())),
+        None => Ok(value),
+    }
+}
@@ -1,141 +1,9 @@
-use crate::graphics::{
-    binding::{hdr::create_hdr_pipeline_bind_group, BindGroupLayouts},
-    shaders::manager::ShaderManager,
-    textures::{cube_texture::CubeTexture, BindableTexture, Texture},
-};
+use crate::graphics::textures::{cube_texture::CubeTexture, Texture};
 use image::codecs::hdr::HdrDecoder;
 use std::io::Cursor;
-use wgpu::TextureFormat;
-use winit::dpi::PhysicalSize;
 
 use crate::core::error::AppError;
 
-use super::common::{create_render_pipeline, PipelineBase};
-
-pub struct HdrPipeline {
-    pipeline: wgpu::RenderPipeline,
-    bind_group: wgpu::BindGroup,
-    texture: Texture,
-    width: u32,
-    height: u32,
-    format: wgpu::TextureFormat,
-}
-
-impl PipelineBase for HdrPipeline {
-    fn create_layout(
-        device: &wgpu::Device,
-        bind_group_layouts: &BindGroupLayouts,
-    ) -> wgpu::PipelineLayout {
-        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("hdr_pipeline_layout"),
-            bind_group_layouts: &[&bind_group_layouts.hdr_pipeline_bind_group_layout],
-            push_constant_ranges: &[],
-        })
-    }
-
-    fn new(
-        device: &wgpu::Device,
-        format: TextureFormat,
-        width: u32,
-        height: u32,
-        shader_manager: &mut ShaderManager,
-        bind_group_layouts: &BindGroupLayouts,
-    ) -> Self {
-        let layout = Self::create_layout(device, &bind_group_layouts);
-
-        let pipeline = create_render_pipeline(
-            device,
-            &layout,
-            format.add_srgb_suffix(),
-            None,
-            &[],
-            wgpu::PrimitiveTopology::TriangleList,
-            "effects/tone_mapping.wgsl",
-            shader_manager,
-        )
-        .expect("Hdr pipeline");
-
-        let texture = Texture::create_2d_texture(
-            device,
-            width,
-            height,
-            format,
-            wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
-            wgpu::FilterMode::Nearest,
-            Some("hdr_texture"),
-        );
-
-        let bind_group = create_hdr_pipeline_bind_group(
-            device,
-            &bind_group_layouts.hdr_pipeline_bind_group_layout,
-            &texture,
-        );
-
-        Self {
-            pipeline,
-            texture,
-            width,
-            height,
-            format,
-            bind_group,
-        }
-    }
-
-    fn resize<P: winit::dpi::Pixel>(
-        &mut self,
-        device: &wgpu::Device,
-        new_size: PhysicalSize<P>,
-        bind_group_layouts: &BindGroupLayouts,
-    ) {
-        self.width = new_size.width.cast();
-        self.height = new_size.height.cast();
-
-        self.texture = Texture::create_2d_texture(
-            device,
-            self.width,
-            self.height,
-            self.format,
-            wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
-            wgpu::FilterMode::Nearest,
-            Some("hdr_texture"),
-        );
-
-        self.bind_group = create_hdr_pipeline_bind_group(
-            device,
-            &bind_group_layouts.hdr_pipeline_bind_group_layout,
-            &self.texture,
-        );
-    }
-
-    fn view(&self) -> &wgpu::TextureView {
-        &self.texture.view
-    }
-
-    fn format(&self) -> wgpu::TextureFormat {
-        self.format
-    }
-
-    fn process(&self, encoder: &mut wgpu::CommandEncoder, output: &wgpu::TextureView) {
-        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Hdr::process"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: output,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Load,
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
-
-        pass.set_pipeline(&self.pipeline);
-        pass.set_bind_group(0, &self.bind_group, &[]);
-        pass.draw(0..3, 0..1);
-    }
-}
 pub struct HdrLoader {
     texture_format: wgpu::TextureFormat,
     equirect_layout: wgpu::BindGroupLayout,
@@ -221,10 +89,39 @@ impl HdrLoader {
             pixels
         };
 
-        let src = Texture::create_2d_texture(
+        self.from_equirectangular_pixels(
             device,
+            queue,
             meta.width,
             meta.height,
+            &pixels,
+            dst_size,
+            label,
+        )
+    }
+
+    pub fn from_equirectangular_pixels(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        width: u32,
+        height: u32,
+        pixels: &[[f32; 4]],
+        dst_size: u32,
+        label: Option<&str>,
+    ) -> Result<CubeTexture, AppError> {
+        if pixels.len() != (width * height) as usize {
+            return Err(AppError::ResourceCreationFailed(format!(
+                "Equirectangular image is {}x{} but has {} pixels",
+                width,
+                height,
+                pixels.len()
+            )));
+        }
+        let src = Texture::create_2d_texture(
+            device,
+            width,
+            height,
             self.texture_format,
             wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             wgpu::FilterMode::Linear,
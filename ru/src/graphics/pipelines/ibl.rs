@@ -0,0 +1,362 @@
+use wgpu::util::DeviceExt;
+
+use crate::{
+    core::error::AppError,
+    graphics::{
+        textures::{cube_texture::CubeTexture, sampler::SamplerConfig, Texture},
+        uniform::ibl::IblParams,
+    },
+    log_info,
+};
+
+pub const IBL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const RADIANCE_SIZE: u32 = 128;
+pub const IRRADIANCE_SIZE: u32 = 32;
+pub const PREFILTER_SIZE: u32 = 128;
+pub const PREFILTER_MIP_LEVELS: u32 = 5;
+pub const BRDF_LUT_SIZE: u32 = 128;
+const SAMPLE_COUNT: u32 = 256;
+const WORKGROUP_SIZE: u32 = 8;
+
+pub struct EnvironmentMaps {
+    pub sky: CubeTexture,
+    pub irradiance: CubeTexture,
+    pub prefiltered: CubeTexture,
+    pub brdf_lut: Texture,
+    pub sampler: wgpu::Sampler,
+}
+
+pub struct IblBaker {
+    unfiltered_layout: wgpu::BindGroupLayout,
+    filtered_layout: wgpu::BindGroupLayout,
+    lut_layout: wgpu::BindGroupLayout,
+    downsample: wgpu::ComputePipeline,
+    irradiance: wgpu::ComputePipeline,
+    prefilter: wgpu::ComputePipeline,
+    integrate_brdf: wgpu::ComputePipeline,
+}
+
+impl IblBaker {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let module = device
+            .create_shader_module(wgpu::include_wgsl!("../../assets/shaders/compute/ibl.wgsl"));
+        let unfiltered_layout = Self::create_source_layout(device, false);
+        let filtered_layout = Self::create_source_layout(device, true);
+        let lut_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("IblBaker::lut_layout"),
+            entries: &[
+                Self::params_entry(),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: IBL_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let create_pipeline = |layout: &wgpu::BindGroupLayout, entry_point: &str| {
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(entry_point),
+                bind_group_layouts: &[layout],
+                push_constant_ranges: &[],
+            });
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point,
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let downsample = create_pipeline(&unfiltered_layout, "downsample");
+        let irradiance = create_pipeline(&filtered_layout, "irradiance");
+        let prefilter = create_pipeline(&filtered_layout, "prefilter");
+        let integrate_brdf = create_pipeline(&lut_layout, "integrate_brdf");
+
+        Self {
+            unfiltered_layout,
+            filtered_layout,
+            lut_layout,
+            downsample,
+            irradiance,
+            prefilter,
+            integrate_brdf,
+        }
+    }
+
+    pub fn bake(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        sky: CubeTexture,
+    ) -> Result<EnvironmentMaps, AppError> {
+        let cube_usage =
+            wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING;
+        let radiance = CubeTexture::create_2d(
+            device,
+            RADIANCE_SIZE,
+            RADIANCE_SIZE,
+            IBL_FORMAT,
+            1,
+            cube_usage,
+            wgpu::FilterMode::Linear,
+            Some("ibl_radiance"),
+        );
+        let irradiance = CubeTexture::create_2d(
+            device,
+            IRRADIANCE_SIZE,
+            IRRADIANCE_SIZE,
+            IBL_FORMAT,
+            1,
+            cube_usage,
+            wgpu::FilterMode::Linear,
+            Some("ibl_irradiance"),
+        );
+        let prefiltered = CubeTexture::create_2d(
+            device,
+            PREFILTER_SIZE,
+            PREFILTER_SIZE,
+            IBL_FORMAT,
+            PREFILTER_MIP_LEVELS,
+            cube_usage,
+            wgpu::FilterMode::Linear,
+            Some("ibl_prefiltered"),
+        );
+        let brdf_lut = Texture::create_2d_texture(
+            device,
+            BRDF_LUT_SIZE,
+            BRDF_LUT_SIZE,
+            IBL_FORMAT,
+            wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            wgpu::FilterMode::Linear,
+            Some("ibl_brdf_lut"),
+        );
+        let sampler = SamplerConfig::default()
+            .with_address_mode(wgpu::AddressMode::ClampToEdge)
+            .with_anisotropy(1)
+            .create_sampler(device, Some("ibl_sampler"));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("IblBaker::bake"),
+        });
+        let params = IblParams::new(0.0, SAMPLE_COUNT);
+
+        let bind_group = Self::source_bind_group(
+            device,
+            &self.unfiltered_layout,
+            sky.view(),
+            sky.sampler(),
+            &Self::storage_view(radiance.texture(), 0),
+            params,
+        );
+        Self::dispatch(
+            &mut encoder,
+            &self.downsample,
+            &bind_group,
+            RADIANCE_SIZE,
+            6,
+        );
+
+        let bind_group = Self::source_bind_group(
+            device,
+            &self.filtered_layout,
+            radiance.view(),
+            &sampler,
+            &Self::storage_view(irradiance.texture(), 0),
+            params,
+        );
+        Self::dispatch(
+            &mut encoder,
+            &self.irradiance,
+            &bind_group,
+            IRRADIANCE_SIZE,
+            6,
+        );
+
+        for mip in 0..PREFILTER_MIP_LEVELS {
+            let roughness = mip as f32 / (PREFILTER_MIP_LEVELS - 1) as f32;
+            let bind_group = Self::source_bind_group(
+                device,
+                &self.filtered_layout,
+                radiance.view(),
+                &sampler,
+                &Self::storage_view(prefiltered.texture(), mip),
+                IblParams::new(roughness, SAMPLE_COUNT),
+            );
+            Self::dispatch(
+                &mut encoder,
+                &self.prefilter,
+                &bind_group,
+                (PREFILTER_SIZE >> mip).max(1),
+                6,
+            );
+        }
+
+        let params_buffer = Self::params_buffer(device, params);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("IblBaker::lut"),
+            layout: &self.lut_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&brdf_lut.view),
+                },
+            ],
+        });
+        Self::dispatch(
+            &mut encoder,
+            &self.integrate_brdf,
+            &bind_group,
+            BRDF_LUT_SIZE,
+            1,
+        );
+
+        queue.submit([encoder.finish()]);
+        log_info!(
+            "Baked image based lighting: {}px irradiance, {}px prefiltered ({} mips), {}px BRDF LUT",
+            IRRADIANCE_SIZE,
+            PREFILTER_SIZE,
+            PREFILTER_MIP_LEVELS,
+            BRDF_LUT_SIZE
+        );
+
+        Ok(EnvironmentMaps {
+            sky,
+            irradiance,
+            prefiltered,
+            brdf_lut,
+            sampler,
+        })
+    }
+
+    fn dispatch(
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &wgpu::ComputePipeline,
+        bind_group: &wgpu::BindGroup,
+        size: u32,
+        layers: u32,
+    ) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("IblBaker::dispatch"),
+            timestamp_writes: None,
+        });
+        let workgroups = size.div_ceil(WORKGROUP_SIZE);
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.dispatch_workgroups(workgroups, workgroups, layers);
+    }
+
+    fn source_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        src: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+        dst: &wgpu::TextureView,
+        params: IblParams,
+    ) -> wgpu::BindGroup {
+        let params_buffer = Self::params_buffer(device, params);
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("IblBaker::source"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(src),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(dst),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    fn params_buffer(device: &wgpu::Device, params: IblParams) -> wgpu::Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("IblParamsBuffer"),
+            contents: bytemuck::cast_slice(&[params]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        })
+    }
+
+    fn storage_view(texture: &wgpu::Texture, mip: u32) -> wgpu::TextureView {
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("IblBaker::storage_view"),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            base_mip_level: mip,
+            mip_level_count: Some(1),
+            ..Default::default()
+        })
+    }
+
+    fn params_entry() -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: 3,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
+    }
+
+    fn create_source_layout(device: &wgpu::Device, filterable: bool) -> wgpu::BindGroupLayout {
+        let sampler_type = if filterable {
+            wgpu::SamplerBindingType::Filtering
+        } else {
+            wgpu::SamplerBindingType::NonFiltering
+        };
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("IblBaker::source_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable },
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Sampler(sampler_type),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: IBL_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                    },
+                    count: None,
+                },
+                Self::params_entry(),
+            ],
+        })
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use wgpu::RenderPipeline;
 
 use crate::{
@@ -5,150 +7,408 @@ use crate::{
     ecs::{components::instance::model::InstanceRaw, traits::Cache},
     graphics::{
         binding::BindGroupLayouts,
-        shaders::manager::ShaderManager,
-        vertex::{ModelVertex, Vertex},
+        shaders::{manager::ShaderManager, module::shader_variant},
+        vertex::{ModelVertex, SkinnedVertex, Vertex},
         PrimitiveTopology,
     },
+    log_info,
     prelude::cache::{CacheKey, HashCache},
 };
 
-use super::common::create_render_pipeline;
+use super::common::{create_render_pipeline, with_validation_scope};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PipelineRecipe {
+    Normal(PrimitiveTopology),
+    Pbr(PrimitiveTopology),
+    SkinnedNormal(PrimitiveTopology),
+    SkinnedPbr(PrimitiveTopology),
+    Light(PrimitiveTopology),
+    Skybox,
+}
+
+impl PipelineRecipe {
+    pub fn shader_path(&self) -> &'static str {
+        match self {
+            PipelineRecipe::Normal(_) | PipelineRecipe::SkinnedNormal(_) => "core/normal.wgsl",
+            PipelineRecipe::Pbr(_) | PipelineRecipe::SkinnedPbr(_) => "core/pbr.wgsl",
+            PipelineRecipe::Light(_) => "core/lighting.wgsl",
+            PipelineRecipe::Skybox => "objects/skybox.wgsl",
+        }
+    }
+
+    pub fn shader_defines(&self) -> &'static [&'static str] {
+        match self {
+            PipelineRecipe::SkinnedNormal(_) | PipelineRecipe::SkinnedPbr(_) => &["SKINNED"],
+            _ => &[],
+        }
+    }
+
+    pub fn shader_variant(&self) -> String {
+        shader_variant(self.shader_path(), self.shader_defines())
+    }
+
+    pub fn cache_key(&self) -> CacheKey {
+        match self {
+            PipelineRecipe::Normal(topology) => {
+                CacheKey::from(format!("{}_pipeline", topology.label()).as_str())
+            }
+            PipelineRecipe::Pbr(topology) => {
+                CacheKey::from(format!("{}_pbr_pipeline", topology.label()).as_str())
+            }
+            PipelineRecipe::SkinnedNormal(topology) => {
+                CacheKey::from(format!("{}_skinned_pipeline", topology.label()).as_str())
+            }
+            PipelineRecipe::SkinnedPbr(topology) => {
+                CacheKey::from(format!("{}_skinned_pbr_pipeline", topology.label()).as_str())
+            }
+            PipelineRecipe::Light(topology) => {
+                CacheKey::from(format!("{}_light_pipeline", topology.label()).as_str())
+            }
+            PipelineRecipe::Skybox => CacheKey::from("skybox"),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct PipelineManager {
     pub pipelines: HashCache<RenderPipeline>,
+    pub recipes: HashMap<CacheKey, PipelineRecipe>,
+    variants: HashMap<u32, HashCache<RenderPipeline>>,
+    hdr_format: wgpu::TextureFormat,
+    depth_format: Option<wgpu::TextureFormat>,
+    sample_count: u32,
 }
 
 impl PipelineManager {
-    pub fn new() -> Self {
-        Self {
-            pipelines: HashCache::new(),
-        }
-    }
-    fn create_pipeline<F>(
-        device: &wgpu::Device,
-        layout_desc: &wgpu::PipelineLayoutDescriptor,
+    pub fn new(
         hdr_format: wgpu::TextureFormat,
         depth_format: Option<wgpu::TextureFormat>,
-        vertex_descs: &[wgpu::VertexBufferLayout],
-        topology: wgpu::PrimitiveTopology,
-        shader_path: &str,
-        shader_manager: &mut ShaderManager,
-        create_pipeline_fn: F,
-    ) -> Result<RenderPipeline, AppError>
-    where
-        F: Fn(&wgpu::Device, &wgpu::PipelineLayoutDescriptor) -> wgpu::PipelineLayout,
-    {
-        let layout = create_pipeline_fn(device, layout_desc);
-        create_render_pipeline(
-            device,
-            &layout,
+        sample_count: u32,
+    ) -> Self {
+        Self {
+            pipelines: HashCache::new(),
+            recipes: HashMap::new(),
+            variants: HashMap::new(),
             hdr_format,
             depth_format,
-            vertex_descs,
-            topology,
-            shader_path,
-            shader_manager,
-        )
+            sample_count,
+        }
     }
-    pub fn setup(
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    fn build_pipeline(
+        &self,
         device: &wgpu::Device,
-        topologies: Vec<PrimitiveTopology>,
-        depth_format: Option<wgpu::TextureFormat>,
+        recipe: PipelineRecipe,
+        sample_count: u32,
         bind_group_layouts: &BindGroupLayouts,
-        hdr_format: wgpu::TextureFormat,
         shader_manager: &mut ShaderManager,
-    ) -> Result<PipelineManager, AppError> {
-        let mut pipeline_manager = PipelineManager::new();
+    ) -> Result<RenderPipeline, AppError> {
+        match recipe {
+            PipelineRecipe::Normal(topology) => {
+                let render_pipeline_layout =
+                    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some("Normal Render Pipeline Layout"),
+                        bind_group_layouts: &[
+                            &bind_group_layouts.material_bind_group_layout,
+                            &bind_group_layouts.camera_bind_group_layout,
+                            &bind_group_layouts.light_bind_group_layout,
+                            &bind_group_layouts.environment_bind_group_layout,
+                        ],
+                        push_constant_ranges: &[],
+                    });
 
-        for topology in topologies {
-            let topology_label = topology.label();
-
-            let normal_pipeline_id =
-                CacheKey::from(format!("{}_pipeline", topology_label).as_str());
-            let _ = pipeline_manager
-                .pipelines
-                .get_or_create(normal_pipeline_id, || {
-                    let render_pipeline_layout =
-                        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                            label: Some("Normal Render Pipeline Layout"),
-                            bind_group_layouts: &[
-                                &bind_group_layouts.texture_bind_group_layout,
-                                &bind_group_layouts.camera_bind_group_layout,
-                                &bind_group_layouts.light_bind_group_layout,
-                                &bind_group_layouts.skybox_bind_group_layout,
-                            ],
-                            push_constant_ranges: &[],
-                        });
-
-                    Ok(create_render_pipeline(
-                        &device,
-                        &render_pipeline_layout,
-                        hdr_format,
-                        depth_format,
-                        &[ModelVertex::desc(), InstanceRaw::desc()],
-                        topology.to_wgpu_topology(),
-                        "core/normal.wgsl",
-                        shader_manager,
-                    )
-                    .expect("Normal pipeline"))
-                });
+                create_render_pipeline(
+                    device,
+                    &render_pipeline_layout,
+                    self.hdr_format,
+                    self.depth_format,
+                    &[ModelVertex::desc(), InstanceRaw::desc()],
+                    topology.to_wgpu_topology(),
+                    sample_count,
+                    &recipe.shader_variant(),
+                    shader_manager,
+                )
+            }
+            PipelineRecipe::Pbr(topology) => {
+                let render_pipeline_layout =
+                    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some("PBR Render Pipeline Layout"),
+                        bind_group_layouts: &[
+                            &bind_group_layouts.pbr_material_bind_group_layout,
+                            &bind_group_layouts.camera_bind_group_layout,
+                            &bind_group_layouts.light_bind_group_layout,
+                            &bind_group_layouts.environment_bind_group_layout,
+                        ],
+                        push_constant_ranges: &[],
+                    });
 
-            let light_pipeline_id =
-                CacheKey::from(format!("{}_light_pipeline", topology_label).as_str());
-            pipeline_manager
-                .pipelines
-                .get_or_create(light_pipeline_id, || {
-                    let render_pipeline_layout =
-                        device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                            label: Some("Light Render Pipeline Layout"),
-                            bind_group_layouts: &[
-                                &bind_group_layouts.camera_bind_group_layout,
-                                &bind_group_layouts.light_bind_group_layout,
-                            ],
-                            push_constant_ranges: &[],
-                        });
-
-                    Ok(create_render_pipeline(
-                        &device,
-                        &render_pipeline_layout,
-                        hdr_format,
-                        depth_format,
-                        &[ModelVertex::desc()],
-                        topology.to_wgpu_topology(),
-                        "core/lighting.wgsl",
-                        shader_manager,
-                    )
-                    .expect("Lighting pipeline"))
-                })?;
-        }
+                create_render_pipeline(
+                    device,
+                    &render_pipeline_layout,
+                    self.hdr_format,
+                    self.depth_format,
+                    &[ModelVertex::desc(), InstanceRaw::desc()],
+                    topology.to_wgpu_topology(),
+                    sample_count,
+                    &recipe.shader_variant(),
+                    shader_manager,
+                )
+            }
+            PipelineRecipe::SkinnedNormal(topology) => {
+                let render_pipeline_layout =
+                    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some("Skinned Render Pipeline Layout"),
+                        bind_group_layouts: &[
+                            &bind_group_layouts.material_bind_group_layout,
+                            &bind_group_layouts.skinned_camera_bind_group_layout,
+                            &bind_group_layouts.light_bind_group_layout,
+                            &bind_group_layouts.environment_bind_group_layout,
+                        ],
+                        push_constant_ranges: &[],
+                    });
+
+                create_render_pipeline(
+                    device,
+                    &render_pipeline_layout,
+                    self.hdr_format,
+                    self.depth_format,
+                    &[SkinnedVertex::desc(), InstanceRaw::desc()],
+                    topology.to_wgpu_topology(),
+                    sample_count,
+                    &recipe.shader_variant(),
+                    shader_manager,
+                )
+            }
+            PipelineRecipe::SkinnedPbr(topology) => {
+                let render_pipeline_layout =
+                    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some("Skinned PBR Render Pipeline Layout"),
+                        bind_group_layouts: &[
+                            &bind_group_layouts.pbr_material_bind_group_layout,
+                            &bind_group_layouts.skinned_camera_bind_group_layout,
+                            &bind_group_layouts.light_bind_group_layout,
+                            &bind_group_layouts.environment_bind_group_layout,
+                        ],
+                        push_constant_ranges: &[],
+                    });
+
+                create_render_pipeline(
+                    device,
+                    &render_pipeline_layout,
+                    self.hdr_format,
+                    self.depth_format,
+                    &[SkinnedVertex::desc(), InstanceRaw::desc()],
+                    topology.to_wgpu_topology(),
+                    sample_count,
+                    &recipe.shader_variant(),
+                    shader_manager,
+                )
+            }
+            PipelineRecipe::Light(topology) => {
+                let render_pipeline_layout =
+                    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some("Light Render Pipeline Layout"),
+                        bind_group_layouts: &[
+                            &bind_group_layouts.camera_bind_group_layout,
+                            &bind_group_layouts.light_bind_group_layout,
+                        ],
+                        push_constant_ranges: &[],
+                    });
 
-        let sky_pipeline_id = CacheKey::from("skybox");
-        pipeline_manager
-            .pipelines
-            .get_or_create(sky_pipeline_id, || {
+                create_render_pipeline(
+                    device,
+                    &render_pipeline_layout,
+                    self.hdr_format,
+                    self.depth_format,
+                    &[ModelVertex::desc()],
+                    topology.to_wgpu_topology(),
+                    sample_count,
+                    &recipe.shader_variant(),
+                    shader_manager,
+                )
+            }
+            PipelineRecipe::Skybox => {
                 let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                     label: Some("Skybox Pipeline Layout"),
                     bind_group_layouts: &[
                         &bind_group_layouts.camera_bind_group_layout,
-                        &bind_group_layouts.skybox_bind_group_layout,
+                        &bind_group_layouts.environment_bind_group_layout,
                     ],
                     push_constant_ranges: &[],
                 });
 
-                Ok(create_render_pipeline(
-                    &device,
+                create_render_pipeline(
+                    device,
                     &layout,
-                    hdr_format,
-                    depth_format,
+                    self.hdr_format,
+                    self.depth_format,
                     &[],
                     wgpu::PrimitiveTopology::TriangleList,
-                    "objects/skybox.wgsl",
+                    sample_count,
+                    &recipe.shader_variant(),
                     shader_manager,
                 )
-                .expect("Skybox pipeline"))
-            })?;
+            }
+        }
+    }
+
+    fn add_recipe(
+        &mut self,
+        device: &wgpu::Device,
+        recipe: PipelineRecipe,
+        bind_group_layouts: &BindGroupLayouts,
+        shader_manager: &mut ShaderManager,
+    ) -> Result<(), AppError> {
+        let key = recipe.cache_key();
+        let pipeline = self.build_pipeline(
+            device,
+            recipe,
+            self.sample_count,
+            bind_group_layouts,
+            shader_manager,
+        )?;
+        self.pipelines.put(key, pipeline);
+        self.recipes.insert(key, recipe);
+        Ok(())
+    }
+
+    pub fn setup(
+        device: &wgpu::Device,
+        topologies: Vec<PrimitiveTopology>,
+        depth_format: Option<wgpu::TextureFormat>,
+        bind_group_layouts: &BindGroupLayouts,
+        hdr_format: wgpu::TextureFormat,
+        sample_count: u32,
+        shader_manager: &mut ShaderManager,
+    ) -> Result<PipelineManager, AppError> {
+        let mut pipeline_manager = PipelineManager::new(hdr_format, depth_format, sample_count);
+
+        for topology in topologies {
+            pipeline_manager.add_recipe(
+                device,
+                PipelineRecipe::Normal(topology),
+                bind_group_layouts,
+                shader_manager,
+            )?;
+            pipeline_manager.add_recipe(
+                device,
+                PipelineRecipe::Pbr(topology),
+                bind_group_layouts,
+                shader_manager,
+            )?;
+            pipeline_manager.add_recipe(
+                device,
+                PipelineRecipe::SkinnedNormal(topology),
+                bind_group_layouts,
+                shader_manager,
+            )?;
+            pipeline_manager.add_recipe(
+                device,
+                PipelineRecipe::SkinnedPbr(topology),
+                bind_group_layouts,
+                shader_manager,
+            )?;
+            pipeline_manager.add_recipe(
+                device,
+                PipelineRecipe::Light(topology),
+                bind_group_layouts,
+                shader_manager,
+            )?;
+        }
+        pipeline_manager.add_recipe(
+            device,
+            PipelineRecipe::Skybox,
+            bind_group_layouts,
+            shader_manager,
+        )?;
 
         Ok(pipeline_manager)
     }
+
+    pub fn rebuild_for_shader(
+        &mut self,
+        device: &wgpu::Device,
+        shader_path: &str,
+        bind_group_layouts: &BindGroupLayouts,
+        shader_manager: &mut ShaderManager,
+    ) -> Result<usize, AppError> {
+        let affected: Vec<(CacheKey, PipelineRecipe)> = self
+            .recipes
+            .iter()
+            .filter(|(_, recipe)| recipe.shader_path() == shader_path)
+            .map(|(key, recipe)| (*key, *recipe))
+            .collect();
+
+        let mut rebuilt = Vec::with_capacity(affected.len());
+        for (key, recipe) in &affected {
+            let pipeline = with_validation_scope(device, || {
+                self.build_pipeline(
+                    device,
+                    *recipe,
+                    self.sample_count,
+                    bind_group_layouts,
+                    shader_manager,
+                )
+            })??;
+            rebuilt.push((*key, pipeline));
+        }
+
+        for (key, pipeline) in rebuilt {
+            self.pipelines.put(key, pipeline);
+        }
+        if !affected.is_empty() {
+            self.variants.clear();
+        }
+        log_info!(
+            "Rebuilt {} pipeline(s) using {}",
+            affected.len(),
+            shader_path
+        );
+        Ok(affected.len())
+    }
+
+    pub fn set_sample_count(
+        &mut self,
+        device: &wgpu::Device,
+        sample_count: u32,
+        bind_group_layouts: &BindGroupLayouts,
+        shader_manager: &mut ShaderManager,
+    ) -> Result<(), AppError> {
+        if sample_count == self.sample_count {
+            return Ok(());
+        }
+        let pipelines = match self.variants.remove(&sample_count) {
+            Some(pipelines) => pipelines,
+            None => {
+                let mut pipelines = HashCache::new();
+                for (key, recipe) in &self.recipes {
+                    let pipeline = with_validation_scope(device, || {
+                        self.build_pipeline(
+                            device,
+                            *recipe,
+                            sample_count,
+                            bind_group_layouts,
+                            shader_manager,
+                        )
+                    })??;
+                    pipelines.put(*key, pipeline);
+                }
+                log_info!(
+                    "Built {} pipeline(s) for {}x MSAA",
+                    self.recipes.len(),
+                    sample_count
+                );
+                pipelines
+            }
+        };
+        let previous = std::mem::replace(&mut self.pipelines, pipelines);
+        self.variants.insert(self.sample_count, previous);
+        self.sample_count = sample_count;
+        Ok(())
+    }
 }
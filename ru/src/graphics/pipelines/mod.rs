@@ -1,5 +1,6 @@
 pub mod common;
 pub mod hdr;
+pub mod ibl;
 pub mod manager;
 
 #[derive(Debug, PartialEq)]
@@ -0,0 +1,274 @@
+pub mod settings;
+
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use crate::{
+    core::error::AppError,
+    ecs::traits::Cache,
+    graphics::{
+        binding::{hdr::create_hdr_pipeline_bind_group, BindGroupLayouts},
+        graph::resource::{TextureDesc, TextureSize},
+        pipelines::common::with_validation_scope,
+        shaders::{manager::ShaderManager, module::RupyShader},
+        textures::sampler::SamplerConfig,
+        uniform::post::PostUniform,
+    },
+    prelude::{cache::CacheKey, constant::WGSL_VS_MAIN},
+};
+
+use self::settings::{PostEffect, PostSettings};
+
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const BLOOM_DOWNSAMPLE: &str = "bloom_down";
+pub const BLOOM_UPSAMPLE: &str = "bloom_up";
+pub const POST_TARGET: &str = "post_target";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PostStage {
+    Copy,
+    Effect(PostEffect),
+    BloomPrefilter,
+    BloomDownsample,
+    BloomUpsample,
+}
+
+impl PostStage {
+    const OUTPUT_STAGES: [PostStage; 6] = [
+        PostStage::Copy,
+        PostStage::Effect(PostEffect::Bloom),
+        PostStage::Effect(PostEffect::Exposure),
+        PostStage::Effect(PostEffect::ToneMapping),
+        PostStage::Effect(PostEffect::Vignette),
+        PostStage::Effect(PostEffect::FilmGrain),
+    ];
+    const BLOOM_STAGES: [PostStage; 3] = [
+        PostStage::BloomPrefilter,
+        PostStage::BloomDownsample,
+        PostStage::BloomUpsample,
+    ];
+
+    pub fn entry_point(&self) -> &'static str {
+        match self {
+            PostStage::Copy => "fs_copy",
+            PostStage::Effect(PostEffect::Bloom) => "fs_bloom",
+            PostStage::Effect(PostEffect::Exposure) => "fs_exposure",
+            PostStage::Effect(PostEffect::ToneMapping) => "fs_tone_map",
+            PostStage::Effect(PostEffect::Vignette) => "fs_vignette",
+            PostStage::Effect(PostEffect::FilmGrain) => "fs_film_grain",
+            PostStage::BloomPrefilter => "fs_bloom_prefilter",
+            PostStage::BloomDownsample => "fs_bloom_downsample",
+            PostStage::BloomUpsample => "fs_bloom_upsample",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PostStage::Copy => "post_copy",
+            PostStage::Effect(PostEffect::Bloom) => "bloom",
+            PostStage::Effect(PostEffect::Exposure) => "exposure",
+            PostStage::Effect(PostEffect::ToneMapping) => "tonemap",
+            PostStage::Effect(PostEffect::Vignette) => "vignette",
+            PostStage::Effect(PostEffect::FilmGrain) => "film_grain",
+            PostStage::BloomPrefilter => "bloom_prefilter",
+            PostStage::BloomDownsample => "bloom_downsample",
+            PostStage::BloomUpsample => "bloom_upsample",
+        }
+    }
+}
+
+pub struct PostProcessStack {
+    pub settings: PostSettings,
+    uniform_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    pipelines: HashMap<(PostStage, wgpu::TextureFormat), wgpu::RenderPipeline>,
+    output_format: wgpu::TextureFormat,
+    frame: u32,
+}
+
+impl PostProcessStack {
+    pub const SHADER_PATH: &'static str = "effects/post_process.wgsl";
+
+    pub fn new(
+        device: &wgpu::Device,
+        settings: PostSettings,
+        output_format: wgpu::TextureFormat,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<Self, AppError> {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("PostUniformBuffer"),
+            contents: bytemuck::cast_slice(&[PostUniform::new(&settings, 0)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let sampler = SamplerConfig::clamped(wgpu::FilterMode::Linear)
+            .create_sampler(device, Some("post_sampler"));
+        let pipelines =
+            Self::create_pipelines(device, output_format, shader_manager, bind_group_layouts)?;
+
+        Ok(Self {
+            settings,
+            uniform_buffer,
+            sampler,
+            pipelines,
+            output_format,
+            frame: 0,
+        })
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        HDR_FORMAT
+    }
+
+    pub fn output_format(&self) -> wgpu::TextureFormat {
+        self.output_format.add_srgb_suffix()
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue) {
+        self.frame = self.frame.wrapping_add(1);
+        let uniform = PostUniform::new(&self.settings, self.frame);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub fn bloom_desc(chain: &str, level: u32) -> TextureDesc {
+        TextureDesc::new(
+            &format!("{}_{}", chain, level),
+            TextureSize::Scaled(2 << level),
+            HDR_FORMAT,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        )
+    }
+
+    pub fn target_desc(index: usize) -> TextureDesc {
+        TextureDesc::surface(
+            &format!("{}_{}", POST_TARGET, index),
+            HDR_FORMAT,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        )
+    }
+
+    pub fn bind_group(
+        &self,
+        device: &wgpu::Device,
+        bind_group_layouts: &BindGroupLayouts,
+        input: &wgpu::TextureView,
+        bloom: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        create_hdr_pipeline_bind_group(
+            device,
+            &bind_group_layouts.hdr_pipeline_bind_group_layout,
+            input,
+            bloom,
+            &self.sampler,
+            &self.uniform_buffer,
+        )
+    }
+
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        stage: PostStage,
+        format: wgpu::TextureFormat,
+        input: &wgpu::BindGroup,
+        output: &wgpu::TextureView,
+    ) -> Result<(), AppError> {
+        let pipeline = self.pipelines.get(&(stage, format)).ok_or_else(|| {
+            AppError::ResourceNotFound(format!("Post pipeline {:?} for {:?}", stage, format))
+        })?;
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(stage.label()),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, input, &[]);
+        pass.draw(0..3, 0..1);
+        Ok(())
+    }
+
+    pub fn reload_pipelines(
+        &mut self,
+        device: &wgpu::Device,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<(), AppError> {
+        self.pipelines = with_validation_scope(device, || {
+            Self::create_pipelines(
+                device,
+                self.output_format,
+                shader_manager,
+                bind_group_layouts,
+            )
+        })??;
+        Ok(())
+    }
+
+    fn create_pipelines(
+        device: &wgpu::Device,
+        output_format: wgpu::TextureFormat,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<HashMap<(PostStage, wgpu::TextureFormat), wgpu::RenderPipeline>, AppError> {
+        let shader = shader_manager
+            .shaders
+            .get_or_create(CacheKey::from(Self::SHADER_PATH), || {
+                RupyShader::load(device, Self::SHADER_PATH)
+            })?;
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Process Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layouts.hdr_pipeline_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let output_format = output_format.add_srgb_suffix();
+        let variants = PostStage::OUTPUT_STAGES
+            .iter()
+            .flat_map(|stage| [(*stage, HDR_FORMAT), (*stage, output_format)])
+            .chain(
+                PostStage::BLOOM_STAGES
+                    .iter()
+                    .map(|stage| (*stage, HDR_FORMAT)),
+            );
+
+        let mut pipelines = HashMap::new();
+        for (stage, format) in variants {
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(stage.label()),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader.module,
+                    entry_point: WGSL_VS_MAIN,
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader.module,
+                    entry_point: stage.entry_point(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+            pipelines.insert((stage, format), pipeline);
+        }
+        Ok(pipelines)
+    }
+}
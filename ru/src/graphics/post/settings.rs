@@ -0,0 +1,417 @@
+use serde::Deserialize;
+
+use crate::core::{error::AppError, vfs::load_config_section};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostEffect {
+    Bloom,
+    Exposure,
+    ToneMapping,
+    Vignette,
+    FilmGrain,
+}
+
+impl PostEffect {
+    pub const ALL: [PostEffect; 5] = [
+        PostEffect::Bloom,
+        PostEffect::Exposure,
+        PostEffect::ToneMapping,
+        PostEffect::Vignette,
+        PostEffect::FilmGrain,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PostEffect::Bloom => "bloom",
+            PostEffect::Exposure => "exposure",
+            PostEffect::ToneMapping => "tone_mapping",
+            PostEffect::Vignette => "vignette",
+            PostEffect::FilmGrain => "film_grain",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<PostEffect> {
+        match name {
+            "tonemap" | "tonemapper" => Some(PostEffect::ToneMapping),
+            "grain" => Some(PostEffect::FilmGrain),
+            _ => Self::ALL.into_iter().find(|effect| effect.name() == name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToneMapper {
+    Aces,
+    Reinhard,
+    Agx,
+    Filmic,
+    None,
+}
+
+impl ToneMapper {
+    pub const ALL: [ToneMapper; 5] = [
+        ToneMapper::Aces,
+        ToneMapper::Reinhard,
+        ToneMapper::Agx,
+        ToneMapper::Filmic,
+        ToneMapper::None,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneMapper::Aces => "aces",
+            ToneMapper::Reinhard => "reinhard",
+            ToneMapper::Agx => "agx",
+            ToneMapper::Filmic => "filmic",
+            ToneMapper::None => "none",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ToneMapper> {
+        Self::ALL.into_iter().find(|mapper| mapper.name() == name)
+    }
+
+    pub fn code(&self) -> u32 {
+        match self {
+            ToneMapper::Aces => 0,
+            ToneMapper::Reinhard => 1,
+            ToneMapper::Agx => 2,
+            ToneMapper::Filmic => 3,
+            ToneMapper::None => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BloomSettings {
+    pub enabled: bool,
+    pub intensity: f32,
+    pub threshold: f32,
+    pub knee: f32,
+    pub filter_radius: f32,
+    pub mip_count: u32,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            intensity: 0.04,
+            threshold: 1.0,
+            knee: 0.5,
+            filter_radius: 1.0,
+            mip_count: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ExposureSettings {
+    pub enabled: bool,
+    pub stops: f32,
+}
+
+impl Default for ExposureSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stops: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ToneMappingSettings {
+    pub enabled: bool,
+    pub mapper: ToneMapper,
+}
+
+impl Default for ToneMappingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mapper: ToneMapper::Aces,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct VignetteSettings {
+    pub enabled: bool,
+    pub intensity: f32,
+    pub smoothness: f32,
+}
+
+impl Default for VignetteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            intensity: 0.35,
+            smoothness: 0.45,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FilmGrainSettings {
+    pub enabled: bool,
+    pub intensity: f32,
+}
+
+impl Default for FilmGrainSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            intensity: 0.05,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PostSettings {
+    pub order: Vec<PostEffect>,
+    pub bloom: BloomSettings,
+    pub exposure: ExposureSettings,
+    pub tone_mapping: ToneMappingSettings,
+    pub vignette: VignetteSettings,
+    pub film_grain: FilmGrainSettings,
+}
+
+impl Default for PostSettings {
+    fn default() -> Self {
+        Self {
+            order: PostEffect::ALL.to_vec(),
+            bloom: BloomSettings::default(),
+            exposure: ExposureSettings::default(),
+            tone_mapping: ToneMappingSettings::default(),
+            vignette: VignetteSettings::default(),
+            film_grain: FilmGrainSettings::default(),
+        }
+    }
+}
+
+impl PostSettings {
+    pub const MAX_BLOOM_MIPS: u32 = 8;
+
+    pub fn load() -> Result<PostSettings, AppError> {
+        load_config_section("post")
+    }
+
+    pub fn is_enabled(&self, effect: PostEffect) -> bool {
+        match effect {
+            PostEffect::Bloom => self.bloom.enabled,
+            PostEffect::Exposure => self.exposure.enabled,
+            PostEffect::ToneMapping => self.tone_mapping.enabled,
+            PostEffect::Vignette => self.vignette.enabled,
+            PostEffect::FilmGrain => self.film_grain.enabled,
+        }
+    }
+
+    pub fn set_enabled(&mut self, effect: PostEffect, enabled: bool) {
+        match effect {
+            PostEffect::Bloom => self.bloom.enabled = enabled,
+            PostEffect::Exposure => self.exposure.enabled = enabled,
+            PostEffect::ToneMapping => self.tone_mapping.enabled = enabled,
+            PostEffect::Vignette => self.vignette.enabled = enabled,
+            PostEffect::FilmGrain => self.film_grain.enabled = enabled,
+        }
+    }
+
+    pub fn active_effects(&self) -> Vec<PostEffect> {
+        let mut effects: Vec<PostEffect> = Vec::with_capacity(self.order.len());
+        for effect in &self.order {
+            if self.is_enabled(*effect) && !effects.contains(effect) {
+                effects.push(*effect);
+            }
+        }
+        effects
+    }
+
+    pub fn bloom_mip_count(&self) -> u32 {
+        self.bloom.mip_count.clamp(1, Self::MAX_BLOOM_MIPS)
+    }
+
+    pub fn exposure_scale(&self) -> f32 {
+        2.0_f32.powf(self.exposure.stops)
+    }
+
+    pub fn summary(&self) -> String {
+        let effects: Vec<String> = self
+            .order
+            .iter()
+            .map(|effect| {
+                let state = if self.is_enabled(*effect) {
+                    "on"
+                } else {
+                    "off"
+                };
+                format!("{} {}", effect.name(), state)
+            })
+            .collect();
+        format!(
+            "{} | exposure {:+.2} stops, tone mapper {}, bloom intensity {:.3}",
+            effects.join(", "),
+            self.exposure.stops,
+            self.tone_mapping.mapper.name(),
+            self.bloom.intensity
+        )
+    }
+
+    /// Applies a `post` console command. Numbers are only ever values and words are only
+    /// ever states, so `exposure 0` sets 0 stops while `bloom off` disables bloom:
+    ///
+    /// - `post [list]`, `post order <effect>...`, `post tonemap <mapper>`
+    /// - `post exposure <stops>`
+    /// - `post <effect> on|off|true|false|toggle`
+    /// - `post <effect> <parameter> <value>`
+    pub fn apply_command(&mut self, args: &[&str]) -> Result<String, AppError> {
+        match args {
+            [] | ["list"] => Ok(self.summary()),
+            ["order", effects @ ..] if !effects.is_empty() => {
+                self.order = effects
+                    .iter()
+                    .map(|name| Self::parse_effect(name))
+                    .collect::<Result<_, _>>()?;
+                Ok(self.summary())
+            }
+            ["tonemapper" | "tonemap", name] => {
+                self.tone_mapping.mapper = ToneMapper::from_name(name).ok_or_else(|| {
+                    AppError::ConfigError(format!("Unknown tone mapper: {}", name))
+                })?;
+                self.tone_mapping.enabled = true;
+                Ok(format!("Tone mapper: {}", name))
+            }
+            ["exposure", value] if value.parse::<f32>().is_ok() => {
+                self.exposure.stops = Self::parse_value(value)?;
+                self.exposure.enabled = true;
+                Ok(format!("Exposure: {:+.2} stops", self.exposure.stops))
+            }
+            [name, value] if value.parse::<f32>().is_ok() => {
+                let effect = Self::parse_effect(name)?;
+                Err(AppError::ConfigError(format!(
+                    "{} takes on, off or toggle; set values with `post {} <parameter> <value>`",
+                    effect.name(),
+                    name
+                )))
+            }
+            [name, state] if Self::parse_state(state).is_some() => {
+                let effect = Self::parse_effect(name)?;
+                let enabled = match Self::parse_state(state) {
+                    Some(Some(enabled)) => enabled,
+                    _ => !self.is_enabled(effect),
+                };
+                self.set_enabled(effect, enabled);
+                Ok(format!(
+                    "{}: {}",
+                    effect.name(),
+                    if enabled { "on" } else { "off" }
+                ))
+            }
+            [name, parameter, value] => {
+                let effect = Self::parse_effect(name)?;
+                let value = Self::parse_value(value)?;
+                let target = match (effect, *parameter) {
+                    (PostEffect::Bloom, "intensity") => &mut self.bloom.intensity,
+                    (PostEffect::Bloom, "threshold") => &mut self.bloom.threshold,
+                    (PostEffect::Bloom, "knee") => &mut self.bloom.knee,
+                    (PostEffect::Bloom, "radius") => &mut self.bloom.filter_radius,
+                    (PostEffect::Exposure, "stops") => &mut self.exposure.stops,
+                    (PostEffect::Vignette, "intensity") => &mut self.vignette.intensity,
+                    (PostEffect::Vignette, "smoothness") => &mut self.vignette.smoothness,
+                    (PostEffect::FilmGrain, "intensity") => &mut self.film_grain.intensity,
+                    _ => {
+                        return Err(AppError::ConfigError(format!(
+                            "Unknown {} parameter: {}",
+                            effect.name(),
+                            parameter
+                        )))
+                    }
+                };
+                *target = value;
+                Ok(format!("{} {}: {}", effect.name(), parameter, value))
+            }
+            _ => Err(AppError::ConfigError(format!(
+                "Unknown post command: {}",
+                args.join(" ")
+            ))),
+        }
+    }
+
+    fn parse_effect(name: &str) -> Result<PostEffect, AppError> {
+        PostEffect::from_name(name)
+            .ok_or_else(|| AppError::ConfigError(format!("Unknown post effect: {}", name)))
+    }
+
+    fn parse_value(value: &str) -> Result<f32, AppError> {
+        value
+            .parse::<f32>()
+            .map_err(|_| AppError::ConfigError(format!("Expected a number, got {}", value)))
+    }
+
+    fn parse_state(state: &str) -> Option<Option<bool>> {
+        match state {
+            "on" | "true" => Some(Some(true)),
+            "off" | "false" => Some(Some(false)),
+            "toggle" => Some(None),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_values_and_words_are_states() {
+        let mut settings = PostSettings::default();
+        settings.apply_command(&["exposure", "0"]).unwrap();
+        assert_eq!(settings.exposure.stops, 0.0);
+        assert!(settings.exposure.enabled);
+
+        settings.apply_command(&["exposure", "-1.5"]).unwrap();
+        assert_eq!(settings.exposure.stops, -1.5);
+        settings.apply_command(&["exposure", "off"]).unwrap();
+        assert!(!settings.exposure.enabled);
+        assert_eq!(settings.exposure.stops, -1.5);
+
+        settings.apply_command(&["bloom", "on"]).unwrap();
+        assert!(settings.bloom.enabled);
+        assert!(settings.apply_command(&["bloom", "0"]).is_err());
+        assert!(settings.bloom.enabled);
+        settings.apply_command(&["bloom", "toggle"]).unwrap();
+        assert!(!settings.bloom.enabled);
+    }
+
+    #[test]
+    fn parameters_and_order_are_set_by_name() {
+        let mut settings = PostSettings::default();
+        settings
+            .apply_command(&["bloom", "intensity", "0"])
+            .unwrap();
+        assert_eq!(settings.bloom.intensity, 0.0);
+        settings.apply_command(&["exposure", "stops", "2"]).unwrap();
+        assert_eq!(settings.exposure.stops, 2.0);
+        assert!(settings.apply_command(&["bloom", "stops", "1"]).is_err());
+        assert!(settings
+            .apply_command(&["bloom", "intensity", "high"])
+            .is_err());
+
+        settings
+            .apply_command(&["order", "grain", "bloom"])
+            .unwrap();
+        assert_eq!(
+            settings.order,
+            vec![PostEffect::FilmGrain, PostEffect::Bloom]
+        );
+        assert!(settings.apply_command(&["order", "blur"]).is_err());
+    }
+}
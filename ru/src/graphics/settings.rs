@@ -0,0 +1,43 @@
+use super::{
+    msaa::MsaaSettings, post::settings::PostSettings, shadow::settings::ShadowSettings,
+    textures::sampler::TextureSettings,
+};
+use crate::log_warning;
+
+/// Renderer settings from the `[shadows]`, `[post]`, `[msaa]` and `[textures]` config
+/// sections. Tests build these directly so renders don't depend on the local config file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderSettings {
+    pub shadows: ShadowSettings,
+    pub post: PostSettings,
+    pub msaa: MsaaSettings,
+    pub textures: TextureSettings,
+}
+
+impl RenderSettings {
+    /// Loads every section, falling back to its defaults with a warning when it is invalid.
+    pub fn load() -> RenderSettings {
+        let shadows = ShadowSettings::load().unwrap_or_else(|e| {
+            log_warning!("Using default shadow settings: {}", e);
+            ShadowSettings::default()
+        });
+        let post = PostSettings::load().unwrap_or_else(|e| {
+            log_warning!("Using default post processing settings: {}", e);
+            PostSettings::default()
+        });
+        let msaa = MsaaSettings::load().unwrap_or_else(|e| {
+            log_warning!("Using default MSAA settings: {}", e);
+            MsaaSettings::default()
+        });
+        let textures = TextureSettings::load().unwrap_or_else(|e| {
+            log_warning!("Using default texture settings: {}", e);
+            TextureSettings::default()
+        });
+        RenderSettings {
+            shadows,
+            post,
+            msaa,
+            textures,
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use crate::{
     core::cache::{CacheKey, HashCache},
     ecs::traits::Cache,
+    log_info,
 };
 
 use crate::core::error::AppError;
 
-use super::module::RupyShader;
+use super::module::{split_shader_variant, RupyShader};
 
 pub struct ShaderManager {
     pub shaders: HashCache<RupyShader>,
@@ -37,4 +38,95 @@ impl ShaderManager {
 
         Ok(())
     }
+
+    /// Recompiles `path` and every cached variant of it. The cache is only updated once all
+    /// of them compile, so a broken edit keeps the previous modules in use. The returned
+    /// [`ShaderReload`] holds the replaced modules for [`ShaderManager::revert`] in case the
+    /// pipelines built from them fail.
+    pub fn reload_shader(
+        &mut self,
+        device: &wgpu::Device,
+        path: &str,
+    ) -> Result<ShaderReload, AppError> {
+        let targets = reload_targets(
+            self.shaders.iter().map(|(_, shader)| shader.path.as_str()),
+            path,
+        );
+        let shaders = targets
+            .iter()
+            .map(|name| RupyShader::reload(device, name))
+            .collect::<Result<Vec<_>, AppError>>()?;
+        let mut previous = Vec::with_capacity(shaders.len());
+        for (name, shader) in targets.iter().zip(shaders) {
+            let key = CacheKey::from(name.as_str());
+            previous.push((key, self.shaders.take(&key)));
+            self.shaders.put(key, shader);
+        }
+        log_info!(
+            "Reloaded shader: {} ({} variant(s))",
+            path,
+            targets.len() - 1
+        );
+        Ok(ShaderReload { previous })
+    }
+
+    /// Puts back the modules a reload replaced.
+    pub fn revert(&mut self, reload: ShaderReload) {
+        for (key, shader) in reload.previous {
+            match shader {
+                Some(shader) => self.shaders.put(key, shader),
+                None => self.shaders.remove(&key),
+            }
+        }
+    }
+}
+
+/// The modules a [`ShaderManager::reload_shader`] call replaced, by cache key.
+pub struct ShaderReload {
+    previous: Vec<(CacheKey, Option<RupyShader>)>,
+}
+
+/// The base shader followed by the cached variants built from it, sorted.
+fn reload_targets<'a>(cached: impl Iterator<Item = &'a str>, path: &str) -> Vec<String> {
+    let mut variants: Vec<String> = cached
+        .filter(|name| *name != path && split_shader_variant(name).0 == path)
+        .map(String::from)
+        .collect();
+    variants.sort();
+    variants.dedup();
+    let mut targets = vec![path.to_string()];
+    targets.extend(variants);
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::shaders::module::shader_variant;
+
+    #[test]
+    fn reload_targets_base_then_its_variants() {
+        let skinned = shader_variant("core/normal.wgsl", &["SKINNED"]);
+        let shadow = shader_variant("core/normal.wgsl", &["SHADOW", "SKINNED"]);
+        let other = shader_variant("core/pbr.wgsl", &["SKINNED"]);
+        let cached = [
+            shadow.as_str(),
+            "core/normal.wgsl",
+            "core/normal.wgsl.bak",
+            other.as_str(),
+            skinned.as_str(),
+        ];
+
+        let targets = reload_targets(cached.into_iter(), "core/normal.wgsl");
+        assert_eq!(
+            targets,
+            vec!["core/normal.wgsl".to_string(), shadow, skinned]
+        );
+    }
+
+    #[test]
+    fn reload_targets_base_without_cached_variants() {
+        let targets = reload_targets(std::iter::empty(), "sky.wgsl");
+        assert_eq!(targets, vec!["sky.wgsl".to_string()]);
+    }
 }
@@ -1,8 +1,8 @@
-use std::path::{Path, PathBuf};
-
-use crate::core::{error::AppError, files::FileSystem};
+use crate::core::{error::AppError, files::FileSystem, vfs::AssetDir};
 use wgpu::ShaderModule;
 
+pub const SHADER_VARIANT_SEPARATOR: char = '#';
+
 #[derive(Debug)]
 pub struct RupyShader {
     pub path: String,
@@ -19,12 +19,34 @@ impl RupyShader {
         })
     }
 
+    pub fn reload(device: &wgpu::Device, path: &str) -> Result<RupyShader, AppError> {
+        let src = read_shader_source(path)?;
+        RupyShader::validate(&src)?;
+        let (module, source) = RupyShader::create_shader_module(device, path)?;
+        Ok(RupyShader {
+            path: String::from(path),
+            module,
+            source,
+        })
+    }
+
+    pub fn validate(src: &str) -> Result<(), AppError> {
+        let module = naga::front::wgsl::parse_str(src)
+            .map_err(|e| AppError::ShaderValidationError(e.emit_to_string(src)))?;
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|e| AppError::ShaderValidationError(format!("{:?}", e.into_inner())))?;
+        Ok(())
+    }
+
     pub fn create_shader_module(
         device: &wgpu::Device,
         path: &str,
     ) -> Result<(wgpu::ShaderModule, std::string::String), AppError> {
-        let shader_path_buf = shader_path(path)?;
-        let src = read_shader_source_from_path(shader_path_buf)?;
+        let src = read_shader_source(path)?;
         let source = wgpu::ShaderSource::Wgsl(src.clone().into());
         let label = Some("Shader Module");
         let module = device.create_shader_module(wgpu::ShaderModuleDescriptor { label, source });
@@ -33,12 +55,111 @@ impl RupyShader {
     }
 }
 
-fn read_shader_source_from_path<P: AsRef<Path> + std::fmt::Debug>(
-    path: P,
-) -> Result<std::string::String, std::io::Error> {
-    let source_data_string = FileSystem::read_to_string(path)?;
-    Ok(source_data_string)
+pub fn shader_variant(path: &str, defines: &[&str]) -> String {
+    if defines.is_empty() {
+        return path.to_string();
+    }
+    format!("{}{}{}", path, SHADER_VARIANT_SEPARATOR, defines.join(","))
 }
-fn shader_path(name: &str) -> Result<PathBuf, AppError> {
-    FileSystem::get_shader_file_path(name)
+
+pub fn split_shader_variant(name: &str) -> (&str, Vec<&str>) {
+    match name.split_once(SHADER_VARIANT_SEPARATOR) {
+        Some((path, defines)) => (path, defines.split(',').filter(|d| !d.is_empty()).collect()),
+        None => (name, Vec::new()),
+    }
+}
+
+pub fn preprocess(src: &str, defines: &[&str]) -> Result<String, AppError> {
+    let mut out = String::with_capacity(src.len());
+    let mut stack: Vec<(bool, bool)> = Vec::new();
+    for (number, line) in src.lines().enumerate() {
+        let trimmed = line.trim();
+        let mut words = trimmed.split_whitespace();
+        let active = stack.iter().all(|(enabled, _)| *enabled);
+        match words.next() {
+            Some("#ifdef") | Some("#ifndef") => {
+                let name = words.next().ok_or_else(|| {
+                    AppError::ShaderValidationError(format!(
+                        "Missing define name on line {}",
+                        number + 1
+                    ))
+                })?;
+                let enabled = defines.contains(&name) == trimmed.starts_with("#ifdef");
+                stack.push((enabled, false));
+            }
+            Some("#else") => {
+                let (enabled, seen_else) = stack.last_mut().ok_or_else(|| {
+                    AppError::ShaderValidationError(format!(
+                        "Unmatched #else on line {}",
+                        number + 1
+                    ))
+                })?;
+                if *seen_else {
+                    return Err(AppError::ShaderValidationError(format!(
+                        "Duplicate #else on line {}",
+                        number + 1
+                    )));
+                }
+                *enabled = !*enabled;
+                *seen_else = true;
+            }
+            Some("#endif") => {
+                stack.pop().ok_or_else(|| {
+                    AppError::ShaderValidationError(format!(
+                        "Unmatched #endif on line {}",
+                        number + 1
+                    ))
+                })?;
+            }
+            _ if active => {
+                out.push_str(line);
+                out.push('\n');
+            }
+            _ => {}
+        }
+    }
+    if !stack.is_empty() {
+        return Err(AppError::ShaderValidationError(
+            "Unterminated #ifdef block".to_string(),
+        ));
+    }
+    Ok(out)
+}
+
+fn read_shader_source(name: &str) -> Result<std::string::String, AppError> {
+    let (path, defines) = split_shader_variant(name);
+    let src = FileSystem::load_asset_string(AssetDir::Shaders, path)?;
+    preprocess(&src, &defines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shader_variant_round_trips() {
+        assert_eq!(shader_variant("a.wgsl", &[]), "a.wgsl");
+        let name = shader_variant("core/a.wgsl", &["SKINNED", "SHADOW"]);
+        assert_eq!(name, "core/a.wgsl#SKINNED,SHADOW");
+        assert_eq!(
+            split_shader_variant(&name),
+            ("core/a.wgsl", vec!["SKINNED", "SHADOW"])
+        );
+        assert_eq!(split_shader_variant("a.wgsl"), ("a.wgsl", vec![]));
+    }
+
+    #[test]
+    fn preprocess_selects_branches_by_define() {
+        let src = "a\n#ifdef SKINNED\nb\n#else\nc\n#endif\n#ifndef SKINNED\nd\n#endif\n";
+        assert_eq!(preprocess(src, &["SKINNED"]).unwrap(), "a\nb\n");
+        assert_eq!(preprocess(src, &[]).unwrap(), "a\nc\nd\n");
+    }
+
+    #[test]
+    fn preprocess_rejects_unbalanced_directives() {
+        assert!(preprocess("#ifdef A\n", &[]).is_err());
+        assert!(preprocess("#endif\n", &[]).is_err());
+        assert!(preprocess("#else\n", &[]).is_err());
+        assert!(preprocess("#ifdef A\n#else\n#else\n#endif\n", &[]).is_err());
+    }
 }
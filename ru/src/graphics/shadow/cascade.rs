@@ -0,0 +1,93 @@
+use cgmath::{Deg, EuclideanSpace, InnerSpace, Matrix4, Point3, Vector3, Vector4};
+
+use crate::camera::handler::CameraHandler;
+
+#[rustfmt::skip]
+pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+const CUBE_FACE_DIRECTIONS: [([f32; 3], [f32; 3]); 6] = [
+    ([1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ([-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ([0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+    ([0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+    ([0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+];
+
+pub fn frustum_slice_corners(camera: &CameraHandler, near: f32, far: f32) -> [Point3<f32>; 8] {
+    let (forward, right, up) = camera.view.calculate_vectors();
+    let position = camera.view.position;
+    let tan_half_fovy = (camera.projection.fovy.0 * 0.5).tan();
+    let aspect = camera.projection.aspect as f32;
+
+    let mut corners = [Point3::origin(); 8];
+    for (slice, distance) in [near, far].into_iter().enumerate() {
+        let center = position + forward * distance;
+        let half_height = up * (tan_half_fovy * distance);
+        let half_width = right * (tan_half_fovy * distance * aspect);
+        corners[slice * 4] = center - half_width - half_height;
+        corners[slice * 4 + 1] = center + half_width - half_height;
+        corners[slice * 4 + 2] = center + half_width + half_height;
+        corners[slice * 4 + 3] = center - half_width + half_height;
+    }
+    corners
+}
+
+pub fn cascade_view_proj(
+    corners: &[Point3<f32>; 8],
+    light_direction: Vector3<f32>,
+    resolution: u32,
+    caster_distance: f32,
+) -> Matrix4<f32> {
+    let center = Point3::centroid(corners);
+    let radius = corners
+        .iter()
+        .map(|corner| (corner - center).magnitude())
+        .fold(0.0f32, f32::max);
+    let radius = ((radius * 16.0).ceil() / 16.0).max(0.01);
+
+    let direction = light_direction.normalize();
+    let up = if direction.y.abs() > 0.99 {
+        Vector3::unit_z()
+    } else {
+        Vector3::unit_y()
+    };
+    let eye = center - direction * (radius + caster_distance);
+    let view = Matrix4::look_at_rh(eye, center, up);
+    let projection = cgmath::ortho(
+        -radius,
+        radius,
+        -radius,
+        radius,
+        0.0,
+        2.0 * radius + caster_distance,
+    );
+
+    let shadow_matrix = OPENGL_TO_WGPU_MATRIX * projection * view;
+    let half_resolution = resolution.max(1) as f32 * 0.5;
+    let origin = shadow_matrix * Vector4::new(0.0, 0.0, 0.0, 1.0);
+    let snapped_x = (origin.x * half_resolution).round() / half_resolution;
+    let snapped_y = (origin.y * half_resolution).round() / half_resolution;
+    Matrix4::from_translation(Vector3::new(
+        snapped_x - origin.x,
+        snapped_y - origin.y,
+        0.0,
+    )) * shadow_matrix
+}
+
+pub fn cube_face_view_proj(
+    position: Point3<f32>,
+    face: usize,
+    near: f32,
+    far: f32,
+) -> Matrix4<f32> {
+    let (direction, up) = CUBE_FACE_DIRECTIONS[face % CUBE_FACE_DIRECTIONS.len()];
+    let view = Matrix4::look_to_rh(position, Vector3::from(direction), Vector3::from(up));
+    let projection = cgmath::perspective(Deg(90.0), 1.0, near, far);
+    OPENGL_TO_WGPU_MATRIX * projection * view
+}
@@ -0,0 +1,516 @@
+pub mod cascade;
+pub mod settings;
+
+use cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Vector3};
+use wgpu::util::DeviceExt;
+
+use crate::{
+    camera::handler::CameraHandler,
+    core::error::AppError,
+    ecs::{components::instance::model::InstanceRaw, traits::Cache},
+    graphics::{
+        binding::{
+            light::ShadowBindings,
+            shadow::{create_shadow_atlas_bind_group, create_shadow_caster_bind_group},
+            BindGroupLayouts,
+        },
+        graph::resource::{TextureDesc, TextureSize},
+        pipelines::common::{create_render_pipeline, with_validation_scope},
+        shaders::{
+            manager::ShaderManager,
+            module::{shader_variant, RupyShader},
+        },
+        uniform::{
+            lighting::LightUniform,
+            shadow::{
+                ShadowCasterUniform, ShadowUniform, ShadowViewRaw, CUBE_FACES, MAX_CASCADES,
+                SHADOW_VIEW_COUNT,
+            },
+        },
+        vertex::{ModelVertex, SkinnedVertex, Vertex},
+    },
+    log_info, log_warning,
+    prelude::{cache::CacheKey, constant::WGSL_VS_MAIN},
+};
+
+use self::{
+    cascade::{cascade_view_proj, cube_face_view_proj, frustum_slice_corners},
+    settings::ShadowSettings,
+};
+
+pub const ATLAS_COLUMNS: u32 = 4;
+pub const ATLAS_ROWS: u32 = 3;
+pub const ATLAS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+pub const SHADOW_ATLAS: &str = "shadow_atlas";
+pub const CASTER_STRIDE: u64 = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShadowCaster {
+    Directional,
+    Point,
+}
+
+impl ShadowCaster {
+    pub fn for_view(index: usize) -> ShadowCaster {
+        if index < MAX_CASCADES {
+            ShadowCaster::Directional
+        } else {
+            ShadowCaster::Point
+        }
+    }
+}
+
+struct CasterPipelines {
+    directional: wgpu::RenderPipeline,
+    point: wgpu::RenderPipeline,
+    skinned_directional: wgpu::RenderPipeline,
+    skinned_point: wgpu::RenderPipeline,
+}
+
+impl CasterPipelines {
+    fn get(&self, caster: ShadowCaster, skinned: bool) -> &wgpu::RenderPipeline {
+        match (caster, skinned) {
+            (ShadowCaster::Directional, false) => &self.directional,
+            (ShadowCaster::Point, false) => &self.point,
+            (ShadowCaster::Directional, true) => &self.skinned_directional,
+            (ShadowCaster::Point, true) => &self.skinned_point,
+        }
+    }
+}
+
+pub struct ShadowMaps {
+    pub settings: ShadowSettings,
+    uniform: ShadowUniform,
+    active_views: Vec<usize>,
+    uniform_buffer: wgpu::Buffer,
+    caster_buffer: wgpu::Buffer,
+    caster_bind_group: wgpu::BindGroup,
+    sampler: wgpu::Sampler,
+    fallback_atlas: wgpu::TextureView,
+    pipelines: CasterPipelines,
+    atlas_pipeline: wgpu::RenderPipeline,
+    output_format: wgpu::TextureFormat,
+}
+
+impl ShadowMaps {
+    pub const SHADER_PATH: &'static str = "core/shadow.wgsl";
+    pub const ATLAS_SHADER_PATH: &'static str = "effects/shadow_atlas.wgsl";
+
+    pub fn new(
+        device: &wgpu::Device,
+        settings: ShadowSettings,
+        output_format: wgpu::TextureFormat,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<Self, AppError> {
+        let mut settings = settings;
+        let max_tile = device.limits().max_texture_dimension_2d / ATLAS_COLUMNS;
+        if settings.tile_size > max_tile {
+            log_warning!(
+                "Shadow tile size {} exceeds the device limit, using {}",
+                settings.tile_size,
+                max_tile
+            );
+            settings.tile_size = max_tile;
+        }
+
+        let uniform = ShadowUniform::default();
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("ShadowUniformBuffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let caster_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ShadowCasterBuffer"),
+            size: CASTER_STRIDE * SHADOW_VIEW_COUNT as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let caster_bind_group = create_shadow_caster_bind_group(
+            device,
+            &bind_group_layouts.shadow_caster_bind_group_layout,
+            &caster_buffer,
+        );
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("shadow_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+        let fallback_atlas = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("shadow_atlas_fallback"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: ATLAS_FORMAT,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let pipelines = Self::create_caster_pipelines(device, shader_manager, bind_group_layouts)?;
+        let atlas_pipeline =
+            Self::create_atlas_pipeline(device, output_format, shader_manager, bind_group_layouts)?;
+
+        Ok(Self {
+            settings,
+            uniform,
+            active_views: Vec::new(),
+            uniform_buffer,
+            caster_buffer,
+            caster_bind_group,
+            sampler,
+            fallback_atlas,
+            pipelines,
+            atlas_pipeline,
+            output_format,
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    pub fn show_atlas(&self) -> bool {
+        self.settings.enabled && self.settings.show_atlas
+    }
+
+    pub fn toggle_atlas_view(&mut self) -> bool {
+        self.settings.show_atlas = !self.settings.show_atlas;
+        log_info!(
+            "Shadow atlas view {}",
+            if self.settings.show_atlas {
+                "on"
+            } else {
+                "off"
+            }
+        );
+        self.settings.show_atlas
+    }
+
+    pub fn uniform(&self) -> &ShadowUniform {
+        &self.uniform
+    }
+
+    pub fn active_views(&self) -> &[usize] {
+        &self.active_views
+    }
+
+    pub fn atlas_desc(&self) -> TextureDesc {
+        let (width, height) = self.settings.atlas_size();
+        TextureDesc::new(
+            SHADOW_ATLAS,
+            TextureSize::Fixed { width, height },
+            ATLAS_FORMAT,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        )
+    }
+
+    pub fn tile_rect(index: usize) -> [f32; 4] {
+        let column = index as u32 % ATLAS_COLUMNS;
+        let row = index as u32 / ATLAS_COLUMNS;
+        [
+            column as f32 / ATLAS_COLUMNS as f32,
+            row as f32 / ATLAS_ROWS as f32,
+            1.0 / ATLAS_COLUMNS as f32,
+            1.0 / ATLAS_ROWS as f32,
+        ]
+    }
+
+    pub fn tile_viewport(&self, index: usize) -> (f32, f32, f32) {
+        let tile = self.settings.tile_size.max(1) as f32;
+        let column = index as u32 % ATLAS_COLUMNS;
+        let row = index as u32 / ATLAS_COLUMNS;
+        (column as f32 * tile, row as f32 * tile, tile)
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, camera: &CameraHandler, light: &LightUniform) {
+        let settings = &self.settings;
+        let (atlas_width, atlas_height) = settings.atlas_size();
+        let splits = settings.sanitized_splits(camera.projection.znear);
+        let (forward, _, _) = camera.view.calculate_vectors();
+        let sun_direction = Vector3::from(light.sun_direction);
+        let sun_enabled = settings.enabled
+            && !splits.is_empty()
+            && light.sun_intensity > 0.0
+            && sun_direction.magnitude2() > f32::EPSILON;
+        let point_enabled = settings.enabled && light.intensity > 0.0;
+
+        let mut uniform = ShadowUniform {
+            camera_forward: forward.normalize().into(),
+            cascade_count: if sun_enabled { splits.len() as u32 } else { 0 },
+            depth_bias: settings.depth_bias,
+            slope_bias: settings.slope_bias,
+            normal_bias: settings.normal_bias,
+            pcf_radius: settings.pcf_radius.min(ShadowSettings::MAX_PCF_RADIUS),
+            texel_size: [1.0 / atlas_width as f32, 1.0 / atlas_height as f32],
+            sun_enabled: sun_enabled as u32,
+            point_enabled: point_enabled as u32,
+            point_position: light.position,
+            point_far: settings.point_far,
+            ..Default::default()
+        };
+        let mut casters = [ShadowCasterUniform {
+            view_proj: Matrix4::identity().into(),
+            light_position: light.position,
+            far: settings.point_far,
+        }; SHADOW_VIEW_COUNT];
+        self.active_views.clear();
+
+        if sun_enabled {
+            let mut near = camera.projection.znear;
+            for (cascade, split) in splits.iter().enumerate() {
+                uniform.cascade_splits[cascade] = *split;
+                let corners = frustum_slice_corners(camera, near, *split);
+                let view_proj = cascade_view_proj(
+                    &corners,
+                    sun_direction,
+                    settings.tile_size,
+                    settings.caster_distance,
+                );
+                uniform.views[cascade] = ShadowViewRaw {
+                    view_proj: view_proj.into(),
+                    rect: Self::tile_rect(cascade),
+                };
+                casters[cascade].view_proj = view_proj.into();
+                self.active_views.push(cascade);
+                near = *split;
+            }
+        }
+
+        if point_enabled {
+            let position = Point3::from(light.position);
+            for face in 0..CUBE_FACES {
+                let index = MAX_CASCADES + face;
+                let view_proj =
+                    cube_face_view_proj(position, face, settings.point_near, settings.point_far);
+                uniform.views[index] = ShadowViewRaw {
+                    view_proj: view_proj.into(),
+                    rect: Self::tile_rect(index),
+                };
+                casters[index].view_proj = view_proj.into();
+                self.active_views.push(index);
+            }
+        }
+
+        for (index, caster) in casters.iter().enumerate() {
+            queue.write_buffer(
+                &self.caster_buffer,
+                index as u64 * CASTER_STRIDE,
+                bytemuck::cast_slice(&[*caster]),
+            );
+        }
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+        self.uniform = uniform;
+    }
+
+    pub fn bindings<'a>(&'a self, atlas: Option<&'a wgpu::TextureView>) -> ShadowBindings<'a> {
+        ShadowBindings {
+            uniform: &self.uniform_buffer,
+            atlas: atlas.unwrap_or(&self.fallback_atlas),
+            sampler: &self.sampler,
+        }
+    }
+
+    pub fn atlas_bind_group(
+        &self,
+        device: &wgpu::Device,
+        bind_group_layouts: &BindGroupLayouts,
+        atlas: &wgpu::TextureView,
+    ) -> wgpu::BindGroup {
+        create_shadow_atlas_bind_group(
+            device,
+            &bind_group_layouts.shadow_atlas_bind_group_layout,
+            atlas,
+        )
+    }
+
+    pub fn caster_bind_group(&self) -> &wgpu::BindGroup {
+        &self.caster_bind_group
+    }
+
+    pub fn caster_offset(index: usize) -> u32 {
+        (index as u64 * CASTER_STRIDE) as u32
+    }
+
+    pub fn caster_pipeline(&self, caster: ShadowCaster, skinned: bool) -> &wgpu::RenderPipeline {
+        self.pipelines.get(caster, skinned)
+    }
+
+    pub fn draw_atlas(&self, render_pass: &mut wgpu::RenderPass, atlas: &wgpu::BindGroup) {
+        render_pass.set_pipeline(&self.atlas_pipeline);
+        render_pass.set_bind_group(0, atlas, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    pub fn reload_pipelines(
+        &mut self,
+        device: &wgpu::Device,
+        shader_path: &str,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<(), AppError> {
+        if shader_path == Self::SHADER_PATH {
+            self.pipelines = with_validation_scope(device, || {
+                Self::create_caster_pipelines(device, shader_manager, bind_group_layouts)
+            })??;
+        } else if shader_path == Self::ATLAS_SHADER_PATH {
+            self.atlas_pipeline = with_validation_scope(device, || {
+                Self::create_atlas_pipeline(
+                    device,
+                    self.output_format,
+                    shader_manager,
+                    bind_group_layouts,
+                )
+            })??;
+        }
+        Ok(())
+    }
+
+    fn create_caster_pipelines(
+        device: &wgpu::Device,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<CasterPipelines, AppError> {
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Caster Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layouts.shadow_caster_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let skinned_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Skinned Shadow Caster Pipeline Layout"),
+            bind_group_layouts: &[
+                &bind_group_layouts.shadow_caster_bind_group_layout,
+                &bind_group_layouts.shadow_joints_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        Ok(CasterPipelines {
+            directional: Self::create_caster_pipeline(
+                device,
+                &layout,
+                ShadowCaster::Directional,
+                false,
+                shader_manager,
+            )?,
+            point: Self::create_caster_pipeline(
+                device,
+                &layout,
+                ShadowCaster::Point,
+                false,
+                shader_manager,
+            )?,
+            skinned_directional: Self::create_caster_pipeline(
+                device,
+                &skinned_layout,
+                ShadowCaster::Directional,
+                true,
+                shader_manager,
+            )?,
+            skinned_point: Self::create_caster_pipeline(
+                device,
+                &skinned_layout,
+                ShadowCaster::Point,
+                true,
+                shader_manager,
+            )?,
+        })
+    }
+
+    fn create_caster_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        caster: ShadowCaster,
+        skinned: bool,
+        shader_manager: &mut ShaderManager,
+    ) -> Result<wgpu::RenderPipeline, AppError> {
+        let shader_path = if skinned {
+            shader_variant(Self::SHADER_PATH, &["SKINNED"])
+        } else {
+            Self::SHADER_PATH.to_string()
+        };
+        let shader = shader_manager
+            .shaders
+            .get_or_create(CacheKey::from(shader_path.as_str()), || {
+                RupyShader::load(device, &shader_path)
+            })?;
+        let vertex_layout = if skinned {
+            SkinnedVertex::desc()
+        } else {
+            ModelVertex::desc()
+        };
+
+        Ok(
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(&format!("{} {:?}", shader_path, caster)),
+                layout: Some(layout),
+                vertex: wgpu::VertexState {
+                    module: &shader.module,
+                    entry_point: WGSL_VS_MAIN,
+                    buffers: &[vertex_layout, InstanceRaw::desc()],
+                    compilation_options: Default::default(),
+                },
+                fragment: match caster {
+                    ShadowCaster::Directional => None,
+                    ShadowCaster::Point => Some(wgpu::FragmentState {
+                        module: &shader.module,
+                        entry_point: "fs_point",
+                        targets: &[],
+                        compilation_options: Default::default(),
+                    }),
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    cull_mode: None,
+                    ..Default::default()
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: ATLAS_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::LessEqual,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            }),
+        )
+    }
+
+    fn create_atlas_pipeline(
+        device: &wgpu::Device,
+        output_format: wgpu::TextureFormat,
+        shader_manager: &mut ShaderManager,
+        bind_group_layouts: &BindGroupLayouts,
+    ) -> Result<wgpu::RenderPipeline, AppError> {
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Atlas Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layouts.shadow_atlas_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        create_render_pipeline(
+            device,
+            &layout,
+            output_format.add_srgb_suffix(),
+            None,
+            &[],
+            wgpu::PrimitiveTopology::TriangleList,
+            1,
+            Self::ATLAS_SHADER_PATH,
+            shader_manager,
+        )
+    }
+}
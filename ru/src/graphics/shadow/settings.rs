@@ -0,0 +1,86 @@
+use serde::Deserialize;
+
+use crate::{
+    core::{error::AppError, vfs::load_config_section},
+    graphics::uniform::shadow::MAX_CASCADES,
+};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ShadowSettings {
+    pub enabled: bool,
+    pub tile_size: u32,
+    pub cascade_splits: Vec<f32>,
+    pub caster_distance: f32,
+    pub depth_bias: f32,
+    pub slope_bias: f32,
+    pub normal_bias: f32,
+    pub pcf_radius: u32,
+    pub point_near: f32,
+    pub point_far: f32,
+    pub show_atlas: bool,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tile_size: 512,
+            cascade_splits: vec![8.0, 20.0, 45.0, 100.0],
+            caster_distance: 20.0,
+            depth_bias: 0.002,
+            slope_bias: 0.004,
+            normal_bias: 0.03,
+            pcf_radius: 1,
+            point_near: 0.1,
+            point_far: 30.0,
+            show_atlas: false,
+        }
+    }
+}
+
+impl ShadowSettings {
+    pub const MAX_PCF_RADIUS: u32 = 3;
+
+    pub fn load() -> Result<ShadowSettings, AppError> {
+        load_config_section("shadows")
+    }
+
+    pub fn practical_splits(count: usize, near: f32, far: f32, lambda: f32) -> Vec<f32> {
+        let count = count.clamp(1, MAX_CASCADES);
+        (1..=count)
+            .map(|i| {
+                let fraction = i as f32 / count as f32;
+                let logarithmic = near * (far / near).powf(fraction);
+                let uniform = near + (far - near) * fraction;
+                lambda * logarithmic + (1.0 - lambda) * uniform
+            })
+            .collect()
+    }
+
+    pub fn with_cascade_splits(mut self, splits: &[f32]) -> Self {
+        self.cascade_splits = splits.to_vec();
+        self
+    }
+
+    pub fn cascade_count(&self) -> usize {
+        self.cascade_splits.len().min(MAX_CASCADES)
+    }
+
+    pub fn sanitized_splits(&self, near: f32) -> Vec<f32> {
+        let mut previous = near;
+        self.cascade_splits
+            .iter()
+            .take(MAX_CASCADES)
+            .map(|split| {
+                previous = split.max(previous + f32::EPSILON);
+                previous
+            })
+            .collect()
+    }
+
+    pub fn atlas_size(&self) -> (u32, u32) {
+        let tile = self.tile_size.max(1);
+        (tile * super::ATLAS_COLUMNS, tile * super::ATLAS_ROWS)
+    }
+}
@@ -0,0 +1,63 @@
+use image::{Rgba, RgbaImage};
+
+use super::sampler::{SamplerConfig, TextureOptions};
+
+pub const CHECKER_SIZE: u32 = 64;
+pub const CHECKER_CELL: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefaultTexture {
+    White,
+    Black,
+    FlatNormal,
+    Missing,
+}
+
+impl DefaultTexture {
+    pub const ALL: [DefaultTexture; 4] = [
+        DefaultTexture::White,
+        DefaultTexture::Black,
+        DefaultTexture::FlatNormal,
+        DefaultTexture::Missing,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DefaultTexture::White => "texture:default:white",
+            DefaultTexture::Black => "texture:default:black",
+            DefaultTexture::FlatNormal => "texture:default:flat_normal",
+            DefaultTexture::Missing => "texture:default:missing",
+        }
+    }
+
+    pub fn options(&self) -> TextureOptions {
+        let sampler = match self {
+            DefaultTexture::Missing => SamplerConfig::nearest(),
+            _ => SamplerConfig::default(),
+        };
+        TextureOptions {
+            is_normal_map: *self == DefaultTexture::FlatNormal,
+            generate_mipmaps: false,
+            sampler,
+        }
+    }
+
+    pub fn image(&self) -> RgbaImage {
+        match self {
+            DefaultTexture::White => RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 255])),
+            DefaultTexture::Black => RgbaImage::from_pixel(1, 1, Rgba([0, 0, 0, 255])),
+            DefaultTexture::FlatNormal => RgbaImage::from_pixel(1, 1, Rgba([128, 128, 255, 255])),
+            DefaultTexture::Missing => checker_image(CHECKER_SIZE, CHECKER_CELL),
+        }
+    }
+}
+
+pub fn checker_image(size: u32, cell: u32) -> RgbaImage {
+    RgbaImage::from_fn(size, size, |x, y| {
+        if ((x / cell) + (y / cell)).is_multiple_of(2) {
+            Rgba([255, 0, 255, 255])
+        } else {
+            Rgba([0, 0, 0, 255])
+        }
+    })
+}
@@ -1,18 +1,130 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::core::cache::HashCache;
+use crate::{
+    core::{
+        cache::{CacheKey, HashCache},
+        error::AppError,
+        files::FileSystem,
+    },
+    ecs::traits::Cache,
+};
 
-use super::Texture;
+use super::{defaults::DefaultTexture, mipmap::MipmapGenerator, sampler::TextureOptions, Texture};
 
 #[derive(Debug)]
 pub struct TextureManager {
     pub textures: HashCache<Arc<Texture>>,
+    pub default_options: TextureOptions,
+    pub options: HashMap<CacheKey, TextureOptions>,
+    pub mipmaps: MipmapGenerator,
 }
 
 impl TextureManager {
     pub fn new() -> Self {
         Self {
             textures: HashCache::new(),
+            default_options: TextureOptions::default(),
+            options: HashMap::new(),
+            mipmaps: MipmapGenerator::new(),
         }
     }
+
+    pub fn set_options(&mut self, file_name: &str, options: TextureOptions) {
+        self.options.insert(CacheKey::from(file_name), options);
+    }
+
+    pub fn options_for(&self, file_name: &str, is_normal_map: bool) -> TextureOptions {
+        let options = self
+            .options
+            .get(&CacheKey::from(file_name))
+            .unwrap_or(&self.default_options);
+        TextureOptions {
+            is_normal_map,
+            ..*options
+        }
+    }
+
+    pub fn lookup(&self, key: Option<CacheKey>) -> Option<Arc<Texture>> {
+        key.and_then(|key| self.textures.get(&key).cloned())
+    }
+
+    pub fn default_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        default: DefaultTexture,
+    ) -> Result<Arc<Texture>, AppError> {
+        let mipmaps = &mut self.mipmaps;
+        let texture = self
+            .textures
+            .get_or_create(CacheKey::from(default.label()), || {
+                let img = image::DynamicImage::ImageRgba8(default.image());
+                Ok(Arc::new(Texture::from_image_with_options(
+                    device,
+                    queue,
+                    &img,
+                    Some(default.label()),
+                    &default.options(),
+                    mipmaps,
+                )?))
+            })?;
+        Ok(Arc::clone(texture))
+    }
+
+    pub fn insert_placeholder(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        file_name: &str,
+        is_normal_map: bool,
+    ) -> Result<CacheKey, AppError> {
+        let key = CacheKey::from(file_name);
+        let img = image::DynamicImage::ImageRgba8(DefaultTexture::Missing.image());
+        let options = TextureOptions {
+            is_normal_map,
+            ..DefaultTexture::Missing.options()
+        };
+        let texture = Texture::from_image_with_options(
+            device,
+            queue,
+            &img,
+            Some(file_name),
+            &options,
+            &mut self.mipmaps,
+        )?;
+        self.textures.put(key, Arc::new(texture));
+        Ok(key)
+    }
+
+    pub fn reload_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        file_name: &str,
+    ) -> Result<bool, AppError> {
+        let key = CacheKey::from(file_name);
+        let existing = self.textures.get(&key).ok_or_else(|| {
+            AppError::ResourceNotFound(format!("Texture {} is not loaded", file_name))
+        })?;
+        let img = image::load_from_memory(&FileSystem::load_binary(file_name)?)?;
+        let options = self.options_for(file_name, existing.options.is_normal_map);
+
+        if existing.options == options
+            && existing.write_image(device, queue, &img, &mut self.mipmaps)
+        {
+            return Ok(false);
+        }
+
+        let texture = Texture::from_image_with_options(
+            device,
+            queue,
+            &img,
+            Some(file_name),
+            &options,
+            &mut self.mipmaps,
+        )?;
+        self.textures.put(key, Arc::new(texture));
+        Ok(true)
+    }
 }
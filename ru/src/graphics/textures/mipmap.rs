@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use image::imageops::FilterType;
+
+use crate::{
+    core::{error::AppError, files::FileSystem, vfs::AssetDir},
+    graphics::pipelines::common::with_validation_scope,
+    prelude::constant::{WGSL_FS_MAIN, WGSL_VS_MAIN},
+};
+
+pub const MIPMAP_SHADER_PATH: &str = "effects/mipmap.wgsl";
+
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Downsampling pipelines for GPU mip generation. The shader and sampler are created on
+/// first use and a pipeline is kept per texture format.
+#[derive(Debug, Default)]
+pub struct MipmapGenerator {
+    shader: Option<wgpu::ShaderModule>,
+    sampler: Option<wgpu::Sampler>,
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl MipmapGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn generate(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) -> Result<(), AppError> {
+        let mip_count = texture.mip_level_count();
+        if mip_count <= 1 {
+            return Ok(());
+        }
+        if !texture
+            .usage()
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+        {
+            return Err(AppError::ResourceCreationFailed(
+                "Mipmap generation requires a renderable texture".to_string(),
+            ));
+        }
+
+        let format = texture.format();
+        if !self.pipelines.contains_key(&format) {
+            let pipeline = self.create_pipeline(device, format)?;
+            self.pipelines.insert(format, pipeline);
+        }
+        let pipeline = &self.pipelines[&format];
+        let sampler = self.sampler.get_or_insert_with(|| {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("mipmap_sampler"),
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            })
+        });
+
+        with_validation_scope(device, || {
+            let layout = pipeline.get_bind_group_layout(0);
+            let views: Vec<wgpu::TextureView> = (0..mip_count)
+                .map(|mip| {
+                    texture.create_view(&wgpu::TextureViewDescriptor {
+                        label: Some("mip_view"),
+                        base_mip_level: mip,
+                        mip_level_count: Some(1),
+                        ..Default::default()
+                    })
+                })
+                .collect();
+
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("mipmap_encoder"),
+            });
+            for target in 1..mip_count as usize {
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("mipmap_bind_group"),
+                    layout: &layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&views[target - 1]),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(sampler),
+                        },
+                    ],
+                });
+
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("mipmap_pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &views[target],
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+            queue.submit(Some(encoder.finish()));
+        })
+    }
+
+    fn create_pipeline(
+        &mut self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+    ) -> Result<wgpu::RenderPipeline, AppError> {
+        let shader = match &self.shader {
+            Some(shader) => shader,
+            None => {
+                let source = FileSystem::load_asset_string(AssetDir::Shaders, MIPMAP_SHADER_PATH)?;
+                let shader = with_validation_scope(device, || {
+                    device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                        label: Some(MIPMAP_SHADER_PATH),
+                        source: wgpu::ShaderSource::Wgsl(source.into()),
+                    })
+                })?;
+                self.shader.insert(shader)
+            }
+        };
+
+        with_validation_scope(device, || {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("mipmap_pipeline"),
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: shader,
+                    entry_point: WGSL_VS_MAIN,
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: shader,
+                    entry_point: WGSL_FS_MAIN,
+                    targets: &[Some(format.into())],
+                    compilation_options: Default::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        })
+    }
+
+    pub fn pipeline_count(&self) -> usize {
+        self.pipelines.len()
+    }
+}
+
+pub fn generate_mipmaps_cpu(queue: &wgpu::Queue, texture: &wgpu::Texture, img: &image::RgbaImage) {
+    let mut level = img.clone();
+    for mip in 1..texture.mip_level_count() {
+        let width = (img.width() >> mip).max(1);
+        let height = (img.height() >> mip).max(1);
+        level = image::imageops::resize(&level, width, height, FilterType::Triangle);
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                texture,
+                mip_level: mip,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            &level,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
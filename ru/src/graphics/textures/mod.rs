@@ -1,8 +1,12 @@
 pub mod cube_texture;
-pub mod depth_texture;
+pub mod defaults;
 pub mod manager;
-use crate::core::error::AppError;
+pub mod mipmap;
+pub mod sampler;
+use crate::{core::error::AppError, log_warning};
 use image::GenericImageView;
+use mipmap::MipmapGenerator;
+use sampler::{SamplerConfig, TextureOptions};
 use winit::dpi::PhysicalSize;
 
 pub trait BindableTexture {
@@ -18,6 +22,7 @@ pub struct Texture {
     pub view: wgpu::TextureView,
     pub sampler: wgpu::Sampler,
     pub size: wgpu::Extent3d,
+    pub options: TextureOptions,
 }
 
 impl Texture {
@@ -65,6 +70,10 @@ impl Texture {
             view,
             sampler,
             size,
+            options: TextureOptions {
+                generate_mipmaps: false,
+                ..Default::default()
+            },
         }
     }
 
@@ -75,9 +84,22 @@ impl Texture {
         bytes: &[u8],
         label: &str,
         is_normal_map: bool,
+        mipmaps: &mut MipmapGenerator,
+    ) -> Result<Self, AppError> {
+        let img = image::load_from_memory(bytes)?;
+        Self::from_image(device, queue, &img, Some(label), is_normal_map, mipmaps)
+    }
+
+    pub fn from_bytes_with_options(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        label: &str,
+        options: &TextureOptions,
+        mipmaps: &mut MipmapGenerator,
     ) -> Result<Self, AppError> {
         let img = image::load_from_memory(bytes)?;
-        Self::from_image(device, queue, &img, Some(label), is_normal_map)
+        Self::from_image_with_options(device, queue, &img, Some(label), options, mipmaps)
     }
 
     pub fn from_image(
@@ -86,35 +108,64 @@ impl Texture {
         img: &image::DynamicImage,
         label: Option<&str>,
         is_normal_map: bool,
+        mipmaps: &mut MipmapGenerator,
     ) -> Result<Self, AppError> {
-        let dimensions = img.dimensions();
-        let rgba = img.to_rgba8();
-
-        let format = if is_normal_map {
-            wgpu::TextureFormat::Rgba8Unorm
-        } else {
-            wgpu::TextureFormat::Rgba8UnormSrgb
+        let options = TextureOptions {
+            is_normal_map,
+            ..Default::default()
         };
-        let usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        Self::from_image_with_options(device, queue, img, label, &options, mipmaps)
+    }
+
+    pub fn from_image_with_options(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img: &image::DynamicImage,
+        label: Option<&str>,
+        options: &TextureOptions,
+        mipmaps: &mut MipmapGenerator,
+    ) -> Result<Self, AppError> {
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        if options.generate_mipmaps {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
         let size = wgpu::Extent3d {
             width: img.width(),
             height: img.height(),
             depth_or_array_layers: 1,
         };
-        let texture = Self::create_2d_texture(
+        let texture = Self::create_texture_with_options(
             device,
-            size.width,
-            size.height,
-            format,
-            usage,
-            wgpu::FilterMode::Linear,
             label,
+            size,
+            options.format(),
+            usage,
+            wgpu::TextureDimension::D2,
+            options,
         );
 
+        texture.write_image(device, queue, img, mipmaps);
+
+        Ok(texture)
+    }
+
+    pub fn write_image(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img: &image::DynamicImage,
+        mipmaps: &mut MipmapGenerator,
+    ) -> bool {
+        let dimensions = img.dimensions();
+        if dimensions != (self.size.width, self.size.height) {
+            return false;
+        }
+        let rgba = img.to_rgba8();
+
         queue.write_texture(
             wgpu::ImageCopyTexture {
                 aspect: wgpu::TextureAspect::All,
-                texture: &texture.texture,
+                texture: &self.texture,
                 mip_level: 0,
                 origin: wgpu::Origin3d::ZERO,
             },
@@ -124,10 +175,16 @@ impl Texture {
                 bytes_per_row: Some(4 * dimensions.0),
                 rows_per_image: Some(dimensions.1),
             },
-            size,
+            self.size,
         );
 
-        Ok(texture)
+        if self.texture.mip_level_count() > 1 {
+            if let Err(e) = mipmaps.generate(device, queue, &self.texture) {
+                log_warning!("GPU mipmap generation failed, using CPU fallback: {}", e);
+                mipmap::generate_mipmaps_cpu(queue, &self.texture, &rgba);
+            }
+        }
+        true
     }
 
     pub(crate) fn create_2d_texture(
@@ -164,10 +221,34 @@ impl Texture {
         dimension: wgpu::TextureDimension,
         mag_filter: wgpu::FilterMode,
     ) -> Self {
+        let options = TextureOptions {
+            is_normal_map: format == wgpu::TextureFormat::Rgba8Unorm,
+            generate_mipmaps: false,
+            sampler: SamplerConfig::clamped(mag_filter),
+        };
+        Self::create_texture_with_options(device, label, size, format, usage, dimension, &options)
+    }
+
+    /// Creates a texture with a full mip chain when `options.generate_mipmaps` is set and a
+    /// sampler from `options.sampler`. Mip contents are left for the caller to fill.
+    pub fn create_texture_with_options(
+        device: &wgpu::Device,
+        label: Option<&str>,
+        size: wgpu::Extent3d,
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsages,
+        dimension: wgpu::TextureDimension,
+        options: &TextureOptions,
+    ) -> Self {
+        let mip_level_count = if options.generate_mipmaps {
+            mipmap::mip_level_count(size.width, size.height)
+        } else {
+            1
+        };
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label,
             size,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension,
             format,
@@ -176,21 +257,13 @@ impl Texture {
         });
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
 
         Self {
-            texture,
             view,
-            sampler,
+            sampler: options.sampler.create_sampler(device, label),
             size,
+            options: *options,
+            texture,
         }
     }
 }
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::core::{error::AppError, vfs::load_config_section};
+
+use super::manager::TextureManager;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplerConfig {
+    pub address_mode: wgpu::AddressMode,
+    pub mag_filter: wgpu::FilterMode,
+    pub min_filter: wgpu::FilterMode,
+    pub mipmap_filter: wgpu::FilterMode,
+    pub anisotropy: u16,
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        Self {
+            address_mode: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            anisotropy: 16,
+        }
+    }
+}
+
+impl SamplerConfig {
+    pub fn nearest() -> Self {
+        Self {
+            address_mode: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            anisotropy: 1,
+        }
+    }
+
+    pub fn clamped(mag_filter: wgpu::FilterMode) -> Self {
+        Self {
+            address_mode: wgpu::AddressMode::ClampToEdge,
+            mag_filter,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            anisotropy: 1,
+        }
+    }
+
+    pub fn with_address_mode(mut self, address_mode: wgpu::AddressMode) -> Self {
+        self.address_mode = address_mode;
+        self
+    }
+
+    pub fn with_anisotropy(mut self, anisotropy: u16) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    pub fn create_sampler(&self, device: &wgpu::Device, label: Option<&str>) -> wgpu::Sampler {
+        // wgpu only accepts anisotropic filtering when every filter is linear.
+        let linear = self.mag_filter == wgpu::FilterMode::Linear
+            && self.min_filter == wgpu::FilterMode::Linear
+            && self.mipmap_filter == wgpu::FilterMode::Linear;
+        let anisotropy_clamp = if linear {
+            self.anisotropy.clamp(1, 16)
+        } else {
+            1
+        };
+
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label,
+            address_mode_u: self.address_mode,
+            address_mode_v: self.address_mode,
+            address_mode_w: self.address_mode,
+            mag_filter: self.mag_filter,
+            min_filter: self.min_filter,
+            mipmap_filter: self.mipmap_filter,
+            anisotropy_clamp,
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureOptions {
+    pub is_normal_map: bool,
+    pub generate_mipmaps: bool,
+    pub sampler: SamplerConfig,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            is_normal_map: false,
+            generate_mipmaps: true,
+            sampler: SamplerConfig::default(),
+        }
+    }
+}
+
+impl TextureOptions {
+    pub fn normal_map() -> Self {
+        Self {
+            is_normal_map: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        if self.is_normal_map {
+            wgpu::TextureFormat::Rgba8Unorm
+        } else {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        }
+    }
+}
+
+/// Sampler and mip overrides for loaded textures. Unset fields keep the current value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TextureOverride {
+    pub address_mode: Option<wgpu::AddressMode>,
+    /// Applied to the mag, min and mipmap filters.
+    pub filter: Option<wgpu::FilterMode>,
+    pub anisotropy: Option<u16>,
+    pub mipmaps: Option<bool>,
+}
+
+impl TextureOverride {
+    pub fn apply(&self, mut options: TextureOptions) -> TextureOptions {
+        if let Some(address_mode) = self.address_mode {
+            options.sampler.address_mode = address_mode;
+        }
+        if let Some(filter) = self.filter {
+            options.sampler.mag_filter = filter;
+            options.sampler.min_filter = filter;
+            options.sampler.mipmap_filter = filter;
+        }
+        if let Some(anisotropy) = self.anisotropy {
+            options.sampler.anisotropy = anisotropy;
+        }
+        if let Some(mipmaps) = self.mipmaps {
+            options.generate_mipmaps = mipmaps;
+        }
+        options
+    }
+}
+
+/// The `[textures]` config section: top-level fields override every texture and
+/// `[textures.files."<name>"]` tables override single textures by the name materials use.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TextureSettings {
+    #[serde(flatten)]
+    pub defaults: TextureOverride,
+    pub files: HashMap<String, TextureOverride>,
+}
+
+impl TextureSettings {
+    pub fn load() -> Result<TextureSettings, AppError> {
+        load_config_section("textures")
+    }
+
+    /// Sets the manager's default and per-file options. Call before materials load;
+    /// already loaded textures pick the options up on their next reload.
+    pub fn apply(&self, manager: &mut TextureManager) {
+        manager.default_options = self.defaults.apply(manager.default_options);
+        for (file_name, file_override) in &self.files {
+            manager.set_options(file_name, file_override.apply(manager.default_options));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::vfs::parse_config_section;
+
+    #[test]
+    fn config_overrides_reach_texture_options() {
+        let content = r#"
+            [textures]
+            anisotropy = 4
+
+            [textures.files."grid.png"]
+            address_mode = "clamp-to-edge"
+            filter = "nearest"
+            mipmaps = false
+        "#;
+        let settings: TextureSettings = parse_config_section(content, "textures").unwrap();
+        let mut manager = TextureManager::new();
+        settings.apply(&mut manager);
+
+        let other = manager.options_for("other.png", false);
+        assert_eq!(other.sampler.anisotropy, 4);
+        assert_eq!(other.sampler.address_mode, wgpu::AddressMode::Repeat);
+
+        let grid = manager.options_for("grid.png", false);
+        assert_eq!(grid.sampler.address_mode, wgpu::AddressMode::ClampToEdge);
+        assert_eq!(grid.sampler.mag_filter, wgpu::FilterMode::Nearest);
+        assert_eq!(grid.sampler.anisotropy, 4);
+        assert!(!grid.generate_mipmaps);
+        assert_eq!(grid.format(), wgpu::TextureFormat::Rgba8UnormSrgb);
+
+        let grid_normal = manager.options_for("grid.png", true);
+        assert!(grid_normal.is_normal_map);
+        assert_eq!(grid_normal.format(), wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(grid_normal.sampler, grid.sampler);
+    }
+}
@@ -0,0 +1,50 @@
+pub const CLUSTER_GRID: [u32; 3] = [16, 9, 24];
+pub const CLUSTER_COUNT: u32 = CLUSTER_GRID[0] * CLUSTER_GRID[1] * CLUSTER_GRID[2];
+pub const MAX_LIGHTS_PER_CLUSTER: u32 = 63;
+pub const CLUSTER_STRIDE: u32 = MAX_LIGHTS_PER_CLUSTER + 1;
+pub const MAX_LIGHTS: usize = 1024;
+
+pub const LIGHT_POINT: u32 = 0;
+pub const LIGHT_SPOT: u32 = 1;
+pub const LIGHT_DIRECTIONAL: u32 = 2;
+
+pub const SHADOW_NONE: u32 = 0;
+pub const SHADOW_SUN: u32 = 1;
+pub const SHADOW_POINT: u32 = 2;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightRaw {
+    pub position: [f32; 3],
+    pub range: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub direction: [f32; 3],
+    pub kind: u32,
+    pub spot_scale: f32,
+    pub spot_offset: f32,
+    pub shadow: u32,
+    pub _padding: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ClusterUniform {
+    pub view: [[f32; 4]; 4],
+    pub screen_size: [f32; 2],
+    pub z_near: f32,
+    pub z_far: f32,
+    pub tan_half_fov: [f32; 2],
+    pub _padding: [f32; 2],
+    pub grid: [u32; 3],
+    pub light_count: u32,
+}
+
+impl Default for ClusterUniform {
+    fn default() -> Self {
+        Self {
+            grid: CLUSTER_GRID,
+            ..bytemuck::Zeroable::zeroed()
+        }
+    }
+}
@@ -0,0 +1,17 @@
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct IblParams {
+    pub roughness: f32,
+    pub sample_count: u32,
+    pub _padding: [f32; 2],
+}
+
+impl IblParams {
+    pub fn new(roughness: f32, sample_count: u32) -> Self {
+        Self {
+            roughness,
+            sample_count,
+            ..Default::default()
+        }
+    }
+}
@@ -1,31 +1,62 @@
 use crate::prelude::constant::Paddings;
 
+pub const DEFAULT_SUN_DIRECTION: [f32; 3] = [-0.35, -1.0, -0.25];
+pub const DEFAULT_SUN_INTENSITY: f32 = 0.6;
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct LightUniform {
     pub position: [f32; 3],
-    pub _padding: u32,
+    pub range: f32,
     pub color: [f32; 3],
-    pub _padding2: u32,
+    pub intensity: f32,
+    pub sun_direction: [f32; 3],
+    pub sun_intensity: f32,
+    pub sun_color: [f32; 3],
+    pub _padding: u32,
 }
 
 impl LightUniform {
     pub fn new(position: [f32; 3], color: [f32; 3]) -> Self {
         LightUniform {
             position,
-            _padding: Paddings::PADDING,
             color,
-            _padding2: Paddings::PADDING,
+            ..Default::default()
         }
     }
+
+    pub fn with_point(
+        mut self,
+        position: [f32; 3],
+        color: [f32; 3],
+        intensity: f32,
+        range: f32,
+    ) -> Self {
+        self.position = position;
+        self.color = color;
+        self.intensity = intensity;
+        self.range = range;
+        self
+    }
+
+    pub fn with_sun(mut self, direction: [f32; 3], color: [f32; 3], intensity: f32) -> Self {
+        self.sun_direction = direction;
+        self.sun_color = color;
+        self.sun_intensity = intensity;
+        self
+    }
 }
 impl Default for LightUniform {
     fn default() -> Self {
         Self {
             position: [1.0, 1.0, 1.0],
-            _padding: Paddings::PADDING,
+            range: 0.0,
             color: [1.0, 1.0, 1.0],
-            _padding2: Paddings::PADDING,
+            intensity: 0.0,
+            sun_direction: DEFAULT_SUN_DIRECTION,
+            sun_intensity: 0.0,
+            sun_color: [1.0, 1.0, 1.0],
+            _padding: Paddings::PADDING,
         }
     }
 }
@@ -0,0 +1,66 @@
+use crate::ecs::components::material::model::Material;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MaterialUniform {
+    pub base_color_factor: [f32; 4],
+    pub ambient: [f32; 3],
+    pub shininess: f32,
+    pub diffuse: [f32; 3],
+    pub dissolve: f32,
+    pub specular: [f32; 3],
+    pub illumination_model: u32,
+    pub emissive_factor: [f32; 3],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub occlusion_strength: f32,
+    pub normal_scale: f32,
+    pub optical_density: f32,
+}
+
+impl MaterialUniform {
+    /// MTL `illum 2`: ambient, diffuse and specular highlights. Environment reflection is
+    /// opt-in through `illum 3`..`illum 9`, matching the MTL spec.
+    pub const DEFAULT_ILLUMINATION_MODEL: u32 = 2;
+
+    pub fn from_material(material: &Material) -> Self {
+        Self {
+            base_color_factor: material.base_color_factor,
+            ambient: material.ambient,
+            shininess: material.shininess,
+            diffuse: material.diffuse,
+            dissolve: material.dissolve,
+            specular: material.specular,
+            illumination_model: material
+                .illumination_model
+                .map(u32::from)
+                .unwrap_or(Self::DEFAULT_ILLUMINATION_MODEL),
+            emissive_factor: material.emissive_factor,
+            metallic_factor: material.metallic_factor,
+            roughness_factor: material.roughness_factor,
+            occlusion_strength: material.occlusion_strength,
+            normal_scale: material.normal_scale,
+            optical_density: material.optical_density,
+        }
+    }
+}
+
+impl Default for MaterialUniform {
+    fn default() -> Self {
+        Self {
+            base_color_factor: [1.0; 4],
+            ambient: [1.0; 3],
+            shininess: 32.0,
+            diffuse: [1.0; 3],
+            dissolve: 1.0,
+            specular: [0.5; 3],
+            illumination_model: Self::DEFAULT_ILLUMINATION_MODEL,
+            emissive_factor: [0.0; 3],
+            metallic_factor: 0.0,
+            roughness_factor: 1.0,
+            occlusion_strength: 1.0,
+            normal_scale: 1.0,
+            optical_density: 1.0,
+        }
+    }
+}
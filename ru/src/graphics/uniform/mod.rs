@@ -2,7 +2,12 @@ use camera::CameraUniform;
 use lighting::LightUniform;
 
 pub mod camera;
+pub mod cluster;
+pub mod ibl;
 pub mod lighting;
+pub mod material;
+pub mod post;
+pub mod shadow;
 
 pub struct Uniforms {
     pub camera: CameraUniform,
@@ -0,0 +1,35 @@
+use crate::graphics::post::settings::PostSettings;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PostUniform {
+    pub exposure: f32,
+    pub tone_mapper: u32,
+    pub bloom_intensity: f32,
+    pub bloom_threshold: f32,
+    pub bloom_knee: f32,
+    pub bloom_radius: f32,
+    pub vignette_intensity: f32,
+    pub vignette_smoothness: f32,
+    pub grain_intensity: f32,
+    pub frame: u32,
+    pub _padding: [f32; 2],
+}
+
+impl PostUniform {
+    pub fn new(settings: &PostSettings, frame: u32) -> Self {
+        Self {
+            exposure: settings.exposure_scale(),
+            tone_mapper: settings.tone_mapping.mapper.code(),
+            bloom_intensity: settings.bloom.intensity.clamp(0.0, 1.0),
+            bloom_threshold: settings.bloom.threshold.max(0.0),
+            bloom_knee: settings.bloom.knee.max(1e-4),
+            bloom_radius: settings.bloom.filter_radius.max(0.0),
+            vignette_intensity: settings.vignette.intensity.max(0.0),
+            vignette_smoothness: settings.vignette.smoothness.max(1e-4),
+            grain_intensity: settings.film_grain.intensity.max(0.0),
+            frame,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,42 @@
+pub const MAX_CASCADES: usize = 4;
+pub const CUBE_FACES: usize = 6;
+pub const SHADOW_VIEW_COUNT: usize = MAX_CASCADES + CUBE_FACES;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShadowViewRaw {
+    pub view_proj: [[f32; 4]; 4],
+    pub rect: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShadowUniform {
+    pub views: [ShadowViewRaw; SHADOW_VIEW_COUNT],
+    pub cascade_splits: [f32; 4],
+    pub camera_forward: [f32; 3],
+    pub cascade_count: u32,
+    pub depth_bias: f32,
+    pub slope_bias: f32,
+    pub normal_bias: f32,
+    pub pcf_radius: u32,
+    pub texel_size: [f32; 2],
+    pub sun_enabled: u32,
+    pub point_enabled: u32,
+    pub point_position: [f32; 3],
+    pub point_far: f32,
+}
+
+impl Default for ShadowUniform {
+    fn default() -> Self {
+        bytemuck::Zeroable::zeroed()
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShadowCasterUniform {
+    pub view_proj: [[f32; 4]; 4],
+    pub light_position: [f32; 3],
+    pub far: f32,
+}
@@ -68,19 +68,86 @@ impl Vertex for ModelVertex {
     }
 }
 
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Default, PartialEq)]
+pub struct VertexSkin {
+    pub joints: [u32; 4],
+    pub weights: [f32; 4],
+}
+
+impl VertexSkin {
+    pub fn normalized(mut self) -> VertexSkin {
+        let total: f32 = self.weights.iter().sum();
+        if total > f32::EPSILON {
+            self.weights = self.weights.map(|w| w / total);
+        } else {
+            self.joints = [0; 4];
+            self.weights = [1.0, 0.0, 0.0, 0.0];
+        }
+        self
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
+pub struct SkinnedVertex {
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+    pub normal: [f32; 3],
+    pub tangent: [f32; 3],
+    pub bitangent: [f32; 3],
+    pub joints: [u32; 4],
+    pub weights: [f32; 4],
+}
+impl SkinnedVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 7] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x3, 3 => Float32x3, 4 => Float32x3, 13 => Uint32x4, 14 => Float32x4];
+
+    pub fn new(vertex: ModelVertex, skin: VertexSkin) -> SkinnedVertex {
+        SkinnedVertex {
+            position: vertex.position,
+            tex_coords: vertex.tex_coords,
+            normal: vertex.normal,
+            tangent: vertex.tangent,
+            bitangent: vertex.bitangent,
+            joints: skin.joints,
+            weights: skin.weights,
+        }
+    }
+}
+impl Vertex for SkinnedVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<SkinnedVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum VertexType {
     Textured(VertexTexture),
     Colored(VertexColor),
     Modeled(ModelVertex),
+    Skinned(SkinnedVertex),
 }
 
 impl VertexType {
+    pub fn position(&self) -> [f32; 3] {
+        match self {
+            VertexType::Textured(v) => v.position,
+            VertexType::Colored(v) => v.position,
+            VertexType::Modeled(v) => v.position,
+            VertexType::Skinned(v) => v.position,
+        }
+    }
     pub fn as_bytes(&self) -> &[u8] {
         match self {
             VertexType::Textured(v) => bytemuck::cast_slice(std::slice::from_ref(v)),
             VertexType::Colored(v) => bytemuck::cast_slice(std::slice::from_ref(v)),
             VertexType::Modeled(v) => bytemuck::cast_slice(std::slice::from_ref(v)),
+            VertexType::Skinned(v) => bytemuck::cast_slice(std::slice::from_ref(v)),
         }
     }
 
@@ -89,6 +156,7 @@ impl VertexType {
             VertexType::Textured(data) => bytemuck::cast_slice(std::slice::from_ref(data)).to_vec(),
             VertexType::Colored(data) => bytemuck::cast_slice(std::slice::from_ref(data)).to_vec(),
             VertexType::Modeled(data) => bytemuck::cast_slice(std::slice::from_ref(data)).to_vec(),
+            VertexType::Skinned(data) => bytemuck::cast_slice(std::slice::from_ref(data)).to_vec(),
         }
     }
 }
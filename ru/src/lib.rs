@@ -4,6 +4,7 @@ pub mod core;
 pub mod ecs;
 pub mod graphics;
 pub mod math;
+pub mod testing;
 pub mod ui;
 
 pub mod utilities;
@@ -3,8 +3,9 @@ use std::sync::Arc;
 use crossbeam::channel::{self, Receiver, Sender};
 
 use rupy::{
-    app::app::Rupy,
+    app::{app::Rupy, state::State},
     core::{
+        asset_graph::{AssetGraph, AssetId},
         error::AppError,
         events::{
             proxy::{EventBusProxy, EventProxy, EventProxyTrait},
@@ -12,10 +13,14 @@ use rupy::{
         },
         worker::{RupyWorker, WorkerTask},
     },
+    graphics::settings::RenderSettings,
     rupyLogger::factory::LogFactory,
 };
 use winit::event_loop::EventLoop;
 
+const HEADLESS_WIDTH: u32 = 800;
+const HEADLESS_HEIGHT: u32 = 600;
+
 #[tokio::main]
 async fn main() -> Result<(), AppError> {
     cfg_if::cfg_if! {
@@ -31,11 +36,20 @@ async fn main() -> Result<(), AppError> {
         }
     }
 
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("check-assets") {
+        return check_assets(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("render") {
+        return render_headless(&args[2..]).await;
+    }
+
     let (tx, rx): (Sender<RupyAppEvent>, Receiver<RupyAppEvent>) = channel::unbounded();
     let (task_tx, task_rx): (Sender<WorkerTask>, Receiver<WorkerTask>) =
         crossbeam::channel::unbounded();
 
     RupyWorker::spawn(task_rx, tx.clone());
+    spawn_console(tx.clone());
 
     let arc_tx = Arc::new(tx);
 
@@ -63,3 +77,66 @@ async fn main() -> Result<(), AppError> {
     let _ = event_loop.run_app(&mut rupy);
     Ok(())
 }
+
+fn spawn_console(tx: Sender<RupyAppEvent>) {
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines().map_while(Result::ok) {
+            let command = line.trim().to_string();
+            if command.is_empty() {
+                continue;
+            }
+            if tx.send(RupyAppEvent::InputCommand { command }).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+async fn render_headless(args: &[String]) -> Result<(), AppError> {
+    let Some(output) = args.first() else {
+        return Err(AppError::ConfigError(String::from(
+            "Usage: render <output.png> [scene] [WIDTHxHEIGHT]",
+        )));
+    };
+    let mut scene = None;
+    let (mut width, mut height) = (HEADLESS_WIDTH, HEADLESS_HEIGHT);
+    for arg in &args[1..] {
+        match parse_size(arg) {
+            Some(size) => (width, height) = size,
+            None => scene = Some(arg.as_str()),
+        }
+    }
+
+    let mut state = State::headless(width, height, RenderSettings::load()).await?;
+    match scene {
+        Some(scene) => {
+            state.load_scene(scene).await?;
+        }
+        None => state.spawn_demo_scene().await?,
+    }
+    state.update();
+    state.save_png(output)?;
+    println!("Rendered {}x{} image to {}", width, height, output);
+    Ok(())
+}
+
+fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let (width, height) = size.split_once('x')?;
+    let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+fn check_assets(roots: &[String]) -> Result<(), AppError> {
+    let graph = if roots.is_empty() {
+        AssetGraph::from_assets_dir()?
+    } else {
+        AssetGraph::build(roots.iter().map(|r| AssetId::from_reference(r)).collect())?
+    };
+    let report = graph.report()?;
+    println!("Checked {} assets.", graph.nodes.len());
+    print!("{}", report);
+    if !report.is_ok() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
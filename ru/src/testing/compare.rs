@@ -0,0 +1,152 @@
+use std::fmt;
+
+use image::{Rgba, RgbaImage};
+
+use crate::core::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub per_channel: u8,
+    pub max_differing_ratio: f32,
+    pub max_mean_error: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            per_channel: 8,
+            max_differing_ratio: 0.005,
+            max_mean_error: 1.0,
+        }
+    }
+}
+
+impl Tolerance {
+    pub fn exact() -> Self {
+        Self {
+            per_channel: 0,
+            max_differing_ratio: 0.0,
+            max_mean_error: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageComparison {
+    pub width: u32,
+    pub height: u32,
+    pub differing_pixels: u64,
+    pub max_channel_difference: u8,
+    pub mean_error: f32,
+}
+
+impl ImageComparison {
+    pub fn total_pixels(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    pub fn differing_ratio(&self) -> f32 {
+        if self.total_pixels() == 0 {
+            0.0
+        } else {
+            self.differing_pixels as f32 / self.total_pixels() as f32
+        }
+    }
+
+    pub fn passes(&self, tolerance: &Tolerance) -> bool {
+        self.differing_ratio() <= tolerance.max_differing_ratio
+            && self.mean_error <= tolerance.max_mean_error
+    }
+}
+
+impl fmt::Display for ImageComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} pixels differ ({:.3}%), max channel difference {}, mean error {:.3}",
+            self.differing_pixels,
+            self.total_pixels(),
+            self.differing_ratio() * 100.0,
+            self.max_channel_difference,
+            self.mean_error
+        )
+    }
+}
+
+fn check_sizes(actual: &RgbaImage, expected: &RgbaImage) -> Result<(), AppError> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(AppError::ResourceCreationFailed(format!(
+            "Image size {:?} does not match reference size {:?}",
+            actual.dimensions(),
+            expected.dimensions()
+        )));
+    }
+    Ok(())
+}
+
+fn pixel_difference(a: &Rgba<u8>, b: &Rgba<u8>) -> u8 {
+    a.0.iter()
+        .zip(b.0.iter())
+        .map(|(a, b)| a.abs_diff(*b))
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn compare_images(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: &Tolerance,
+) -> Result<ImageComparison, AppError> {
+    check_sizes(actual, expected)?;
+    let mut differing_pixels = 0;
+    let mut max_channel_difference = 0;
+    let mut total_error = 0u64;
+    for (a, b) in actual.pixels().zip(expected.pixels()) {
+        let difference = pixel_difference(a, b);
+        max_channel_difference = max_channel_difference.max(difference);
+        if difference > tolerance.per_channel {
+            differing_pixels += 1;
+        }
+        total_error +=
+            a.0.iter()
+                .zip(b.0.iter())
+                .map(|(a, b)| a.abs_diff(*b) as u64)
+                .sum::<u64>();
+    }
+    let channels = actual.width() as u64 * actual.height() as u64 * 4;
+    Ok(ImageComparison {
+        width: actual.width(),
+        height: actual.height(),
+        differing_pixels,
+        max_channel_difference,
+        mean_error: if channels == 0 {
+            0.0
+        } else {
+            total_error as f32 / channels as f32
+        },
+    })
+}
+
+pub fn diff_image(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: &Tolerance,
+) -> Result<RgbaImage, AppError> {
+    check_sizes(actual, expected)?;
+    Ok(RgbaImage::from_fn(
+        actual.width(),
+        actual.height(),
+        |x, y| {
+            let a = actual.get_pixel(x, y);
+            let b = expected.get_pixel(x, y);
+            let difference = pixel_difference(a, b);
+            if difference > tolerance.per_channel {
+                Rgba([255, 0, difference.saturating_mul(4), 255])
+            } else {
+                let luma = (b.0[0] as u32 * 3 + b.0[1] as u32 * 6 + b.0[2] as u32) / 10;
+                let faded = (luma / 4) as u8;
+                Rgba([faded, faded, faded, 255])
+            }
+        },
+    ))
+}
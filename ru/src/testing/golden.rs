@@ -0,0 +1,259 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use cgmath::{Deg, Point3};
+use image::RgbaImage;
+
+use crate::{
+    app::state::State,
+    camera::Camera,
+    core::{error::AppError, files::FileSystem},
+    graphics::settings::RenderSettings,
+    log_info, log_warning,
+};
+
+use super::compare::{compare_images, diff_image, ImageComparison, Tolerance};
+
+pub const GOLDEN_DIR_ENV: &str = "RUPY_GOLDEN_DIR";
+pub const UPDATE_GOLDEN_ENV: &str = "RUPY_UPDATE_GOLDEN";
+pub const DEFAULT_GOLDEN_DIR: &str = "tests/golden";
+pub const FAILURES_DIR: &str = "failures";
+pub const GOLDEN_MOUNT_PRIORITY: i32 = 10;
+
+/// Fixture roots mounted for one render. They are unmounted on drop, including when the
+/// render fails, so one test's fixtures never shadow another's assets.
+struct FixtureMounts {
+    names: Vec<String>,
+}
+
+impl FixtureMounts {
+    fn mount(roots: &[PathBuf]) -> Result<Self, AppError> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let mut mounts = FixtureMounts { names: Vec::new() };
+        for root in roots {
+            let name = format!("golden:{}:{}", id, root.display());
+            FileSystem::mount(&name, root, GOLDEN_MOUNT_PRIORITY)?;
+            mounts.names.push(name);
+        }
+        Ok(mounts)
+    }
+}
+
+impl Drop for FixtureMounts {
+    fn drop(&mut self) {
+        for name in &self.names {
+            if let Err(e) = FileSystem::unmount(name) {
+                log_warning!("Failed to unmount {}: {}", name, e);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraPose {
+    pub position: Point3<f32>,
+    pub yaw: Deg<f32>,
+    pub pitch: Deg<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GoldenOutcome {
+    Matched(ImageComparison),
+    Updated(PathBuf),
+    Mismatched {
+        comparison: ImageComparison,
+        actual: PathBuf,
+        diff: PathBuf,
+    },
+}
+
+impl GoldenOutcome {
+    pub fn passed(&self) -> bool {
+        !matches!(self, GoldenOutcome::Mismatched { .. })
+    }
+}
+
+impl fmt::Display for GoldenOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoldenOutcome::Matched(comparison) => write!(f, "matched: {}", comparison),
+            GoldenOutcome::Updated(path) => write!(f, "updated reference {:?}", path),
+            GoldenOutcome::Mismatched {
+                comparison,
+                actual,
+                diff,
+            } => write!(
+                f,
+                "mismatched: {} (actual {:?}, diff {:?})",
+                comparison, actual, diff
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GoldenTest {
+    pub name: String,
+    pub scene: Option<String>,
+    pub mounts: Vec<PathBuf>,
+    pub width: u32,
+    pub height: u32,
+    pub frames: u32,
+    pub delta_time: f32,
+    pub camera: Option<CameraPose>,
+    pub tolerance: Tolerance,
+    pub settings: RenderSettings,
+    pub golden_dir: PathBuf,
+}
+
+impl GoldenTest {
+    pub fn new(name: &str) -> Self {
+        let golden_dir = std::env::var(GOLDEN_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_GOLDEN_DIR));
+        Self {
+            name: name.to_string(),
+            scene: None,
+            mounts: Vec::new(),
+            width: 256,
+            height: 256,
+            frames: 1,
+            delta_time: 1.0 / 60.0,
+            camera: None,
+            tolerance: Tolerance::default(),
+            settings: RenderSettings::default(),
+            golden_dir,
+        }
+    }
+
+    pub fn scene(mut self, file_name: &str) -> Self {
+        self.scene = Some(file_name.to_string());
+        self
+    }
+
+    pub fn mount<P: AsRef<Path>>(mut self, root: P) -> Self {
+        self.mounts.push(root.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn frames(mut self, frames: u32) -> Self {
+        self.frames = frames.max(1);
+        self
+    }
+
+    pub fn delta_time(mut self, delta_time: f32) -> Self {
+        self.delta_time = delta_time;
+        self
+    }
+
+    pub fn camera<P: Into<Point3<f32>>>(
+        mut self,
+        position: P,
+        yaw: Deg<f32>,
+        pitch: Deg<f32>,
+    ) -> Self {
+        self.camera = Some(CameraPose {
+            position: position.into(),
+            yaw,
+            pitch,
+        });
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Renderer settings for the test. Defaults are used rather than the config file so
+    /// reference images don't depend on local configuration.
+    pub fn settings(mut self, settings: RenderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn golden_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.golden_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn reference_path(&self) -> PathBuf {
+        self.golden_dir.join(format!("{}.png", self.name))
+    }
+
+    pub fn failure_paths(&self) -> (PathBuf, PathBuf) {
+        let dir = self.golden_dir.join(FAILURES_DIR);
+        (
+            dir.join(format!("{}.actual.png", self.name)),
+            dir.join(format!("{}.diff.png", self.name)),
+        )
+    }
+
+    pub async fn render(&self) -> Result<RgbaImage, AppError> {
+        let _mounts = FixtureMounts::mount(&self.mounts)?;
+
+        let mut state = State::headless(self.width, self.height, self.settings.clone()).await?;
+        state.fixed_delta_time = Some(self.delta_time);
+        match &self.scene {
+            Some(scene) => {
+                state.load_scene(scene).await?;
+            }
+            None => state.spawn_demo_scene().await?,
+        }
+        if let Some(pose) = self.camera {
+            state.camera_handler.view = Camera::new(pose.position, pose.yaw, pose.pitch);
+        }
+
+        for _ in 1..self.frames {
+            state.update();
+            state.render_frame()?;
+        }
+        state.update();
+        state.render_to_image()
+    }
+
+    pub async fn run(&self) -> Result<GoldenOutcome, AppError> {
+        let actual = self.render().await?;
+        let reference = self.reference_path();
+
+        if std::env::var(UPDATE_GOLDEN_ENV).is_ok() {
+            std::fs::create_dir_all(&self.golden_dir)?;
+            actual.save(&reference)?;
+            log_info!("Updated golden image {:?}", reference);
+            return Ok(GoldenOutcome::Updated(reference));
+        }
+        if !reference.exists() {
+            return Err(AppError::FileNotFoundError(format!(
+                "Missing golden image {}, rerun with {}=1 to create it",
+                reference.display(),
+                UPDATE_GOLDEN_ENV
+            )));
+        }
+
+        let expected = image::open(&reference)?.to_rgba8();
+        let comparison = compare_images(&actual, &expected, &self.tolerance)?;
+        if comparison.passes(&self.tolerance) {
+            return Ok(GoldenOutcome::Matched(comparison));
+        }
+
+        let (actual_path, diff_path) = self.failure_paths();
+        std::fs::create_dir_all(self.golden_dir.join(FAILURES_DIR))?;
+        actual.save(&actual_path)?;
+        diff_image(&actual, &expected, &self.tolerance)?.save(&diff_path)?;
+        Ok(GoldenOutcome::Mismatched {
+            comparison,
+            actual: actual_path,
+            diff: diff_path,
+        })
+    }
+}
@@ -0,0 +1,26 @@
+pub mod compare;
+pub mod golden;
+
+use crate::graphics::global::initialize_headless_instance;
+
+pub const SKIP_GPU_TESTS_ENV: &str = "RUPY_SKIP_GPU_TESTS";
+
+pub async fn gpu_available() -> bool {
+    initialize_headless_instance().await.is_ok()
+}
+
+/// Returns whether a GPU test can run. Without an adapter the test fails, unless
+/// `RUPY_SKIP_GPU_TESTS` is set to opt out explicitly, in which case it is skipped.
+pub async fn require_gpu() -> bool {
+    if gpu_available().await {
+        return true;
+    }
+    if std::env::var(SKIP_GPU_TESTS_ENV).is_ok() {
+        eprintln!("Skipping: no GPU adapter and {} is set", SKIP_GPU_TESTS_ENV);
+        return false;
+    }
+    panic!(
+        "No GPU or software adapter is available; install one (e.g. Mesa llvmpipe) or set {}=1 to skip GPU tests",
+        SKIP_GPU_TESTS_ENV
+    );
+}
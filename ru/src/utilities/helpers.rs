@@ -53,6 +53,14 @@ pub fn calculate_hashes<T: Hash>(item: &Vec<T>) -> u64 {
     }
     hasher.finish()
 }
+pub fn content_hash(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
 pub fn string_to_u64(s: &str) -> u64 {
     calculate_hash(&s.to_string())
 }
@@ -1,52 +1,229 @@
-// #[cfg(test)]
-// use rupy::graphics::global::{
-//     get_adapter, get_device, get_instance, get_queue, initialize_instance,
-// };
-// use std::sync::Arc;
-
-// #[tokio::test]
-// async fn test_initialize_instance() {
-//     initialize_instance()
-//         .await
-//         .expect("Failed to initialize GPU resources");
-
-//     let device = get_device().expect("Failed to retrieve device from cache");
-//     let queue = get_queue().expect("Failed to retrieve queue from cache");
-
-//     assert!(
-//         Arc::strong_count(&device) > 1,
-//         "Device was not cached properly"
-//     );
-//     assert!(
-//         Arc::strong_count(&queue) > 1,
-//         "Queue was not cached properly"
-//     );
-
-//     let adapter = get_adapter().expect("Failed to retrieve adapter from cache");
-//     let instance = get_instance().expect("Failed to retrieve instance from cache");
-//     assert!(
-//         Arc::strong_count(&adapter) > 1,
-//         "Adapter was not cached properly"
-//     );
-//     assert!(
-//         Arc::strong_count(&instance) > 1,
-//         "Instance was not cached properly"
-//     );
-// }
-
-// #[tokio::test]
-// async fn test_reinitialize_instance() {
-//     initialize_instance()
-//         .await
-//         .expect("Failed to initialize GPU resources");
-
-//     initialize_instance()
-//         .await
-//         .expect("Failed to initialize GPU resources");
-
-//     let device_count = Arc::strong_count(&get_device().unwrap());
-//     let queue_count = Arc::strong_count(&get_queue().unwrap());
-
-//     assert!(device_count > 1, "Device should still be cached");
-//     assert!(queue_count > 1, "Queue should still be cached");
-// }
+use std::sync::Arc;
+
+use cgmath::Deg;
+use image::{DynamicImage, Rgba, RgbaImage};
+use rupy::{
+    core::cache::CacheKey,
+    ecs::{components::material::model::load_material_textures, traits::Cache},
+    graphics::{
+        global::{get_adapter, get_device, get_queue, initialize_headless_instance},
+        shaders::manager::ShaderManager,
+        textures::{
+            manager::TextureManager,
+            mipmap::MipmapGenerator,
+            sampler::{TextureOverride, TextureSettings},
+            Texture,
+        },
+    },
+    testing::{
+        compare::{compare_images, diff_image, Tolerance},
+        golden::GoldenTest,
+        require_gpu,
+    },
+};
+
+const FIXTURES_DIR: &str = "tests/fixtures";
+
+fn primitives_test(name: &str) -> GoldenTest {
+    GoldenTest::new(name)
+        .mount(FIXTURES_DIR)
+        .scene("golden_primitives.toml")
+        .size(160, 120)
+        .frames(3)
+        .camera((0.0, 1.5, -5.0), Deg(90.0), Deg(-15.0))
+}
+
+#[tokio::test]
+async fn headless_instance_is_cached() {
+    if !require_gpu().await {
+        return;
+    }
+    initialize_headless_instance()
+        .await
+        .expect("Failed to initialize GPU resources");
+
+    let device = get_device().expect("Failed to retrieve device from cache");
+    let queue = get_queue().expect("Failed to retrieve queue from cache");
+    let adapter = get_adapter().expect("Failed to retrieve adapter from cache");
+    assert!(Arc::strong_count(&device) > 1);
+    assert!(Arc::strong_count(&queue) > 1);
+    assert!(Arc::strong_count(&adapter) > 1);
+}
+
+#[tokio::test]
+async fn mipmap_pipelines_are_reused_per_format() {
+    if !require_gpu().await {
+        return;
+    }
+    initialize_headless_instance()
+        .await
+        .expect("Failed to initialize GPU resources");
+    let device = get_device().expect("Failed to retrieve device from cache");
+    let queue = get_queue().expect("Failed to retrieve queue from cache");
+
+    let mut mipmaps = MipmapGenerator::new();
+    let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(16, 16, Rgba([255, 0, 0, 255])));
+    for is_normal_map in [false, false, true] {
+        let texture = Texture::from_image(
+            &device,
+            &queue,
+            &img,
+            Some("mip"),
+            is_normal_map,
+            &mut mipmaps,
+        )
+        .expect("Failed to create texture");
+        assert_eq!(texture.texture.mip_level_count(), 5);
+    }
+    assert_eq!(mipmaps.pipeline_count(), 2);
+}
+
+#[tokio::test]
+async fn texture_overrides_reach_loaded_material_textures() {
+    if !require_gpu().await {
+        return;
+    }
+    initialize_headless_instance()
+        .await
+        .expect("Failed to initialize GPU resources");
+    let device = get_device().expect("Failed to retrieve device from cache");
+    let queue = get_queue().expect("Failed to retrieve queue from cache");
+
+    let clamped = TextureOverride {
+        address_mode: Some(wgpu::AddressMode::ClampToEdge),
+        filter: Some(wgpu::FilterMode::Nearest),
+        mipmaps: Some(false),
+        ..Default::default()
+    };
+    let settings = TextureSettings {
+        files: [
+            ("cube_diffuse.jpg".to_string(), clamped),
+            ("cube_normal.png".to_string(), clamped),
+        ]
+        .into(),
+        ..Default::default()
+    };
+    let mut manager = TextureManager::new();
+    settings.apply(&mut manager);
+
+    let material = tobj::Material {
+        name: "overridden".to_string(),
+        diffuse_texture: "cube_diffuse.jpg".to_string(),
+        normal_texture: "cube_normal.png".to_string(),
+        ..Default::default()
+    };
+    load_material_textures(&device, &queue, vec![&material], &mut manager)
+        .await
+        .expect("Failed to load material textures");
+
+    for (file_name, is_normal_map) in [("cube_diffuse.jpg", false), ("cube_normal.png", true)] {
+        let texture = manager
+            .textures
+            .get(&CacheKey::from(file_name))
+            .expect("Texture was not cached");
+        assert_eq!(texture.options.is_normal_map, is_normal_map);
+        assert_eq!(
+            texture.options.sampler.address_mode,
+            wgpu::AddressMode::ClampToEdge
+        );
+        assert_eq!(
+            texture.options.sampler.mag_filter,
+            wgpu::FilterMode::Nearest
+        );
+        assert_eq!(texture.texture.format(), texture.options.format());
+        assert_eq!(texture.texture.mip_level_count(), 1);
+    }
+}
+
+#[tokio::test]
+async fn reverted_shader_reload_restores_the_previous_module() {
+    if !require_gpu().await {
+        return;
+    }
+    initialize_headless_instance()
+        .await
+        .expect("Failed to initialize GPU resources");
+    let device = get_device().expect("Failed to retrieve device from cache");
+
+    let path = "core/lighting.wgsl";
+    let key = CacheKey::from(path);
+    let mut manager = ShaderManager::new(&device, vec![path]).expect("Failed to load shader");
+    let module_id = |manager: &ShaderManager| manager.shaders.get(&key).unwrap().module.global_id();
+    let original = module_id(&manager);
+
+    let reload = manager
+        .reload_shader(&device, path)
+        .expect("Failed to reload shader");
+    assert_ne!(module_id(&manager), original);
+    manager.revert(reload);
+    assert_eq!(module_id(&manager), original);
+}
+
+#[test]
+fn comparison_applies_per_pixel_and_aggregate_tolerances() {
+    let expected = RgbaImage::from_pixel(10, 10, Rgba([100, 100, 100, 255]));
+    let mut actual = expected.clone();
+    actual.put_pixel(0, 0, Rgba([104, 100, 100, 255]));
+    actual.put_pixel(1, 0, Rgba([160, 100, 100, 255]));
+
+    let tolerance = Tolerance {
+        per_channel: 8,
+        max_differing_ratio: 0.02,
+        max_mean_error: 1.0,
+    };
+    let comparison = compare_images(&actual, &expected, &tolerance).unwrap();
+    assert_eq!(comparison.differing_pixels, 1);
+    assert_eq!(comparison.max_channel_difference, 60);
+    assert!(comparison.passes(&tolerance));
+
+    assert!(!comparison.passes(&Tolerance {
+        max_differing_ratio: 0.0,
+        ..tolerance
+    }));
+    assert!(!comparison.passes(&Tolerance {
+        max_mean_error: 0.1,
+        ..tolerance
+    }));
+}
+
+#[test]
+fn comparison_rejects_mismatched_sizes() {
+    let expected = RgbaImage::new(4, 4);
+    let actual = RgbaImage::new(4, 5);
+    assert!(compare_images(&actual, &expected, &Tolerance::default()).is_err());
+}
+
+#[test]
+fn diff_image_highlights_differing_pixels() {
+    let expected = RgbaImage::from_pixel(2, 1, Rgba([200, 200, 200, 255]));
+    let mut actual = expected.clone();
+    actual.put_pixel(1, 0, Rgba([0, 0, 0, 255]));
+
+    let diff = diff_image(&actual, &expected, &Tolerance::exact()).unwrap();
+    assert_eq!(diff.get_pixel(1, 0).0[0], 255);
+    assert!(diff.get_pixel(0, 0).0[0] < 100);
+}
+
+#[tokio::test]
+async fn headless_render_is_deterministic() {
+    if !require_gpu().await {
+        return;
+    }
+    let test = primitives_test("deterministic");
+    let first = test.render().await.expect("Failed to render first image");
+    let second = test.render().await.expect("Failed to render second image");
+    let comparison = compare_images(&first, &second, &Tolerance::exact()).unwrap();
+    assert!(comparison.passes(&Tolerance::exact()), "{}", comparison);
+}
+
+#[tokio::test]
+async fn primitives_match_golden_image() {
+    if !require_gpu().await {
+        return;
+    }
+    let outcome = primitives_test("primitives")
+        .run()
+        .await
+        .expect("Failed to run golden test");
+    assert!(outcome.passed(), "{}", outcome);
+}
@@ -0,0 +1,246 @@
+use cgmath::{InnerSpace, Vector3};
+use rupy::ecs::components::mesh::processing::{
+    weld_vertices, MeshData, MeshProcessingOptions, NormalMode,
+};
+
+const EPSILON: f32 = 1e-4;
+
+fn quad_positions() -> Vec<f32> {
+    vec![
+        0.0, 0.0, 0.0, //
+        1.0, 0.0, 0.0, //
+        1.0, 1.0, 0.0, //
+        0.0, 1.0, 0.0,
+    ]
+}
+
+fn quad_tex_coords() -> Vec<f32> {
+    vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0]
+}
+
+fn assert_vec_eq(actual: [f32; 3], expected: [f32; 3]) {
+    for axis in 0..3 {
+        assert!(
+            (actual[axis] - expected[axis]).abs() < EPSILON,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn missing_normals_are_generated_smooth() {
+    let data = MeshData::from_attributes(
+        &quad_positions(),
+        &quad_tex_coords(),
+        &[],
+        &[0, 1, 2, 0, 2, 3],
+    );
+    assert!(!data.has_normals);
+
+    let data = data.process(&MeshProcessingOptions::default());
+    assert!(data.has_normals);
+    assert_eq!(data.vertices.len(), 4);
+    for vertex in &data.vertices {
+        assert_vec_eq(vertex.normal, [0.0, 0.0, 1.0]);
+    }
+}
+
+#[test]
+fn smooth_normals_average_across_faces() {
+    let positions = vec![
+        0.0, 0.0, 0.0, //
+        1.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, //
+        0.0, 0.0, 1.0,
+    ];
+    let data = MeshData::from_attributes(&positions, &[], &[], &[0, 1, 2, 0, 3, 1]).process(
+        &MeshProcessingOptions {
+            weld: false,
+            generate_tangents: false,
+            ..Default::default()
+        },
+    );
+
+    let shared = Vector3::from(data.vertices[0].normal);
+    assert!((shared.magnitude() - 1.0).abs() < EPSILON);
+    assert_vec_eq(
+        shared.into(),
+        Vector3::new(0.0, 1.0, 1.0).normalize().into(),
+    );
+}
+
+#[test]
+fn flat_normals_split_shared_vertices() {
+    let positions = vec![
+        0.0, 0.0, 0.0, //
+        1.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, //
+        0.0, 0.0, 1.0,
+    ];
+    let data = MeshData::from_attributes(&positions, &[], &[], &[0, 1, 2, 0, 3, 1]).process(
+        &MeshProcessingOptions {
+            normal_mode: NormalMode::Flat,
+            generate_tangents: false,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(data.vertices.len(), 6);
+    for index in &data.indices[0..3] {
+        assert_vec_eq(data.vertices[*index as usize].normal, [0.0, 0.0, 1.0]);
+    }
+    for index in &data.indices[3..6] {
+        assert_vec_eq(data.vertices[*index as usize].normal, [0.0, 1.0, 0.0]);
+    }
+}
+
+#[test]
+fn missing_tex_coords_get_planar_defaults() {
+    let data = MeshData::from_attributes(&quad_positions(), &[], &[], &[0, 1, 2, 0, 2, 3]);
+    assert!(!data.has_tex_coords);
+
+    let data = data.process(&MeshProcessingOptions::default());
+    assert!(data.has_tex_coords);
+    for vertex in &data.vertices {
+        for c in vertex.tex_coords {
+            assert!((0.0..=1.0).contains(&c));
+        }
+    }
+    let corners: Vec<[f32; 2]> = data.vertices.iter().map(|v| v.tex_coords).collect();
+    for expected in [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]] {
+        assert!(corners.contains(&expected), "missing uv {:?}", expected);
+    }
+}
+
+#[test]
+fn short_attribute_arrays_and_bad_indices_do_not_panic() {
+    let data = MeshData::from_attributes(
+        &quad_positions(),
+        &[0.0, 0.0],
+        &[0.0, 0.0, 1.0],
+        &[0, 1, 2, 0, 2, 9, 3],
+    )
+    .process(&MeshProcessingOptions::default());
+
+    assert_eq!(data.indices.len(), 3);
+    assert!(data
+        .indices
+        .iter()
+        .all(|&i| (i as usize) < data.vertices.len()));
+}
+
+#[test]
+fn duplicate_vertices_are_welded() {
+    let positions = vec![
+        0.0, 0.0, 0.0, //
+        1.0, 0.0, 0.0, //
+        1.0, 1.0, 0.0, //
+        0.0, 0.0, 0.0, //
+        1.0, 1.0, 0.0, //
+        0.0, 1.0, 0.0,
+    ];
+    let tex_coords = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+    let normals = [0.0, 0.0, 1.0].repeat(6);
+    let data = MeshData::from_attributes(&positions, &tex_coords, &normals, &[]);
+
+    let (vertices, indices) = weld_vertices(&data.vertices, &data.indices, 1e-5);
+    assert_eq!(vertices.len(), 4);
+    assert_eq!(indices, vec![0, 1, 2, 0, 2, 3]);
+}
+
+#[test]
+fn vertices_on_uv_seams_are_not_welded() {
+    let positions = vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+    let tex_coords = vec![0.0, 0.0, 1.0, 0.0];
+    let data = MeshData::from_attributes(
+        &positions,
+        &tex_coords,
+        &[0.0, 0.0, 1.0, 0.0, 0.0, 1.0],
+        &[],
+    );
+
+    let (vertices, _) = weld_vertices(&data.vertices, &[0, 1], 1e-5);
+    assert_eq!(vertices.len(), 2);
+}
+
+#[test]
+fn tangents_are_orthonormal_and_follow_uv_direction() {
+    let normals = [0.0, 0.0, 1.0].repeat(4);
+    let data = MeshData::from_attributes(
+        &quad_positions(),
+        &quad_tex_coords(),
+        &normals,
+        &[0, 1, 2, 0, 2, 3],
+    )
+    .process(&MeshProcessingOptions::default());
+
+    assert_eq!(data.vertices.len(), 4);
+    for vertex in &data.vertices {
+        let n = Vector3::from(vertex.normal);
+        let t = Vector3::from(vertex.tangent);
+        let b = Vector3::from(vertex.bitangent);
+        assert!((t.magnitude() - 1.0).abs() < EPSILON);
+        assert!(t.dot(n).abs() < EPSILON);
+        assert!(b.dot(t).abs() < EPSILON);
+        assert_vec_eq(vertex.tangent, [1.0, 0.0, 0.0]);
+        assert_vec_eq(b.into(), n.cross(t).into());
+    }
+}
+
+#[test]
+fn mirrored_uvs_produce_negative_handedness() {
+    let normals = [0.0, 0.0, 1.0].repeat(4);
+    let mirrored = vec![1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+    let data =
+        MeshData::from_attributes(&quad_positions(), &mirrored, &normals, &[0, 1, 2, 0, 2, 3])
+            .process(&MeshProcessingOptions::default());
+
+    for vertex in &data.vertices {
+        let n = Vector3::from(vertex.normal);
+        let t = Vector3::from(vertex.tangent);
+        let b = Vector3::from(vertex.bitangent);
+        assert_vec_eq(vertex.tangent, [-1.0, 0.0, 0.0]);
+        assert!(n.cross(t).dot(b) < 0.0);
+    }
+}
+
+#[test]
+fn vertices_shared_across_mirrored_halves_are_split() {
+    let positions = vec![
+        0.0, 0.0, 0.0, //
+        1.0, 0.0, 0.0, //
+        1.0, 1.0, 0.0, //
+        0.0, 1.0, 0.0, //
+        2.0, 0.0, 0.0, //
+        2.0, 1.0, 0.0,
+    ];
+    let tex_coords = vec![
+        0.0, 0.0, //
+        1.0, 0.0, //
+        1.0, 1.0, //
+        0.0, 1.0, //
+        0.0, 0.0, //
+        0.0, 1.0,
+    ];
+    let normals = [0.0, 0.0, 1.0].repeat(6);
+    let indices = [0, 1, 2, 0, 2, 3, 1, 4, 5, 1, 5, 2];
+    let data = MeshData::from_attributes(&positions, &tex_coords, &normals, &indices)
+        .process(&MeshProcessingOptions::default());
+
+    assert_eq!(data.vertices.len(), 8);
+    for tri in data.indices.chunks_exact(3) {
+        let signs: Vec<bool> = tri
+            .iter()
+            .map(|&i| {
+                let v = data.vertices[i as usize];
+                Vector3::from(v.normal)
+                    .cross(Vector3::from(v.tangent))
+                    .dot(Vector3::from(v.bitangent))
+                    > 0.0
+            })
+            .collect();
+        assert!(signs.iter().all(|s| *s == signs[0]));
+    }
+}